@@ -5,6 +5,10 @@
 
 use crate::registry::defaults::default_starlight_registry;
 use crate::renderer::mdast::RenderBlock;
+use crate::transform::imports::{
+    ASSET_BINDING_PREFIX, GENERATED_ALIAS_PREFIX, declared_bindings, exported_names,
+    resolve_component_imports,
+};
 use crate::{PropValue, RegistryConfig};
 use std::fmt::Write as FmtWrite;
 
@@ -690,7 +694,8 @@ where
 
 /// Emits an HTML render block as a Fragment with `set:html`.
 fn emit_html_block(content: &str, result: &mut String) {
-    if has_pascal_case_tag(content) && content.contains("={") {
+    if (has_pascal_case_tag(content) || references_asset_binding(content)) && content.contains("={")
+    {
         result.push_str(&html_entities_to_jsx(content));
         return;
     }
@@ -699,6 +704,14 @@ fn emit_html_block(content: &str, result: &mut String) {
     result.push_str("} />");
 }
 
+/// Returns true if HTML binds an attribute to a hoisted image import
+/// (`src={__xmdx_img0.src}`), which only resolves when emitted as JSX.
+fn references_asset_binding(html: &str) -> bool {
+    ["={", "=&#123;"]
+        .iter()
+        .any(|open| html.contains(&format!("{open}{ASSET_BINDING_PREFIX}")))
+}
+
 /// Emits a code render block as a `<pre><code>` Fragment with `set:html`.
 fn emit_code_block(code: &str, lang: Option<&str>, result: &mut String) {
     let mut html = String::new();
@@ -840,7 +853,7 @@ fn emit_slot_html(slot_html: &str, result: &mut String) {
     if slot_html.is_empty() {
         return;
     }
-    if has_pascal_case_tag(slot_html) {
+    if has_pascal_case_tag(slot_html) || references_asset_binding(slot_html) {
         result.push_str(&html_entities_to_jsx(slot_html));
     } else {
         result.push_str("<_Fragment set:html={");
@@ -870,10 +883,32 @@ fn normalize_slot_by_registry(
     slot_html: &str,
     registry: &RegistryConfig,
 ) -> String {
+    // Aliased generated imports (`__xmdx_Steps`) normalize like their component.
+    let component = component
+        .strip_prefix(GENERATED_ALIAS_PREFIX)
+        .unwrap_or(component);
     if let Some(normalization) = registry.get_slot_normalization(component) {
+        let child = normalization.child_component.as_deref();
+        let child_prop = normalization.child_prop.as_deref();
         match normalization.strategy.as_str() {
             "wrap_in_ol" => normalize_wrap_in_ol(slot_html),
             "wrap_in_ul" => normalize_wrap_in_ul(slot_html, normalization.wrapper_class.as_deref()),
+            "wrap_in_div" => {
+                normalize_wrap_in_div(slot_html, normalization.wrapper_class.as_deref())
+            }
+            "split_by_heading" => match child {
+                Some(child) => normalize_split_by_heading(
+                    slot_html,
+                    &format!("h{}", normalization.heading_level()),
+                    child,
+                    child_prop,
+                ),
+                None => slot_html.to_string(),
+            },
+            "list_to_items" => match child {
+                Some(child) => normalize_list_to_items(slot_html, child, child_prop),
+                None => slot_html.to_string(),
+            },
             _ => slot_html.to_string(),
         }
     } else {
@@ -1044,6 +1079,217 @@ fn normalize_wrap_in_ul(slot_html: &str, wrapper_class: Option<&str>) -> String
     }
 }
 
+/// Normalizes slot content by wrapping it in a single `<div>` element.
+fn normalize_wrap_in_div(slot_html: &str, wrapper_class: Option<&str>) -> String {
+    let class_attr = wrapper_class
+        .map(|c| format!(" class=\"{}\"", escape_attr_value_for_html(c)))
+        .unwrap_or_default();
+    format!("<div{}>{}</div>", class_attr, slot_html.trim())
+}
+
+/// Normalizes slot content by turning each `heading` section into a child component.
+///
+/// `heading` is the section tag (`h2` by default). Content before the first
+/// heading is kept as-is. Each heading's text becomes `child_prop` (when set)
+/// and the content up to the next heading becomes the child's slot, e.g.
+/// `<TabItem label="npm">...</TabItem>` for Tabs.
+fn normalize_split_by_heading(
+    slot_html: &str,
+    heading: &str,
+    child: &str,
+    child_prop: Option<&str>,
+) -> String {
+    let Some(first) = find_open_tag(slot_html, heading) else {
+        return slot_html.to_string();
+    };
+
+    let mut result = slot_html[..first].to_string();
+    let mut rest = &slot_html[first..];
+    while let Some((inner_start, inner_end, end)) = match_element(rest, heading) {
+        let label = strip_tags(&rest[inner_start..inner_end]);
+        let after = &rest[end..];
+        let next = find_open_tag(after, heading).unwrap_or(after.len());
+        push_child_component(&mut result, child, child_prop, &label, &after[..next]);
+        rest = &after[next..];
+        if rest.is_empty() {
+            break;
+        }
+    }
+    // Malformed trailing heading: keep it verbatim.
+    result.push_str(rest);
+    result
+}
+
+/// Normalizes slot content by turning each top-level `<li>` into a child component.
+///
+/// The first list in the slot is unwrapped; surrounding content is kept. When
+/// `child_prop` is set, the item's first paragraph (or, for tight items, its
+/// leading text) is lifted into that prop, e.g. `<Card title="Stars">...</Card>`.
+fn normalize_list_to_items(slot_html: &str, child: &str, child_prop: Option<&str>) -> String {
+    let list = ["ul", "ol"]
+        .into_iter()
+        .filter_map(|tag| find_open_tag(slot_html, tag).map(|pos| (pos, tag)))
+        .min_by_key(|(pos, _)| *pos);
+    let Some((start, tag)) = list else {
+        return slot_html.to_string();
+    };
+    let Some((inner_start, inner_end, end)) = match_element(&slot_html[start..], tag) else {
+        return slot_html.to_string();
+    };
+
+    let mut result = slot_html[..start].to_string();
+    let mut items = &slot_html[start + inner_start..start + inner_end];
+    while let Some(li_start) = find_open_tag(items, "li") {
+        let Some((li_inner_start, li_inner_end, li_end)) = match_element(&items[li_start..], "li")
+        else {
+            break;
+        };
+        let item = &items[li_start + li_inner_start..li_start + li_inner_end];
+        match child_prop {
+            Some(_) => {
+                let (lead, body) = split_leading_item_text(item);
+                push_child_component(&mut result, child, child_prop, &strip_tags(lead), body);
+            }
+            None => push_child_component(&mut result, child, None, "", item),
+        }
+        items = &items[li_start + li_end..];
+    }
+    result.push_str(&slot_html[start + end..]);
+    result
+}
+
+/// Splits a list item into its leading text and the remaining content.
+fn split_leading_item_text(item: &str) -> (&str, &str) {
+    let trimmed = item.trim_start();
+    if trimmed.starts_with("<p>")
+        && let Some(close) = trimmed.find("</p>")
+    {
+        return (&trimmed[3..close], &trimmed[close + 4..]);
+    }
+    let split = ["\n", "<p", "<ul", "<ol", "<div", "<pre"]
+        .iter()
+        .filter_map(|marker| trimmed.find(marker))
+        .min()
+        .unwrap_or(trimmed.len());
+    (&trimmed[..split], &trimmed[split..])
+}
+
+fn push_child_component(
+    result: &mut String,
+    child: &str,
+    child_prop: Option<&str>,
+    label: &str,
+    body: &str,
+) {
+    result.push('<');
+    result.push_str(child);
+    if let Some(prop) = child_prop {
+        let _ = write!(
+            result,
+            " {}=\"{}\"",
+            prop,
+            label.trim().replace('"', "&quot;")
+        );
+    }
+    result.push('>');
+    result.push_str(body.trim());
+    result.push_str("</");
+    result.push_str(child);
+    result.push('>');
+}
+
+/// Finds the next `<tag` opening (followed by `>`, `/` or whitespace),
+/// ignoring text inside quoted attribute values of other tags.
+fn find_open_tag(s: &str, tag: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut in_tag = false;
+    let mut in_quote: Option<u8> = None;
+    for (pos, &b) in bytes.iter().enumerate() {
+        if let Some(q) = in_quote {
+            if b == q {
+                in_quote = None;
+            }
+        } else if in_tag {
+            match b {
+                b'"' | b'\'' => in_quote = Some(b),
+                b'>' => in_tag = false,
+                _ => {}
+            }
+        } else if b == b'<' {
+            if is_open_tag_at(bytes, pos, tag) {
+                return Some(pos);
+            }
+            in_tag = true;
+        }
+    }
+    None
+}
+
+/// Returns whether `bytes[pos..]` starts with an opening `<tag` boundary.
+fn is_open_tag_at(bytes: &[u8], pos: usize, tag: &str) -> bool {
+    bytes[pos..].starts_with(b"<")
+        && bytes[pos + 1..].starts_with(tag.as_bytes())
+        && matches!(
+            bytes.get(pos + 1 + tag.len()),
+            None | Some(b'>' | b'/' | b' ' | b'\t' | b'\n' | b'\r')
+        )
+}
+
+/// For `s` starting with `<tag ...>`, returns `(inner_start, inner_end, end)`
+/// byte offsets of the element's content and the end of its closing tag,
+/// accounting for nested elements with the same tag name.
+fn match_element(s: &str, tag: &str) -> Option<(usize, usize, usize)> {
+    let inner_start = s.find('>')? + 1;
+    let closing = format!("</{}>", tag);
+    let mut depth = 1usize;
+    let mut pos = inner_start;
+    while pos < s.len() {
+        let rest = &s[pos..];
+        let next_open = find_open_tag(rest, tag);
+        let next_close = rest.find(&closing)?;
+        match next_open {
+            Some(open) if open < next_close => {
+                depth += 1;
+                pos += open + 1;
+            }
+            _ => {
+                depth -= 1;
+                if depth == 0 {
+                    let inner_end = pos + next_close;
+                    return Some((inner_start, inner_end, inner_end + closing.len()));
+                }
+                pos += next_close + closing.len();
+            }
+        }
+    }
+    None
+}
+
+/// Removes HTML tags, keeping text content (entities are left encoded).
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for ch in html.chars() {
+        match ch {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(ch),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Returns whether hoisted `statements` already bind or export `toc`, in
+/// which case the generated `toc` export is left out to avoid a duplicate
+/// declaration.
+pub fn declares_toc(statements: &[String]) -> bool {
+    declared_bindings(statements)
+        .into_iter()
+        .chain(exported_names(statements))
+        .any(|name| name == "toc")
+}
+
 /// Options for Astro module generation.
 #[derive(Debug, Clone, Default)]
 pub struct AstroModuleOptions<'a> {
@@ -1057,6 +1303,10 @@ pub struct AstroModuleOptions<'a> {
     pub frontmatter_json: &'a str,
     /// Serialized headings as JSON.
     pub headings_json: &'a str,
+    /// Serialized table of contents as JSON (None omits the `toc` export).
+    /// The export is also omitted when the hoisted statements already
+    /// declare or export `toc` (see [`declares_toc`]).
+    pub toc_json: Option<&'a str>,
     /// File path for the module.
     pub filepath: &'a str,
     /// URL for the module (None means `undefined`).
@@ -1068,6 +1318,11 @@ pub struct AstroModuleOptions<'a> {
     /// Custom JSX import source (default: "astro").
     /// Controls the `from '…/jsx-runtime'` import specifier.
     pub jsx_import_source: Option<&'a str>,
+    /// Registry used to auto-import `components`. `None` disables auto-imports.
+    pub registry: Option<&'a RegistryConfig>,
+    /// Component bindings `jsx` uses (see
+    /// [`BlocksResult::jsx_component_names`](crate::renderer::mdast::BlocksResult::jsx_component_names)).
+    pub components: &'a [String],
 }
 
 /// Builder for constructing Astro-compatible JavaScript modules.
@@ -1092,11 +1347,14 @@ pub struct AstroModuleBuilder<'a> {
     jsx: Option<&'a str>,
     frontmatter_json: Option<&'a str>,
     headings_json: Option<&'a str>,
+    toc_json: Option<&'a str>,
     url: Option<&'a str>,
     layout_path: Option<&'a str>,
     has_user_default_export: bool,
     include_runtime_imports: bool,
     jsx_import_source: Option<&'a str>,
+    component_registry: Option<&'a RegistryConfig>,
+    components: &'a [String],
 }
 
 impl<'a> AstroModuleBuilder<'a> {
@@ -1109,11 +1367,14 @@ impl<'a> AstroModuleBuilder<'a> {
             jsx: None,
             frontmatter_json: None,
             headings_json: None,
+            toc_json: None,
             url: None,
             layout_path: None,
             has_user_default_export: false,
             include_runtime_imports: false,
             jsx_import_source: None,
+            component_registry: None,
+            components: &[],
         }
     }
 
@@ -1181,6 +1442,12 @@ impl<'a> AstroModuleBuilder<'a> {
         self
     }
 
+    /// Sets the table of contents JSON, exported as `toc`.
+    pub fn with_toc(mut self, json: &'a str) -> Self {
+        self.toc_json = Some(json);
+        self
+    }
+
     /// Sets the URL for the module.
     pub fn with_url(mut self, url: &'a str) -> Self {
         self.url = Some(url);
@@ -1193,6 +1460,18 @@ impl<'a> AstroModuleBuilder<'a> {
         self
     }
 
+    /// Auto-imports the registered `components` the JSX uses that are not
+    /// already imported.
+    pub fn with_component_imports(
+        mut self,
+        registry: &'a RegistryConfig,
+        components: &'a [String],
+    ) -> Self {
+        self.component_registry = Some(registry);
+        self.components = components;
+        self
+    }
+
     /// Creates a builder from AstroModuleOptions for backwards compatibility.
     pub fn from_options(options: &AstroModuleOptions<'a>) -> Self {
         let mut builder = Self::new(options.filepath)
@@ -1204,6 +1483,10 @@ impl<'a> AstroModuleBuilder<'a> {
             .add_exports(options.hoisted_exports)
             .has_user_default_export(options.has_user_default_export);
 
+        if let Some(toc) = options.toc_json {
+            builder = builder.with_toc(toc);
+        }
+
         if let Some(url) = options.url {
             builder = builder.with_url(url);
         }
@@ -1214,6 +1497,10 @@ impl<'a> AstroModuleBuilder<'a> {
 
         builder = builder.with_jsx_import_source(options.jsx_import_source);
 
+        if let Some(registry) = options.registry {
+            builder = builder.with_component_imports(registry, options.components);
+        }
+
         builder
     }
 
@@ -1236,6 +1523,11 @@ impl<'a> AstroModuleBuilder<'a> {
             let _ = writeln!(code, "{}", import);
         }
 
+        // Registry imports for components used without an explicit import
+        for import in self.component_imports() {
+            let _ = writeln!(code, "{}", import);
+        }
+
         // User exports
         for export in &self.exports {
             let _ = writeln!(code, "{}", export);
@@ -1253,6 +1545,24 @@ impl<'a> AstroModuleBuilder<'a> {
         code
     }
 
+    fn component_imports(&self) -> Vec<String> {
+        let Some(registry) = self.component_registry else {
+            return Vec::new();
+        };
+        let existing: Vec<String> = self
+            .imports
+            .iter()
+            .chain(&self.exports)
+            .map(|s| s.to_string())
+            .collect();
+        let used = self
+            .components
+            .iter()
+            .map(String::as_str)
+            .filter(|name| self.layout_path.is_none() || *name != "Layout");
+        resolve_component_imports(used, &existing, registry).imports
+    }
+
     fn write_runtime_imports(&self, code: &mut String) {
         let source = self.jsx_import_source.unwrap_or("astro");
         let _ = writeln!(
@@ -1292,6 +1602,16 @@ impl<'a> AstroModuleBuilder<'a> {
         let _ = writeln!(code, "export function getHeadings() {{");
         let _ = writeln!(code, "  return {};", headings);
         let _ = writeln!(code, "}}");
+
+        let user_statements: Vec<String> = self
+            .imports
+            .iter()
+            .chain(&self.exports)
+            .map(|s| s.to_string())
+            .collect();
+        if let Some(toc) = self.toc_json.filter(|_| !declares_toc(&user_statements)) {
+            let _ = writeln!(code, "export const toc = {};", toc);
+        }
     }
 
     fn write_xmdx_content(&self, code: &mut String) {
@@ -1879,11 +2199,14 @@ mod tests {
             hoisted_exports: &[],
             frontmatter_json: "{}",
             headings_json: "[]",
+            toc_json: None,
             filepath: "/test.md",
             url: None,
             layout_import: None,
             has_user_default_export: false,
             jsx_import_source: None,
+            registry: None,
+            components: &[],
         };
 
         let code = generate_astro_module(&options);
@@ -1896,6 +2219,41 @@ mod tests {
         assert!(code.contains("export default xmdxContent;"));
     }
 
+    #[test]
+    fn test_generate_astro_module_exports_toc() {
+        let toc = r#"[{"depth":2,"slug":"intro","text":"Intro","children":[]}]"#;
+        let options = AstroModuleOptions {
+            jsx: "<p>Hello</p>",
+            frontmatter_json: "{}",
+            headings_json: "[]",
+            toc_json: Some(toc),
+            filepath: "/test.md",
+            ..Default::default()
+        };
+
+        let code = generate_astro_module(&options);
+        assert!(code.contains(&format!("export const toc = {};", toc)));
+
+        let without = generate_astro_module(&AstroModuleOptions {
+            toc_json: None,
+            ..options
+        });
+        assert!(!without.contains("export const toc"));
+
+        for user in ["export const toc = [];", "export { outline as toc };"] {
+            let exports = vec![user.to_string()];
+            let code = generate_astro_module(&AstroModuleOptions {
+                hoisted_exports: &exports,
+                ..options.clone()
+            });
+            assert!(code.contains(user), "{code}");
+            assert!(
+                !code.contains(&format!("export const toc = {};", toc)),
+                "{code}"
+            );
+        }
+    }
+
     #[test]
     fn test_generate_astro_module_with_layout() {
         let options = AstroModuleOptions {
@@ -1904,11 +2262,14 @@ mod tests {
             hoisted_exports: &[],
             frontmatter_json: "{}",
             headings_json: "[]",
+            toc_json: None,
             filepath: "/test.md",
             url: None,
             layout_import: Some("../layouts/Base.astro"),
             has_user_default_export: false,
             jsx_import_source: None,
+            registry: None,
+            components: &[],
         };
 
         let code = generate_astro_module(&options);
@@ -1925,11 +2286,14 @@ mod tests {
             hoisted_exports: &[],
             frontmatter_json: "{}",
             headings_json: "[]",
+            toc_json: None,
             filepath: "/test.md",
             url: None,
             layout_import: None,
             has_user_default_export: true,
             jsx_import_source: None,
+            registry: None,
+            components: &[],
         };
 
         let code = generate_astro_module(&options);
@@ -1945,11 +2309,14 @@ mod tests {
             hoisted_exports: &["export const bar = 1;".to_string()],
             frontmatter_json: "{}",
             headings_json: "[]",
+            toc_json: None,
             filepath: "/test.md",
             url: None,
             layout_import: None,
             has_user_default_export: false,
             jsx_import_source: None,
+            registry: None,
+            components: &[],
         };
 
         let code = generate_astro_module(&options);
@@ -1973,11 +2340,14 @@ mod tests {
             hoisted_exports: &[],
             frontmatter_json: "{}",
             headings_json: "[]",
+            toc_json: None,
             filepath: "/test.md",
             url: None,
             layout_import: None,
             has_user_default_export: false,
             jsx_import_source: Some("preact"),
+            registry: None,
+            components: &[],
         };
 
         let code = generate_astro_module(&options);
@@ -1986,6 +2356,33 @@ mod tests {
         assert!(!code.contains("astro/jsx-runtime"));
     }
 
+    #[test]
+    fn test_generate_astro_module_auto_imports_registry_components() {
+        let registry = default_starlight_registry();
+        let components = ["Tabs", "TabItem", "Card", "Unknown"].map(String::from);
+        let options = AstroModuleOptions {
+            jsx: "<Tabs><TabItem label=\"a\"><Card title=\"x\" /></TabItem></Tabs><Unknown />",
+            hoisted_imports: &["import Card from './MyCard.astro';".to_string()],
+            hoisted_exports: &[],
+            frontmatter_json: "{}",
+            headings_json: "[]",
+            toc_json: None,
+            filepath: "/test.mdx",
+            url: None,
+            layout_import: None,
+            has_user_default_export: false,
+            jsx_import_source: None,
+            registry: Some(&registry),
+            components: &components,
+        };
+
+        let code = generate_astro_module(&options);
+
+        assert!(code.contains("import { Tabs, TabItem } from '@astrojs/starlight/components';"));
+        assert!(!code.contains("import { Card }"));
+        assert!(!code.contains("Unknown }"));
+    }
+
     #[test]
     fn test_blocks_to_jsx_string_fragment_slot_uses_span_wrapper() {
         // Fragment-with-slot children should be rendered as <span style="display:contents" slot="name">
@@ -2248,4 +2645,92 @@ mod tests {
         let result3 = normalize_wrap_in_ol(input3);
         assert_eq!(result3, "<ol><li>résumé</li><li>item</li><li>🎉</li></ol>");
     }
+
+    fn registry_with_normalization(
+        component: &str,
+        strategy: &str,
+        child: Option<&str>,
+        prop: Option<&str>,
+    ) -> RegistryConfig {
+        let mut registry = default_starlight_registry();
+        registry
+            .slot_normalizations
+            .push(crate::registry::SlotNormalization {
+                component: component.to_string(),
+                strategy: strategy.to_string(),
+                wrapper_class: None,
+                child_component: child.map(str::to_string),
+                child_prop: prop.map(str::to_string),
+                heading_level: None,
+            });
+        registry
+    }
+
+    #[test]
+    fn normalize_wrap_in_div_with_class() {
+        assert_eq!(
+            normalize_wrap_in_div("<p>Hi</p>", Some("grid")),
+            "<div class=\"grid\"><p>Hi</p></div>"
+        );
+    }
+
+    #[test]
+    fn normalize_split_by_heading_creates_child_per_section() {
+        let html = "<p>Intro</p><h2 id=\"npm\">npm &amp; co</h2><p>npm i</p><h2 id=\"pnpm\"><code>pnpm</code></h2><p>pnpm add</p>";
+        assert_eq!(
+            normalize_split_by_heading(html, "h2", "TabItem", Some("label")),
+            "<p>Intro</p><TabItem label=\"npm &amp; co\"><p>npm i</p></TabItem><TabItem label=\"pnpm\"><p>pnpm add</p></TabItem>"
+        );
+        // No headings: unchanged
+        assert_eq!(
+            normalize_split_by_heading("<p>x</p>", "h2", "TabItem", Some("label")),
+            "<p>x</p>"
+        );
+        // Other heading levels split only on their own tag.
+        assert_eq!(
+            normalize_split_by_heading(
+                "<h2>Top</h2><h3>A</h3><p>a</p><h3>B</h3>",
+                "h3",
+                "TabItem",
+                Some("label")
+            ),
+            "<h2>Top</h2><TabItem label=\"A\"><p>a</p></TabItem><TabItem label=\"B\"></TabItem>"
+        );
+    }
+
+    #[test]
+    fn normalize_list_to_items_lifts_leading_text() {
+        let tight = "<ul>\n<li>Stars\n<p>Body</p></li>\n<li>Don't <em>panic</em><ul><li>nested</li></ul></li>\n</ul>";
+        assert_eq!(
+            normalize_list_to_items(tight, "Card", Some("title")),
+            "<Card title=\"Stars\"><p>Body</p></Card><Card title=\"Don't panic\"><ul><li>nested</li></ul></Card>"
+        );
+        let loose = "<ol><li><p>One</p><p>Body</p></li></ol><p>after</p>";
+        assert_eq!(
+            normalize_list_to_items(loose, "Card", None),
+            "<Card><p>One</p><p>Body</p></Card><p>after</p>"
+        );
+    }
+
+    #[test]
+    fn registry_split_by_heading_emits_child_components() {
+        let registry =
+            registry_with_normalization("Tabs", "split_by_heading", Some("TabItem"), Some("label"));
+        let blocks = vec![RenderBlock::Component {
+            name: "Tabs".to_string(),
+            props: BTreeMap::new(),
+            slot_children: vec![RenderBlock::Html {
+                content: "<h2 id=\"a\">A</h2><p>one</p><h2 id=\"b\">B</h2><p>two</p>".to_string(),
+            }],
+        }];
+        let jsx = blocks_to_jsx_string_with_registry(
+            &blocks,
+            None::<fn(&str) -> Option<DirectiveMappingResult>>,
+            Some(&registry),
+        );
+        assert_eq!(
+            jsx,
+            "<Tabs><TabItem label=\"A\"><p>one</p></TabItem><TabItem label=\"B\"><p>two</p></TabItem></Tabs>"
+        );
+    }
 }
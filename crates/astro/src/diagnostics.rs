@@ -0,0 +1,292 @@
+//! Compile warnings reported by the Node and WASM bindings.
+//!
+//! Both bindings render a document the same way, so they build warnings
+//! here from the [`BlocksResult`] to keep types, messages and line numbers
+//! identical.
+
+use crate::codegen::declares_toc;
+use crate::registry::RegistryConfig;
+use crate::renderer::mdast::{BlocksResult, SnippetIssue, TransclusionIssue, TransclusionKind};
+use crate::transform::imports::{declaration_line, resolve_component_imports};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A problem found while compiling a document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CompileWarning {
+    /// Warning type (e.g., "unknown_component", "invalid_math").
+    pub warning_type: String,
+    /// 1-based line in the source file, or 0 when no line applies.
+    pub line: u32,
+    /// Human-readable message.
+    pub message: String,
+}
+
+/// The document context the warnings are built in.
+#[derive(Debug, Clone, Copy)]
+pub struct WarningContext<'a> {
+    /// The full source file, frontmatter included.
+    pub source: &'a str,
+    /// Source line of each line of the rendered body; see [`body_line_map`].
+    pub line_map: &'a [u32],
+    /// The document's own import and export statements.
+    pub declared: &'a [String],
+    /// Registry used to resolve components.
+    pub registry: &'a RegistryConfig,
+    /// Tags the renderer rewrote to their canonical component.
+    pub component_renames: &'a BTreeMap<String, String>,
+    /// Component rendered after external links, if configured.
+    pub icon_component: Option<&'a str>,
+}
+
+impl WarningContext<'_> {
+    /// Maps a 1-based body line to its source line.
+    pub fn source_line(&self, line: usize) -> u32 {
+        line.checked_sub(1)
+            .and_then(|idx| self.line_map.get(idx).copied())
+            .unwrap_or(line as u32)
+    }
+}
+
+/// Maps each line of the hoist-stripped body back to its 1-based source line.
+///
+/// `body_lines` is a subsequence of `raw_body`'s lines (hoisted imports and
+/// exports removed), so a greedy forward match recovers the original line.
+/// `line_offset` is the number of source lines preceding the body (frontmatter).
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::diagnostics::body_line_map;
+///
+/// let body = "import A from './a';\n\n# Title\n";
+/// let lines = vec![String::new(), "# Title".to_string()];
+/// assert_eq!(body_line_map(body, &lines, 3), vec![5, 6]);
+/// ```
+pub fn body_line_map(raw_body: &str, body_lines: &[String], line_offset: u32) -> Vec<u32> {
+    let raw_lines: Vec<&str> = raw_body.lines().collect();
+    let mut map = Vec::with_capacity(body_lines.len());
+    let mut cursor = 0usize;
+    for line in body_lines {
+        let found = raw_lines[cursor.min(raw_lines.len())..]
+            .iter()
+            .position(|raw| raw == line)
+            .map(|pos| cursor + pos);
+        let index = found.unwrap_or(cursor);
+        map.push(index as u32 + 1 + line_offset);
+        cursor = index + 1;
+    }
+    map
+}
+
+/// Builds the warnings for a rendered document, ordered by line.
+pub fn compile_warnings(result: &BlocksResult, cx: &WarningContext<'_>) -> Vec<CompileWarning> {
+    let warning = |warning_type: &str, line: u32, message: String| CompileWarning {
+        warning_type: warning_type.to_string(),
+        line,
+        message,
+    };
+    let unresolved = resolve_component_imports(
+        result
+            .components
+            .iter()
+            .map(|usage| usage.name.as_str())
+            .filter(|name| !cx.component_renames.contains_key(*name)),
+        cx.declared,
+        cx.registry,
+    )
+    .unresolved;
+    let mut warnings: Vec<CompileWarning> = result
+        .components
+        .iter()
+        .filter(|usage| unresolved.contains(&usage.name))
+        .map(|usage| {
+            warning(
+                "unknown_component",
+                cx.source_line(usage.line),
+                format!(
+                    "Component <{}> is used but neither imported nor registered",
+                    usage.name
+                ),
+            )
+        })
+        .collect();
+    if let Some(icon) = cx
+        .icon_component
+        .filter(|icon| result.element_components.iter().any(|c| c == icon))
+        && !resolve_component_imports([icon], cx.declared, cx.registry)
+            .unresolved
+            .is_empty()
+    {
+        warnings.push(warning(
+            "unknown_component",
+            0,
+            format!("External link icon <{icon}> is used but neither imported nor registered"),
+        ));
+    }
+    warnings.extend(result.alias_usages.iter().map(|usage| {
+        let subject = match &usage.directive {
+            Some(directive) => format!(":::{} maps to <{}>, which", directive, usage.alias),
+            None => format!("<{}>", usage.alias),
+        };
+        let mut message = format!("{} is a deprecated alias of <{}>", subject, usage.canonical);
+        if let Some(note) = cx
+            .registry
+            .get_component(&usage.canonical)
+            .and_then(|def| def.deprecated.as_deref())
+        {
+            message.push_str(": ");
+            message.push_str(note);
+        }
+        warning("deprecated_component", cx.source_line(usage.line), message)
+    }));
+    warnings.extend(result.missing_images.iter().map(|image| {
+        warning(
+            "missing_image",
+            cx.source_line(image.line),
+            format!("Image {} not found at {}", image.src, image.path),
+        )
+    }));
+    warnings.extend(result.math_issues.iter().map(|issue| {
+        warning(
+            "invalid_math",
+            cx.source_line(issue.line),
+            format!("Invalid TeX at column {}: {}", issue.column, issue.message),
+        )
+    }));
+    warnings.extend(result.unresolved_wikilinks.iter().map(|link| {
+        warning(
+            "unresolved_wikilink",
+            cx.source_line(link.line),
+            format!("Wiki link [[{}]] does not match any page", link.page),
+        )
+    }));
+    warnings.extend(result.transclusions.iter().filter_map(|transclusion| {
+        let (warning_type, reference) = match transclusion.kind {
+            TransclusionKind::Embed => (
+                "unresolved_embed",
+                format!("Embed ![[{}]]", transclusion.target),
+            ),
+            TransclusionKind::Include => (
+                "unresolved_include",
+                format!("Include {}", transclusion.target),
+            ),
+        };
+        let location = match &transclusion.via {
+            Some(via) => format!(" (in {via})"),
+            None => String::new(),
+        };
+        let message = match transclusion.issue? {
+            TransclusionIssue::NotFound => format!("{reference}{location} does not match any file"),
+            TransclusionIssue::MissingSection => format!(
+                "{reference}{location} names a section that is not a heading of {}",
+                transclusion.path.as_deref().unwrap_or_default()
+            ),
+            TransclusionIssue::Cycle => {
+                format!("{reference}{location} would include itself and was skipped")
+            }
+            TransclusionIssue::ParseError => format!(
+                "{reference}{location} could not be parsed: {}",
+                transclusion.path.as_deref().unwrap_or_default()
+            ),
+            TransclusionIssue::OutsideRoot => format!(
+                "{reference}{location} resolves outside the project root and was not read"
+            ),
+            TransclusionIssue::NotMarkdown => {
+                format!("{reference}{location} is not a .md or .mdx file and was not read")
+            }
+            TransclusionIssue::DroppedEsm => format!(
+                "{reference}{location} has import/export statements that were left out; components it imports may be unresolved"
+            ),
+        };
+        Some(warning(
+            warning_type,
+            cx.source_line(transclusion.line),
+            message,
+        ))
+    }));
+    warnings.extend(result.code_snippets.iter().filter_map(|snippet| {
+        let mut file = snippet.file.clone();
+        if let Some(region) = &snippet.region {
+            file.push('#');
+            file.push_str(region);
+        }
+        let message = match snippet.issue? {
+            SnippetIssue::MissingFile => {
+                format!(
+                    "Snippet file {} not found at {}",
+                    snippet.file, snippet.path
+                )
+            }
+            SnippetIssue::MissingRegion => format!(
+                "Snippet region #{} not found in {}",
+                snippet.region.as_deref().unwrap_or_default(),
+                snippet.path
+            ),
+            SnippetIssue::InvalidLines => format!(
+                "Snippet lines={} is not a valid range of {file}",
+                snippet.lines.as_deref().unwrap_or_default()
+            ),
+            SnippetIssue::OutsideRoot => format!(
+                "Snippet file {} resolves outside the project root and was not read",
+                snippet.file
+            ),
+        };
+        Some(warning(
+            "missing_snippet",
+            cx.source_line(snippet.line),
+            message,
+        ))
+    }));
+    if declares_toc(cx.declared) {
+        warnings.push(warning(
+            "export_collision",
+            declaration_line(cx.source, "toc").unwrap_or(0),
+            "The document declares `toc`; the generated table of contents export was skipped"
+                .to_string(),
+        ));
+    }
+    warnings.sort_by_key(|warning| warning.line);
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_fence::collect_root_statements;
+    use crate::registry::defaults::default_starlight_registry;
+    use crate::renderer::mdast::{Options, to_blocks};
+
+    #[test]
+    fn warnings_use_source_lines() {
+        let source = "---\ntitle: Guide\n---\nimport Local from './Local.astro';\n\n<Local />\n\n<Missing />\n";
+        let body_start = source.find("import").unwrap();
+        let body = &source[body_start..];
+        let (hoisted, body_lines) = collect_root_statements(body);
+        let line_map = body_line_map(body, &body_lines, 3);
+        let result = to_blocks(&body_lines.join("\n"), &Options::default()).unwrap();
+        let declared = hoisted.imports;
+        let registry = default_starlight_registry();
+        let renames = BTreeMap::new();
+        let warnings = compile_warnings(
+            &result,
+            &WarningContext {
+                source,
+                line_map: &line_map,
+                declared: &declared,
+                registry: &registry,
+                component_renames: &renames,
+                icon_component: None,
+            },
+        );
+        assert_eq!(
+            warnings,
+            vec![CompileWarning {
+                warning_type: "unknown_component".to_string(),
+                line: 8,
+                message: "Component <Missing> is used but neither imported nor registered"
+                    .to_string(),
+            }]
+        );
+    }
+}
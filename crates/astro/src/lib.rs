@@ -3,6 +3,8 @@
 
 /// Code generation utilities for Astro/MDX output.
 pub mod codegen;
+/// Compile warnings shared by the bindings.
+pub mod diagnostics;
 /// Component registry for directive/component mappings.
 pub mod registry;
 /// Rendering layer (MDAST-based block renderer).
@@ -11,9 +13,13 @@ pub mod renderer;
 pub mod transform;
 
 pub use registry::{
-    ComponentDefinition, DirectiveMapping, PropSource, RegistryConfig, SlotNormalization,
+    ComponentDefinition, DirectiveMapping, ElementMapping, FenceMapping, PropSource,
+    RegistryConfig, RegistryError, RegistryRemovals, SlotNormalization, SlotRouting,
 };
 pub use renderer::mdast::{
-    BlocksResult, HeadingEntry, Options as MdastOptions, PropValue, RenderBlock, to_blocks,
+    AliasUsage, AssetKind, AssetReference, BlocksResult, CodeSnippet, ComponentUsage, HeadingEntry,
+    LinkReference, MathIssue, MissingImage, Options as MdastOptions, PropValue, RenderBlock,
+    SnippetIssue, TocEntry, Transclusion, TransclusionIssue, TransclusionKind, UnresolvedWikiLink,
+    to_blocks,
 };
 pub use transform::{code_fence, directives};
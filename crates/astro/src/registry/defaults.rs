@@ -32,46 +32,55 @@ pub fn default_starlight_registry() -> RegistryConfig {
                 name: "Aside".to_string(),
                 module_path: "@astrojs/starlight/components".to_string(),
                 export_type: "named".to_string(),
+                ..Default::default()
             },
             ComponentDefinition {
                 name: "Tabs".to_string(),
                 module_path: "@astrojs/starlight/components".to_string(),
                 export_type: "named".to_string(),
+                ..Default::default()
             },
             ComponentDefinition {
                 name: "TabItem".to_string(),
                 module_path: "@astrojs/starlight/components".to_string(),
                 export_type: "named".to_string(),
+                ..Default::default()
             },
             ComponentDefinition {
                 name: "Steps".to_string(),
                 module_path: "@astrojs/starlight/components".to_string(),
                 export_type: "named".to_string(),
+                ..Default::default()
             },
             ComponentDefinition {
                 name: "FileTree".to_string(),
                 module_path: "@astrojs/starlight/components".to_string(),
                 export_type: "named".to_string(),
+                ..Default::default()
             },
             ComponentDefinition {
                 name: "CardGrid".to_string(),
                 module_path: "@astrojs/starlight/components".to_string(),
                 export_type: "named".to_string(),
+                ..Default::default()
             },
             ComponentDefinition {
                 name: "LinkCard".to_string(),
                 module_path: "@astrojs/starlight/components".to_string(),
                 export_type: "named".to_string(),
+                ..Default::default()
             },
             ComponentDefinition {
                 name: "LinkButton".to_string(),
                 module_path: "@astrojs/starlight/components".to_string(),
                 export_type: "named".to_string(),
+                ..Default::default()
             },
             ComponentDefinition {
                 name: "Card".to_string(),
                 module_path: "@astrojs/starlight/components".to_string(),
                 export_type: "named".to_string(),
+                ..Default::default()
             },
         ],
         directive_mappings: vec![
@@ -87,13 +96,26 @@ pub fn default_starlight_registry() -> RegistryConfig {
                 component: "Steps".to_string(),
                 strategy: "wrap_in_ol".to_string(),
                 wrapper_class: None,
+                ..Default::default()
             },
             SlotNormalization {
                 component: "FileTree".to_string(),
                 strategy: "wrap_in_ul".to_string(),
                 wrapper_class: None,
+                ..Default::default()
             },
         ],
+        ..Default::default()
+    }
+}
+
+/// Looks up a built-in registry by the name used in `extends`.
+///
+/// Currently only `"starlight"` is available.
+pub fn builtin_registry(name: &str) -> Option<RegistryConfig> {
+    match name {
+        "starlight" => Some(default_starlight_registry()),
+        _ => None,
     }
 }
 
@@ -104,7 +126,7 @@ fn create_aside_mapping(directive: &str) -> DirectiveMapping {
         "type".to_string(),
         PropSource {
             source: "directive_name".to_string(),
-            value: None,
+            ..Default::default()
         },
     );
 
@@ -112,6 +134,7 @@ fn create_aside_mapping(directive: &str) -> DirectiveMapping {
         directive: directive.to_string(),
         component: "Aside".to_string(),
         inject_props: Some(inject_props),
+        ..Default::default()
     }
 }
 
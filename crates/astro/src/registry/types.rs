@@ -4,20 +4,100 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Configuration for the component registry passed from JavaScript.
+///
+/// A registry can either be complete or a delta on top of a built-in base:
+///
+/// ```json
+/// {
+///   "extends": "starlight",
+///   "components": [{ "name": "Badge", "modulePath": "~/components", "exportType": "named" }],
+///   "remove": { "slotNormalizations": ["FileTree"] }
+/// }
+/// ```
+///
+/// Call [`RegistryConfig::resolve`] to turn a delta into a complete registry.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct RegistryConfig {
+    /// Name of a built-in registry to start from (e.g., "starlight").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
     /// Available components.
+    #[serde(default)]
     pub components: Vec<ComponentDefinition>,
     /// Directive to component mappings.
+    #[serde(default)]
     pub directive_mappings: Vec<DirectiveMapping>,
     /// Slot normalization rules for components like Steps, FileTree.
     #[serde(default)]
     pub slot_normalizations: Vec<SlotNormalization>,
+    /// Standard Markdown elements rendered through components instead of HTML.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub element_mappings: Vec<ElementMapping>,
+    /// Code fence languages rendered through components instead of code blocks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fence_mappings: Vec<FenceMapping>,
+    /// Entries to drop from the base registry when this config is merged onto it.
+    #[serde(default, skip_serializing_if = "RegistryRemovals::is_empty")]
+    pub remove: RegistryRemovals,
+}
+
+/// Keys of registry entries to remove during a merge.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryRemovals {
+    /// Component names to remove.
+    #[serde(default)]
+    pub components: Vec<String>,
+    /// Directive names whose mappings should be removed.
+    #[serde(default)]
+    pub directives: Vec<String>,
+    /// Component names whose slot normalizations should be removed.
+    #[serde(default)]
+    pub slot_normalizations: Vec<String>,
+    /// Element kinds whose mappings should be removed.
+    #[serde(default)]
+    pub elements: Vec<String>,
+    /// Fence languages whose mappings should be removed.
+    #[serde(default)]
+    pub fences: Vec<String>,
+}
+
+impl RegistryRemovals {
+    /// Returns true when no removals are listed.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+            && self.directives.is_empty()
+            && self.slot_normalizations.is_empty()
+            && self.elements.is_empty()
+            && self.fences.is_empty()
+    }
+}
+
+/// Errors raised while loading or resolving a registry configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistryError {
+    /// The registry JSON did not match the expected shape.
+    Invalid(String),
+    /// `extends` named a base registry that does not exist.
+    UnknownBase(String),
 }
 
+impl std::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::Invalid(msg) => write!(f, "Invalid registry config: {}", msg),
+            RegistryError::UnknownBase(name) => {
+                write!(f, "Unknown base registry '{}' in `extends`", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
 /// A single component definition.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ComponentDefinition {
     /// Component name (e.g., "Aside", "Tabs").
@@ -26,6 +106,15 @@ pub struct ComponentDefinition {
     pub module_path: String,
     /// Export type: "named" or "default".
     pub export_type: String,
+    /// Former names that still resolve to this component (e.g., "Callout" for
+    /// "Aside"). Tags and directive mappings using an alias are rewritten to
+    /// `name`, and each use is reported as deprecated.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Migration note reported with each use of an alias
+    /// (e.g., "Callout was renamed to Aside in v2").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
 }
 
 /// Deprecated alias for `ComponentDefinition`.
@@ -39,7 +128,7 @@ pub type ComponentDef = ComponentDefinition;
 /// Some components (like Starlight's Steps and FileTree) require their slot content
 /// to be wrapped in specific HTML structures. This configuration allows the registry
 /// to define these requirements without hardcoding them in the core renderer.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct SlotNormalization {
     /// Component name this normalization applies to (e.g., "Steps", "FileTree").
@@ -47,14 +136,72 @@ pub struct SlotNormalization {
     /// Normalization strategy to apply.
     /// - "wrap_in_ol": Wrap content in a single `<ol>` element
     /// - "wrap_in_ul": Wrap content in a single `<ul>` element
+    /// - "wrap_in_div": Wrap content in a single `<div>` element
+    /// - "split_by_heading": Turn each heading section (`<h2>` unless
+    ///   `heading_level` says otherwise) into a `child_component`
+    /// - "list_to_items": Turn each top-level `<li>` into a `child_component`
     pub strategy: String,
     /// Optional CSS class to add to the wrapper element.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub wrapper_class: Option<String>,
+    /// Child component created for each section or item (e.g., "TabItem", "Card").
+    /// Used by `split_by_heading` and `list_to_items`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub child_component: Option<String>,
+    /// Child prop that receives the section's heading text or the item's
+    /// leading text (e.g., "label", "title"). When unset, nothing is lifted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub child_prop: Option<String>,
+    /// Heading level that starts a section for `split_by_heading` (1-6).
+    /// Defaults to 2.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading_level: Option<u8>,
+}
+
+/// Slot normalization strategies understood by the renderer.
+pub const SLOT_NORMALIZATION_STRATEGIES: &[&str] = &[
+    "wrap_in_ol",
+    "wrap_in_ul",
+    "wrap_in_div",
+    "split_by_heading",
+    "list_to_items",
+];
+
+impl SlotNormalization {
+    /// Heading level used by `split_by_heading`.
+    pub fn heading_level(&self) -> u8 {
+        self.heading_level.unwrap_or(2)
+    }
+
+    /// Checks that the strategy is known and has the options it needs.
+    pub fn validate(&self) -> Result<(), RegistryError> {
+        let component = &self.component;
+        let strategy = self.strategy.as_str();
+        if !SLOT_NORMALIZATION_STRATEGIES.contains(&strategy) {
+            return Err(RegistryError::Invalid(format!(
+                "slot normalization for {component} has unknown strategy \"{strategy}\""
+            )));
+        }
+        if matches!(strategy, "split_by_heading" | "list_to_items")
+            && self.child_component.is_none()
+        {
+            return Err(RegistryError::Invalid(format!(
+                "slot normalization for {component} uses \"{strategy}\" without childComponent"
+            )));
+        }
+        if let Some(level) = self.heading_level
+            && !(1..=6).contains(&level)
+        {
+            return Err(RegistryError::Invalid(format!(
+                "slot normalization for {component} has headingLevel {level} (expected 1-6)"
+            )));
+        }
+        Ok(())
+    }
 }
 
 /// Mapping from a directive name to a component.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct DirectiveMapping {
     /// Directive name (e.g., "note", "tip").
@@ -64,31 +211,323 @@ pub struct DirectiveMapping {
     /// Optional props to inject when mapping.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub inject_props: Option<HashMap<String, PropSource>>,
+    /// Optional rules routing parts of the directive into named slots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slots: Option<SlotRouting>,
+}
+
+/// Mapping from a standard Markdown element to a component, like MDX's
+/// `components` option but resolved at compile time.
+///
+/// ```json
+/// { "element": "img", "component": "Image" }
+/// ```
+///
+/// The renderer emits the component with the node's attributes as props
+/// (`src`/`alt`/`title` for `img`, `href`/`title` for `a`, `id` for
+/// headings, `code`/`lang`/`meta` for `pre`) and its content as the slot.
+/// A `table` component receives the rendered `<table>` as its slot.
+/// Only Markdown syntax is mapped; raw HTML and JSX elements in the source
+/// (`<img>`, `<a>`, ...) are passed through unchanged.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementMapping {
+    /// Element kind: "img", "a", "table", "h1".."h6", "blockquote" or "pre".
+    pub element: String,
+    /// Component rendered in place of the element (e.g., "Image").
+    pub component: String,
+}
+
+/// Mapping from a code fence language to a component, for fences whose
+/// source is rendered by a component (diagrams, charts) rather than shown.
+///
+/// ```json
+/// { "lang": "mermaid", "component": "Mermaid" }
+/// ```
+///
+/// The fence renders as the component with its source as the `code` prop
+/// (`<Mermaid code={"graph TD; A-->B"} />`), plus `lang` and `meta` props.
+/// Fence mappings take precedence over a `pre` element mapping.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FenceMapping {
+    /// Fence language, matched case-insensitively (e.g., "mermaid", "d2").
+    pub lang: String,
+    /// Component rendered in place of the code block (e.g., "Mermaid").
+    pub component: String,
+}
+
+/// Named slot routing rules for a directive's content.
+///
+/// ```json
+/// { "bracketTitle": "title", "firstParagraph": "lead", "childDirectives": true }
+/// ```
+///
+/// Routed content is emitted as `<Fragment slot="...">` children of the
+/// component, which codegen renders as named slots.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotRouting {
+    /// Slot receiving the bracket title (`:::note[Title]`) instead of the `title` prop.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bracket_title: Option<String>,
+    /// Slot receiving the directive's first heading, when it is the first child.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_heading: Option<String>,
+    /// Slot receiving the directive's first paragraph, when it is the first child.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_paragraph: Option<String>,
+    /// Whether nested directives carrying a `slot` attribute
+    /// (`:::footer{slot=footer}`) are routed into that named slot.
+    #[serde(default)]
+    pub child_directives: bool,
 }
 
 /// Source for an injected prop value.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Sources are evaluated by the renderer for each directive occurrence:
+/// - `"directive_name"`: the directive's name (e.g., `note`)
+/// - `"bracket_title"`: the bracket title (`:::note[Title]`)
+/// - `"literal"`: the fixed `value`
+/// - `"attribute"`: the directive attribute `name` (`:::note{icon=star}`)
+/// - `"frontmatter"`: the dotted `path` into the document frontmatter (e.g., `hero.title`)
+/// - `"heading_slug"`: the slug of the heading whose section contains the directive
+/// - `"index"`: the zero-based ordinal of the directive among its sibling directives
+///
+/// Missing attribute, frontmatter and heading values fall back to `default`;
+/// without one, the prop is omitted.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PropSource {
-    /// Source type: "directive_name", "bracket_title", or "literal".
+    /// Source type (see above).
     pub source: String,
     /// Literal value when source is "literal".
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
+    /// Attribute name when source is "attribute".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Dotted frontmatter path when source is "frontmatter".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Fallback value when the source yields nothing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
 }
 
 impl RegistryConfig {
+    /// Deserializes a registry from JSON and resolves its `extends` base.
+    pub fn from_json(value: serde_json::Value) -> Result<Self, RegistryError> {
+        let config: RegistryConfig =
+            serde_json::from_value(value).map_err(|e| RegistryError::Invalid(e.to_string()))?;
+        config.resolve()
+    }
+
+    /// Resolves `extends` into a complete registry.
+    ///
+    /// The base registry is loaded, then `self` is merged on top of it with
+    /// [`RegistryConfig::merge`]. Without `extends`, `self` is returned as-is
+    /// (minus its removal list, which has nothing to apply to).
+    ///
+    /// Slot normalizations are validated after resolution, so a registry with
+    /// an unknown strategy or a missing `childComponent` fails at load instead
+    /// of leaving slots silently unnormalized.
+    pub fn resolve(mut self) -> Result<Self, RegistryError> {
+        let resolved = match self.extends.take() {
+            Some(base_name) => {
+                let mut base = super::defaults::builtin_registry(&base_name)
+                    .ok_or(RegistryError::UnknownBase(base_name))?;
+                base.merge(self);
+                base
+            }
+            None => {
+                self.remove = RegistryRemovals::default();
+                self
+            }
+        };
+        for normalization in &resolved.slot_normalizations {
+            normalization.validate()?;
+        }
+        Ok(resolved)
+    }
+
+    /// Merges `overlay` into this registry. Overlay entries win.
+    ///
+    /// Precedence:
+    /// 1. Removals listed in `overlay.remove` are applied first.
+    /// 2. Overlay entries replace existing entries with the same key in place
+    ///    (components by `name`, directive mappings by `directive`, slot
+    ///    normalizations by `component`, element and fence mappings by
+    ///    `element` and `lang`).
+    /// 3. Overlay entries with new keys are appended in overlay order.
+    pub fn merge(&mut self, overlay: RegistryConfig) {
+        for name in &overlay.remove.components {
+            self.remove_component(name);
+        }
+        for directive in &overlay.remove.directives {
+            self.remove_directive_mapping(directive);
+        }
+        for component in &overlay.remove.slot_normalizations {
+            self.remove_slot_normalization(component);
+        }
+        for element in &overlay.remove.elements {
+            self.remove_element_mapping(element);
+        }
+        for lang in &overlay.remove.fences {
+            self.remove_fence_mapping(lang);
+        }
+
+        for component in overlay.components {
+            upsert(&mut self.components, component, |c| c.name.clone());
+        }
+        for mapping in overlay.directive_mappings {
+            upsert(&mut self.directive_mappings, mapping, |m| {
+                m.directive.clone()
+            });
+        }
+        for normalization in overlay.slot_normalizations {
+            upsert(&mut self.slot_normalizations, normalization, |n| {
+                n.component.clone()
+            });
+        }
+        for mapping in overlay.element_mappings {
+            upsert(&mut self.element_mappings, mapping, |m| m.element.clone());
+        }
+        for mapping in overlay.fence_mappings {
+            upsert(&mut self.fence_mappings, mapping, |m| {
+                m.lang.to_ascii_lowercase()
+            });
+        }
+    }
+
+    /// Adds entries from `other` whose keys are not already present. Existing entries win.
+    ///
+    /// Unlike [`RegistryConfig::merge`], removals in `other` are ignored.
+    pub fn extend(&mut self, other: RegistryConfig) {
+        for component in other.components {
+            if self.get_component(&component.name).is_none() {
+                self.components.push(component);
+            }
+        }
+        for mapping in other.directive_mappings {
+            if !self.is_supported_directive(&mapping.directive) {
+                self.directive_mappings.push(mapping);
+            }
+        }
+        for normalization in other.slot_normalizations {
+            if self
+                .get_slot_normalization(&normalization.component)
+                .is_none()
+            {
+                self.slot_normalizations.push(normalization);
+            }
+        }
+        for mapping in other.element_mappings {
+            if self.get_element_component(&mapping.element).is_none() {
+                self.element_mappings.push(mapping);
+            }
+        }
+        for mapping in other.fence_mappings {
+            if self.get_fence_component(&mapping.lang).is_none() {
+                self.fence_mappings.push(mapping);
+            }
+        }
+    }
+
+    /// Removes a component definition by name, returning it if present.
+    ///
+    /// Directive mappings that target the component are left untouched.
+    pub fn remove_component(&mut self, name: &str) -> Option<ComponentDefinition> {
+        let idx = self.components.iter().position(|c| c.name == name)?;
+        Some(self.components.remove(idx))
+    }
+
+    /// Removes the mapping for a directive name, returning it if present.
+    pub fn remove_directive_mapping(&mut self, directive: &str) -> Option<DirectiveMapping> {
+        let idx = self
+            .directive_mappings
+            .iter()
+            .position(|m| m.directive == directive)?;
+        Some(self.directive_mappings.remove(idx))
+    }
+
+    /// Removes the slot normalization for a component, returning it if present.
+    pub fn remove_slot_normalization(&mut self, component: &str) -> Option<SlotNormalization> {
+        let idx = self
+            .slot_normalizations
+            .iter()
+            .position(|n| n.component == component)?;
+        Some(self.slot_normalizations.remove(idx))
+    }
+
+    /// Removes the mapping for an element kind, returning it if present.
+    pub fn remove_element_mapping(&mut self, element: &str) -> Option<ElementMapping> {
+        let idx = self
+            .element_mappings
+            .iter()
+            .position(|m| m.element == element)?;
+        Some(self.element_mappings.remove(idx))
+    }
+
+    /// Removes the mapping for a fence language, returning it if present.
+    pub fn remove_fence_mapping(&mut self, lang: &str) -> Option<FenceMapping> {
+        let idx = self
+            .fence_mappings
+            .iter()
+            .position(|m| m.lang.eq_ignore_ascii_case(lang))?;
+        Some(self.fence_mappings.remove(idx))
+    }
+
+    /// Get a component definition by name.
+    pub fn get_component(&self, name: &str) -> Option<&ComponentDefinition> {
+        self.components.iter().find(|c| c.name == name)
+    }
+
+    /// Finds the component that lists `name` among its aliases.
+    pub fn canonical_component(&self, name: &str) -> Option<&ComponentDefinition> {
+        self.components
+            .iter()
+            .find(|c| c.aliases.iter().any(|a| a == name))
+    }
+
+    /// Returns the canonical name for `name`, resolving aliases.
+    pub fn canonical_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.canonical_component(name)
+            .map(|c| c.name.as_str())
+            .unwrap_or(name)
+    }
+
+    /// Returns the names of all mapped directives.
+    pub fn directive_names(&self) -> Vec<&str> {
+        self.directive_mappings
+            .iter()
+            .map(|m| m.directive.as_str())
+            .collect()
+    }
+
+    /// Get directive names whose mapping routes slot-carrying child
+    /// directives ([`SlotRouting::child_directives`]).
+    pub fn slot_parent_directive_names(&self) -> Vec<&str> {
+        self.directive_mappings
+            .iter()
+            .filter(|m| m.slots.as_ref().is_some_and(|slots| slots.child_directives))
+            .map(|m| m.directive.as_str())
+            .collect()
+    }
+
     /// Check if a directive name is supported.
     pub fn is_supported_directive(&self, name: &str) -> bool {
         self.directive_mappings.iter().any(|m| m.directive == name)
     }
 
     /// Get the component name for a directive.
+    ///
+    /// Mappings that target an alias resolve to the canonical component.
     pub fn get_directive_component(&self, directive: &str) -> Option<&str> {
         self.directive_mappings
             .iter()
             .find(|m| m.directive == directive)
-            .map(|m| m.component.as_str())
+            .map(|m| self.canonical_name(&m.component))
     }
 
     /// Get the module path for a component.
@@ -106,10 +545,339 @@ impl RegistryConfig {
             .find(|m| m.directive == directive)
     }
 
+    /// Get the component an element kind (e.g., "img", "h2") renders through.
+    ///
+    /// Like directive mappings, aliases resolve to the canonical component.
+    pub fn get_element_component(&self, element: &str) -> Option<&str> {
+        self.element_mappings
+            .iter()
+            .find(|m| m.element == element)
+            .map(|m| self.canonical_name(&m.component))
+    }
+
+    /// Get the component a code fence language (e.g., "mermaid") renders through.
+    ///
+    /// Like directive mappings, aliases resolve to the canonical component.
+    pub fn get_fence_component(&self, lang: &str) -> Option<&str> {
+        self.fence_mappings
+            .iter()
+            .find(|m| m.lang.eq_ignore_ascii_case(lang))
+            .map(|m| self.canonical_name(&m.component))
+    }
+
+    /// Returns the canonical components of all element and fence mappings.
+    pub fn mapped_element_components(&self) -> impl Iterator<Item = &str> {
+        self.element_mappings
+            .iter()
+            .map(|m| m.component.as_str())
+            .chain(self.fence_mappings.iter().map(|m| m.component.as_str()))
+            .map(|component| self.canonical_name(component))
+    }
+
     /// Get slot normalization configuration for a component.
     pub fn get_slot_normalization(&self, component: &str) -> Option<&SlotNormalization> {
         self.slot_normalizations
             .iter()
             .find(|n| n.component == component)
     }
+
+    /// Child components that slot normalizations add inside `components`
+    /// (e.g., `TabItem` for Tabs split by heading), without duplicates.
+    pub fn slot_child_components<'n>(
+        &self,
+        components: impl IntoIterator<Item = &'n str>,
+    ) -> Vec<&str> {
+        let mut children: Vec<&str> = Vec::new();
+        for component in components {
+            if let Some(child) = self
+                .get_slot_normalization(component)
+                .and_then(|n| n.child_component.as_deref())
+                && !children.contains(&child)
+            {
+                children.push(child);
+            }
+        }
+        children
+    }
+}
+
+/// Replaces the entry with the same key in place, or appends it.
+fn upsert<T>(entries: &mut Vec<T>, entry: T, key: impl Fn(&T) -> String) {
+    let entry_key = key(&entry);
+    match entries.iter_mut().find(|e| key(e) == entry_key) {
+        Some(existing) => *existing = entry,
+        None => entries.push(entry),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::defaults::default_starlight_registry;
+
+    fn component(name: &str, module_path: &str) -> ComponentDefinition {
+        ComponentDefinition {
+            name: name.to_string(),
+            module_path: module_path.to_string(),
+            export_type: "named".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merge_overrides_existing_and_appends_new() {
+        let mut registry = default_starlight_registry();
+        let aside_idx = registry
+            .components
+            .iter()
+            .position(|c| c.name == "Aside")
+            .unwrap();
+
+        registry.merge(RegistryConfig {
+            components: vec![component("Aside", "~/Aside"), component("Badge", "~/Badge")],
+            ..Default::default()
+        });
+
+        assert_eq!(registry.components[aside_idx].module_path, "~/Aside");
+        assert_eq!(registry.components.last().unwrap().name, "Badge");
+        assert_eq!(
+            registry.get_component_module("Tabs"),
+            Some("@astrojs/starlight/components")
+        );
+    }
+
+    #[test]
+    fn merge_applies_removals_before_entries() {
+        let mut registry = default_starlight_registry();
+        registry.merge(RegistryConfig {
+            slot_normalizations: vec![SlotNormalization {
+                component: "Steps".to_string(),
+                strategy: "wrap_in_ul".to_string(),
+                wrapper_class: None,
+                ..Default::default()
+            }],
+            remove: RegistryRemovals {
+                directives: vec!["danger".to_string()],
+                slot_normalizations: vec!["Steps".to_string(), "FileTree".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        assert!(!registry.is_supported_directive("danger"));
+        assert!(registry.get_slot_normalization("FileTree").is_none());
+        // Re-added by the overlay after removal.
+        assert_eq!(
+            registry.get_slot_normalization("Steps").unwrap().strategy,
+            "wrap_in_ul"
+        );
+    }
+
+    #[test]
+    fn extend_keeps_existing_entries() {
+        let mut registry = default_starlight_registry();
+        registry.extend(RegistryConfig {
+            components: vec![component("Aside", "~/Aside"), component("Badge", "~/Badge")],
+            ..Default::default()
+        });
+
+        assert_eq!(
+            registry.get_component_module("Aside"),
+            Some("@astrojs/starlight/components")
+        );
+        assert_eq!(registry.get_component_module("Badge"), Some("~/Badge"));
+    }
+
+    #[test]
+    fn remove_component_returns_removed_entry() {
+        let mut registry = default_starlight_registry();
+        let removed = registry.remove_component("Card").unwrap();
+        assert_eq!(removed.name, "Card");
+        assert!(registry.get_component("Card").is_none());
+        assert!(registry.remove_component("Card").is_none());
+    }
+
+    #[test]
+    fn from_json_resolves_extends_delta() {
+        let registry = RegistryConfig::from_json(serde_json::json!({
+            "extends": "starlight",
+            "components": [{ "name": "Box", "modulePath": "~/Box", "exportType": "default" }],
+            "directiveMappings": [{ "directive": "note", "component": "Box" }],
+            "remove": { "components": ["LinkButton"] }
+        }))
+        .unwrap();
+
+        assert!(registry.extends.is_none());
+        assert!(registry.remove.is_empty());
+        assert_eq!(registry.get_directive_component("note"), Some("Box"));
+        assert_eq!(registry.get_directive_component("tip"), Some("Aside"));
+        assert!(registry.get_component("LinkButton").is_none());
+        assert_eq!(registry.get_component_module("Box"), Some("~/Box"));
+    }
+
+    #[test]
+    fn slot_child_components_lists_distinct_children() {
+        let registry = RegistryConfig::from_json(serde_json::json!({
+            "extends": "starlight",
+            "slotNormalizations": [
+                { "component": "Tabs", "strategy": "split_by_heading", "childComponent": "TabItem" },
+                { "component": "SyncedTabs", "strategy": "split_by_heading", "childComponent": "TabItem" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(
+            registry.slot_child_components(["Steps", "Tabs", "Card", "SyncedTabs"]),
+            vec!["TabItem"]
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_invalid_slot_normalizations() {
+        let invalid = [
+            (
+                serde_json::json!({ "component": "Tabs", "strategy": "split_by_heading" }),
+                "without childComponent",
+            ),
+            (
+                serde_json::json!({ "component": "Cards", "strategy": "list_to_items" }),
+                "without childComponent",
+            ),
+            (
+                serde_json::json!({ "component": "Box", "strategy": "wrap_in_span" }),
+                "unknown strategy",
+            ),
+            (
+                serde_json::json!({
+                    "component": "Tabs",
+                    "strategy": "split_by_heading",
+                    "childComponent": "TabItem",
+                    "headingLevel": 7
+                }),
+                "headingLevel 7",
+            ),
+        ];
+        for (normalization, expected) in invalid {
+            for extends in [None, Some("starlight")] {
+                let err = RegistryConfig::from_json(serde_json::json!({
+                    "extends": extends,
+                    "slotNormalizations": [normalization.clone()]
+                }))
+                .unwrap_err();
+                assert!(err.to_string().contains(expected), "{err}");
+            }
+        }
+
+        let registry = RegistryConfig::from_json(serde_json::json!({
+            "slotNormalizations": [{
+                "component": "Tabs",
+                "strategy": "split_by_heading",
+                "childComponent": "TabItem",
+                "headingLevel": 3
+            }]
+        }))
+        .unwrap();
+        assert_eq!(
+            registry
+                .get_slot_normalization("Tabs")
+                .unwrap()
+                .heading_level(),
+            3
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_unknown_base() {
+        let err =
+            RegistryConfig::from_json(serde_json::json!({ "extends": "docusaurus" })).unwrap_err();
+        assert_eq!(err, RegistryError::UnknownBase("docusaurus".to_string()));
+    }
+
+    #[test]
+    fn aliases_resolve_to_canonical_component() {
+        let registry = RegistryConfig::from_json(serde_json::json!({
+            "extends": "starlight",
+            "components": [{
+                "name": "Aside",
+                "modulePath": "@astrojs/starlight/components",
+                "exportType": "named",
+                "aliases": ["Callout"],
+                "deprecated": "Callout was renamed to Aside"
+            }],
+            "directiveMappings": [{ "directive": "callout", "component": "Callout" }]
+        }))
+        .unwrap();
+
+        assert_eq!(registry.canonical_name("Callout"), "Aside");
+        assert_eq!(registry.canonical_name("Tabs"), "Tabs");
+        assert!(registry.get_component("Callout").is_none());
+        assert_eq!(
+            registry
+                .canonical_component("Callout")
+                .unwrap()
+                .deprecated
+                .as_deref(),
+            Some("Callout was renamed to Aside")
+        );
+        assert_eq!(registry.get_directive_component("callout"), Some("Aside"));
+    }
+
+    #[test]
+    fn element_mappings_merge_and_remove() {
+        let mut registry = RegistryConfig::from_json(serde_json::json!({
+            "extends": "starlight",
+            "elementMappings": [
+                { "element": "img", "component": "Image" },
+                { "element": "table", "component": "Table" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(registry.get_element_component("img"), Some("Image"));
+
+        registry.merge(RegistryConfig {
+            element_mappings: vec![ElementMapping {
+                element: "img".to_string(),
+                component: "Picture".to_string(),
+            }],
+            remove: RegistryRemovals {
+                elements: vec!["table".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        assert_eq!(registry.get_element_component("img"), Some("Picture"));
+        assert!(registry.get_element_component("table").is_none());
+    }
+
+    #[test]
+    fn fence_mappings_match_case_insensitively_and_merge() {
+        let mut registry = RegistryConfig::from_json(serde_json::json!({
+            "extends": "starlight",
+            "fenceMappings": [
+                { "lang": "mermaid", "component": "Mermaid" },
+                { "lang": "d2", "component": "Diagram" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(registry.get_fence_component("Mermaid"), Some("Mermaid"));
+        assert!(registry.get_fence_component("ts").is_none());
+
+        registry.merge(RegistryConfig {
+            fence_mappings: vec![FenceMapping {
+                lang: "MERMAID".to_string(),
+                component: "Chart".to_string(),
+            }],
+            remove: RegistryRemovals {
+                fences: vec!["D2".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        assert_eq!(registry.fence_mappings.len(), 1);
+        assert_eq!(registry.get_fence_component("mermaid"), Some("Chart"));
+        assert!(registry.get_fence_component("d2").is_none());
+        assert_eq!(
+            registry.mapped_element_components().collect::<Vec<_>>(),
+            vec!["Chart"]
+        );
+    }
 }
@@ -0,0 +1,891 @@
+//! Metadata collection passes over the parsed mdast.
+//!
+//! These walks run alongside rendering and gather document-level facts
+//! (e.g., which JSX components are referenced) without affecting output.
+
+use super::Options;
+use super::render::{directive_slot_name, extract_text_from_nodes};
+use super::types::{
+    AliasUsage, AssetKind, AssetReference, CodeSnippet, ComponentUsage, LinkReference, MathIssue,
+    MissingImage, Transclusion, TransclusionKind, UnresolvedWikiLink,
+};
+use crate::RegistryConfig;
+use crate::transform::code_fence::{FencePhase, FenceState, advance_fence_state};
+use crate::transform::links::{ExternalLinkOptions, rewrite_markdown_link};
+use crate::transform::tex::{TexIssue, expand_macros, validate_tex};
+use crate::transform::wikilinks::{WikiLink, WikiLinkResolver, find_wikilinks};
+use markdown::mdast::{AttributeContent, AttributeValue, Node};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Collects every PascalCase JSX element name referenced in the tree.
+///
+/// Each name is reported once, at its first occurrence. Member expressions
+/// like `<Tabs.Item>` are reported by their root binding (`Tabs`), and the
+/// built-in `Fragment` is skipped.
+pub fn collect_component_usages(tree: &Node) -> Vec<ComponentUsage> {
+    let mut usages = Vec::new();
+    walk_components(tree, &mut usages);
+    usages
+}
+
+fn walk_components(node: &Node, usages: &mut Vec<ComponentUsage>) {
+    let name = match node {
+        Node::MdxJsxFlowElement(elem) => elem.name.as_deref(),
+        Node::MdxJsxTextElement(elem) => elem.name.as_deref(),
+        _ => None,
+    };
+
+    if let Some(name) = name.and_then(component_binding)
+        && !usages.iter().any(|u| u.name == name)
+    {
+        let (line, column) = node
+            .position()
+            .map(|p| (p.start.line, p.start.column))
+            .unwrap_or((0, 0));
+        usages.push(ComponentUsage {
+            name: name.to_string(),
+            line,
+            column,
+        });
+    }
+
+    if let Some(children) = node.children() {
+        for child in children {
+            walk_components(child, usages);
+        }
+    }
+}
+
+/// Collects the registry components that directives in the tree render to.
+///
+/// Names are canonical (before any import alias) and listed once each, in
+/// first-use order. Directives without a mapping fall back to `Aside`,
+/// matching the renderer.
+pub fn collect_directive_components(tree: &Node, registry: &RegistryConfig) -> Vec<String> {
+    let mut components = Vec::new();
+    walk_directives(tree, registry, false, &mut components);
+    components
+}
+
+/// `routes_slots` is set for children of a directive whose mapping routes
+/// `{slot=...}` child directives; those render as named slots, not components.
+fn walk_directives(
+    node: &Node,
+    registry: &RegistryConfig,
+    routes_slots: bool,
+    components: &mut Vec<String>,
+) {
+    let element = match node {
+        Node::MdxJsxFlowElement(elem) => Some((elem.name.as_deref(), &elem.attributes)),
+        Node::MdxJsxTextElement(elem) => Some((elem.name.as_deref(), &elem.attributes)),
+        _ => None,
+    };
+    let mut child_routes_slots = false;
+    if let Some((Some("mf-directive"), attributes)) = element {
+        let directive = directive_name(attributes);
+        child_routes_slots = directive
+            .and_then(|name| registry.get_directive_mapping(name))
+            .and_then(|mapping| mapping.slots.as_ref())
+            .is_some_and(|slots| slots.child_directives);
+        let routed = routes_slots && directive_slot_name(node).is_some();
+        let component = directive
+            .and_then(|name| registry.get_directive_component(name))
+            .unwrap_or("Aside");
+        if !routed && !components.iter().any(|c| c == component) {
+            components.push(component.to_string());
+        }
+    }
+
+    if let Some(children) = node.children() {
+        for child in children {
+            walk_directives(child, registry, child_routes_slots, components);
+        }
+    }
+}
+
+/// Collects the registry components that Markdown elements render through
+/// via element and fence mappings (e.g., `Image` for `![alt](src)`,
+/// `Mermaid` for a `mermaid` fence).
+///
+/// Names are canonical and listed once each, in first-use order.
+pub fn collect_element_components(tree: &Node, registry: &RegistryConfig) -> Vec<String> {
+    let mut components = Vec::new();
+    if !registry.element_mappings.is_empty() || !registry.fence_mappings.is_empty() {
+        walk_elements(tree, registry, &mut components);
+    }
+    components
+}
+
+fn walk_elements(node: &Node, registry: &RegistryConfig, components: &mut Vec<String>) {
+    let heading_tag;
+    let element = match node {
+        Node::Image(_) => Some("img"),
+        Node::Link(_) => Some("a"),
+        Node::Table(_) => Some("table"),
+        Node::Blockquote(_) => Some("blockquote"),
+        Node::Code(_) => Some("pre"),
+        Node::Heading(heading) => {
+            heading_tag = format!("h{}", heading.depth);
+            Some(heading_tag.as_str())
+        }
+        _ => None,
+    };
+    let fence_component = match node {
+        Node::Code(code) => code
+            .lang
+            .as_deref()
+            .and_then(|lang| registry.get_fence_component(lang)),
+        _ => None,
+    };
+    if let Some(component) =
+        fence_component.or_else(|| element.and_then(|e| registry.get_element_component(e)))
+        && !components.iter().any(|c| c == component)
+    {
+        components.push(component.to_string());
+    }
+
+    if let Some(children) = node.children() {
+        for child in children {
+            walk_elements(child, registry, components);
+        }
+    }
+}
+
+/// Collects every use of a registry component alias.
+///
+/// JSX tags are reported when their root is a key of `renames`; directives
+/// are reported when their mapping names an alias instead of the canonical
+/// component. Uses are listed in document order.
+pub fn collect_alias_usages(
+    tree: &Node,
+    registry: &RegistryConfig,
+    renames: &BTreeMap<String, String>,
+) -> Vec<AliasUsage> {
+    let mut usages = Vec::new();
+    walk_aliases(tree, registry, renames, &mut usages);
+    usages
+}
+
+fn walk_aliases(
+    node: &Node,
+    registry: &RegistryConfig,
+    renames: &BTreeMap<String, String>,
+    usages: &mut Vec<AliasUsage>,
+) {
+    let element = match node {
+        Node::MdxJsxFlowElement(elem) => Some((elem.name.as_deref(), &elem.attributes)),
+        Node::MdxJsxTextElement(elem) => Some((elem.name.as_deref(), &elem.attributes)),
+        _ => None,
+    };
+    let usage = match element {
+        Some((Some("mf-directive"), attributes)) => directive_name(attributes).and_then(|name| {
+            let mapped = registry.get_directive_mapping(name)?;
+            let canonical = registry.canonical_component(&mapped.component)?;
+            Some((mapped.component.clone(), canonical.name.clone(), Some(name)))
+        }),
+        Some((Some(tag), _)) => {
+            let root = tag.split('.').next().unwrap_or(tag);
+            renames
+                .get(root)
+                .map(|canonical| (root.to_string(), canonical.clone(), None))
+        }
+        _ => None,
+    };
+    if let Some((alias, canonical, directive)) = usage {
+        let (line, column) = node
+            .position()
+            .map(|p| (p.start.line, p.start.column))
+            .unwrap_or((0, 0));
+        usages.push(AliasUsage {
+            alias,
+            canonical,
+            directive: directive.map(str::to_string),
+            line,
+            column,
+        });
+    }
+
+    if let Some(children) = node.children() {
+        for child in children {
+            walk_aliases(child, registry, renames, usages);
+        }
+    }
+}
+
+/// Collects every outbound link (`[text](href)`, autolinks, JSX `<a href>`
+/// and resolved wiki links).
+///
+/// Links are classified with the configured external link policy (any
+/// `http(s)` host counts as external when none is set), and rewritten routes
+/// are reported when link rewriting applies.
+pub fn collect_links(tree: &Node, options: &Options) -> Vec<LinkReference> {
+    let default_policy = ExternalLinkOptions::default();
+    let policy = options.external_links.as_ref().unwrap_or(&default_policy);
+    let wiki = options.wiki_links.as_ref().map(|wiki| wiki.resolver());
+    let mut links = Vec::new();
+    walk_links(tree, options, policy, wiki.as_ref(), &mut links);
+    links
+}
+
+fn walk_links(
+    node: &Node,
+    options: &Options,
+    policy: &ExternalLinkOptions,
+    wiki: Option<&WikiLinkResolver>,
+    links: &mut Vec<LinkReference>,
+) {
+    let target = match node {
+        Node::Link(link) => Some((link.url.as_str(), &link.children)),
+        Node::MdxJsxFlowElement(elem) if elem.name.as_deref() == Some("a") => {
+            literal_attribute(&elem.attributes, "href").map(|href| (href, &elem.children))
+        }
+        Node::MdxJsxTextElement(elem) if elem.name.as_deref() == Some("a") => {
+            literal_attribute(&elem.attributes, "href").map(|href| (href, &elem.children))
+        }
+        _ => None,
+    };
+    if let Some((href, children)) = target {
+        let (line, column) = start_of(node);
+        let route = options.link_rewrite.as_ref().and_then(|rewrite| {
+            let filepath = options.filepath.as_deref().unwrap_or_default();
+            rewrite_markdown_link(href, filepath, rewrite)
+        });
+        links.push(LinkReference {
+            href: href.to_string(),
+            route,
+            text: extract_text_from_nodes(children),
+            external: policy.is_external(href) || has_non_web_scheme(href),
+            fragment: href
+                .split_once('#')
+                .map(|(_, fragment)| fragment.to_string()),
+            via: None,
+            line,
+            column,
+        });
+    }
+
+    if let (Node::Text(text), Some(wiki)) = (node, wiki) {
+        for (link, (line, column)) in text_wikilinks(node, &text.value) {
+            if let Some(href) = wiki.resolve(link.page, link.heading) {
+                links.push(LinkReference {
+                    fragment: href
+                        .split_once('#')
+                        .map(|(_, fragment)| fragment.to_string()),
+                    href,
+                    route: None,
+                    text: link.display_text(),
+                    external: false,
+                    via: None,
+                    line,
+                    column,
+                });
+            }
+        }
+    }
+
+    // Wiki links are not expanded inside links.
+    let wiki = wiki.filter(|_| !is_link(node));
+    if let Some(children) = node.children() {
+        for child in children {
+            walk_links(child, options, policy, wiki, links);
+        }
+    }
+}
+
+/// Collects wiki links whose page cannot be resolved.
+pub fn collect_unresolved_wikilinks(tree: &Node, options: &Options) -> Vec<UnresolvedWikiLink> {
+    let mut unresolved = Vec::new();
+    if let Some(wiki) = &options.wiki_links {
+        let wiki = wiki.resolver();
+        walk_text(tree, &mut |node, text| {
+            for (link, (line, column)) in text_wikilinks(node, text) {
+                if wiki.resolve(link.page, link.heading).is_none() {
+                    unresolved.push(UnresolvedWikiLink {
+                        page: link.page.to_string(),
+                        line,
+                        column,
+                    });
+                }
+            }
+        });
+    }
+    unresolved
+}
+
+/// Validates the TeX of every math node after its `macros` are expanded,
+/// including whether they expand within budget.
+///
+/// Issues are reported against the formula as written: problems inside
+/// expanded text point at the first macro use. Positions are estimated
+/// from the tree; [`locate_math_issues`] re-anchors them to the original
+/// input.
+pub fn collect_math_issues(tree: &Node, macros: &BTreeMap<String, String>) -> Vec<MathIssue> {
+    let mut issues = Vec::new();
+    walk_math(tree, &mut |node, formula| {
+        let (line, column) = start_of(node);
+        // Block math starts on the line after its opening `$$`.
+        let (line, column) = match node {
+            Node::Math(_) => (line + 1, 1),
+            _ => (line, column + 1),
+        };
+        let found = match expand_macros(formula, macros) {
+            Ok(expanded) => validate_tex(&expanded)
+                .into_iter()
+                .map(|issue| written_issue(formula, &expanded, issue))
+                .collect(),
+            Err(overrun) => vec![overrun],
+        };
+        for issue in found {
+            let before = &formula[..issue.offset];
+            let position = match before.rfind('\n') {
+                Some(newline) => (
+                    line + before.matches('\n').count(),
+                    before[newline + 1..].chars().count() + 1,
+                ),
+                None => (line, column + before.chars().count()),
+            };
+            issues.push(MathIssue {
+                message: issue.message,
+                formula: formula.to_string(),
+                offset: issue.offset,
+                line: position.0,
+                column: position.1,
+            });
+        }
+    });
+    issues
+}
+
+/// Maps an issue found in the macro-expanded `expanded` back to `formula`.
+///
+/// Offsets in the text before the first and after the last macro use are
+/// moved exactly; offsets inside expanded text point at the first use and
+/// say so in the message.
+fn written_issue(formula: &str, expanded: &str, issue: TexIssue) -> TexIssue {
+    if formula == expanded {
+        return issue;
+    }
+    let mut prefix = formula
+        .bytes()
+        .zip(expanded.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !formula.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    // Point at the macro's backslash rather than into its name.
+    if let Some(slash) = formula[..prefix].rfind('\\')
+        && formula[slash + 1..prefix]
+            .bytes()
+            .all(|b| b.is_ascii_alphabetic())
+    {
+        prefix = slash;
+    }
+    let suffix = formula[prefix..]
+        .bytes()
+        .rev()
+        .zip(expanded[prefix..].bytes().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if issue.offset < prefix {
+        issue
+    } else if issue.offset >= expanded.len() - suffix {
+        TexIssue {
+            offset: formula.len() - (expanded.len() - issue.offset),
+            ..issue
+        }
+    } else {
+        TexIssue {
+            offset: prefix,
+            message: format!("{} (after macro expansion)", issue.message),
+        }
+    }
+}
+
+/// Returns whether the tree has a Markdown link or `<a href>` element that
+/// `policy` treats as external, i.e., one the renderer adds an indicator to.
+pub fn has_external_link(node: &Node, policy: &ExternalLinkOptions) -> bool {
+    let href = match node {
+        Node::Link(link) => Some(link.url.as_str()),
+        Node::MdxJsxFlowElement(elem) if elem.name.as_deref() == Some("a") => {
+            literal_attribute(&elem.attributes, "href")
+        }
+        Node::MdxJsxTextElement(elem) if elem.name.as_deref() == Some("a") => {
+            literal_attribute(&elem.attributes, "href")
+        }
+        _ => None,
+    };
+    href.is_some_and(|href| policy.is_external(href))
+        || node.children().is_some_and(|children| {
+            children
+                .iter()
+                .any(|child| has_external_link(child, policy))
+        })
+}
+
+/// Calls `visit` with every block and inline math node in the tree.
+fn walk_math(node: &Node, visit: &mut impl FnMut(&Node, &str)) {
+    match node {
+        Node::Math(math) => visit(node, &math.value),
+        Node::InlineMath(math) => visit(node, &math.value),
+        _ => {}
+    }
+    if let Some(children) = node.children() {
+        for child in children {
+            walk_math(child, visit);
+        }
+    }
+}
+
+/// Calls `visit` with every text node in the tree.
+/// Visits text nodes outside links, where wiki links are expanded.
+fn walk_text(node: &Node, visit: &mut impl FnMut(&Node, &str)) {
+    if let Node::Text(text) = node {
+        visit(node, &text.value);
+    }
+    if is_link(node) {
+        return;
+    }
+    if let Some(children) = node.children() {
+        for child in children {
+            walk_text(child, visit);
+        }
+    }
+}
+
+/// Returns whether `node` is a Markdown link or a JSX `<a>` element.
+fn is_link(node: &Node) -> bool {
+    match node {
+        Node::Link(_) => true,
+        Node::MdxJsxFlowElement(elem) => elem.name.as_deref() == Some("a"),
+        Node::MdxJsxTextElement(elem) => elem.name.as_deref() == Some("a"),
+        _ => false,
+    }
+}
+
+/// Wiki links in a text node, with their approximate `(line, column)`.
+fn text_wikilinks<'t>(node: &Node, text: &'t str) -> Vec<(WikiLink<'t>, (usize, usize))> {
+    let (line, column) = start_of(node);
+    find_wikilinks(text)
+        .into_iter()
+        .map(|link| {
+            let before = &text[..link.start];
+            let position = match before.rfind('\n') {
+                Some(newline) => (
+                    line + before.matches('\n').count(),
+                    before[newline + 1..].chars().count() + 1,
+                ),
+                None => (line, column + before.chars().count()),
+            };
+            (link, position)
+        })
+        .collect()
+}
+
+/// Collects images, audio/video sources and linked downloads.
+///
+/// Downloads are links whose target file has an extension other than a page
+/// extension (`.md`, `.mdx`, `.html`, `.htm`), or JSX `<a download>` links.
+pub fn collect_asset_references(tree: &Node) -> Vec<AssetReference> {
+    let mut assets = Vec::new();
+    walk_assets(tree, &mut assets);
+    assets
+}
+
+fn walk_assets(node: &Node, assets: &mut Vec<AssetReference>) {
+    let reference = match node {
+        Node::Image(image) => Some((image.url.as_str(), AssetKind::Image)),
+        Node::Link(link) => {
+            is_download(&link.url).then_some((link.url.as_str(), AssetKind::Download))
+        }
+        Node::MdxJsxFlowElement(elem) => jsx_asset(elem.name.as_deref(), &elem.attributes),
+        Node::MdxJsxTextElement(elem) => jsx_asset(elem.name.as_deref(), &elem.attributes),
+        _ => None,
+    };
+    if let Some((src, kind)) = reference
+        && !src.is_empty()
+    {
+        let (line, column) = start_of(node);
+        assets.push(AssetReference {
+            src: src.to_string(),
+            kind,
+            via: None,
+            line,
+            column,
+        });
+    }
+
+    if let Some(children) = node.children() {
+        for child in children {
+            walk_assets(child, assets);
+        }
+    }
+}
+
+fn jsx_asset<'n>(
+    name: Option<&str>,
+    attributes: &'n [AttributeContent],
+) -> Option<(&'n str, AssetKind)> {
+    match name? {
+        "img" | "Image" | "Picture" => {
+            literal_attribute(attributes, "src").map(|src| (src, AssetKind::Image))
+        }
+        "video" | "audio" | "source" => {
+            literal_attribute(attributes, "src").map(|src| (src, AssetKind::Media))
+        }
+        "a" => {
+            let href = literal_attribute(attributes, "href")?;
+            let download = attributes.iter().any(
+                |attr| matches!(attr, AttributeContent::Property(prop) if prop.name == "download"),
+            );
+            (download || is_download(href)).then_some((href, AssetKind::Download))
+        }
+        _ => None,
+    }
+}
+
+/// Returns true if `href` targets a file that is not a page.
+fn is_download(href: &str) -> bool {
+    let path = href.split(['?', '#']).next().unwrap_or(href);
+    let path = ["https://", "http://", "//"]
+        .iter()
+        .find_map(|prefix| path.strip_prefix(prefix))
+        .map_or(path, |rest| {
+            rest.find('/').map_or("", |slash| &rest[slash..])
+        });
+    // Other schemes (`mailto:`, `tel:`) never name a file.
+    if path.contains(':') {
+        return false;
+    }
+    let file = path.rsplit('/').next().unwrap_or(path);
+    file.rsplit_once('.').is_some_and(|(stem, ext)| {
+        !stem.is_empty()
+            && !ext.is_empty()
+            && !["md", "mdx", "html", "htm"]
+                .iter()
+                .any(|page| ext.eq_ignore_ascii_case(page))
+    })
+}
+
+/// Returns true if `href` starts with a URI scheme other than `http(s)`
+/// (`mailto:`, `tel:`, ...); web URLs are left to the external link policy.
+fn has_non_web_scheme(href: &str) -> bool {
+    href.split(['/', '?', '#'])
+        .next()
+        .and_then(|head| head.split_once(':'))
+        .is_some_and(|(scheme, _)| {
+            !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https")
+        })
+}
+
+fn start_of(node: &Node) -> (usize, usize) {
+    node.position()
+        .map(|p| (p.start.line, p.start.column))
+        .unwrap_or((0, 0))
+}
+
+/// Returns the value of a literal JSX attribute.
+fn literal_attribute<'n>(attributes: &'n [AttributeContent], name: &str) -> Option<&'n str> {
+    attributes.iter().find_map(|attr| match attr {
+        AttributeContent::Property(prop) if prop.name == name => match &prop.value {
+            Some(AttributeValue::Literal(value)) => Some(value.as_str()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Returns the literal `name` property of an `<mf-directive>` element.
+fn directive_name(attributes: &[AttributeContent]) -> Option<&str> {
+    literal_attribute(attributes, "name")
+}
+
+/// Re-anchors usage positions to the original (pre-normalization) input.
+///
+/// The mdast is parsed from preprocessed text whose line structure can
+/// differ from the input (directives expanded, wrapper tags collapsed), so
+/// each usage is located at the first `<Name` outside a code fence. Usages
+/// that cannot be found keep their mdast position.
+pub fn locate_in_source(usages: &mut [ComponentUsage], source: &str) {
+    let needles: Vec<Needle> = usages.iter().map(|u| Needle::Tag(&u.name)).collect();
+    let found = locate_occurrences(&needles, source);
+    for (usage, position) in usages.iter_mut().zip(found) {
+        if let Some((line, column)) = position {
+            usage.line = line;
+            usage.column = column;
+        }
+    }
+}
+
+/// Re-anchors alias usages to the original input, like [`locate_in_source`].
+///
+/// Repeated uses of the same alias are matched to successive `<Alias` tags
+/// (or `:::directive` openers) in order.
+pub fn locate_alias_usages(usages: &mut [AliasUsage], source: &str) {
+    let needles: Vec<Needle> = usages
+        .iter()
+        .map(|u| match &u.directive {
+            Some(directive) => Needle::Directive(directive),
+            None => Needle::Tag(&u.alias),
+        })
+        .collect();
+    let found = locate_occurrences(&needles, source);
+    for (usage, position) in usages.iter_mut().zip(found) {
+        if let Some((line, column)) = position {
+            usage.line = line;
+            usage.column = column;
+        }
+    }
+}
+
+/// Re-anchors missing images to the original input, like [`locate_in_source`].
+///
+/// Repeated sources are matched to successive occurrences in order.
+pub fn locate_missing_images(images: &mut [MissingImage], source: &str) {
+    let needles: Vec<Needle> = images.iter().map(|i| Needle::Text(&i.src)).collect();
+    let found = locate_occurrences(&needles, source);
+    for (image, position) in images.iter_mut().zip(found) {
+        if let Some((line, column)) = position {
+            image.line = line;
+            image.column = column;
+        }
+    }
+}
+
+/// Re-anchors links to the original input, like [`locate_in_source`].
+///
+/// Repeated targets are matched to successive occurrences in order.
+pub fn locate_links(links: &mut [LinkReference], source: &str) {
+    let needles: Vec<Needle> = links.iter().map(|l| Needle::Text(&l.href)).collect();
+    let found = locate_occurrences(&needles, source);
+    for (link, position) in links.iter_mut().zip(found) {
+        if let Some((line, column)) = position {
+            link.line = line;
+            link.column = column;
+        }
+    }
+}
+
+/// Re-anchors asset references to the original input, like [`locate_in_source`].
+pub fn locate_asset_references(assets: &mut [AssetReference], source: &str) {
+    let needles: Vec<Needle> = assets.iter().map(|a| Needle::Text(&a.src)).collect();
+    let found = locate_occurrences(&needles, source);
+    for (asset, position) in assets.iter_mut().zip(found) {
+        if let Some((line, column)) = position {
+            asset.line = line;
+            asset.column = column;
+        }
+    }
+}
+
+/// Re-anchors unresolved wiki links to the original input, like [`locate_in_source`].
+pub fn locate_unresolved_wikilinks(links: &mut [UnresolvedWikiLink], source: &str) {
+    let openers: Vec<String> = links.iter().map(|l| format!("[[{}", l.page)).collect();
+    let needles: Vec<Needle> = openers.iter().map(|o| Needle::Text(o)).collect();
+    let found = locate_occurrences(&needles, source);
+    for (link, position) in links.iter_mut().zip(found) {
+        if let Some((line, column)) = position {
+            link.line = line;
+            link.column = column;
+        }
+    }
+}
+
+/// Re-anchors math issues to `source`, starting from the tree's estimate.
+///
+/// Each issue's formula line is matched at the occurrence outside code
+/// fences nearest to the estimated position (closest line, then closest
+/// column), so the same text elsewhere in the document cannot capture it.
+/// Issues whose line is not found keep the estimate.
+pub fn locate_math_issues(issues: &mut [MathIssue], source: &str) {
+    let mut fence = FenceState::default();
+    let lines: Vec<Option<&str>> = source
+        .lines()
+        .map(|line| {
+            let outcome = advance_fence_state(line, fence);
+            fence = outcome.next_state;
+            (!outcome.skip_imports).then_some(line)
+        })
+        .collect();
+    for issue in issues.iter_mut() {
+        let line_start = issue.formula[..issue.offset]
+            .rfind('\n')
+            .map_or(0, |newline| newline + 1);
+        let line_end = issue.formula[line_start..]
+            .find('\n')
+            .map_or(issue.formula.len(), |end| line_start + end);
+        let text = &issue.formula[line_start..line_end];
+        if text.trim().is_empty() {
+            continue;
+        }
+        let within = issue.formula[line_start..issue.offset].chars().count();
+        let estimate = (issue.line, issue.column.saturating_sub(within));
+        let nearest = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, line)| Some((idx + 1, (*line)?)))
+            .flat_map(|(number, line)| {
+                line.match_indices(text)
+                    .map(move |(pos, _)| (number, line[..pos].chars().count() + 1))
+            })
+            .min_by_key(|&(line, column)| (line.abs_diff(estimate.0), column.abs_diff(estimate.1)));
+        if let Some((line, column)) = nearest {
+            issue.line = line;
+            issue.column = column + within;
+        }
+    }
+}
+
+/// Fills in the line/column of transclusions written in this document.
+///
+/// Nested transclusions (written in a transcluded document) keep `0`.
+pub fn locate_transclusions(transclusions: &mut [Transclusion], source: &str) {
+    let mut direct: Vec<&mut Transclusion> = transclusions
+        .iter_mut()
+        .filter(|t| t.via.is_none())
+        .collect();
+    let openers: Vec<String> = direct
+        .iter()
+        .map(|t| match t.kind {
+            TransclusionKind::Embed => format!("![[{}", t.target),
+            TransclusionKind::Include => "::include{".to_string(),
+        })
+        .collect();
+    let needles: Vec<Needle> = openers.iter().map(|o| Needle::Text(o)).collect();
+    let found = locate_occurrences(&needles, source);
+    for (transclusion, position) in direct.iter_mut().zip(found) {
+        if let Some((line, column)) = position {
+            transclusion.line = line;
+            transclusion.column = column;
+        }
+    }
+}
+
+/// Fills in the line/column of the code fences that imported snippets.
+///
+/// The n-th snippet naming a file is matched to the n-th fence opener whose
+/// meta contains that file. Nested snippets (in transcluded documents) keep `0`.
+pub fn locate_code_snippets(snippets: &mut [CodeSnippet], source: &str) {
+    let mut openers = Vec::new();
+    let mut fence = FenceState::default();
+    for (line_idx, line) in source.lines().enumerate() {
+        let was_outside = fence.phase == FencePhase::Outside;
+        fence = advance_fence_state(line, fence).next_state;
+        if was_outside && fence.phase == FencePhase::InsideFence {
+            let column = line.len() - line.trim_start().len() + 1;
+            openers.push((line, (line_idx + 1, column)));
+        }
+    }
+    let mut next_opener: HashMap<&str, usize> = HashMap::new();
+    for snippet in snippets.iter_mut().filter(|s| s.via.is_none()) {
+        let start = next_opener.entry(snippet.file.as_str()).or_default();
+        let Some(offset) = openers[*start..]
+            .iter()
+            .position(|(line, _)| line.contains(snippet.file.as_str()))
+        else {
+            continue;
+        };
+        let (_, (line, column)) = openers[*start + offset];
+        snippet.line = line;
+        snippet.column = column;
+        *start += offset + 1;
+    }
+}
+
+/// Source text that marks where a collected node was written.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Needle<'a> {
+    /// A JSX opening tag, `<Name`.
+    Tag(&'a str),
+    /// A container directive opener, `:::name`.
+    Directive(&'a str),
+    /// Literal text, such as an image source.
+    Text(&'a str),
+}
+
+/// Finds the 1-indexed `(line, column)` of each needle outside code fences.
+///
+/// The n-th needle with a given value is matched to that value's n-th
+/// occurrence in the source; unmatched needles yield `None`.
+fn locate_occurrences(needles: &[Needle], source: &str) -> Vec<Option<(usize, usize)>> {
+    let mut found = vec![None; needles.len()];
+    // For each distinct needle: indices of the needles still waiting for a match.
+    let mut pending: HashMap<Needle, VecDeque<usize>> = HashMap::new();
+    for (idx, needle) in needles.iter().enumerate() {
+        pending.entry(*needle).or_default().push_back(idx);
+    }
+
+    let mut fence = FenceState::default();
+    for (line_idx, line) in source.lines().enumerate() {
+        let outcome = advance_fence_state(line, fence);
+        fence = outcome.next_state;
+        if outcome.skip_imports {
+            continue;
+        }
+        pending.retain(|needle, waiting| {
+            let columns: Vec<usize> = match needle {
+                Needle::Tag(name) => find_tag_opens(line, name),
+                Needle::Directive(name) => find_directive_open(line, name).into_iter().collect(),
+                Needle::Text(text) => line.match_indices(text).map(|(pos, _)| pos).collect(),
+            };
+            for col in columns {
+                let Some(idx) = waiting.pop_front() else {
+                    break;
+                };
+                found[idx] = Some((line_idx + 1, col + 1));
+            }
+            !waiting.is_empty()
+        });
+        if pending.is_empty() {
+            break;
+        }
+    }
+    found
+}
+
+fn find_directive_open(line: &str, name: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    let rest = trimmed.strip_prefix(":::")?;
+    let after = rest
+        .get(..name.len())
+        .filter(|head| head.eq_ignore_ascii_case(name))
+        .map(|_| &rest[name.len()..])?;
+    if after
+        .chars()
+        .next()
+        .is_none_or(|c| !(c.is_ascii_alphanumeric() || c == '-'))
+    {
+        Some(line.len() - trimmed.len())
+    } else {
+        None
+    }
+}
+
+fn find_tag_opens(line: &str, name: &str) -> Vec<usize> {
+    let needle = format!("<{}", name);
+    line.match_indices(&needle)
+        .map(|(pos, _)| pos)
+        .filter(|&pos| {
+            line[pos + needle.len()..]
+                .chars()
+                .next()
+                .is_none_or(|c| !(c.is_ascii_alphanumeric() || c == '_'))
+        })
+        .collect()
+}
+
+/// Returns the local binding a JSX tag name refers to, if it is a component.
+///
+/// PascalCase means an uppercase first letter followed by at least one
+/// lowercase letter, so all-caps HTML tags (`<DIV>`) are not components.
+fn component_binding(tag_name: &str) -> Option<&str> {
+    let root = tag_name.split('.').next().unwrap_or(tag_name);
+    let mut chars = root.chars();
+    let is_pascal = chars.next().is_some_and(|c| c.is_ascii_uppercase())
+        && chars.any(|c| c.is_ascii_lowercase());
+    if is_pascal && root != "Fragment" {
+        Some(root)
+    } else {
+        None
+    }
+}
@@ -1,11 +1,22 @@
 //! Rendering context for the mdast renderer.
 
-use super::Options;
-use super::types::{BlocksResult, HeadingEntry, PropValue, RenderBlock, Scope};
+use super::images::{ImageSize, ProbeError, percent_decode, resolve_image_path};
+use super::snippets::{SnippetError, extract_snippet, parse_snippet_meta, resolve_snippet_path};
+use super::toc::build_toc;
+use super::types::{
+    BlocksResult, CodeSnippet, HeadingEntry, MissingImage, PropValue, RenderBlock, Scope,
+    SnippetIssue, Transclusion,
+};
+use super::{LocalFileError, Options, resolve_local_file};
 use crate::RegistryConfig;
 use crate::registry::defaults::default_starlight_registry;
+use crate::transform::highlight::highlight_code;
+use crate::transform::imports::{asset_binding, is_local_asset_path};
+use crate::transform::links::{ExternalLinkOptions, rewrite_markdown_link};
+use crate::transform::wikilinks::WikiLinkResolver;
 use markdown::mdast::Node;
 use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 use xmdx_core::Slugger;
 
 /// Normalizes a footnote identifier for use in HTML fragment IDs.
@@ -53,7 +64,7 @@ pub(super) fn escape_html_attr(s: &str) -> String {
 }
 
 /// Escapes code text for HTML output (including JSX braces and newlines).
-fn escape_code_text(s: &str) -> String {
+pub(super) fn escape_code_text(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
     for c in s.chars() {
         match c {
@@ -175,6 +186,14 @@ fn blocks_to_inline_html(component_name: &str, blocks: &[RenderBlock]) -> String
     result
 }
 
+/// Identifies a file for cycle detection: its canonical path when it
+/// exists, otherwise the path as given.
+fn path_key(path: &str) -> String {
+    std::fs::canonicalize(path)
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
 /// Manages the current rendering state with block-based architecture.
 ///
 /// This struct tracks the rendering context as we traverse the markdown AST,
@@ -190,6 +209,9 @@ pub struct Context<'a> {
     /// Extracted heading metadata for table of contents.
     pub headings: Vec<HeadingEntry>,
 
+    /// Headings not excluded from the table of contents.
+    pub toc_headings: Vec<HeadingEntry>,
+
     /// Slugger for generating unique heading IDs.
     slugger: Slugger,
 
@@ -219,12 +241,48 @@ pub struct Context<'a> {
 
     /// Next ordinal to assign.
     footnote_ordinal_counter: usize,
+
+    /// Directive counters per nesting level, used for `"index"` prop sources.
+    /// The last entry counts directives rendered at the current level.
+    directive_ordinals: Vec<usize>,
+
+    /// Relative image sources hoisted to imports, indexed by binding number.
+    assets: Vec<String>,
+
+    /// Local images that were not found while probing dimensions.
+    missing_images: Vec<MissingImage>,
+
+    /// Documents rendered into this one, in render order.
+    transclusions: Vec<Transclusion>,
+
+    /// Paths of the documents enclosing the one being rendered, outermost
+    /// first. Used to detect transclusion cycles.
+    transclusion_stack: Vec<String>,
+
+    /// Rendered trees of transcluded documents with their paths, scanned
+    /// for the components, links and assets they use once rendering finishes.
+    transcluded_trees: Vec<(String, Node)>,
+
+    /// Code fences filled from local files.
+    code_snippets: Vec<CodeSnippet>,
+
+    /// Wiki link resolver, built once from `options.wiki_links`.
+    wiki_links: Option<WikiLinkResolver<'a>>,
+
+    /// Number of enclosing links; wiki links are not expanded inside links.
+    link_depth: usize,
+
+    /// Slug of the section enclosing a child context, used until the
+    /// children render a heading of their own.
+    enclosing_section: Option<String>,
 }
 
 impl<'a> Context<'a> {
-    /// Creates a new context with default Starlight registry.
+    /// Creates a new context using the registry from `options`.
+    ///
+    /// Falls back to the default Starlight registry when none is configured.
     pub fn new(options: &'a Options) -> Self {
-        Self::with_registry(options, None)
+        Self::with_registry(options, options.registry.clone())
     }
 
     /// Creates a new context with a custom registry.
@@ -235,6 +293,7 @@ impl<'a> Context<'a> {
             blocks: Vec::new(),
             current_html: String::with_capacity(4096),
             headings: Vec::new(),
+            toc_headings: Vec::new(),
             slugger: Slugger::new(),
             stack: vec![Scope::Root],
             options,
@@ -244,6 +303,16 @@ impl<'a> Context<'a> {
             footnote_safe_ids: HashMap::new(),
             footnote_ordinals: HashMap::new(),
             footnote_ordinal_counter: 0,
+            directive_ordinals: vec![0],
+            assets: Vec::new(),
+            missing_images: Vec::new(),
+            transclusions: Vec::new(),
+            transclusion_stack: Vec::new(),
+            transcluded_trees: Vec::new(),
+            code_snippets: Vec::new(),
+            wiki_links: options.wiki_links.as_ref().map(|wiki| wiki.resolver()),
+            link_depth: 0,
+            enclosing_section: None,
         }
     }
 
@@ -252,6 +321,86 @@ impl<'a> Context<'a> {
         &self.registry
     }
 
+    /// Returns the local binding to emit for a registry component.
+    ///
+    /// This is the component name itself unless the generated import was
+    /// aliased via [`Options::component_aliases`].
+    pub fn component_binding<'n>(&'n self, name: &'n str) -> &'n str {
+        self.options
+            .component_aliases
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or(name)
+    }
+
+    /// Returns the tag name to emit for a JSX element, rewriting a registry
+    /// alias at its root (`Callout.Title` → `Aside.Title`) to the canonical
+    /// component's binding.
+    pub fn jsx_component_name(&self, tag_name: &str) -> String {
+        let (root, rest) = match tag_name.split_once('.') {
+            Some((root, rest)) => (root, Some(rest)),
+            None => (tag_name, None),
+        };
+        let Some(canonical) = self.options.component_renames.get(root) else {
+            return tag_name.to_string();
+        };
+        let binding = self.component_binding(canonical);
+        match rest {
+            Some(rest) => format!("{}.{}", binding, rest),
+            None => binding.to_string(),
+        }
+    }
+
+    /// Returns the binding to emit for an element kind the registry maps to
+    /// a component (e.g., `img` → `Image`), if any.
+    pub fn element_component(&self, element: &str) -> Option<String> {
+        let component = self.registry.get_element_component(element)?;
+        Some(self.component_binding(component).to_string())
+    }
+
+    /// Returns the binding to emit for a code fence language the registry
+    /// maps to a component (e.g., `mermaid` → `Mermaid`), if any.
+    pub fn fence_component(&self, lang: &str) -> Option<String> {
+        let component = self.registry.get_fence_component(lang)?;
+        Some(self.component_binding(component).to_string())
+    }
+
+    /// Returns the parsed document frontmatter (`Null` when absent).
+    pub fn frontmatter(&self) -> &serde_json::Value {
+        &self.options.frontmatter
+    }
+
+    /// Returns the slug of the most recent heading, i.e. the current section.
+    pub fn current_heading_slug(&self) -> Option<&str> {
+        self.headings
+            .last()
+            .map(|h| h.slug.as_str())
+            .or(self.enclosing_section.as_deref())
+    }
+
+    /// Returns the zero-based ordinal of the next directive at the current
+    /// nesting level and advances the counter.
+    pub fn next_directive_index(&mut self) -> usize {
+        let Some(counter) = self.directive_ordinals.last_mut() else {
+            return 0;
+        };
+        let index = *counter;
+        *counter += 1;
+        index
+    }
+
+    /// Starts a fresh directive counter for a directive's body.
+    pub fn enter_directive_body(&mut self) {
+        self.directive_ordinals.push(0);
+    }
+
+    /// Restores the enclosing directive counter.
+    pub fn exit_directive_body(&mut self) {
+        if self.directive_ordinals.len() > 1 {
+            self.directive_ordinals.pop();
+        }
+    }
+
     /// Writes a raw string to the current HTML buffer without escaping (for safe HTML tags).
     pub fn push_raw(&mut self, s: &str) {
         self.current_html.push_str(s);
@@ -389,6 +538,21 @@ impl<'a> Context<'a> {
         }
     }
 
+    /// Runs `render` against an empty HTML buffer and returns the blocks it
+    /// produced, leaving previously buffered HTML untouched.
+    ///
+    /// Unlike [`Context::render_children_to_blocks`], rendering happens in
+    /// this context, so slug, footnote and scope state are shared.
+    pub fn capture_blocks(&mut self, render: impl FnOnce(&mut Self)) -> Vec<RenderBlock> {
+        let saved_html = std::mem::take(&mut self.current_html);
+        let start = self.blocks.len();
+        render(self);
+        self.flush_html();
+        let captured = self.blocks.split_off(start);
+        self.current_html = saved_html;
+        captured
+    }
+
     /// Adds a component block to the rendering output.
     ///
     /// This first flushes any pending HTML, then adds a Component block.
@@ -406,11 +570,22 @@ impl<'a> Context<'a> {
         });
     }
 
+    /// Returns `code` as highlighted HTML when Rust-side highlighting is
+    /// configured and the language has a bundled grammar.
+    pub fn highlighted_code(&self, code: &str, lang: Option<&str>) -> Option<String> {
+        let options = self.options.syntax_highlighting.as_ref()?;
+        highlight_code(code, lang, options)
+    }
+
     /// Renders a code block inline to the HTML buffer.
     ///
     /// Used when inside a list or table to avoid fragmenting the structure
     /// by flushing HTML and emitting a separate `RenderBlock::Code`.
     pub fn push_code_inline(&mut self, code: &str, lang: Option<&str>) {
+        if let Some(html) = self.highlighted_code(code, lang) {
+            self.current_html.push_str(&html);
+            return;
+        }
         self.current_html
             .push_str(r#"<pre class="astro-code" tabindex="0">"#);
         if let Some(l) = lang {
@@ -510,6 +685,14 @@ impl<'a> Context<'a> {
         child_ctx.footnote_ordinal_counter = self.footnote_ordinal_counter;
         child_ctx.footnote_ref_counts = self.footnote_ref_counts.clone();
         child_ctx.footnote_safe_ids = self.footnote_safe_ids.clone();
+        child_ctx.assets = std::mem::take(&mut self.assets);
+        child_ctx.missing_images = std::mem::take(&mut self.missing_images);
+        child_ctx.transclusions = std::mem::take(&mut self.transclusions);
+        child_ctx.transclusion_stack = self.transclusion_stack.clone();
+        child_ctx.transcluded_trees = std::mem::take(&mut self.transcluded_trees);
+        child_ctx.code_snippets = std::mem::take(&mut self.code_snippets);
+        child_ctx.link_depth = self.link_depth;
+        child_ctx.enclosing_section = self.current_heading_slug().map(str::to_string);
 
         for child in children {
             render_node(child, &mut child_ctx);
@@ -518,6 +701,7 @@ impl<'a> Context<'a> {
 
         // Bubble up headings from child context to parent (for TOC)
         self.headings.append(&mut child_ctx.headings);
+        self.toc_headings.append(&mut child_ctx.toc_headings);
 
         // Bubble up footnote definitions from child context to parent
         self.pending_footnotes
@@ -528,10 +712,112 @@ impl<'a> Context<'a> {
         self.footnote_ordinals = std::mem::take(&mut child_ctx.footnote_ordinals);
         self.footnote_ordinal_counter = child_ctx.footnote_ordinal_counter;
         self.footnote_safe_ids = std::mem::take(&mut child_ctx.footnote_safe_ids);
+        self.assets = std::mem::take(&mut child_ctx.assets);
+        self.missing_images = std::mem::take(&mut child_ctx.missing_images);
+        self.transclusions = std::mem::take(&mut child_ctx.transclusions);
+        self.transcluded_trees = std::mem::take(&mut child_ctx.transcluded_trees);
+        self.code_snippets = std::mem::take(&mut child_ctx.code_snippets);
+
+        child_ctx.blocks
+    }
+
+    /// Renders the nodes of a transcluded document to blocks.
+    ///
+    /// `options` are the options of the transcluded document, so relative
+    /// links and images resolve against its path. Slug, footnote, asset and
+    /// heading state is shared with this context.
+    pub fn render_transcluded(&mut self, options: &Options, nodes: &[Node]) -> Vec<RenderBlock> {
+        use super::render::render_node;
+
+        let mut child_ctx = Context::with_registry(options, Some(self.registry.clone()));
+        child_ctx.slugger = std::mem::take(&mut self.slugger);
+        child_ctx.footnote_ordinals = std::mem::take(&mut self.footnote_ordinals);
+        child_ctx.footnote_ordinal_counter = self.footnote_ordinal_counter;
+        child_ctx.footnote_ref_counts = std::mem::take(&mut self.footnote_ref_counts);
+        child_ctx.footnote_safe_ids = std::mem::take(&mut self.footnote_safe_ids);
+        child_ctx.assets = std::mem::take(&mut self.assets);
+        child_ctx.missing_images = std::mem::take(&mut self.missing_images);
+        child_ctx.transclusions = std::mem::take(&mut self.transclusions);
+        child_ctx.transcluded_trees = std::mem::take(&mut self.transcluded_trees);
+        child_ctx.code_snippets = std::mem::take(&mut self.code_snippets);
+        child_ctx.transclusion_stack = self.transclusion_stack.clone();
+        child_ctx
+            .transclusion_stack
+            .extend(self.options.filepath.as_deref().map(path_key));
+
+        for node in nodes {
+            render_node(node, &mut child_ctx);
+        }
+        child_ctx.flush_html();
+
+        self.headings.append(&mut child_ctx.headings);
+        self.toc_headings.append(&mut child_ctx.toc_headings);
+        self.pending_footnotes
+            .append(&mut child_ctx.pending_footnotes);
+        self.slugger = std::mem::take(&mut child_ctx.slugger);
+        self.footnote_ordinals = std::mem::take(&mut child_ctx.footnote_ordinals);
+        self.footnote_ordinal_counter = child_ctx.footnote_ordinal_counter;
+        self.footnote_ref_counts = std::mem::take(&mut child_ctx.footnote_ref_counts);
+        self.footnote_safe_ids = std::mem::take(&mut child_ctx.footnote_safe_ids);
+        self.assets = std::mem::take(&mut child_ctx.assets);
+        self.missing_images = std::mem::take(&mut child_ctx.missing_images);
+        self.transclusions = std::mem::take(&mut child_ctx.transclusions);
+        self.transcluded_trees = std::mem::take(&mut child_ctx.transcluded_trees);
+        self.code_snippets = std::mem::take(&mut child_ctx.code_snippets);
 
         child_ctx.blocks
     }
 
+    /// Appends rendered blocks, inlining them as HTML inside lists and
+    /// tables so the enclosing structure is not fragmented.
+    pub fn push_blocks(&mut self, blocks: Vec<RenderBlock>) {
+        if self.is_in_list() || self.is_in_table() {
+            let html = blocks_to_inline_html("", &blocks);
+            self.current_html.push_str(&html);
+        } else {
+            self.flush_html();
+            self.blocks.extend(blocks);
+        }
+    }
+
+    /// Returns the options of the document being rendered.
+    pub fn options(&self) -> &'a Options {
+        self.options
+    }
+
+    /// Returns whether `path` is the document being rendered or one that
+    /// encloses it through transclusion.
+    pub fn is_transcluding(&self, path: &str) -> bool {
+        let key = path_key(path);
+        self.options.filepath.as_deref().map(path_key).as_ref() == Some(&key)
+            || self.transclusion_stack.contains(&key)
+    }
+
+    /// Returns the path of the document being rendered when it is itself
+    /// transcluded, or `None` at the top level.
+    pub fn transcluded_from(&self) -> Option<&str> {
+        if self.transclusion_stack.is_empty() {
+            return None;
+        }
+        self.options.filepath.as_deref()
+    }
+
+    /// Records a transclusion for [`BlocksResult::transclusions`].
+    pub fn record_transclusion(&mut self, transclusion: Transclusion) {
+        self.transclusions.push(transclusion);
+    }
+
+    /// Keeps the rendered tree of the transcluded document at `path` for
+    /// later passes.
+    pub fn add_transcluded_tree(&mut self, path: String, tree: Node) {
+        self.transcluded_trees.push((path, tree));
+    }
+
+    /// Returns the path and rendered tree of each document transcluded so far.
+    pub fn transcluded_trees(&self) -> &[(String, Node)] {
+        &self.transcluded_trees
+    }
+
     /// Renders child nodes to an HTML string for inline embedding.
     ///
     /// Used when children need to be embedded directly in the HTML buffer
@@ -556,11 +842,154 @@ impl<'a> Context<'a> {
         self.headings.push(entry);
     }
 
+    /// Adds a heading entry to the table of contents.
+    pub fn add_toc_heading(&mut self, entry: HeadingEntry) {
+        self.toc_headings.push(entry);
+    }
+
     /// Returns whether lazy image loading is enabled.
     pub fn lazy_images_enabled(&self) -> bool {
         self.options.lazy_images()
     }
 
+    /// Returns the import binding for an image source, hoisting it on first use.
+    ///
+    /// Returns `None` unless [`Options::import_local_images`] is enabled and
+    /// `src` is a relative path. Bare paths (`hero.png`) are imported as
+    /// `./hero.png` so they are not mistaken for packages.
+    pub fn image_asset_binding(&mut self, src: &str) -> Option<String> {
+        if !self.options.import_local_images || !is_local_asset_path(src) {
+            return None;
+        }
+        let specifier = if src.starts_with("./") || src.starts_with("../") {
+            src.to_string()
+        } else {
+            format!("./{src}")
+        };
+        let index = match self.assets.iter().position(|a| *a == specifier) {
+            Some(index) => index,
+            None => {
+                self.assets.push(specifier);
+                self.assets.len() - 1
+            }
+        };
+        Some(asset_binding(index))
+    }
+
+    /// Returns the intrinsic size of a local image, if dimension probing is
+    /// enabled and the file can be read.
+    ///
+    /// A file that does not exist is recorded as a [`MissingImage`]; files
+    /// outside [`Options::project_root`] are not read.
+    pub fn image_size(&mut self, src: &str) -> Option<ImageSize> {
+        if !self.options.probe_image_dimensions || !is_local_asset_path(src) {
+            return None;
+        }
+        let document_path = self.options.filepath.as_deref()?;
+        let file = percent_decode(src.split(['?', '#']).next().unwrap_or(src));
+        let (path, result) = match resolve_local_file(self.options, &file) {
+            Ok(path) => {
+                let result = self.options.image_sizes.probe(&path);
+                (path, result)
+            }
+            Err(LocalFileError::Missing) => (
+                resolve_image_path(document_path, src)?,
+                Err(ProbeError::Missing),
+            ),
+            // Images outside the project root are left unsized, never read.
+            Err(LocalFileError::OutsideRoot) => return None,
+        };
+        match result {
+            Ok(size) => Some(size),
+            Err(ProbeError::Missing) => {
+                self.missing_images.push(MissingImage {
+                    src: src.to_string(),
+                    path: path.display().to_string(),
+                    line: 0,
+                    column: 0,
+                });
+                None
+            }
+            Err(ProbeError::Unsupported) => None,
+        }
+    }
+
+    /// Returns the contents of the local file a code fence's meta names
+    /// (`file=./x.ts#region lines=10-25`), if snippet imports are enabled.
+    ///
+    /// Every attempt is recorded as a [`CodeSnippet`]; on failure `None` is
+    /// returned and the fence keeps its written contents.
+    pub fn code_snippet(&mut self, meta: Option<&str>) -> Option<String> {
+        if !self.options.import_code_snippets {
+            return None;
+        }
+        let spec = parse_snippet_meta(meta?)?;
+        let document_path = self.options.filepath.as_deref()?;
+        let (path, result) = match resolve_local_file(self.options, &spec.file) {
+            Ok(path) => {
+                let result = std::fs::read_to_string(&path)
+                    .map_err(|_| SnippetError::MissingFile)
+                    .and_then(|source| extract_snippet(&source, &spec));
+                (path, result)
+            }
+            Err(LocalFileError::Missing) => (
+                resolve_snippet_path(document_path, &spec.file)?,
+                Err(SnippetError::MissingFile),
+            ),
+            Err(LocalFileError::OutsideRoot) => {
+                (PathBuf::from(&spec.file), Err(SnippetError::OutsideRoot))
+            }
+        };
+        let issue = result.as_ref().err().map(|err| match err {
+            SnippetError::MissingFile => SnippetIssue::MissingFile,
+            SnippetError::MissingRegion => SnippetIssue::MissingRegion,
+            SnippetError::InvalidLines => SnippetIssue::InvalidLines,
+            SnippetError::OutsideRoot => SnippetIssue::OutsideRoot,
+        });
+        self.code_snippets.push(CodeSnippet {
+            file: spec.file,
+            path: path.display().to_string(),
+            region: spec.region,
+            lines: spec.lines,
+            issue,
+            via: self.transcluded_from().map(str::to_string),
+            line: 0,
+            column: 0,
+        });
+        result.ok()
+    }
+
+    /// Returns the route for a link to a `.md`/`.mdx` file when link
+    /// rewriting is configured, or `None` to keep the link as written.
+    pub fn rewrite_link(&self, url: &str) -> Option<String> {
+        let options = self.options.link_rewrite.as_ref()?;
+        let filepath = self.options.filepath.as_deref().unwrap_or_default();
+        rewrite_markdown_link(url, filepath, options)
+    }
+
+    /// Returns the external link policy if one is configured and `href`
+    /// points to another host.
+    pub fn external_link_policy(&self, href: &str) -> Option<&'a ExternalLinkOptions> {
+        let options: &'a Options = self.options;
+        options
+            .external_links
+            .as_ref()
+            .filter(|policy| policy.is_external(href))
+    }
+
+    /// Returns the wiki link resolver, if wiki links are enabled and the
+    /// current position is not inside a link.
+    pub fn wiki_links(&self) -> Option<&WikiLinkResolver<'a>> {
+        self.wiki_links.as_ref().filter(|_| self.link_depth == 0)
+    }
+
+    /// Renders `f` as the content of a link, where wiki links stay literal.
+    pub fn within_link(&mut self, f: impl FnOnce(&mut Self)) {
+        self.link_depth += 1;
+        f(self);
+        self.link_depth -= 1;
+    }
+
     /// Returns whether raw HTML passthrough is enabled.
     pub fn raw_html_allowed(&self) -> bool {
         self.options.allow_raw_html()
@@ -696,9 +1125,23 @@ impl<'a> Context<'a> {
             self.blocks.push(RenderBlock::Html { content: section });
         }
 
+        let toc = build_toc(&self.toc_headings, &self.options.toc);
         BlocksResult {
             blocks: self.blocks,
             headings: self.headings,
+            toc,
+            components: Vec::new(),
+            directive_components: Vec::new(),
+            element_components: Vec::new(),
+            alias_usages: Vec::new(),
+            assets: self.assets,
+            missing_images: self.missing_images,
+            links: Vec::new(),
+            asset_references: Vec::new(),
+            unresolved_wikilinks: Vec::new(),
+            transclusions: self.transclusions,
+            code_snippets: self.code_snippets,
+            math_issues: Vec::new(),
         }
     }
 }
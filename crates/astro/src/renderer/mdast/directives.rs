@@ -1,7 +1,9 @@
 //! Directive preprocessing for the mdast renderer.
 
 use crate::transform::code_fence::{FenceState, advance_fence_state};
-use crate::transform::directives::{is_directive_closer, parse_opening_directive};
+use crate::transform::directives::{
+    is_directive_closer, parse_opening_directive_with, parse_slot_child_directive,
+};
 use std::fmt::Write;
 
 /// Preprocesses input markdown to convert directive syntax into internal JSX tags.
@@ -97,7 +99,11 @@ fn convert_directive_opening(
     tag
 }
 
-pub fn preprocess_directives(input: &str) -> String {
+/// When `names` is `Some`, only those directive names are recognized;
+/// otherwise the built-in set is used. Directly inside one of
+/// `slot_parents`, any directive carrying a `slot` attribute
+/// (`:::footer{slot=footer}`) is recognized as well.
+pub fn preprocess_directives(input: &str, names: Option<&[&str]>, slot_parents: &[&str]) -> String {
     let mut fence_state = FenceState::default();
     let mut output = String::with_capacity(input.len());
     // Track directive names, leading whitespace, and whether we've seen content
@@ -122,7 +128,14 @@ pub fn preprocess_directives(input: &str) -> String {
         }
 
         // Check for directive opening
-        if let Some(opening) = parse_opening_directive(line) {
+        let in_slot_parent = directive_stack
+            .last()
+            .is_some_and(|(name, _, _)| slot_parents.contains(&name.as_str()));
+        let opening = in_slot_parent
+            .then(|| parse_slot_child_directive(line))
+            .flatten()
+            .or_else(|| parse_opening_directive_with(line, names));
+        if let Some(opening) = opening {
             let leading_ws: String = line.chars().take_while(|c| c.is_whitespace()).collect();
             directive_stack.push((opening.name.clone(), leading_ws.clone(), false));
             output.push_str(&convert_directive_opening(&opening, &leading_ws));
@@ -167,3 +180,66 @@ fn is_numbered_list_item(trimmed: &str) -> bool {
     // Check for ". " after digits
     matches!((chars.next(), chars.next()), (Some('.'), Some(' ')))
 }
+
+/// Converts `::toc` lines into `<mf-toc />` JSX tags, leaving code
+/// fences untouched.
+pub fn preprocess_toc(input: &str) -> String {
+    let mut fence_state = FenceState::default();
+    let mut output = String::with_capacity(input.len());
+    for line in input.lines() {
+        let fence_outcome = advance_fence_state(line, fence_state);
+        fence_state = fence_outcome.next_state;
+        let trimmed = line.trim();
+        if matches!(trimmed, "::toc" | "::toc{}") && !fence_outcome.skip_imports {
+            let leading_ws = &line[..line.len() - line.trim_start().len()];
+            writeln!(output, "{}<mf-toc />", leading_ws).ok();
+        } else {
+            writeln!(output, "{}", line).ok();
+        }
+    }
+    output
+}
+
+/// Converts `::include{file="..."}` lines into `<mf-include attrs="..." />`
+/// JSX tags, leaving code fences untouched.
+///
+/// # Examples
+///
+/// Input:
+/// ```text
+/// ::include{file="../_shared/prereqs.md" section="Node"}
+/// ```
+///
+/// Output:
+/// ```text
+/// <mf-include attrs="file=&quot;../_shared/prereqs.md&quot; section=&quot;Node&quot;" />
+/// ```
+pub fn preprocess_includes(input: &str) -> String {
+    let mut fence_state = FenceState::default();
+    let mut output = String::with_capacity(input.len());
+    for line in input.lines() {
+        let fence_outcome = advance_fence_state(line, fence_state);
+        fence_state = fence_outcome.next_state;
+        let attrs = line
+            .trim()
+            .strip_prefix("::include{")
+            .and_then(|rest| rest.strip_suffix('}'))
+            .filter(|_| !fence_outcome.skip_imports);
+        match attrs {
+            Some(attrs) => {
+                let leading_ws = &line[..line.len() - line.trim_start().len()];
+                writeln!(
+                    output,
+                    "{}<mf-include attrs=\"{}\" />",
+                    leading_ws,
+                    attrs.replace('"', "&quot;")
+                )
+                .ok();
+            }
+            None => {
+                writeln!(output, "{}", line).ok();
+            }
+        }
+    }
+    output
+}
@@ -0,0 +1,419 @@
+//! Local image dimension probing.
+//!
+//! Reads an image header to learn its intrinsic size so `render_image` can
+//! emit `width`/`height` attributes and avoid layout shift. PNG, JPEG, GIF,
+//! WebP and SVG (`width`/`height` or `viewBox`) are understood.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Bytes read from the start of an image before probing.
+///
+/// Raster headers fit well within this; the whole file is only read when the
+/// prefix is not enough (typically an SVG whose root tag comes late).
+const PROBE_PREFIX_BYTES: u64 = 64 * 1024;
+
+/// Intrinsic size of an image, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageSize {
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+}
+
+/// Why an image size could not be determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeError {
+    /// The file does not exist or could not be read.
+    Missing,
+    /// The file is not a recognized image format.
+    Unsupported,
+}
+
+/// Shared per-path cache of probed image sizes.
+///
+/// Clones share the same entries, so one cache can be handed to every
+/// document of a batch and each image is read from disk at most once.
+#[derive(Debug, Clone, Default)]
+pub struct ImageSizeCache {
+    entries: Arc<Mutex<HashMap<PathBuf, Result<ImageSize, ProbeError>>>>,
+}
+
+impl ImageSizeCache {
+    /// Returns the size of the image at `path`, reading it on first request.
+    ///
+    /// Paths are normalized lexically first, so `/docs/./a.png` and
+    /// `/docs/guide/../a.png` share one entry.
+    pub fn probe(&self, path: &Path) -> Result<ImageSize, ProbeError> {
+        let path = normalize_path(path);
+        if let Ok(entries) = self.entries.lock()
+            && let Some(cached) = entries.get(&path)
+        {
+            return *cached;
+        }
+        let result = read_image_size(&path);
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(path, result);
+        }
+        result
+    }
+}
+
+/// Probes a bounded prefix of the file, falling back to a full read when the
+/// prefix was truncated and did not contain a recognizable size.
+fn read_image_size(path: &Path) -> Result<ImageSize, ProbeError> {
+    let file = std::fs::File::open(path).map_err(|_| ProbeError::Missing)?;
+    let mut prefix = Vec::new();
+    file.take(PROBE_PREFIX_BYTES)
+        .read_to_end(&mut prefix)
+        .map_err(|_| ProbeError::Missing)?;
+    if let Some(size) = probe_image_size(&prefix) {
+        return Ok(size);
+    }
+    if (prefix.len() as u64) < PROBE_PREFIX_BYTES {
+        return Err(ProbeError::Unsupported);
+    }
+    let bytes = std::fs::read(path).map_err(|_| ProbeError::Missing)?;
+    probe_image_size(&bytes).ok_or(ProbeError::Unsupported)
+}
+
+/// Removes `.` components and folds `..` into the preceding component.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Resolves an image `src` relative to the document at `document_path`.
+///
+/// Query strings and fragments are dropped and percent-escapes decoded.
+pub fn resolve_image_path(document_path: &str, src: &str) -> Option<PathBuf> {
+    let src = src.split(['?', '#']).next().unwrap_or(src);
+    let dir = Path::new(document_path).parent()?;
+    Some(dir.join(percent_decode(src)))
+}
+
+/// Decodes `%XX` escapes, returning `s` unchanged if the result is not UTF-8.
+pub fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(hex) = s.get(i + 1..i + 3)
+            && let Ok(byte) = u8::from_str_radix(hex, 16)
+        {
+            out.push(byte);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(out).unwrap_or_else(|_| s.to_string())
+}
+
+/// Reads the intrinsic size from the leading bytes of an image file.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::renderer::mdast::images::{ImageSize, probe_image_size};
+///
+/// let gif = b"GIF89a\x20\x00\x10\x00";
+/// assert_eq!(probe_image_size(gif), Some(ImageSize { width: 32, height: 16 }));
+/// assert_eq!(probe_image_size(b"not an image"), None);
+/// ```
+pub fn probe_image_size(bytes: &[u8]) -> Option<ImageSize> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png_size(bytes)
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        gif_size(bytes)
+    } else if bytes.starts_with(&[0xFF, 0xD8]) {
+        jpeg_size(bytes)
+    } else if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        webp_size(bytes)
+    } else {
+        svg_size(std::str::from_utf8(bytes).ok()?)
+    }
+}
+
+fn size(width: u32, height: u32) -> Option<ImageSize> {
+    (width > 0 && height > 0).then_some(ImageSize { width, height })
+}
+
+fn be_u16(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u32::from(u16::from_be_bytes([b[0], b[1]])))
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u32::from(u16::from_le_bytes([b[0], b[1]])))
+}
+
+fn le_u24(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 3)?;
+    Some(u32::from(b[0]) | u32::from(b[1]) << 8 | u32::from(b[2]) << 16)
+}
+
+fn png_size(bytes: &[u8]) -> Option<ImageSize> {
+    if bytes.get(12..16) != Some(b"IHDR") {
+        return None;
+    }
+    let width = bytes.get(16..20)?;
+    let height = bytes.get(20..24)?;
+    size(
+        u32::from_be_bytes(width.try_into().ok()?),
+        u32::from_be_bytes(height.try_into().ok()?),
+    )
+}
+
+fn gif_size(bytes: &[u8]) -> Option<ImageSize> {
+    size(le_u16(bytes, 6)?, le_u16(bytes, 8)?)
+}
+
+fn jpeg_size(bytes: &[u8]) -> Option<ImageSize> {
+    let mut i = 2;
+    while i + 1 < bytes.len() {
+        if bytes[i] != 0xFF {
+            return None;
+        }
+        let marker = bytes[i + 1];
+        if marker == 0xFF {
+            // Fill byte before a marker.
+            i += 1;
+            continue;
+        }
+        // Standalone markers carry no length.
+        if marker == 0x01 || (0xD0..=0xD9).contains(&marker) {
+            i += 2;
+            continue;
+        }
+        let length = be_u16(bytes, i + 2)? as usize;
+        // Start-of-frame markers, excluding DHT (C4), JPG (C8) and DAC (CC).
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            return size(be_u16(bytes, i + 7)?, be_u16(bytes, i + 5)?);
+        }
+        i += 2 + length;
+    }
+    None
+}
+
+fn webp_size(bytes: &[u8]) -> Option<ImageSize> {
+    match bytes.get(12..16)? {
+        b"VP8 " => size(le_u16(bytes, 26)? & 0x3FFF, le_u16(bytes, 28)? & 0x3FFF),
+        b"VP8L" => {
+            let b = bytes.get(21..25)?;
+            let (b0, b1, b2, b3) = (
+                u32::from(b[0]),
+                u32::from(b[1]),
+                u32::from(b[2]),
+                u32::from(b[3]),
+            );
+            size(
+                1 + (b0 | (b1 & 0x3F) << 8),
+                1 + (b1 >> 6 | b2 << 2 | (b3 & 0x0F) << 10),
+            )
+        }
+        b"VP8X" => size(1 + le_u24(bytes, 24)?, 1 + le_u24(bytes, 27)?),
+        _ => None,
+    }
+}
+
+fn svg_size(text: &str) -> Option<ImageSize> {
+    let start = text.find("<svg")?;
+    let end = start + text[start..].find('>')?;
+    let tag = &text[start..end];
+
+    let length = |name: &str| {
+        svg_attr(tag, name)
+            .map(|value| value.trim().trim_end_matches("px"))
+            .and_then(|value| value.parse::<f64>().ok())
+            .and_then(svg_dimension)
+    };
+    if let (Some(width), Some(height)) = (length("width"), length("height")) {
+        return size(width, height);
+    }
+
+    let view_box: Vec<f64> = svg_attr(tag, "viewBox")?
+        .split([' ', ','])
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.parse().ok())
+        .collect();
+    match view_box[..] {
+        [_, _, width, height] => size(svg_dimension(width)?, svg_dimension(height)?),
+        _ => None,
+    }
+}
+
+/// Rounds an SVG length to whole pixels, refusing values that are not
+/// finite or do not fit a `u32` instead of saturating them.
+fn svg_dimension(value: f64) -> Option<u32> {
+    let rounded = value.round();
+    (rounded.is_finite() && rounded >= 0.0 && rounded <= f64::from(u32::MAX))
+        .then_some(rounded as u32)
+}
+
+fn svg_attr<'t>(tag: &'t str, name: &str) -> Option<&'t str> {
+    let mut rest = tag;
+    while let Some(pos) = rest.find(name) {
+        let before = rest[..pos].chars().next_back();
+        let after = rest[pos + name.len()..].trim_start();
+        rest = &rest[pos + name.len()..];
+        if !before.is_some_and(char::is_whitespace) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let value = &value[1..];
+        return value.find(quote).map(|end| &value[..end]);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probes_raster_headers() {
+        let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(
+            probe_image_size(&png),
+            Some(ImageSize {
+                width: 640,
+                height: 480
+            })
+        );
+
+        // SOI, an APP0 segment, then SOF0 with height 200 and width 300.
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x00,
+            0xC8, 0x01, 0x2C, 0x03,
+        ];
+        assert_eq!(
+            probe_image_size(&jpeg),
+            Some(ImageSize {
+                width: 300,
+                height: 200
+            })
+        );
+
+        let mut webp = b"RIFF\x00\x00\x00\x00WEBPVP8X\x0a\x00\x00\x00\x00\x00\x00\x00".to_vec();
+        webp.extend_from_slice(&[0x7F, 0x02, 0x00, 0xDF, 0x01, 0x00]);
+        assert_eq!(
+            probe_image_size(&webp),
+            Some(ImageSize {
+                width: 640,
+                height: 480
+            })
+        );
+    }
+
+    #[test]
+    fn probes_svg_dimensions() {
+        let sized = r#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg" width="24px" height="12"></svg>"#;
+        assert_eq!(
+            probe_image_size(sized.as_bytes()),
+            Some(ImageSize {
+                width: 24,
+                height: 12
+            })
+        );
+
+        let view_box = r#"<svg viewBox="0 0 100.4 50" width="100%"><path/></svg>"#;
+        assert_eq!(
+            probe_image_size(view_box.as_bytes()),
+            Some(ImageSize {
+                width: 100,
+                height: 50
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_svg_dimensions() {
+        for svg in [
+            r#"<svg viewBox="0 0 1e400 5"></svg>"#,
+            r#"<svg viewBox="0 0 5000000000 5"></svg>"#,
+            r#"<svg viewBox="0 0 -10 5"></svg>"#,
+            r#"<svg width="NaN" height="5"></svg>"#,
+        ] {
+            assert_eq!(probe_image_size(svg.as_bytes()), None, "{svg}");
+        }
+
+        let fallback = r#"<svg width="1e400" height="5" viewBox="0 0 20 10"></svg>"#;
+        assert_eq!(
+            probe_image_size(fallback.as_bytes()),
+            Some(ImageSize {
+                width: 20,
+                height: 10
+            })
+        );
+    }
+
+    #[test]
+    fn probe_reads_late_svg_roots_and_shares_normalized_keys() {
+        let dir = std::env::temp_dir().join(format!("xmdx-image-probe-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let comment = "x".repeat(PROBE_PREFIX_BYTES as usize);
+        let svg = format!(r#"<!-- {comment} --><svg width="8" height="4"></svg>"#);
+        std::fs::write(dir.join("late.svg"), svg).unwrap();
+
+        let cache = ImageSizeCache::default();
+        let size = cache.probe(&dir.join("late.svg"));
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(
+            size,
+            Ok(ImageSize {
+                width: 8,
+                height: 4
+            })
+        );
+        // Served from the cache under the normalized key after the file is gone.
+        assert_eq!(cache.probe(&dir.join("./late.svg")), size);
+    }
+
+    #[test]
+    fn normalizes_cache_keys() {
+        assert_eq!(
+            normalize_path(Path::new("/docs/./guide/../img/a.png")),
+            PathBuf::from("/docs/img/a.png")
+        );
+        assert_eq!(
+            normalize_path(Path::new("../img/./a.png")),
+            PathBuf::from("../img/a.png")
+        );
+    }
+
+    #[test]
+    fn resolves_paths_relative_to_document() {
+        assert_eq!(
+            resolve_image_path("/docs/guide/intro.md", "./my%20shot.png?raw#x"),
+            Some(PathBuf::from("/docs/guide/./my shot.png"))
+        );
+    }
+}
@@ -9,21 +9,44 @@
 //! - `types` - Type definitions (PropValue, RenderBlock, HeadingEntry, etc.)
 //! - `context` - Rendering context for tracking state during traversal
 //! - `render` - AST node rendering functions
+//! - `collect` - Metadata collection passes (component and directive usages)
 //! - `directives` - Directive syntax preprocessing
+//! - `images` - Local image dimension probing
+//! - `snippets` - Code fence contents imported from local files
+//! - `transclusion` - Rendering other documents in place (embeds, includes)
+//! - `toc` - Table of contents built from the headings
 
+mod collect;
 mod context;
 mod directives;
+pub mod images;
 pub mod render;
+pub mod snippets;
+pub mod toc;
+pub mod transclusion;
 mod types;
 
 pub use context::Context;
-pub use types::{AsideMeta, BlocksResult, CardMeta, HeadingEntry, PropValue, RenderBlock, Scope};
+pub use types::{
+    AliasUsage, AsideMeta, AssetKind, AssetReference, BlocksResult, CardMeta, CodeSnippet,
+    ComponentUsage, HeadingEntry, LinkReference, MathIssue, MissingImage, PropValue, RenderBlock,
+    Scope, SnippetIssue, TocEntry, Transclusion, TransclusionIssue, TransclusionKind,
+    UnresolvedWikiLink,
+};
 
+use crate::RegistryConfig;
+use crate::transform::highlight::HighlightOptions;
 use crate::transform::jsx_normalize::{
     collapse_multiline_wrapper_tags, normalize_list_jsx_components, normalize_mdx_jsx_indentation,
 };
+use crate::transform::links::{ExternalLinkOptions, LinkRewriteOptions};
+use crate::transform::mathml::MathOutput;
 use crate::transform::smartypants::apply_smartypants;
+use crate::transform::wikilinks::WikiLinkOptions;
 use render::render_node;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use xmdx_core::MarkflowError;
 
 /// Rendering options for the mdast renderer.
@@ -51,6 +74,98 @@ pub struct Options {
     /// When enabled, math expressions are rendered as `<MathBlock>` and `<MathInline>` components.
     #[serde(default)]
     pub enable_math: bool,
+    /// Component registry used for directive and component mappings.
+    /// When `None`, the default Starlight registry is used.
+    #[serde(default)]
+    pub registry: Option<RegistryConfig>,
+    /// Local bindings for registry components emitted by directives, keyed by
+    /// component name. Used when the generated import had to be aliased
+    /// (e.g., `Aside` → `__xmdx_Aside`) to avoid colliding with a user import.
+    #[serde(default)]
+    pub component_aliases: BTreeMap<String, String>,
+    /// Registry aliases to rewrite in JSX tags, mapped to their canonical
+    /// component (e.g., `Callout` → `Aside`). See
+    /// [`alias_renames`](crate::transform::imports::alias_renames).
+    #[serde(default)]
+    pub component_renames: BTreeMap<String, String>,
+    /// Parsed document frontmatter, read by `"frontmatter"` prop sources in
+    /// directive mappings. `Null` when the document has none.
+    #[serde(default)]
+    pub frontmatter: serde_json::Value,
+    /// Whether to hoist relative image sources (`![](./hero.png)`,
+    /// `<img src="./hero.png">`) to ESM imports so Astro can optimize them.
+    /// The referenced paths are reported in [`BlocksResult::assets`].
+    #[serde(default)]
+    pub import_local_images: bool,
+    /// Whether to read local image headers and add `width`/`height` to
+    /// Markdown images. Sources resolve relative to [`Options::filepath`];
+    /// files that are not found are reported in [`BlocksResult::missing_images`].
+    #[serde(default)]
+    pub probe_image_dimensions: bool,
+    /// Path of the document being rendered, used to resolve relative images.
+    #[serde(default)]
+    pub filepath: Option<String>,
+    /// Directory that includes, embeds and code snippets may read files
+    /// from. Paths resolving outside it are refused. When `None`, the
+    /// current working directory is used.
+    #[serde(default)]
+    pub project_root: Option<String>,
+    /// Probed image sizes, shared across the documents of a batch.
+    #[serde(skip)]
+    pub image_sizes: images::ImageSizeCache,
+    /// Rewrites links to `.md`/`.mdx` files into site routes, resolving
+    /// relative links against [`Options::filepath`]. Disabled when `None`.
+    #[serde(default)]
+    pub link_rewrite: Option<LinkRewriteOptions>,
+    /// Attributes and indicators for links to other hosts. Disabled when `None`.
+    #[serde(default)]
+    pub external_links: Option<ExternalLinkOptions>,
+    /// Renders `[[Page#Heading|label]]` wiki links, resolving page names with
+    /// these options. Unresolved pages are reported in
+    /// [`BlocksResult::unresolved_wikilinks`]. Disabled when `None`.
+    #[serde(default)]
+    pub wiki_links: Option<WikiLinkOptions>,
+    /// Whether paragraphs made of `![[Note]]` / `![[Note#Section]]` lines
+    /// render the embedded document in place. Notes resolve against
+    /// [`Options::embed_documents`], then against files next to
+    /// [`Options::filepath`]; see [`BlocksResult::transclusions`].
+    #[serde(default)]
+    pub enable_embeds: bool,
+    /// Documents available to embeds, typically the other files of a batch.
+    #[serde(skip)]
+    pub embed_documents: transclusion::EmbedDocuments,
+    /// Whether `::include{file="..." section="..."}` lines render a Markdown
+    /// file read relative to [`Options::filepath`] in place; see
+    /// [`BlocksResult::transclusions`].
+    #[serde(default)]
+    pub enable_includes: bool,
+    /// Whether code fences whose meta names a local file
+    /// (`file=./examples/client.ts#region lines=10-25`) are filled from it.
+    /// Files resolve relative to [`Options::filepath`]; every import is
+    /// reported in [`BlocksResult::code_snippets`].
+    #[serde(default)]
+    pub import_code_snippets: bool,
+    /// Highlights code fences in Rust, emitting HTML instead of
+    /// [`RenderBlock::Code`] for languages with a bundled grammar. Fences
+    /// whose meta sets a title, line marks or line numbers stay
+    /// [`RenderBlock::Code`] so those annotations are not lost. Requires
+    /// the `highlight` Cargo feature; otherwise fences render unchanged.
+    #[serde(default)]
+    pub syntax_highlighting: Option<HighlightOptions>,
+    /// How math is emitted when [`Options::enable_math`] is set. With
+    /// [`MathOutput::Mathml`], formulas outside the supported TeX subset
+    /// still render as `<MathBlock>`/`<MathInline>`.
+    #[serde(default)]
+    pub math_output: MathOutput,
+    /// Macros expanded in math before it is emitted, keyed by command with
+    /// or without the backslash (e.g., `\R` → `\mathbb{R}`). Bodies take
+    /// arguments as `#1`…`#9`.
+    #[serde(default)]
+    pub math_macros: BTreeMap<String, String>,
+    /// Table of contents depth range and exclusions, and whether `::toc`
+    /// renders it in place.
+    #[serde(default)]
+    pub toc: toc::TocOptions,
 }
 
 impl Options {
@@ -83,6 +198,26 @@ impl Default for Options {
             allow_raw_html: default_allow_raw_html(),
             enable_heading_autolinks: false,
             enable_math: false,
+            registry: None,
+            component_aliases: BTreeMap::new(),
+            component_renames: BTreeMap::new(),
+            frontmatter: serde_json::Value::Null,
+            import_local_images: false,
+            probe_image_dimensions: false,
+            filepath: None,
+            project_root: None,
+            image_sizes: images::ImageSizeCache::default(),
+            link_rewrite: None,
+            external_links: None,
+            wiki_links: None,
+            enable_embeds: false,
+            embed_documents: transclusion::EmbedDocuments::default(),
+            enable_includes: false,
+            import_code_snippets: false,
+            syntax_highlighting: None,
+            math_output: MathOutput::default(),
+            math_macros: BTreeMap::new(),
+            toc: toc::TocOptions::default(),
         }
     }
 }
@@ -112,11 +247,155 @@ impl Default for Options {
 /// let blocks = to_blocks(input, &options).unwrap();
 /// ```
 pub fn to_blocks(input: &str, options: &Options) -> Result<BlocksResult, MarkflowError> {
-    // 1. Preprocess directives if enabled
+    // 1-6. Preprocess and parse to MDAST
+    let (tree, raw_masks) = parse_document(input, options)?;
+
+    // 7. Traverse the AST and render to blocks
+    let mut ctx = Context::new(options);
+    render_node(&tree, &mut ctx);
+
+    // 8. Finish and get blocks, then unmask raw HTML that was temporarily hidden
+    //    Components used by transcluded documents are imported by this one.
+    let mut directive_components = collect::collect_directive_components(&tree, ctx.registry());
+    let mut element_components = collect::collect_element_components(&tree, ctx.registry());
+    let mut transcluded_components = Vec::new();
+    // Links and assets of transcluded documents resolve against their own
+    // path and keep no position in this document.
+    let mut transcluded_links = Vec::new();
+    let mut transcluded_assets = Vec::new();
+    for (path, transcluded) in ctx.transcluded_trees() {
+        let transcluded_options = Options {
+            filepath: Some(path.clone()),
+            ..options.clone()
+        };
+        transcluded_links.extend(
+            collect::collect_links(transcluded, &transcluded_options)
+                .into_iter()
+                .map(|link| LinkReference {
+                    via: Some(path.clone()),
+                    line: 0,
+                    column: 0,
+                    ..link
+                }),
+        );
+        transcluded_assets.extend(
+            collect::collect_asset_references(transcluded)
+                .into_iter()
+                .map(|asset| AssetReference {
+                    via: Some(path.clone()),
+                    line: 0,
+                    column: 0,
+                    ..asset
+                }),
+        );
+        extend_unique(
+            &mut directive_components,
+            collect::collect_directive_components(transcluded, ctx.registry()),
+        );
+        extend_unique(
+            &mut element_components,
+            collect::collect_element_components(transcluded, ctx.registry()),
+        );
+        transcluded_components.extend(collect::collect_component_usages(transcluded));
+    }
+    // The external link icon is emitted by the renderer, so it is imported
+    // like a mapped element whenever a link gets the indicator.
+    if let Some(policy) = &options.external_links
+        && let Some(icon) = &policy.icon_component
+        && std::iter::once(&tree)
+            .chain(ctx.transcluded_trees().iter().map(|(_, tree)| tree))
+            .any(|tree| collect::has_external_link(tree, policy))
+    {
+        extend_unique(&mut element_components, vec![icon.clone()]);
+    }
+    let alias_usages =
+        collect::collect_alias_usages(&tree, ctx.registry(), &options.component_renames);
+    let mut result = ctx.finish();
+    result.components = collect::collect_component_usages(&tree);
+    result.directive_components = directive_components;
+    result.element_components = element_components;
+    result.alias_usages = alias_usages;
+    collect::locate_in_source(&mut result.components, input);
+    for usage in transcluded_components {
+        if !result.components.iter().any(|c| c.name == usage.name) {
+            result.components.push(usage);
+        }
+    }
+    collect::locate_alias_usages(&mut result.alias_usages, input);
+    collect::locate_missing_images(&mut result.missing_images, input);
+    result.links = collect::collect_links(&tree, options);
+    result.asset_references = collect::collect_asset_references(&tree);
+    collect::locate_links(&mut result.links, input);
+    collect::locate_asset_references(&mut result.asset_references, input);
+    result.links.extend(transcluded_links);
+    result.asset_references.extend(transcluded_assets);
+    result.unresolved_wikilinks = collect::collect_unresolved_wikilinks(&tree, options);
+    collect::locate_unresolved_wikilinks(&mut result.unresolved_wikilinks, input);
+    collect::locate_transclusions(&mut result.transclusions, input);
+    collect::locate_code_snippets(&mut result.code_snippets, input);
+    result.math_issues = collect::collect_math_issues(&tree, &options.math_macros);
+    collect::locate_math_issues(&mut result.math_issues, input);
+    unmask_raw_html_blocks(&mut result.blocks, &raw_masks);
+    // `::toc` placeholders are filled now that every heading is known.
+    if options.toc.directive {
+        let component = options.toc.component.as_deref();
+        let binding = component.map(|name| {
+            options
+                .component_aliases
+                .get(name)
+                .map_or(name, String::as_str)
+        });
+        if toc::fill_toc_placeholders(&mut result.blocks, &result.toc, binding)
+            && let Some(name) = component
+        {
+            extend_unique(&mut result.element_components, vec![name.to_string()]);
+        }
+    }
+
+    // 9. Apply smartypants if enabled
+    if options.enable_smartypants {
+        for block in &mut result.blocks {
+            if let RenderBlock::Html { content } = block {
+                *content = apply_smartypants(content);
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Preprocesses and parses a document to MDAST (steps 1-6 of [`to_blocks`]).
+///
+/// Returns the tree and the raw HTML blocks masked out of it, which the
+/// rendered blocks must be unmasked with.
+fn parse_document(
+    input: &str,
+    options: &Options,
+) -> Result<(markdown::mdast::Node, Vec<RawHtmlMask>), MarkflowError> {
+    // 1. Preprocess directives if enabled. A custom registry decides which
+    //    directive names are recognized; otherwise the built-in set applies.
+    //    `::include` lines become `<mf-include>` tags when includes are on,
+    //    and `::toc` lines become `<mf-toc />` when the TOC directive is on.
+    let input = if options.toc.directive {
+        Cow::Owned(directives::preprocess_toc(input))
+    } else {
+        Cow::Borrowed(input)
+    };
     let preprocessed = if options.enable_directives {
-        directives::preprocess_directives(input)
+        let names = options.registry.as_ref().map(|r| r.directive_names());
+        let slot_parents = options
+            .registry
+            .as_ref()
+            .map(|r| r.slot_parent_directive_names())
+            .unwrap_or_default();
+        directives::preprocess_directives(&input, names.as_deref(), &slot_parents)
+    } else {
+        input.into_owned()
+    };
+    let preprocessed = if options.enable_includes {
+        directives::preprocess_includes(&preprocessed)
     } else {
-        input.to_string()
+        preprocessed
     };
 
     // 2. Collapse multiline wrapper tags to prevent tag mismatch errors
@@ -170,25 +449,54 @@ pub fn to_blocks(input: &str, options: &Options) -> Result<BlocksResult, Markflo
         let loc = xmdx_core::parse::message_location(&e);
         MarkflowError::parse_error(format!("Markdown parse error: {}", e), loc.line, loc.column)
     })?;
+    Ok((tree, raw_masks))
+}
 
-    // 7. Traverse the AST and render to blocks
-    let mut ctx = Context::new(options);
-    render_node(&tree, &mut ctx);
+/// Why a local file referenced by a document was not read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LocalFileError {
+    /// The file does not exist, or there is no document path to resolve it against.
+    Missing,
+    /// The path is absolute or resolves outside [`Options::project_root`].
+    OutsideRoot,
+}
 
-    // 8. Finish and get blocks, then unmask raw HTML that was temporarily hidden
-    let mut result = ctx.finish();
-    unmask_raw_html_blocks(&mut result.blocks, &raw_masks);
+/// Resolves `file` relative to the directory of [`Options::filepath`].
+///
+/// Absolute paths and paths that resolve outside [`Options::project_root`]
+/// (after `..` segments and symlinks) are refused, so a document cannot pull
+/// arbitrary files from disk into the page.
+fn resolve_local_file(options: &Options, file: &str) -> Result<PathBuf, LocalFileError> {
+    let relative = Path::new(file);
+    if relative.has_root() || relative.is_absolute() {
+        return Err(LocalFileError::OutsideRoot);
+    }
+    let dir = options
+        .filepath
+        .as_deref()
+        .and_then(|path| Path::new(path).parent())
+        .ok_or(LocalFileError::Missing)?;
+    let path = dir.join(relative);
+    let resolved = std::fs::canonicalize(&path).map_err(|_| LocalFileError::Missing)?;
+    let root = match &options.project_root {
+        Some(root) => PathBuf::from(root),
+        None => std::env::current_dir().map_err(|_| LocalFileError::OutsideRoot)?,
+    };
+    let root = std::fs::canonicalize(root).map_err(|_| LocalFileError::OutsideRoot)?;
+    if resolved.starts_with(&root) {
+        Ok(path)
+    } else {
+        Err(LocalFileError::OutsideRoot)
+    }
+}
 
-    // 9. Apply smartypants if enabled
-    if options.enable_smartypants {
-        for block in &mut result.blocks {
-            if let RenderBlock::Html { content } = block {
-                *content = apply_smartypants(content);
-            }
+/// Appends the names in `more` that `names` does not contain yet.
+fn extend_unique(names: &mut Vec<String>, more: Vec<String>) {
+    for name in more {
+        if !names.contains(&name) {
+            names.push(name);
         }
     }
-
-    Ok(result)
 }
 
 /// A raw HTML block (script/style) that was temporarily masked during parsing.
@@ -2214,6 +2522,346 @@ Inner ref[^1].
         );
     }
 
+    #[test]
+    fn test_mathml_output_renders_inline_and_block_math() {
+        let input = "Area $\\pi r^2$.\n\n$$\n\\begin{bmatrix} a & b \\\\ c & d \\end{bmatrix}\n$$";
+        let options = Options {
+            enable_math: true,
+            math_output: MathOutput::Mathml,
+            ..Default::default()
+        };
+
+        let blocks = to_blocks(input, &options).unwrap();
+        assert_eq!(blocks.blocks.len(), 1, "{:?}", blocks.blocks);
+        let RenderBlock::Html { content } = &blocks.blocks[0] else {
+            panic!("expected HTML: {:?}", blocks.blocks);
+        };
+        assert!(
+            content.contains(
+                r#"<p>Area <math xmlns="http://www.w3.org/1998/Math/MathML"><semantics><mrow><mi>π</mi><msup><mi>r</mi><mn>2</mn></msup></mrow>"#
+            ),
+            "{content}"
+        );
+        assert!(content.contains(r#"display="block""#), "{content}");
+        assert!(content.contains("<mtable>"), "{content}");
+        assert!(!content.contains('{'), "{content}");
+    }
+
+    #[test]
+    fn test_mathml_output_falls_back_to_components() {
+        let input = "$\\color{red}{x}$ and $y$";
+        let options = Options {
+            enable_math: true,
+            math_output: MathOutput::Mathml,
+            ..Default::default()
+        };
+
+        let blocks = to_blocks(input, &options).unwrap();
+        let math_inline: Vec<_> = blocks
+            .blocks
+            .iter()
+            .filter_map(|b| match b {
+                RenderBlock::Component { name, props, .. } if name == "MathInline" => {
+                    Some(props.get("expr").cloned())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            math_inline,
+            vec![Some(PropValue::literal("\\color{red}{x}"))],
+            "{:?}",
+            blocks.blocks
+        );
+        assert!(
+            blocks.blocks.iter().any(
+                |b| matches!(b, RenderBlock::Html { content } if content.contains("<mi>y</mi>"))
+            ),
+            "{:?}",
+            blocks.blocks
+        );
+    }
+
+    #[test]
+    fn test_math_macros_expand_in_both_output_modes() {
+        let mut options = Options {
+            enable_math: true,
+            math_macros: BTreeMap::from([("\\R".to_string(), "\\mathbb{R}".to_string())]),
+            ..Default::default()
+        };
+
+        let blocks = to_blocks("$x \\in \\R$", &options).unwrap();
+        assert!(
+            blocks.blocks.iter().any(|b| matches!(
+                b,
+                RenderBlock::Component { name, props, .. }
+                    if name == "MathInline"
+                        && props.get("expr") == Some(&PropValue::literal("x \\in \\mathbb{R}"))
+            )),
+            "{:?}",
+            blocks.blocks
+        );
+
+        options.math_output = MathOutput::Mathml;
+        let blocks = to_blocks("$x \\in \\R$", &options).unwrap();
+        let RenderBlock::Html { content } = &blocks.blocks[0] else {
+            panic!("expected HTML: {:?}", blocks.blocks);
+        };
+        assert!(
+            content.contains(r#"<mi mathvariant="normal">ℝ</mi>"#),
+            "{content}"
+        );
+    }
+
+    #[test]
+    fn test_recursive_math_macros_render_as_written_and_report_an_issue() {
+        let options = Options {
+            enable_math: true,
+            math_macros: BTreeMap::from([("\\x".to_string(), "\\x\\x".to_string())]),
+            ..Default::default()
+        };
+
+        let result = to_blocks("Grows $a + \\x$ here.", &options).unwrap();
+        assert!(
+            result.blocks.iter().any(|b| matches!(
+                b,
+                RenderBlock::Component { name, props, .. }
+                    if name == "MathInline"
+                        && props.get("expr") == Some(&PropValue::literal("a + \\x"))
+            )),
+            "{:?}",
+            result.blocks
+        );
+        let found: Vec<_> = result
+            .math_issues
+            .iter()
+            .map(|i| (i.message.as_str(), i.line, i.column))
+            .collect();
+        assert_eq!(
+            found,
+            vec![(
+                "macro expansion exceeded 1000 expansions (recursive macro?)",
+                1,
+                8
+            )]
+        );
+    }
+
+    #[test]
+    fn test_math_issues_validate_expanded_macros() {
+        let options = Options {
+            enable_math: true,
+            math_macros: BTreeMap::from([
+                ("\\open".to_string(), "{".to_string()),
+                ("\\half".to_string(), "\\frac{1}{2".to_string()),
+                ("\\R".to_string(), "\\mathbb{R}".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        let input =
+            "Closed $\\open a }$ here.\n\nBroken $x + \\half + y$ here.\n\nAfter $\\R + }$ here.";
+        let result = to_blocks(input, &options).unwrap();
+        let found: Vec<_> = result
+            .math_issues
+            .iter()
+            .map(|i| (i.message.as_str(), i.offset, i.line, i.column))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("unclosed `{` (after macro expansion)", 4, 3, 13),
+                ("unbalanced `}`", 5, 5, 13),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_math_issues_are_located_in_source() {
+        let input = "# Title\n\nInline $\\frac{a}{b$ and $ok$.\n\n$$\n\\begin{matrix}\na & b\n\\end{pmatrix}\n$$\n\n$\\frac{a}{b$";
+        let options = Options {
+            enable_math: true,
+            ..Default::default()
+        };
+
+        let result = to_blocks(input, &options).unwrap();
+        let found: Vec<_> = result
+            .math_issues
+            .iter()
+            .map(|i| (i.message.as_str(), i.line, i.column))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("unclosed `{`", 3, 17),
+                (r"`\begin{matrix}` closed by `\end{pmatrix}`", 8, 1),
+                ("unclosed `{`", 11, 10),
+            ]
+        );
+        assert_eq!(result.math_issues[1].formula.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_math_issues_ignore_same_text_earlier_in_document() {
+        let options = Options {
+            enable_math: true,
+            ..Default::default()
+        };
+
+        let input = "Valid $\\frac{a}{b}$ here.\n\nBroken $\\frac{a}{b$ here.";
+        let result = to_blocks(input, &options).unwrap();
+        let found: Vec<_> = result
+            .math_issues
+            .iter()
+            .map(|i| (i.line, i.column))
+            .collect();
+        assert_eq!(found, vec![(3, 17)]);
+
+        let input = "A brace } in prose.\n\n$$\n\\left(\n}\n$$";
+        let result = to_blocks(input, &options).unwrap();
+        let found: Vec<_> = result
+            .math_issues
+            .iter()
+            .map(|i| (i.line, i.column))
+            .collect();
+        assert_eq!(found[0], (5, 1), "{found:?}");
+    }
+
+    #[test]
+    fn test_toc_nests_headings_and_renders_attributes() {
+        let input = "# Title\n\n## Setup {.no-toc}\n\n## Install {#install .lead data-step=\"1\"}\n\n### Linux\n\n#### Deep\n\n## Using {props}\n";
+        let result = to_blocks(input, &Options::default()).unwrap();
+
+        let outline: Vec<(&str, Vec<&str>)> = result
+            .toc
+            .iter()
+            .map(|e| {
+                (
+                    e.slug.as_str(),
+                    e.children.iter().map(|c| c.slug.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            outline,
+            vec![("install", vec!["linux"]), ("using-props", vec![])]
+        );
+        assert_eq!(result.headings.len(), 6);
+        assert_eq!(result.headings[1].text, "Setup");
+
+        let RenderBlock::Html { content } = &result.blocks[0] else {
+            panic!("expected HTML: {:?}", result.blocks);
+        };
+        assert!(
+            content.contains(r#"<h2 id="setup" class="no-toc">Setup</h2>"#),
+            "{content}"
+        );
+        assert!(
+            content.contains(r#"<h2 id="install" class="lead" data-step="1">Install</h2>"#),
+            "{content}"
+        );
+        assert!(
+            content.contains("Using &#123;props&#125;</h2>"),
+            "{content}"
+        );
+
+        // Reserved `id=`/`class=` keys merge with `#id`/`.class`.
+        let result =
+            to_blocks("## Ship {id=ship class=\"a b\" .c}\n", &Options::default()).unwrap();
+        assert_eq!(result.headings[0].slug, "ship");
+        let RenderBlock::Html { content } = &result.blocks[0] else {
+            panic!("expected HTML: {:?}", result.blocks);
+        };
+        assert_eq!(content, r#"<h2 id="ship" class="a b c">Ship</h2>"#);
+    }
+
+    #[test]
+    fn test_toc_exclusion_by_attribute_and_depth_range() {
+        let options = Options {
+            toc: toc::TocOptions {
+                min_depth: 1,
+                max_depth: 2,
+                exclude_class: None,
+                exclude_attribute: Some("data-toc".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let input = "# Title\n\n## A {.no-toc}\n\n## B {data-toc=skip}\n\n### C\n";
+        let result = to_blocks(input, &options).unwrap();
+
+        assert_eq!(result.toc.len(), 1);
+        let slugs: Vec<&str> = result.toc[0]
+            .children
+            .iter()
+            .map(|e| e.slug.as_str())
+            .collect();
+        assert_eq!(slugs, vec!["a"]);
+    }
+
+    #[test]
+    fn test_toc_directive_renders_nested_list() {
+        let options = Options {
+            toc: toc::TocOptions {
+                directive: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let input = "::toc\n\n## A & B\n\n### C\n\n```md\n::toc\n```\n";
+        let result = to_blocks(input, &options).unwrap();
+
+        let RenderBlock::Html { content } = &result.blocks[0] else {
+            panic!("expected HTML: {:?}", result.blocks);
+        };
+        assert!(
+            content.starts_with(
+                r##"<ul><li><a href="#a--b">A &amp; B</a><ul><li><a href="#c">C</a></li></ul></li></ul>"##
+            ),
+            "{content}"
+        );
+        let code = result.blocks.iter().find_map(|b| match b {
+            RenderBlock::Code { code, .. } => Some(code.as_str()),
+            _ => None,
+        });
+        assert_eq!(code, Some("::toc"));
+
+        let result = to_blocks(input, &Options::default()).unwrap();
+        let RenderBlock::Html { content } = &result.blocks[0] else {
+            panic!("expected HTML: {:?}", result.blocks);
+        };
+        assert!(content.starts_with("<p>::toc</p>"), "{content}");
+    }
+
+    #[test]
+    fn test_toc_directive_renders_component() {
+        let options = Options {
+            toc: toc::TocOptions {
+                directive: true,
+                component: Some("TableOfContents".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let result = to_blocks("::toc\n\n## Intro\n", &options).unwrap();
+
+        let RenderBlock::Component { name, props, .. } = &result.blocks[0] else {
+            panic!("expected component: {:?}", result.blocks);
+        };
+        assert_eq!(name, "TableOfContents");
+        assert_eq!(
+            props.get("toc"),
+            Some(&PropValue::expression(
+                r#"[{"depth":2,"slug":"intro","text":"Intro","children":[]}]"#
+            ))
+        );
+        assert!(
+            result
+                .element_components
+                .contains(&"TableOfContents".to_string())
+        );
+    }
+
     #[test]
     fn test_math_disabled_by_default() {
         let input = "Price is $5 and $10";
@@ -2235,4 +2883,1205 @@ Inner ref[^1].
             all_html
         );
     }
+
+    #[test]
+    fn test_registry_option_drives_directive_names() {
+        let registry = crate::RegistryConfig::from_json(serde_json::json!({
+            "extends": "starlight",
+            "components": [{ "name": "Box", "modulePath": "~/Box", "exportType": "named" }],
+            "directiveMappings": [{ "directive": "custom-box", "component": "Box" }]
+        }))
+        .unwrap();
+        let options = Options {
+            enable_directives: true,
+            registry: Some(registry),
+            ..Default::default()
+        };
+
+        let result = to_blocks(":::custom-box\nInside\n:::", &options).unwrap();
+        assert!(
+            matches!(&result.blocks[0], RenderBlock::Component { name, .. } if name == "Box"),
+            "Expected Box component, got: {:?}",
+            result.blocks
+        );
+    }
+
+    #[test]
+    fn test_collects_component_usages_with_source_positions() {
+        let input = "```mdx\n<Fake />\n```\n\n<Tabs>\n  <Tabs.Item>\n    Text with <Badge text=\"x\" /> and <Fragment>raw</Fragment>\n  </Tabs.Item>\n</Tabs>\n";
+        let result = to_blocks(input, &Options::default()).unwrap();
+        let names: Vec<_> = result.components.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Tabs", "Badge"]);
+        assert_eq!(
+            (result.components[0].line, result.components[0].column),
+            (5, 1)
+        );
+        assert_eq!(
+            (result.components[1].line, result.components[1].column),
+            (7, 15)
+        );
+    }
+
+    #[test]
+    fn test_registry_slot_routing_for_directives() {
+        let registry = crate::RegistryConfig::from_json(serde_json::json!({
+            "extends": "starlight",
+            "components": [{ "name": "Card", "modulePath": "~/Card", "exportType": "named" }],
+            "directiveMappings": [{
+                "directive": "card",
+                "component": "Card",
+                "slots": { "bracketTitle": "title", "firstHeading": "heading", "childDirectives": true }
+            }]
+        }))
+        .unwrap();
+        let options = Options {
+            enable_directives: true,
+            registry: Some(registry),
+            ..Default::default()
+        };
+
+        let input =
+            ":::card[Hello]\n## Big\n\nBody text\n\n:::footer{slot=footer}\nBye\n:::\n\nTail\n:::";
+        let result = to_blocks(input, &options).unwrap();
+        let RenderBlock::Component {
+            name,
+            props,
+            slot_children,
+        } = &result.blocks[0]
+        else {
+            panic!("Expected Card component, got: {:?}", result.blocks);
+        };
+        assert_eq!(name, "Card");
+        assert!(!props.contains_key("title"));
+
+        let slots: Vec<_> = slot_children
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Component {
+                    name,
+                    props,
+                    slot_children,
+                } if name == "Fragment" => Some((props["slot"].clone(), slot_children)),
+                _ => None,
+            })
+            .collect();
+        let slot_names: Vec<_> = slots.iter().map(|(slot, _)| slot.clone()).collect();
+        assert_eq!(
+            slot_names,
+            vec![
+                PropValue::literal("title"),
+                PropValue::literal("heading"),
+                PropValue::literal("footer"),
+            ]
+        );
+        let default_html: String = slot_children
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Html { content } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(default_html.contains("Body text"), "{default_html}");
+        assert!(default_html.contains("Tail"), "{default_html}");
+        assert!(!default_html.contains("Bye"), "{default_html}");
+        assert!(!default_html.contains("Big"), "{default_html}");
+        // The routed footer directive must not pull in an Aside import.
+        assert_eq!(result.directive_components, vec!["Card".to_string()]);
+
+        // Outside a parent routing child directives, slot directives with
+        // unknown names stay plain text.
+        for input in [
+            ":::footer{slot=footer}\nBye\n:::",
+            ":::note\n:::footer{slot=footer}\nBye\n:::\n:::",
+        ] {
+            let result = to_blocks(input, &options).unwrap();
+            let html =
+                crate::codegen::blocks_to_jsx_string(&result.blocks, None::<fn(&str) -> Option<_>>);
+            assert!(html.contains(":::footer"), "{html}");
+            assert!(!html.contains(" slot="), "{html}");
+        }
+    }
+
+    #[test]
+    fn test_registry_prop_sources_from_context() {
+        let registry = crate::RegistryConfig::from_json(serde_json::json!({
+            "extends": "starlight",
+            "components": [{ "name": "Card", "modulePath": "~/Card", "exportType": "named" }],
+            "directiveMappings": [{
+                "directive": "card",
+                "component": "Card",
+                "injectProps": {
+                    "icon": { "source": "attribute", "name": "icon", "default": "star" },
+                    "product": { "source": "frontmatter", "path": "meta.product" },
+                    "version": { "source": "frontmatter", "path": "meta.version" },
+                    "section": { "source": "heading_slug" },
+                    "position": { "source": "index" }
+                }
+            }]
+        }))
+        .unwrap();
+        let options = Options {
+            enable_directives: true,
+            registry: Some(registry),
+            frontmatter: serde_json::json!({ "meta": { "product": "Xmdx", "version": 2 } }),
+            ..Default::default()
+        };
+
+        let input = "## Setup Steps\n\n:::card{icon=rocket}\nOne\n:::\n\n:::card\nTwo\n:::";
+        let result = to_blocks(input, &options).unwrap();
+        let cards: Vec<_> = result
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Component { name, props, .. } if name == "Card" => Some(props),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(cards.len(), 2, "{:?}", result.blocks);
+
+        assert_eq!(cards[0]["icon"], PropValue::literal("rocket"));
+        assert_eq!(cards[1]["icon"], PropValue::literal("star"));
+        assert_eq!(cards[0]["product"], PropValue::literal("Xmdx"));
+        assert_eq!(cards[0]["version"], PropValue::expression("2"));
+        assert_eq!(cards[1]["section"], PropValue::literal("setup-steps"));
+        assert_eq!(cards[0]["position"], PropValue::expression("0"));
+        assert_eq!(cards[1]["position"], PropValue::expression("1"));
+        assert_eq!(cards[0]["type"], PropValue::literal("card"));
+    }
+
+    #[test]
+    fn test_heading_slug_prop_source_inside_nested_content() {
+        let registry = crate::RegistryConfig::from_json(serde_json::json!({
+            "extends": "starlight",
+            "components": [{ "name": "Card", "modulePath": "~/Card", "exportType": "named" }],
+            "directiveMappings": [{
+                "directive": "card",
+                "component": "Card",
+                "injectProps": { "section": { "source": "heading_slug" } }
+            }]
+        }))
+        .unwrap();
+        let options = Options {
+            enable_directives: true,
+            registry: Some(registry),
+            ..Default::default()
+        };
+
+        fn sections(blocks: &[RenderBlock], out: &mut Vec<PropValue>) {
+            for block in blocks {
+                if let RenderBlock::Component {
+                    name,
+                    props,
+                    slot_children,
+                } = block
+                {
+                    if name == "Card" {
+                        out.push(props["section"].clone());
+                    }
+                    sections(slot_children, out);
+                }
+            }
+        }
+
+        let input = "## Install\n\n<Tabs>\n\n:::card\nIn JSX\n:::\n\n</Tabs>\n\n## Usage\n\n::::note\n:::card\nIn a directive\n:::\n::::\n\n::::note\n### Inner\n\n:::card\nAfter a nested heading\n:::\n::::\n";
+        let result = to_blocks(input, &options).unwrap();
+        let mut found = Vec::new();
+        sections(&result.blocks, &mut found);
+        assert_eq!(
+            found,
+            vec![
+                PropValue::literal("install"),
+                PropValue::literal("usage"),
+                PropValue::literal("inner"),
+            ],
+            "{:?}",
+            result.blocks
+        );
+    }
+
+    #[test]
+    fn test_component_renames_rewrite_tags_and_record_uses() {
+        let registry = crate::RegistryConfig::from_json(serde_json::json!({
+            "extends": "starlight",
+            "components": [{
+                "name": "Aside",
+                "modulePath": "@astrojs/starlight/components",
+                "exportType": "named",
+                "aliases": ["Callout"]
+            }],
+            "directiveMappings": [{ "directive": "legacy", "component": "Callout" }]
+        }))
+        .unwrap();
+        let mut component_renames = BTreeMap::new();
+        component_renames.insert("Callout".to_string(), "Aside".to_string());
+        let options = Options {
+            enable_directives: true,
+            registry: Some(registry),
+            component_renames,
+            ..Default::default()
+        };
+
+        let input =
+            "<Callout>\nOne\n</Callout>\n\nText <Callout>two</Callout>\n\n:::legacy\nThree\n:::\n";
+        let result = to_blocks(input, &options).unwrap();
+        let names: Vec<_> = result
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Component { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec!["Aside", "Aside"], "{:?}", result.blocks);
+        let html: String = result
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Html { content } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(html.contains("<Aside>two</Aside>"), "{html}");
+
+        let uses: Vec<_> = result
+            .alias_usages
+            .iter()
+            .map(|u| (u.alias.as_str(), u.directive.as_deref(), u.line, u.column))
+            .collect();
+        assert_eq!(
+            uses,
+            vec![
+                ("Callout", None, 1, 1),
+                ("Callout", None, 5, 6),
+                ("Callout", Some("legacy"), 7, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_element_mappings_render_components() {
+        let registry = crate::RegistryConfig::from_json(serde_json::json!({
+            "extends": "starlight",
+            "components": [
+                { "name": "Image", "modulePath": "astro:assets", "exportType": "named" },
+                { "name": "Link", "modulePath": "~/Link.astro", "exportType": "default" },
+                { "name": "Heading", "modulePath": "~/Heading.astro", "exportType": "default" },
+                { "name": "Table", "modulePath": "~/Table.astro", "exportType": "default" },
+                { "name": "Quote", "modulePath": "~/Quote.astro", "exportType": "default" },
+                { "name": "Code", "modulePath": "astro:components", "exportType": "named" }
+            ],
+            "elementMappings": [
+                { "element": "img", "component": "Image" },
+                { "element": "a", "component": "Link" },
+                { "element": "h2", "component": "Heading" },
+                { "element": "table", "component": "Table" },
+                { "element": "blockquote", "component": "Quote" },
+                { "element": "pre", "component": "Code" }
+            ]
+        }))
+        .unwrap();
+        let options = Options {
+            registry: Some(registry),
+            ..Default::default()
+        };
+
+        let input = "# Title\n\n## Intro\n\nSee ![Hero](./hero.png \"Big\") and [docs](/docs/).\n\n| a |\n| - |\n| 1 |\n\n> quoted\n\n```js title=x\nlet a;\n```\n";
+        let result = to_blocks(input, &options).unwrap();
+        let components: Vec<_> = result
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Component {
+                    name,
+                    props,
+                    slot_children,
+                } => Some((name.as_str(), props, slot_children)),
+                _ => None,
+            })
+            .collect();
+        let names: Vec<_> = components.iter().map(|(name, _, _)| *name).collect();
+        assert_eq!(names, vec!["Heading", "Table", "Quote", "Code"]);
+
+        let (_, heading_props, heading_slot) = &components[0];
+        assert_eq!(heading_props["id"], PropValue::literal("intro"));
+        assert!(matches!(
+            &heading_slot[..],
+            [RenderBlock::Html { content }] if content == "Intro"
+        ));
+        assert!(matches!(
+            &components[1].2[..],
+            [RenderBlock::Html { content }] if content.starts_with("<table><thead>")
+        ));
+        assert_eq!(components[3].1["code"], PropValue::literal("let a;"));
+        assert_eq!(components[3].1["meta"], PropValue::literal("title=x"));
+
+        let html: String = result
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Html { content } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(html.contains("<h1 id=\"title\">Title</h1>"), "{html}");
+        assert!(
+            html.contains("<Image alt={\"Hero\"} src={\"./hero.png\"} title={\"Big\"}></Image>"),
+            "{html}"
+        );
+        assert!(
+            html.contains("<Link href={\"/docs/\"}>docs</Link>"),
+            "{html}"
+        );
+        assert_eq!(
+            result.element_components,
+            vec!["Heading", "Image", "Link", "Table", "Quote", "Code"]
+        );
+    }
+
+    #[test]
+    fn test_import_local_images_hoists_relative_sources() {
+        let options = Options {
+            import_local_images: true,
+            ..Default::default()
+        };
+        let input = "![Hero](./hero.png) ![Again](./hero.png) ![Logo](logo.svg)\n\n![Remote](https://example.com/a.png) ![Root](/b.png)\n\n<img src=\"../shots/one.png\" alt=\"One\" />\n";
+        let result = to_blocks(input, &options).unwrap();
+        assert_eq!(
+            result.assets,
+            vec!["./hero.png", "./logo.svg", "../shots/one.png"]
+        );
+
+        let jsx =
+            crate::codegen::blocks_to_jsx_string(&result.blocks, None::<fn(&str) -> Option<_>>);
+        assert!(
+            jsx.contains("<img src={__xmdx_img0.src} alt=\"Hero\" />"),
+            "{jsx}"
+        );
+        assert!(
+            jsx.contains("<img src={__xmdx_img0.src} alt=\"Again\" />"),
+            "{jsx}"
+        );
+        assert!(jsx.contains("<img src={__xmdx_img1.src}"), "{jsx}");
+        assert!(jsx.contains("__xmdx_img2.src"), "{jsx}");
+        assert!(
+            jsx.contains("https://example.com/a.png") && jsx.contains("/b.png"),
+            "{jsx}"
+        );
+
+        let disabled = to_blocks(input, &Options::default()).unwrap();
+        assert!(disabled.assets.is_empty());
+    }
+
+    #[test]
+    fn test_probe_image_dimensions_adds_size_attributes() {
+        let dir = std::env::temp_dir().join(format!("xmdx-image-size-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("docs/img")).unwrap();
+        std::fs::write(dir.join("docs/img/pixel.gif"), b"GIF89a\x40\x00\x20\x00").unwrap();
+        std::fs::write(dir.join("outside.gif"), b"GIF89a\x40\x00\x20\x00").unwrap();
+
+        let options = Options {
+            probe_image_dimensions: true,
+            filepath: Some(dir.join("docs/page.md").display().to_string()),
+            project_root: Some(dir.join("docs").display().to_string()),
+            enable_lazy_images: true,
+            ..Default::default()
+        };
+        let input = "# Title\n\n![Pixel](./img/pixel.gif)\n\n![Gone](img/missing.png)\n\n![Out](../outside.gif)\n";
+        let result = to_blocks(input, &options).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        let html: String = result
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Html { content } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(
+            html.contains(
+                "<img src=\"./img/pixel.gif\" alt=\"Pixel\" width=\"64\" height=\"32\" loading=\"lazy\" />"
+            ),
+            "{html}"
+        );
+        assert!(
+            html.contains("<img src=\"img/missing.png\" alt=\"Gone\" loading=\"lazy\" />"),
+            "{html}"
+        );
+        assert!(
+            html.contains("<img src=\"../outside.gif\" alt=\"Out\" loading=\"lazy\" />"),
+            "{html}"
+        );
+        assert_eq!(result.missing_images.len(), 1);
+        assert_eq!(result.missing_images[0].src, "img/missing.png");
+        assert_eq!(
+            (
+                result.missing_images[0].line,
+                result.missing_images[0].column
+            ),
+            (5, 9)
+        );
+    }
+
+    #[test]
+    fn test_link_rewrite_points_markdown_links_at_routes() {
+        let options = Options {
+            filepath: Some("/site/src/content/docs/start/intro.md".to_string()),
+            link_rewrite: Some(LinkRewriteOptions {
+                base: "/docs".to_string(),
+                trailing_slash: crate::transform::links::TrailingSlash::Always,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let input = "[Setup](../guide/setup.md#install) [Top](#top) [Ext](https://example.com/a.md)\n\n<a href=\"./next.mdx\">Next</a>\n\n```md\n[Raw](./raw.md)\n```\n";
+        let result = to_blocks(input, &options).unwrap();
+        let jsx =
+            crate::codegen::blocks_to_jsx_string(&result.blocks, None::<fn(&str) -> Option<_>>);
+        assert!(
+            jsx.contains("href=\\\"/docs/guide/setup/#install\\\""),
+            "{jsx}"
+        );
+        assert!(jsx.contains("href=\\\"#top\\\""), "{jsx}");
+        assert!(
+            jsx.contains("href=\\\"https://example.com/a.md\\\""),
+            "{jsx}"
+        );
+        assert!(jsx.contains("href=\\\"/docs/start/next/\\\""), "{jsx}");
+        assert!(jsx.contains("./raw.md"), "{jsx}");
+    }
+
+    #[test]
+    fn test_external_links_get_rel_target_and_indicator() {
+        let options = Options {
+            external_links: Some(ExternalLinkOptions {
+                site_hosts: vec!["docs.example.com".to_string()],
+                target_blank: true,
+                new_tab_label: Some("(opens in new tab)".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let input = "[GitHub](https://github.com) [Guide](https://docs.example.com/guide/) www.rust-lang.org\n\n<a href=\"https://astro.build\">Astro</a>\n";
+        let result = to_blocks(input, &options).unwrap();
+        let jsx =
+            crate::codegen::blocks_to_jsx_string(&result.blocks, None::<fn(&str) -> Option<_>>);
+        assert!(
+            jsx.contains("href=\\\"https://github.com\\\" rel=\\\"noopener noreferrer\\\" target=\\\"_blank\\\">GitHub<span class=\\\"sr-only\\\">(opens in new tab)</span></a>"),
+            "{jsx}"
+        );
+        assert!(
+            jsx.contains("href=\\\"https://docs.example.com/guide/\\\">Guide</a>"),
+            "{jsx}"
+        );
+        assert!(
+            jsx.contains("href=\\\"http://www.rust-lang.org\\\" rel="),
+            "{jsx}"
+        );
+        assert!(jsx.contains("target=\\\"_blank\\\">Astro<span"), "{jsx}");
+
+        let icon = Options {
+            external_links: Some(ExternalLinkOptions {
+                rel: String::new(),
+                icon_component: Some("ExternalIcon".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let result = to_blocks("[GitHub](https://github.com)\n", &icon).unwrap();
+        assert_eq!(result.element_components, vec!["ExternalIcon"]);
+        let internal = to_blocks("[Guide](/guide/)\n", &icon).unwrap();
+        assert!(internal.element_components.is_empty());
+        let jsx =
+            crate::codegen::blocks_to_jsx_string(&result.blocks, None::<fn(&str) -> Option<_>>);
+        assert!(
+            jsx.contains(
+                "<a href=\"https://github.com\">GitHub<ExternalIcon aria-hidden={\"true\"}></ExternalIcon></a>"
+            ),
+            "{jsx}"
+        );
+    }
+
+    #[test]
+    fn test_collects_link_and_asset_manifest() {
+        let options = Options {
+            filepath: Some("/site/src/content/docs/guide/intro.md".to_string()),
+            link_rewrite: Some(LinkRewriteOptions::default()),
+            ..Default::default()
+        };
+        let input = "# Intro\n\nRead [the *setup*](./setup.md#install), [mail](mailto:a@b.c) and https://astro.build.\n\n![Hero](./hero.png)\n\n[Guide PDF](./guide.pdf)\n\n<video src=\"./demo.mp4\" />\n\n<a href=\"/api/\">API</a>\n\n```md\n[Skip](./skip.md)\n```\n";
+        let result = to_blocks(input, &options).unwrap();
+
+        let links: Vec<_> = result
+            .links
+            .iter()
+            .map(|l| {
+                (
+                    l.href.as_str(),
+                    l.text.as_str(),
+                    l.external,
+                    l.line,
+                    l.column,
+                )
+            })
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                ("./setup.md#install", "the setup", false, 3, 20),
+                ("mailto:a@b.c", "mail", true, 3, 48),
+                ("https://astro.build", "https://astro.build", true, 3, 66),
+                ("./guide.pdf", "Guide PDF", false, 7, 13),
+                ("/api/", "API", false, 11, 10),
+            ]
+        );
+        assert_eq!(
+            result.links[0].route.as_deref(),
+            Some("/guide/setup#install")
+        );
+        assert_eq!(result.links[0].fragment.as_deref(), Some("install"));
+        assert_eq!(result.links[4].route, None);
+
+        let site = Options {
+            external_links: Some(ExternalLinkOptions {
+                site_hosts: vec!["docs.example.com".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let site_input = "[Own](https://docs.example.com/guide/), [Other](https://github.com) and [Call](tel:+123).\n";
+        let site_links = to_blocks(site_input, &site).unwrap();
+        let external: Vec<_> = site_links
+            .links
+            .iter()
+            .map(|l| (l.text.as_str(), l.external))
+            .collect();
+        assert_eq!(
+            external,
+            vec![("Own", false), ("Other", true), ("Call", true)]
+        );
+
+        let assets: Vec<_> = result
+            .asset_references
+            .iter()
+            .map(|a| (a.src.as_str(), a.kind, a.line))
+            .collect();
+        assert_eq!(
+            assets,
+            vec![
+                ("./hero.png", AssetKind::Image, 5),
+                ("./guide.pdf", AssetKind::Download, 7),
+                ("./demo.mp4", AssetKind::Media, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wiki_links_render_as_links_outside_code() {
+        let mut wiki = crate::transform::wikilinks::WikiLinkOptions {
+            slug_fallback: false,
+            ..Default::default()
+        };
+        wiki.pages
+            .insert("Getting Started".to_string(), "/start/".to_string());
+        let options = Options {
+            wiki_links: Some(wiki),
+            ..Default::default()
+        };
+        let input = "See [[getting-started#First Steps|the basics]], [[#Usage]] and [[Missing Page]].\n\nKeep `[[Code]]` and a note[^1].\n\n```md\n[[Fenced]]\n```\n\n[^1]: Footnote.\n";
+        let result = to_blocks(input, &options).unwrap();
+        let html = match &result.blocks[0] {
+            RenderBlock::Html { content } => content.clone(),
+            other => panic!("unexpected block: {other:?}"),
+        };
+        assert!(
+            html.contains(
+                r#"<a href="/start/#first-steps" data-wikilink="getting-started">the basics</a>"#
+            ),
+            "{html}"
+        );
+        assert!(
+            html.contains(r##"<a href="#usage" data-wikilink="">Usage</a>"##),
+            "{html}"
+        );
+        assert!(
+            html.contains(
+                r#"<span data-wikilink="Missing Page" data-unresolved>Missing Page</span>"#
+            ),
+            "{html}"
+        );
+        let all = format!("{:?}", result.blocks);
+        assert!(all.contains("<code>[[Code]]</code>"), "{all}");
+        assert!(all.contains("[[Fenced]]"), "{all}");
+        assert!(all.contains("data-footnote-ref"), "{all}");
+
+        assert_eq!(
+            result
+                .unresolved_wikilinks
+                .iter()
+                .map(|l| (l.page.as_str(), l.line, l.column))
+                .collect::<Vec<_>>(),
+            vec![("Missing Page", 1, 64)]
+        );
+        assert_eq!(result.links[0].href, "/start/#first-steps");
+        assert_eq!(result.links[0].fragment.as_deref(), Some("first-steps"));
+    }
+
+    #[test]
+    fn test_wiki_links_stay_literal_inside_links() {
+        let mut wiki = crate::transform::wikilinks::WikiLinkOptions {
+            slug_fallback: false,
+            ..Default::default()
+        };
+        wiki.pages
+            .insert("Setup".to_string(), "/setup/".to_string());
+        wiki.pages
+            .insert("setup".to_string(), "/other/".to_string());
+        let options = Options {
+            wiki_links: Some(wiki),
+            ..Default::default()
+        };
+        let input = "[See [[Missing]]](https://example.com) and [[setup]].\n";
+        let result = to_blocks(input, &options).unwrap();
+        let html = match &result.blocks[0] {
+            RenderBlock::Html { content } => content.clone(),
+            other => panic!("unexpected block: {other:?}"),
+        };
+        assert!(
+            html.contains(r#"<a href="https://example.com">See [[Missing]]</a>"#),
+            "{html}"
+        );
+        // An exact page name wins over another page with the same slug.
+        assert!(
+            html.contains(r#"<a href="/other/" data-wikilink="setup">setup</a>"#),
+            "{html}"
+        );
+        assert!(result.unresolved_wikilinks.is_empty());
+        assert_eq!(
+            result
+                .links
+                .iter()
+                .map(|l| l.href.as_str())
+                .collect::<Vec<_>>(),
+            vec!["https://example.com", "/other/"]
+        );
+    }
+
+    #[test]
+    fn test_wiki_links_stay_literal_inside_jsx_links() {
+        let mut wiki = crate::transform::wikilinks::WikiLinkOptions::default();
+        wiki.pages.insert("Page".to_string(), "/page/".to_string());
+        let options = Options {
+            wiki_links: Some(wiki),
+            ..Default::default()
+        };
+        let input =
+            "<a href=\"/x\">see [[Page]]</a>\n\n<a href=\"/y\">\n\nsee **[[Page]]**\n\n</a>\n";
+        let result = to_blocks(input, &options).unwrap();
+        let html = format!("{:?}", result.blocks);
+        assert!(!html.contains("data-wikilink"), "{html}");
+        assert!(html.contains("see [[Page]]"), "{html}");
+        assert!(result.unresolved_wikilinks.is_empty());
+        assert_eq!(
+            result
+                .links
+                .iter()
+                .map(|l| l.href.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/x", "/y"]
+        );
+    }
+
+    #[test]
+    fn test_embeds_render_sections_and_guard_cycles() {
+        use crate::renderer::mdast::transclusion::{EmbedDocument, EmbedDocuments};
+
+        let dir = std::env::temp_dir().join(format!("xmdx-embeds-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let page = dir.join("page.md");
+        let input = "# Intro\n\n![[Setup#Install]]\n\n![[Setup#Usage]]\n![[Glossary]]\n\n![[Nowhere]]\n\n## Install\n";
+        std::fs::write(&page, input).unwrap();
+        std::fs::write(
+            dir.join("Setup.md"),
+            "---\ntitle: Setup\n---\n# Setup\n\n## Install\n\nRun **it**.\n\n## Usage\n\n![[page]]\n",
+        )
+        .unwrap();
+
+        let options = Options {
+            enable_embeds: true,
+            filepath: Some(page.display().to_string()),
+            project_root: Some(dir.display().to_string()),
+            embed_documents: EmbedDocuments::new(vec![EmbedDocument {
+                path: "docs/glossary.md".to_string(),
+                source: "Term: meaning.\n".to_string(),
+            }]),
+            ..Default::default()
+        };
+        let result = to_blocks(input, &options).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        let html: String = result
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Html { content } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(
+            html.contains(r#"<div class="markdown-embed" data-embed="Setup"><h2 id="install">Install</h2><p>Run <strong>it</strong>.</p></div>"#),
+            "{html}"
+        );
+        assert!(
+            html.contains(r#"<span data-embed="page" data-unresolved>page</span>"#),
+            "{html}"
+        );
+        assert!(
+            html.contains(
+                r#"<div class="markdown-embed" data-embed="Glossary"><p>Term: meaning.</p></div>"#
+            ),
+            "{html}"
+        );
+        assert!(
+            html.contains(r#"<span data-embed="Nowhere" data-unresolved>Nowhere</span>"#),
+            "{html}"
+        );
+        assert!(!html.contains("title: Setup"), "{html}");
+        let slugs: Vec<&str> = result.headings.iter().map(|h| h.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["intro", "install", "usage", "install-1"]);
+
+        let setup = dir.join("Setup.md").display().to_string();
+        let summary: Vec<_> = result
+            .transclusions
+            .iter()
+            .map(|t| {
+                (
+                    t.target.as_str(),
+                    t.path.clone(),
+                    t.issue,
+                    t.via.is_some(),
+                    t.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Setup#Install", Some(setup.clone()), None, false, 3),
+                ("Setup#Usage", Some(setup), None, false, 5),
+                (
+                    "page",
+                    Some(page.display().to_string()),
+                    Some(TransclusionIssue::Cycle),
+                    true,
+                    0
+                ),
+                (
+                    "Glossary",
+                    Some("docs/glossary.md".to_string()),
+                    None,
+                    false,
+                    6
+                ),
+                ("Nowhere", None, Some(TransclusionIssue::NotFound), false, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_includes_render_shared_files_with_shared_slugs() {
+        let dir = std::env::temp_dir().join(format!("xmdx-includes-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("guide")).unwrap();
+        std::fs::create_dir_all(dir.join("_shared")).unwrap();
+        std::fs::write(
+            dir.join("_shared/prereqs.md"),
+            "---\ntitle: Prerequisites\n---\n## Node\n\nInstall Node.\n\n## Git\n\nInstall Git.\n\n::include{file=\"./loop.md\"}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("_shared/loop.md"),
+            "Looping back:\n\n::include{file=\"prereqs.md\"}\n",
+        )
+        .unwrap();
+
+        let options = Options {
+            enable_includes: true,
+            filepath: Some(dir.join("guide/page.md").display().to_string()),
+            project_root: Some(dir.display().to_string()),
+            ..Default::default()
+        };
+        let input = "# Guide\n\n::include{file=\"../_shared/prereqs.md\" section=\"Node\"}\n\n::include{file=\"../_shared/prereqs.md#git\"}\n\n## Node\n\n::include{file=\"missing.md\"}\n\n```md\n::include{file=\"x.md\"}\n```\n";
+        let result = to_blocks(input, &options).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        let html: String = result
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Html { content } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert!(
+            html.contains(r#"<h2 id="node">Node</h2><p>Install Node.</p><h2 id="git">Git</h2><p>Install Git.</p><p>Looping back:</p><h2 id="node-1">Node</h2>"#),
+            "{html}"
+        );
+        assert!(!html.contains("Prerequisites"), "{html}");
+        let all = format!("{:?}", result.blocks);
+        assert!(all.contains(r#"::include{file=\"x.md\"}"#), "{all}");
+        let slugs: Vec<&str> = result.headings.iter().map(|h| h.slug.as_str()).collect();
+        assert_eq!(slugs, vec!["guide", "node", "git", "node-1"]);
+
+        let summary: Vec<_> = result
+            .transclusions
+            .iter()
+            .map(|t| (t.kind, t.target.as_str(), t.issue, t.line))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (TransclusionKind::Include, "../_shared/prereqs.md", None, 3),
+                (
+                    TransclusionKind::Include,
+                    "../_shared/prereqs.md#git",
+                    None,
+                    5
+                ),
+                (TransclusionKind::Include, "./loop.md", None, 0),
+                (
+                    TransclusionKind::Include,
+                    "prereqs.md",
+                    Some(TransclusionIssue::Cycle),
+                    0
+                ),
+                (
+                    TransclusionKind::Include,
+                    "missing.md",
+                    Some(TransclusionIssue::NotFound),
+                    9
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transcluded_links_and_assets_are_collected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("guide")).unwrap();
+        std::fs::create_dir_all(dir.join("_shared")).unwrap();
+        std::fs::write(
+            dir.join("_shared/setup.md"),
+            "## Install\n\nSee [config](./config.md) and ![Flow](./img/flow.png).\n\n## Other\n\n[Hidden](./hidden.md)\n",
+        )
+        .unwrap();
+
+        let options = Options {
+            enable_includes: true,
+            filepath: Some(dir.join("guide/page.md").display().to_string()),
+            project_root: Some(dir.display().to_string()),
+            ..Default::default()
+        };
+        let input = "# Guide\n\n[Home](./index.md)\n\n::include{file=\"../_shared/setup.md\" section=\"Install\"}\n";
+        let result = to_blocks(input, &options).unwrap();
+        let shared = result.transclusions[0].path.clone().unwrap();
+
+        let links: Vec<_> = result
+            .links
+            .iter()
+            .map(|l| (l.href.as_str(), l.via.as_deref(), l.line))
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                ("./index.md", None, 3),
+                ("./config.md", Some(shared.as_str()), 0),
+            ]
+        );
+        let assets: Vec<_> = result
+            .asset_references
+            .iter()
+            .map(|a| (a.src.as_str(), a.kind, a.via.as_deref(), a.line))
+            .collect();
+        assert_eq!(
+            assets,
+            vec![("./img/flow.png", AssetKind::Image, Some(shared.as_str()), 0)]
+        );
+    }
+
+    #[test]
+    fn test_embeds_drop_root_esm_of_embedded_documents() {
+        use crate::renderer::mdast::transclusion::{EmbedDocument, EmbedDocuments};
+
+        let options = Options {
+            enable_embeds: true,
+            filepath: Some("docs/page.md".to_string()),
+            embed_documents: EmbedDocuments::new(vec![
+                EmbedDocument {
+                    path: "docs/reference/card.mdx".to_string(),
+                    source: "Reference card.\n".to_string(),
+                },
+                EmbedDocument {
+                    path: "docs/guide/card.mdx".to_string(),
+                    source: "---\ntitle: Card\n---\nimport Badge from './Badge.astro';\nexport const meta = 1;\n\nGuide card.\n".to_string(),
+                },
+            ]),
+            ..Default::default()
+        };
+        let result = to_blocks("![[guide/card]]\n\n![[card]]\n", &options).unwrap();
+        let all = format!("{:?}", result.blocks);
+
+        assert!(all.contains("Guide card."), "{all}");
+        assert!(!all.contains("import Badge"), "{all}");
+        assert!(!all.contains("export const"), "{all}");
+        // Bare names match the first document in table order.
+        assert!(all.contains("Reference card."), "{all}");
+        assert_eq!(
+            result
+                .transclusions
+                .iter()
+                .map(|t| (t.path.as_deref(), t.issue))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Some("docs/guide/card.mdx"),
+                    Some(TransclusionIssue::DroppedEsm)
+                ),
+                (Some("docs/reference/card.mdx"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transclusions_stay_inside_project_root() {
+        let dir = std::env::temp_dir().join(format!("xmdx-root-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("site/docs")).unwrap();
+        std::fs::write(dir.join("secret.md"), "Secret.\n").unwrap();
+        std::fs::write(dir.join("site/notes.txt"), "Notes.\n").unwrap();
+
+        let options = Options {
+            enable_embeds: true,
+            enable_includes: true,
+            filepath: Some(dir.join("site/docs/page.md").display().to_string()),
+            project_root: Some(dir.join("site").display().to_string()),
+            ..Default::default()
+        };
+        let secret = dir.join("secret.md").display().to_string();
+        let input = format!(
+            "::include{{file=\"../../secret.md\"}}\n\n::include{{file=\"{secret}\"}}\n\n::include{{file=\"../notes.txt\"}}\n\n![[../../secret]]\n"
+        );
+        let result = to_blocks(&input, &options).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(!format!("{:?}", result.blocks).contains("Secret."));
+        let issues: Vec<_> = result.transclusions.iter().map(|t| t.issue).collect();
+        assert_eq!(
+            issues,
+            vec![
+                Some(TransclusionIssue::OutsideRoot),
+                Some(TransclusionIssue::OutsideRoot),
+                Some(TransclusionIssue::NotMarkdown),
+                Some(TransclusionIssue::OutsideRoot),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_code_snippets_fill_fences_from_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::create_dir_all(dir.join("examples")).unwrap();
+        std::fs::write(
+            dir.join("examples/client.ts"),
+            "import { connect } from './net';\n\nexport function main() {\n  // #region connect\n  const client = connect();\n  client.start();\n  // #endregion connect\n}\n",
+        )
+        .unwrap();
+
+        let options = Options {
+            import_code_snippets: true,
+            filepath: Some(dir.join("guide.md").display().to_string()),
+            project_root: Some(dir.display().to_string()),
+            ..Default::default()
+        };
+        let input = "# Guide\n\n```ts title=\"client.ts\" file=./examples/client.ts#connect\n```\n\n```ts file=./examples/client.ts lines=1\n```\n\n- Step:\n  ```ts file=./examples/client.ts#missing\n  fallback();\n  ```\n\n```ts file=./examples/gone.ts\nstale();\n```\n\n```txt file=/etc/passwd\nkept\n```\n";
+        let result = to_blocks(input, &options).unwrap();
+
+        let codes: Vec<_> = result
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Code {
+                    code, lang, meta, ..
+                } => Some((code.as_str(), lang.as_deref(), meta.as_deref())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                (
+                    "const client = connect();\nclient.start();",
+                    Some("ts"),
+                    Some("title=\"client.ts\" file=./examples/client.ts#connect")
+                ),
+                (
+                    "import { connect } from './net';",
+                    Some("ts"),
+                    Some("file=./examples/client.ts lines=1")
+                ),
+                ("stale();", Some("ts"), Some("file=./examples/gone.ts")),
+                ("kept", Some("txt"), Some("file=/etc/passwd")),
+            ]
+        );
+        let all = format!("{:?}", result.blocks);
+        assert!(all.contains("fallback();"), "{all}");
+
+        let summary: Vec<_> = result
+            .code_snippets
+            .iter()
+            .map(|s| {
+                (
+                    s.file.as_str(),
+                    s.region.as_deref(),
+                    s.issue,
+                    s.line,
+                    s.column,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("./examples/client.ts", Some("connect"), None, 3, 1),
+                ("./examples/client.ts", None, None, 6, 1),
+                (
+                    "./examples/client.ts",
+                    Some("missing"),
+                    Some(SnippetIssue::MissingRegion),
+                    10,
+                    3
+                ),
+                (
+                    "./examples/gone.ts",
+                    None,
+                    Some(SnippetIssue::MissingFile),
+                    14,
+                    1
+                ),
+                ("/etc/passwd", None, Some(SnippetIssue::OutsideRoot), 18, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_code_blocks_carry_parsed_meta() {
+        use crate::transform::code_meta::LineRange;
+
+        let input = "```ts title=\"app.ts\" {2} del={4-5} showLineNumbers wrap\nlet a;\n```\n\n```sh\nls\n```\n";
+        let result = to_blocks(input, &Options::default()).unwrap();
+        let parsed: Vec<_> = result
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Code { parsed_meta, .. } => Some(parsed_meta.as_ref()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(parsed.len(), 2);
+        let meta = parsed[0].expect("first fence has meta");
+        assert_eq!(
+            meta.raw,
+            "title=\"app.ts\" {2} del={4-5} showLineNumbers wrap"
+        );
+        assert_eq!(meta.title.as_deref(), Some("app.ts"));
+        assert_eq!(meta.highlight, vec![LineRange { start: 2, end: 2 }]);
+        assert_eq!(meta.del, vec![LineRange { start: 4, end: 5 }]);
+        assert!(meta.show_line_numbers);
+        assert_eq!(meta.flags, vec!["wrap"]);
+        assert!(parsed[1].is_none());
+    }
+
+    #[test]
+    fn test_syntax_highlighting_renders_known_languages_as_html() {
+        let options = Options {
+            syntax_highlighting: Some(HighlightOptions::default()),
+            ..Default::default()
+        };
+        let input = "```rust\nfn main() {}\n```\n\n- Step:\n  ```js\n  run();\n  ```\n\n```klingon\nQapla'\n```\n\n```rust title=\"main.rs\" {1}\nfn main() {}\n```\n";
+        let result = to_blocks(input, &options).unwrap();
+        let codes: Vec<_> = result
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Code { lang, .. } => lang.as_deref(),
+                _ => None,
+            })
+            .collect();
+        let html = format!("{:?}", result.blocks);
+
+        if cfg!(feature = "highlight") {
+            assert_eq!(codes, vec!["klingon", "rust"]);
+            assert!(
+                html.contains(r#"<pre class=\"astro-code hl-code\" tabindex=\"0\"><code class=\"language-rust\"><span class=\"hl-"#),
+                "{html}"
+            );
+            assert!(
+                html.contains("<li>") && html.contains("language-js\\\"><span"),
+                "{html}"
+            );
+        } else {
+            assert_eq!(codes, vec!["rust", "klingon", "rust"]);
+            assert!(!html.contains("hl-"), "{html}");
+        }
+    }
+
+    #[test]
+    fn test_fence_mappings_render_diagram_components() {
+        let registry = crate::RegistryConfig::from_json(serde_json::json!({
+            "extends": "starlight",
+            "components": [
+                { "name": "Mermaid", "modulePath": "~/Mermaid.astro", "exportType": "default" },
+                { "name": "Code", "modulePath": "astro:components", "exportType": "named" }
+            ],
+            "elementMappings": [{ "element": "pre", "component": "Code" }],
+            "fenceMappings": [{ "lang": "mermaid", "component": "Mermaid" }]
+        }))
+        .unwrap();
+        let options = Options {
+            registry: Some(registry),
+            ..Default::default()
+        };
+
+        let input = "```Mermaid title=\"Flow\"\ngraph TD;\n  A-->B[\"{x}\"]\n```\n\n- Step:\n  ```mermaid\n  A-->B\n  ```\n\n```ts\nlet a;\n```\n";
+        let result = to_blocks(input, &options).unwrap();
+        let components: Vec<_> = result
+            .blocks
+            .iter()
+            .filter_map(|block| match block {
+                RenderBlock::Component { name, props, .. } => Some((name.as_str(), props)),
+                _ => None,
+            })
+            .collect();
+        let names: Vec<_> = components.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, vec!["Mermaid", "Code"]);
+        assert_eq!(
+            components[0].1["code"],
+            PropValue::literal("graph TD;\n  A-->B[\"{x}\"]")
+        );
+        assert_eq!(components[0].1["lang"], PropValue::literal("Mermaid"));
+        assert_eq!(
+            components[0].1["meta"],
+            PropValue::literal("title=\"Flow\"")
+        );
+        assert_eq!(components[1].1["lang"], PropValue::literal("ts"));
+        assert_eq!(result.element_components, vec!["Mermaid", "Code"]);
+
+        let jsx =
+            crate::codegen::blocks_to_jsx_string(&result.blocks, None::<fn(&str) -> Option<_>>);
+        assert!(
+            jsx.contains(r#"<Mermaid {...{"code": "graph TD;\n  A-->B[\"{x}\"]", "lang""#),
+            "{jsx}"
+        );
+        assert!(jsx.contains("<Mermaid code={\"A-->B\"}"), "{jsx}");
+    }
 }
@@ -1,10 +1,19 @@
 //! Rendering functions for the mdast renderer.
 
-use super::context::Context;
+use super::context::{Context, escape_html_attr};
+use super::toc::TOC_PLACEHOLDER;
+use super::transclusion;
 use super::types::{HeadingEntry, PropValue, RenderBlock, Scope};
+use crate::transform::code_meta::CodeMeta;
+use crate::transform::directives::parse_directive_attrs;
+use crate::transform::links::ExternalLinkOptions;
+use crate::transform::mathml::{MathOutput, tex_to_mathml};
+use crate::transform::tex::expand_macros;
+use crate::transform::wikilinks::{WikiLink, find_wikilinks};
 use markdown::mdast::Node;
+use std::borrow::Cow;
 use std::collections::BTreeMap;
-use xmdx_core::slug::extract_custom_id;
+use xmdx_core::slug::{HeadingAttributes, extract_heading_attributes};
 
 /// Extracts plain text from a list of AST nodes (for heading text).
 ///
@@ -172,50 +181,28 @@ fn render_jsx(
 
     // 2. Handle internal directive container: <mf-directive name="..." title="...">...</mf-directive>
     if tag_name == "mf-directive" {
-        let mut directive_type = "note".to_string();
-        let mut title: Option<String> = None;
-
-        for attr in attributes {
-            if let markdown::mdast::AttributeContent::Property(prop) = attr {
-                let val = match &prop.value {
-                    Some(markdown::mdast::AttributeValue::Literal(s)) => s.clone(),
-                    _ => String::new(),
-                };
-
-                match prop.name.as_str() {
-                    "name" => directive_type = val,
-                    "title" => {
-                        title = Some(val.replace("&quot;", "\""));
-                    }
-                    _ => {}
-                }
-            }
-        }
-
-        // Look up the component name from the registry, defaulting to "Aside"
-        // Clone to avoid borrow conflicts with ctx
-        let component_name = ctx
-            .registry()
-            .get_directive_component(&directive_type)
-            .unwrap_or("Aside")
-            .to_string();
-
-        let slot_children = ctx.render_children_to_blocks(children);
+        render_directive(attributes, children, ctx);
+        return;
+    }
 
-        let mut props = BTreeMap::new();
-        props.insert("type".to_string(), PropValue::literal(directive_type));
-        if let Some(t) = title {
-            props.insert("title".to_string(), PropValue::literal(t));
-        }
+    // Internal include marker: <mf-include attrs="file=..." />
+    if tag_name == "mf-include" {
+        let (_, _, attrs) = directive_properties(attributes);
+        transclusion::render_include(&parse_directive_attrs(&attrs), ctx);
+        return;
+    }
 
-        if ctx.is_in_list() {
-            ctx.push_component_inline(&component_name, &props, &slot_children);
-        } else {
-            ctx.push_component(&component_name, props, slot_children);
-        }
+    // Internal TOC marker: <mf-toc />, filled once all headings are known
+    if tag_name == "mf-toc" {
+        ctx.push_blocks(vec![RenderBlock::Html {
+            content: TOC_PLACEHOLDER.to_string(),
+        }]);
         return;
     }
 
+    // Registry aliases (e.g., a renamed component) resolve to the canonical binding.
+    let tag_name = &ctx.jsx_component_name(tag_name);
+
     // 3. Extract props from JSX attributes
     let mut props = BTreeMap::new();
     for attr in attributes {
@@ -238,12 +225,48 @@ fn render_jsx(
         }
     }
 
-    // 5. Render children to structured blocks
+    // 4. Hoist a relative image `src` to an import when enabled. `<img>` needs
+    // the resolved URL; Astro's `<Image>`/`<Picture>` take the image module.
+    let asset_member = match tag_name.as_str() {
+        "img" => Some(".src"),
+        "Image" | "Picture" => Some(""),
+        _ => None,
+    };
+    if let Some(member) = asset_member
+        && let Some(PropValue::Literal { value }) = props.get("src")
+        && let Some(binding) = ctx.image_asset_binding(&value.clone())
+    {
+        props.insert(
+            "src".to_string(),
+            PropValue::expression(format!("{binding}{member}")),
+        );
+    }
+
+    // 5. Point `<a href>` links to Markdown files at their routes and apply
+    // the external link policy
+    if tag_name == "a"
+        && let Some(PropValue::Literal { value }) = props.get("href")
+        && let Some(route) = ctx.rewrite_link(value)
+    {
+        props.insert("href".to_string(), PropValue::literal(route));
+    }
+    let external = match props.get("href") {
+        Some(PropValue::Literal { value }) if tag_name == "a" => ctx.external_link_policy(value),
+        _ => None,
+    };
+    if let Some(policy) = external {
+        insert_external_link_props(policy, &mut props);
+    }
+
+    // 6. Render children to structured blocks
     // Note: Slot normalization (Steps → <ol>, FileTree → <ul>) is handled in codegen.rs
     // based on registry configuration, not here.
-    let slot_children = ctx.render_children_to_blocks(children);
+    let mut slot_children = ctx.render_children_to_blocks(children);
+    if let Some(policy) = external {
+        slot_children.extend(ctx.capture_blocks(|ctx| push_external_link_indicator(policy, ctx)));
+    }
 
-    // 6. Special handling for Fragment with slot attribute
+    // 7. Special handling for Fragment with slot attribute
     if tag_name == "Fragment" && props.contains_key("slot") {
         // Keep slot fragments as standalone component blocks so downstream
         // codegen can safely escape braces inside the slot HTML.
@@ -251,7 +274,7 @@ fn render_jsx(
         return;
     }
 
-    // 7. Push as component block
+    // 8. Push as component block
     // Inline JSX elements inside paragraphs, lists, or tables should be
     // rendered inline to avoid fragmenting the HTML structure.
     if ctx.is_in_list() || ctx.is_in_table() || ctx.is_in_paragraph() {
@@ -261,8 +284,224 @@ fn render_jsx(
     }
 }
 
+/// Reads the literal `name`, `title` and `attrs` properties of an `<mf-directive>`.
+fn directive_properties(
+    attributes: &[markdown::mdast::AttributeContent],
+) -> (Option<String>, Option<String>, String) {
+    let mut name = None;
+    let mut title = None;
+    let mut attrs = String::new();
+    for attr in attributes {
+        if let markdown::mdast::AttributeContent::Property(prop) = attr {
+            let val = match &prop.value {
+                Some(markdown::mdast::AttributeValue::Literal(s)) => s.clone(),
+                _ => String::new(),
+            };
+
+            match prop.name.as_str() {
+                "name" => name = Some(val),
+                "title" => title = Some(val.replace("&quot;", "\"")),
+                "attrs" => attrs = val.replace("&quot;", "\""),
+                _ => {}
+            }
+        }
+    }
+    (name, title, attrs)
+}
+
+/// Returns the `slot` attribute of a nested `<mf-directive>` node, if any.
+pub(super) fn directive_slot_name(node: &Node) -> Option<String> {
+    let Node::MdxJsxFlowElement(elem) = node else {
+        return None;
+    };
+    if elem.name.as_deref() != Some("mf-directive") {
+        return None;
+    }
+    let (_, _, attrs) = directive_properties(&elem.attributes);
+    parse_directive_attrs(&attrs)
+        .into_iter()
+        .find(|(key, _)| key == "slot")
+        .map(|(_, value)| value)
+}
+
+/// Wraps blocks in a `<Fragment slot="...">` so codegen routes them to a named slot.
+fn named_slot_block(slot: &str, slot_children: Vec<RenderBlock>) -> RenderBlock {
+    let mut props = BTreeMap::new();
+    props.insert("slot".to_string(), PropValue::literal(slot));
+    RenderBlock::Component {
+        name: "Fragment".to_string(),
+        props,
+        slot_children,
+    }
+}
+
+/// A directive occurrence, as seen by registry prop sources.
+struct DirectiveSite<'s> {
+    name: &'s str,
+    title: Option<&'s str>,
+    attrs: Vec<(String, String)>,
+    index: usize,
+}
+
+/// Evaluates a registry [`PropSource`](crate::PropSource) for one directive.
+///
+/// Returns `None` when the source yields no value and has no `default`.
+fn evaluate_prop_source(
+    source: &crate::PropSource,
+    site: &DirectiveSite,
+    ctx: &Context,
+) -> Option<PropValue> {
+    let value = match source.source.as_str() {
+        "directive_name" => Some(PropValue::literal(site.name)),
+        "bracket_title" => site.title.map(PropValue::literal),
+        "literal" => source.value.as_deref().map(PropValue::literal),
+        "attribute" => source.name.as_deref().and_then(|name| {
+            site.attrs
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| PropValue::literal(value.as_str()))
+        }),
+        "frontmatter" => source
+            .path
+            .as_deref()
+            .and_then(|path| frontmatter_value(ctx.frontmatter(), path))
+            .and_then(|value| match value {
+                serde_json::Value::Null => None,
+                serde_json::Value::String(s) => Some(PropValue::literal(s.as_str())),
+                other => Some(PropValue::expression(other.to_string())),
+            }),
+        "heading_slug" => ctx.current_heading_slug().map(PropValue::literal),
+        "index" => Some(PropValue::expression(site.index.to_string())),
+        _ => None,
+    };
+    value.or_else(|| source.default.as_deref().map(PropValue::literal))
+}
+
+/// Looks up a dotted path (`hero.actions.0.text`) in a JSON value.
+fn frontmatter_value<'v>(root: &'v serde_json::Value, path: &str) -> Option<&'v serde_json::Value> {
+    path.split('.')
+        .try_fold(root, |value, segment| match value {
+            serde_json::Value::Object(map) => map.get(segment),
+            serde_json::Value::Array(items) => {
+                segment.parse::<usize>().ok().and_then(|i| items.get(i))
+            }
+            _ => None,
+        })
+}
+
+/// Renders an `<mf-directive>` container as its registry component.
+///
+/// The directive mapping's [`SlotRouting`](crate::SlotRouting) rules decide
+/// which parts (bracket title, first heading/paragraph, `{slot=...}` child
+/// directives) become named slots instead of props or default slot content.
+fn render_directive(
+    attributes: &[markdown::mdast::AttributeContent],
+    children: &[Node],
+    ctx: &mut Context,
+) {
+    let (name, mut title, raw_attrs) = directive_properties(attributes);
+    let directive_type = name.unwrap_or_else(|| "note".to_string());
+
+    // Look up the component name from the registry, defaulting to "Aside",
+    // then apply any collision alias for its generated import.
+    // Clone to avoid borrow conflicts with ctx
+    let component = ctx
+        .registry()
+        .get_directive_component(&directive_type)
+        .unwrap_or("Aside");
+    let component_name = ctx.component_binding(component).to_string();
+    let mapping = ctx
+        .registry()
+        .get_directive_mapping(&directive_type)
+        .cloned()
+        .unwrap_or_default();
+    let routing = mapping.slots.unwrap_or_default();
+
+    // Evaluate props before the body renders so `heading_slug` sees the
+    // enclosing section rather than headings inside the directive.
+    let mut props = BTreeMap::new();
+    props.insert(
+        "type".to_string(),
+        PropValue::literal(directive_type.as_str()),
+    );
+    if routing.bracket_title.is_none()
+        && let Some(t) = &title
+    {
+        props.insert("title".to_string(), PropValue::literal(t.as_str()));
+    }
+    let site = DirectiveSite {
+        name: &directive_type,
+        title: title.as_deref(),
+        attrs: parse_directive_attrs(&raw_attrs),
+        index: ctx.next_directive_index(),
+    };
+    for (key, source) in mapping.inject_props.iter().flatten() {
+        if let Some(value) = evaluate_prop_source(source, &site, ctx) {
+            props.insert(key.clone(), value);
+        }
+    }
+    ctx.enter_directive_body();
+
+    let mut named_slots = Vec::new();
+    if let Some(slot) = &routing.bracket_title
+        && let Some(t) = title.take()
+    {
+        let content = escape_html_attr(&t);
+        named_slots.push(named_slot_block(slot, vec![RenderBlock::Html { content }]));
+    }
+
+    let mut body = children;
+    let leading_slot = match body.first() {
+        Some(Node::Heading(_)) => routing.first_heading.as_ref(),
+        Some(Node::Paragraph(_)) => routing.first_paragraph.as_ref(),
+        _ => None,
+    };
+    if let Some(slot) = leading_slot {
+        let blocks = ctx.render_children_to_blocks(&body[..1]);
+        named_slots.push(named_slot_block(slot, blocks));
+        body = &body[1..];
+    }
+
+    // Render default content in contiguous runs so slot-routed child
+    // directives can be pulled out without reordering the rest.
+    let mut slot_children = Vec::new();
+    let mut run_start = 0;
+    for (idx, child) in body.iter().enumerate() {
+        let Some(slot) = directive_slot_name(child).filter(|_| routing.child_directives) else {
+            continue;
+        };
+        slot_children.extend(ctx.render_children_to_blocks(&body[run_start..idx]));
+        if let Some(inner) = child.children() {
+            let blocks = ctx.render_children_to_blocks(inner);
+            named_slots.push(named_slot_block(&slot, blocks));
+        }
+        run_start = idx + 1;
+    }
+    slot_children.extend(ctx.render_children_to_blocks(&body[run_start..]));
+    slot_children.extend(named_slots);
+    ctx.exit_directive_body();
+
+    if ctx.is_in_list() {
+        ctx.push_component_inline(&component_name, &props, &slot_children);
+    } else {
+        ctx.push_component(&component_name, props, slot_children);
+    }
+}
+
 /// Renders a paragraph node, suppressing `<p>` wrappers in tight lists.
+///
+/// A paragraph made only of `![[...]]` lines renders the embedded documents
+/// instead when embeds are enabled.
 fn render_paragraph(para: &markdown::mdast::Paragraph, ctx: &mut Context) {
+    if ctx.options().enable_embeds
+        && let Some(targets) = transclusion::paragraph_embeds(&para.children)
+    {
+        for target in &targets {
+            transclusion::render_embed(target, ctx);
+        }
+        return;
+    }
+
     let in_tight_list = ctx.is_in_tight_list();
     if !in_tight_list {
         ctx.push_raw("<p>");
@@ -279,10 +518,36 @@ fn render_paragraph(para: &markdown::mdast::Paragraph, ctx: &mut Context) {
     }
 }
 
-/// Renders a link node as `<a>`.
+/// Renders a link node as `<a>`, or through its registry element mapping.
 fn render_link(link: &markdown::mdast::Link, ctx: &mut Context) {
+    let href = ctx
+        .rewrite_link(&link.url)
+        .unwrap_or_else(|| link.url.clone());
+    let external = ctx.external_link_policy(&href);
+
+    if let Some(component) = ctx.element_component("a") {
+        let mut props = BTreeMap::new();
+        props.insert("href".to_string(), PropValue::literal(href));
+        if let Some(title) = &link.title {
+            props.insert("title".to_string(), PropValue::literal(title.as_str()));
+        }
+        if let Some(policy) = external {
+            insert_external_link_props(policy, &mut props);
+        }
+        let slot_children = ctx.capture_blocks(|ctx| {
+            for child in &link.children {
+                render_node(child, ctx);
+            }
+            if let Some(policy) = external {
+                push_external_link_indicator(policy, ctx);
+            }
+        });
+        ctx.push_component_inline(&component, &props, &slot_children);
+        return;
+    }
+
     ctx.push_raw(r#"<a href=""#);
-    ctx.push_attr_value(&link.url);
+    ctx.push_attr_value(&href);
     ctx.push_raw(r#"""#);
 
     if let Some(title) = &link.title {
@@ -291,76 +556,220 @@ fn render_link(link: &markdown::mdast::Link, ctx: &mut Context) {
         ctx.push_raw(r#"""#);
     }
 
+    if let Some(policy) = external {
+        if !policy.rel.is_empty() {
+            ctx.push_raw(r#" rel=""#);
+            ctx.push_attr_value(&policy.rel);
+            ctx.push_raw(r#"""#);
+        }
+        if policy.target_blank {
+            ctx.push_raw(r#" target="_blank""#);
+        }
+    }
+
     ctx.push_raw(">");
 
     for child in &link.children {
         render_node(child, ctx);
     }
 
+    if let Some(policy) = external {
+        push_external_link_indicator(policy, ctx);
+    }
+
     ctx.push_raw("</a>");
 }
 
-/// Walks the AST to find a `{#custom-id}` only in the last Text node.
+/// Renders a text node, expanding `[[...]]` wiki links when enabled.
+fn render_text(value: &str, ctx: &mut Context) {
+    if ctx.wiki_links().is_none() || !value.contains("[[") {
+        ctx.push_text(value);
+        return;
+    }
+    let mut cursor = 0;
+    for link in find_wikilinks(value) {
+        ctx.push_text(&value[cursor..link.start]);
+        render_wikilink(&link, ctx);
+        cursor = link.end;
+    }
+    ctx.push_text(&value[cursor..]);
+}
+
+/// Renders a wiki link as `<a href data-wikilink>`, or as a
+/// `<span data-wikilink data-unresolved>` when its page cannot be resolved.
+fn render_wikilink(link: &WikiLink, ctx: &mut Context) {
+    let Some(options) = ctx.wiki_links() else {
+        return;
+    };
+    let text = link.display_text();
+    let Some(href) = options.resolve(link.page, link.heading) else {
+        ctx.push_raw(r#"<span data-wikilink=""#);
+        ctx.push_attr_value(link.page);
+        ctx.push_raw(r#"" data-unresolved>"#);
+        ctx.push_text(&text);
+        ctx.push_raw("</span>");
+        return;
+    };
+
+    if let Some(component) = ctx.element_component("a") {
+        let mut props = BTreeMap::new();
+        props.insert("href".to_string(), PropValue::literal(href));
+        props.insert("data-wikilink".to_string(), PropValue::literal(link.page));
+        let slot_children = ctx.capture_blocks(|ctx| ctx.push_text(&text));
+        ctx.push_component_inline(&component, &props, &slot_children);
+        return;
+    }
+
+    ctx.push_raw(r#"<a href=""#);
+    ctx.push_attr_value(&href);
+    ctx.push_raw(r#"" data-wikilink=""#);
+    ctx.push_attr_value(link.page);
+    ctx.push_raw(r#"">"#);
+    ctx.push_text(&text);
+    ctx.push_raw("</a>");
+}
+
+/// Adds an external link policy's `rel`/`target` props, keeping any the
+/// author already set.
+fn insert_external_link_props(
+    policy: &ExternalLinkOptions,
+    props: &mut BTreeMap<String, PropValue>,
+) {
+    if !policy.rel.is_empty() {
+        props
+            .entry("rel".to_string())
+            .or_insert_with(|| PropValue::literal(policy.rel.as_str()));
+    }
+    if policy.target_blank {
+        props
+            .entry("target".to_string())
+            .or_insert_with(|| PropValue::literal("_blank"));
+    }
+}
+
+/// Writes an external link policy's visually hidden label and icon.
+fn push_external_link_indicator(policy: &ExternalLinkOptions, ctx: &mut Context) {
+    if let Some(label) = &policy.new_tab_label {
+        ctx.push_raw(r#"<span class="sr-only">"#);
+        ctx.push_text(label);
+        ctx.push_raw("</span>");
+    }
+    if let Some(icon) = &policy.icon_component {
+        let icon = ctx.component_binding(icon).to_string();
+        let mut props = BTreeMap::new();
+        props.insert("aria-hidden".to_string(), PropValue::literal("true"));
+        ctx.push_component_inline(&icon, &props, &[]);
+    }
+}
+
+/// Walks the AST to find a `{#id .class key=value}` block only in the last Text node.
 ///
 /// This avoids false positives from InlineCode nodes like `` `{#bar}` ``,
-/// which should be treated as literal code, not custom heading IDs.
-fn find_custom_id_in_last_text_node(nodes: &[Node]) -> Option<&str> {
+/// which should be treated as literal code, not heading attributes.
+pub(super) fn find_heading_attributes_in_last_text_node(
+    nodes: &[Node],
+) -> Option<HeadingAttributes<'_>> {
     let last = nodes.last()?;
     match last {
         Node::Text(t) => {
-            let (_, id) = extract_custom_id(&t.value);
-            id
+            let (_, attrs) = extract_heading_attributes(&t.value);
+            attrs
         }
-        Node::Strong(s) => find_custom_id_in_last_text_node(&s.children),
-        Node::Emphasis(e) => find_custom_id_in_last_text_node(&e.children),
-        Node::Link(l) => find_custom_id_in_last_text_node(&l.children),
-        Node::Delete(d) => find_custom_id_in_last_text_node(&d.children),
-        _ => None, // InlineCode, Image, etc. — not custom ID
+        Node::Strong(s) => find_heading_attributes_in_last_text_node(&s.children),
+        Node::Emphasis(e) => find_heading_attributes_in_last_text_node(&e.children),
+        Node::Link(l) => find_heading_attributes_in_last_text_node(&l.children),
+        Node::Delete(d) => find_heading_attributes_in_last_text_node(&d.children),
+        _ => None, // InlineCode, Image, etc. — not heading attributes
     }
 }
 
 /// Renders a heading node with slug-based id and TOC entry.
 ///
-/// Supports `{#custom-id}` syntax: if the heading text ends with `{#some-id}`,
-/// that ID is used as the slug instead of auto-generating one, and the `{#...}`
-/// suffix is stripped from both the heading text metadata and the rendered output.
+/// Supports a trailing `{#custom-id .class key=value}` block: its `#id` is
+/// used as the slug instead of auto-generating one, classes and attributes
+/// are rendered on the heading, and the block is stripped from both the
+/// heading text metadata and the rendered output. Headings carrying the
+/// configured exclusion class or attribute are left out of the TOC.
 fn render_heading(heading: &markdown::mdast::Heading, ctx: &mut Context) {
     let raw_text = extract_text_from_nodes(&heading.children);
-    let custom_id = find_custom_id_in_last_text_node(&heading.children);
-    let clean_text = if custom_id.is_some() {
-        let (text, _) = extract_custom_id(&raw_text);
+    let attrs = find_heading_attributes_in_last_text_node(&heading.children);
+    let clean_text = if attrs.is_some() {
+        let (text, _) = extract_heading_attributes(&raw_text);
         text
     } else {
         raw_text.as_str()
     };
 
-    let slug = if let Some(id) = custom_id {
+    let slug = if let Some(id) = attrs.as_ref().and_then(|a| a.id) {
         ctx.reserve_slug(id);
         id.to_string()
     } else {
         ctx.generate_slug(clean_text)
     };
 
-    ctx.add_heading(HeadingEntry {
+    let entry = HeadingEntry {
         depth: heading.depth,
         slug: slug.clone(),
         text: clean_text.to_string(),
-    });
+    };
+    if !ctx.options().toc.excludes(attrs.as_ref()) {
+        ctx.add_toc_heading(entry.clone());
+    }
+    ctx.add_heading(entry);
 
+    let has_attributes = attrs.is_some();
+    let (classes, attributes) =
+        attrs.map_or((Vec::new(), Vec::new()), |a| (a.classes, a.attributes));
     let tag = format!("h{}", heading.depth);
-    ctx.push_raw(&format!("<{} id=\"{}\">", tag, slug));
+    if let Some(component) = ctx.element_component(&tag) {
+        let slot_children =
+            ctx.capture_blocks(|ctx| render_heading_content(heading, &slug, has_attributes, ctx));
+        let mut props = BTreeMap::new();
+        for (key, value) in attributes {
+            props.insert(key.to_string(), PropValue::literal(value));
+        }
+        if !classes.is_empty() {
+            props.insert("class".to_string(), PropValue::literal(classes.join(" ")));
+        }
+        props.insert("id".to_string(), PropValue::literal(slug));
+        push_element_component(&component, props, slot_children, ctx);
+        return;
+    }
+
+    ctx.push_raw(&format!("<{} id=\"{}\"", tag, slug));
+    if !classes.is_empty() {
+        ctx.push_raw(" class=\"");
+        ctx.push_attr_value(&classes.join(" "));
+        ctx.push_raw("\"");
+    }
+    for (key, value) in attributes {
+        ctx.push_raw(&format!(" {}=\"", key));
+        ctx.push_attr_value(value);
+        ctx.push_raw("\"");
+    }
+    ctx.push_raw(">");
+    render_heading_content(heading, &slug, has_attributes, ctx);
+    ctx.push_raw(&format!("</{}>", tag));
+}
 
+/// Renders a heading's children, wrapped in a self-link when autolinks are on.
+fn render_heading_content(
+    heading: &markdown::mdast::Heading,
+    slug: &str,
+    has_attributes: bool,
+    ctx: &mut Context,
+) {
     // Wrap heading content in an anchor if autolinks are enabled.
     // Skip when heading already contains a link to avoid invalid nested <a> elements.
     let autolink = ctx.heading_autolinks_enabled() && !children_contain_link(&heading.children);
     if autolink {
         ctx.push_raw("<a href=\"#");
-        ctx.push_raw(&slug);
+        ctx.push_raw(slug);
         ctx.push_raw("\">");
     }
 
-    // Render children, stripping {#id} from the last Text node if present
-    if custom_id.is_some() {
+    // Render children, stripping the attribute block from the last Text node if present
+    if has_attributes {
         render_heading_children(&heading.children, ctx);
     } else {
         for child in &heading.children {
@@ -371,8 +780,21 @@ fn render_heading(heading: &markdown::mdast::Heading, ctx: &mut Context) {
     if autolink {
         ctx.push_raw("</a>");
     }
+}
 
-    ctx.push_raw(&format!("</{}>", tag));
+/// Emits an element mapped to a registry component, inline when inside a
+/// paragraph, list or table so the surrounding HTML structure stays intact.
+fn push_element_component(
+    component: &str,
+    props: BTreeMap<String, PropValue>,
+    slot_children: Vec<RenderBlock>,
+    ctx: &mut Context,
+) {
+    if ctx.is_in_list() || ctx.is_in_table() || ctx.is_in_paragraph() {
+        ctx.push_component_inline(component, &props, &slot_children);
+    } else {
+        ctx.push_component(component, props, slot_children);
+    }
 }
 
 /// Returns true if any node in the tree is a Link (anchor).
@@ -394,30 +816,20 @@ fn children_contain_link(children: &[Node]) -> bool {
                     return true;
                 }
             }
-            Node::MdxJsxFlowElement(elem) => {
-                if children_contain_link(&elem.children) {
-                    return true;
-                }
+            Node::MdxJsxFlowElement(elem) if children_contain_link(&elem.children) => {
+                return true;
             }
-            Node::MdxJsxTextElement(elem) => {
-                if children_contain_link(&elem.children) {
-                    return true;
-                }
+            Node::MdxJsxTextElement(elem) if children_contain_link(&elem.children) => {
+                return true;
             }
-            Node::Strong(n) => {
-                if children_contain_link(&n.children) {
-                    return true;
-                }
+            Node::Strong(n) if children_contain_link(&n.children) => {
+                return true;
             }
-            Node::Emphasis(n) => {
-                if children_contain_link(&n.children) {
-                    return true;
-                }
+            Node::Emphasis(n) if children_contain_link(&n.children) => {
+                return true;
             }
-            Node::Delete(n) => {
-                if children_contain_link(&n.children) {
-                    return true;
-                }
+            Node::Delete(n) if children_contain_link(&n.children) => {
+                return true;
             }
             _ => {}
         }
@@ -425,7 +837,7 @@ fn children_contain_link(children: &[Node]) -> bool {
     false
 }
 
-/// Renders heading children, stripping the trailing `{#...}` from the deepest last Text descendant.
+/// Renders heading children, stripping the trailing `{...}` attribute block from the deepest last Text descendant.
 fn render_heading_children(children: &[Node], ctx: &mut Context) {
     if children.is_empty() {
         return;
@@ -434,23 +846,19 @@ fn render_heading_children(children: &[Node], ctx: &mut Context) {
     let last_idx = children.len() - 1;
     for (i, child) in children.iter().enumerate() {
         if i == last_idx {
-            render_node_stripping_custom_id(child, ctx);
+            render_node_stripping_attributes(child, ctx);
         } else {
             render_node(child, ctx);
         }
     }
 }
 
-/// Renders a node, stripping a trailing `{#...}` from its deepest last Text descendant.
-fn render_node_stripping_custom_id(node: &Node, ctx: &mut Context) {
+/// Renders a node, stripping a trailing `{...}` attribute block from its deepest last Text descendant.
+fn render_node_stripping_attributes(node: &Node, ctx: &mut Context) {
     match node {
         Node::Text(text) => {
-            if let Some(pos) = text.value.rfind("{#") {
-                let trimmed = text.value[..pos].trim_end();
-                ctx.push_text(trimmed);
-            } else {
-                ctx.push_text(&text.value);
-            }
+            let (trimmed, _) = extract_heading_attributes(&text.value);
+            ctx.push_text(trimmed);
         }
         Node::Strong(strong) => {
             ctx.push_raw("<strong>");
@@ -485,26 +893,100 @@ fn render_node_stripping_custom_id(node: &Node, ctx: &mut Context) {
 }
 
 /// Renders a code block, either inline (in lists/tables) or as a structured block.
+///
+/// A registry fence mapping for its language, or else a `pre` mapping,
+/// renders it as that component instead, with the source, language and meta
+/// string as props. Fences whose meta names a
+/// local file (`file=./x.ts#region`) are filled from it when snippet imports
+/// are enabled. Structured blocks carry the meta both raw and parsed; with
+/// Rust-side highlighting, known languages render as HTML instead.
 fn render_code(code: &markdown::mdast::Code, ctx: &mut Context) {
+    let value = ctx
+        .code_snippet(code.meta.as_deref())
+        .unwrap_or_else(|| code.value.clone());
+
+    let component = code
+        .lang
+        .as_deref()
+        .and_then(|lang| ctx.fence_component(lang))
+        .or_else(|| ctx.element_component("pre"));
+    if let Some(component) = component {
+        let mut props = BTreeMap::new();
+        props.insert("code".to_string(), PropValue::literal(value));
+        if let Some(lang) = &code.lang {
+            props.insert("lang".to_string(), PropValue::literal(lang.as_str()));
+        }
+        if let Some(meta) = &code.meta {
+            props.insert("meta".to_string(), PropValue::literal(meta.as_str()));
+        }
+        push_element_component(&component, props, Vec::new(), ctx);
+        return;
+    }
+
     if ctx.is_in_list() || ctx.is_in_table() {
         // Render inline to avoid fragmenting list/table HTML structure
-        ctx.push_code_inline(&code.value, code.lang.as_deref());
+        ctx.push_code_inline(&value, code.lang.as_deref());
     } else {
+        let parsed_meta = code.meta.as_deref().map(CodeMeta::parse);
+        // Annotated fences stay structured so their title and line marks
+        // reach the TypeScript highlighter instead of being dropped.
+        if !parsed_meta.as_ref().is_some_and(CodeMeta::has_annotations)
+            && let Some(html) = ctx.highlighted_code(&value, code.lang.as_deref())
+        {
+            ctx.push_raw(&html);
+            return;
+        }
         // Emit structured Code block for TypeScript processing (ExpressiveCode/Shiki)
         ctx.flush_html();
         ctx.blocks.push(RenderBlock::Code {
-            code: code.value.clone(),
+            code: value,
             lang: code.lang.clone(),
             meta: code.meta.clone(),
+            parsed_meta,
         });
     }
 }
 
-/// Renders an image node as `<img>`.
+/// Renders an image node as `<img>`, or through its registry element mapping.
 fn render_image(img: &markdown::mdast::Image, ctx: &mut Context) {
-    ctx.push_raw(r#"<img src=""#);
-    ctx.push_attr_value(&img.url);
-    ctx.push_raw(r#"""#);
+    let asset = ctx.image_asset_binding(&img.url);
+    let size = ctx.image_size(&img.url);
+
+    if let Some(component) = ctx.element_component("img") {
+        let mut props = BTreeMap::new();
+        let src = match &asset {
+            Some(binding) => PropValue::expression(binding.as_str()),
+            None => PropValue::literal(img.url.as_str()),
+        };
+        props.insert("src".to_string(), src);
+        props.insert("alt".to_string(), PropValue::literal(img.alt.as_str()));
+        if let Some(title) = &img.title {
+            props.insert("title".to_string(), PropValue::literal(title.as_str()));
+        }
+        if let Some(size) = size {
+            props.insert(
+                "width".to_string(),
+                PropValue::literal(size.width.to_string()),
+            );
+            props.insert(
+                "height".to_string(),
+                PropValue::literal(size.height.to_string()),
+            );
+        }
+        if ctx.lazy_images_enabled() {
+            props.insert("loading".to_string(), PropValue::literal("lazy"));
+        }
+        ctx.push_component_inline(&component, &props, &[]);
+        return;
+    }
+
+    if let Some(binding) = &asset {
+        ctx.push_raw(&format!("<img src={{{binding}.src}}"));
+    } else {
+        ctx.push_raw(r#"<img src=""#);
+        ctx.push_attr_value(&img.url);
+        ctx.push_raw(r#"""#);
+    }
 
     ctx.push_raw(r#" alt=""#);
     ctx.push_attr_value(&img.alt);
@@ -516,6 +998,13 @@ fn render_image(img: &markdown::mdast::Image, ctx: &mut Context) {
         ctx.push_raw(r#"""#);
     }
 
+    if let Some(size) = size {
+        ctx.push_raw(&format!(
+            r#" width="{}" height="{}""#,
+            size.width, size.height
+        ));
+    }
+
     if ctx.lazy_images_enabled() {
         ctx.push_raw(r#" loading="lazy""#);
     }
@@ -524,7 +1013,18 @@ fn render_image(img: &markdown::mdast::Image, ctx: &mut Context) {
 }
 
 /// Renders a table node as `<table>` with `<thead>` and optional `<tbody>`.
+///
+/// A registry `table` mapping wraps the rendered table in that component.
 fn render_table(table: &markdown::mdast::Table, ctx: &mut Context) {
+    if let Some(component) = ctx.element_component("table") {
+        let slot_children = ctx.capture_blocks(|ctx| render_table_html(table, ctx));
+        push_element_component(&component, BTreeMap::new(), slot_children, ctx);
+        return;
+    }
+    render_table_html(table, ctx);
+}
+
+fn render_table_html(table: &markdown::mdast::Table, ctx: &mut Context) {
     ctx.enter(Scope::Table);
     ctx.push_raw("<table>");
 
@@ -562,8 +1062,18 @@ fn render_html(html: &markdown::mdast::Html, ctx: &mut Context) {
     }
 }
 
-/// Renders a blockquote node as `<blockquote>`.
+/// Renders a blockquote node as `<blockquote>`, or through its registry element mapping.
 fn render_blockquote(quote: &markdown::mdast::Blockquote, ctx: &mut Context) {
+    if let Some(component) = ctx.element_component("blockquote") {
+        let slot_children = ctx.capture_blocks(|ctx| {
+            for child in &quote.children {
+                render_node(child, ctx);
+            }
+        });
+        push_element_component(&component, BTreeMap::new(), slot_children, ctx);
+        return;
+    }
+
     ctx.push_raw("<blockquote>");
     for child in &quote.children {
         render_node(child, ctx);
@@ -616,9 +1126,9 @@ pub fn render_node(node: &Node, ctx: &mut Context) {
                 render_node(child, ctx);
             }
         }
-        Node::Text(text) => ctx.push_text(&text.value),
+        Node::Text(text) => render_text(&text.value, ctx),
         Node::Paragraph(para) => render_paragraph(para, ctx),
-        Node::Link(link) => render_link(link, ctx),
+        Node::Link(link) => ctx.within_link(|ctx| render_link(link, ctx)),
         Node::Strong(strong) => {
             ctx.push_raw("<strong>");
             for child in &strong.children {
@@ -656,6 +1166,12 @@ pub fn render_node(node: &Node, ctx: &mut Context) {
         Node::Table(table) => render_table(table, ctx),
         Node::TableRow(_) => {}
         Node::TableCell(_) => {}
+        Node::MdxJsxFlowElement(elem) if elem.name.as_deref() == Some("a") => {
+            ctx.within_link(|ctx| render_jsx(Some("a"), &elem.attributes, &elem.children, ctx));
+        }
+        Node::MdxJsxTextElement(elem) if elem.name.as_deref() == Some("a") => {
+            ctx.within_link(|ctx| render_jsx(Some("a"), &elem.attributes, &elem.children, ctx));
+        }
         Node::MdxJsxFlowElement(elem) => {
             render_jsx(elem.name.as_deref(), &elem.attributes, &elem.children, ctx);
         }
@@ -664,18 +1180,32 @@ pub fn render_node(node: &Node, ctx: &mut Context) {
         }
         Node::FootnoteReference(fnref) => render_footnote_reference(fnref, ctx),
         Node::FootnoteDefinition(fndef) => render_footnote_definition(fndef, ctx),
-        Node::Math(math) => {
-            let mut props = BTreeMap::new();
-            props.insert("expr".to_string(), PropValue::literal(&math.value));
-            ctx.push_component("MathBlock", props, Vec::new());
-        }
-        Node::InlineMath(math) => {
-            let mut props = BTreeMap::new();
-            props.insert("expr".to_string(), PropValue::literal(&math.value));
-            ctx.push_component("MathInline", props, Vec::new());
-        }
+        Node::Math(math) => render_math(&math.value, true, ctx),
+        Node::InlineMath(math) => render_math(&math.value, false, ctx),
         _ => {
             log::warn!("Unhandled markdown node type: {:?}", node);
         }
     }
 }
+
+/// Renders a formula, after expanding configured macros, as MathML when
+/// configured and supported, otherwise as a `MathBlock`/`MathInline`
+/// component. A formula whose macros overrun the expansion budget is
+/// rendered as written; the overrun is reported as a math issue.
+fn render_math(tex: &str, display: bool, ctx: &mut Context) {
+    let tex = expand_macros(tex, &ctx.options().math_macros).unwrap_or(Cow::Borrowed(tex));
+    let tex = tex.as_ref();
+    if ctx.options().math_output == MathOutput::Mathml {
+        match tex_to_mathml(tex, display) {
+            Ok(mathml) => {
+                ctx.push_raw(&mathml);
+                return;
+            }
+            Err(err) => log::debug!("Falling back to math component: {err}"),
+        }
+    }
+    let mut props = BTreeMap::new();
+    props.insert("expr".to_string(), PropValue::literal(tex));
+    let name = if display { "MathBlock" } else { "MathInline" };
+    ctx.push_component(name, props, Vec::new());
+}
@@ -0,0 +1,236 @@
+//! Code snippets imported from local source files.
+//!
+//! A fenced code block whose meta names a file (`file=./examples/client.ts`)
+//! is filled with that file's contents, so guides cannot drift from the
+//! example code. A `#region-name` suffix selects the lines between
+//! `#region region-name` and `#endregion` comments, and `lines=10-25`
+//! selects a 1-indexed, inclusive line range (of the region, if any).
+
+use crate::transform::code_meta::CodeMeta;
+use std::path::{Path, PathBuf};
+
+/// A snippet reference parsed from a code fence meta string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnippetSpec {
+    /// File path as written, without the region (e.g., "./examples/client.ts").
+    pub file: String,
+    /// Region name after `#`, if any.
+    pub region: Option<String>,
+    /// Line range as written (e.g., "10-25"), if any.
+    pub lines: Option<String>,
+}
+
+/// Why a snippet could not be imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnippetError {
+    /// The file does not exist or could not be read.
+    MissingFile,
+    /// The file has no `#region` with the requested name.
+    MissingRegion,
+    /// The line range is malformed or outside the file (or region).
+    InvalidLines,
+    /// The path is absolute or resolves outside the project root.
+    OutsideRoot,
+}
+
+/// Reads the `file=` and `lines=` entries of a code fence meta string.
+///
+/// Returns `None` when the meta does not name a file. Values may be quoted.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::renderer::mdast::snippets::parse_snippet_meta;
+///
+/// let spec = parse_snippet_meta(r#"title="Client" file=./examples/client.ts#setup lines=2-4"#).unwrap();
+/// assert_eq!(spec.file, "./examples/client.ts");
+/// assert_eq!(spec.region.as_deref(), Some("setup"));
+/// assert_eq!(spec.lines.as_deref(), Some("2-4"));
+/// assert_eq!(parse_snippet_meta("title=\"a.ts\" {1,3}"), None);
+/// ```
+pub fn parse_snippet_meta(meta: &str) -> Option<SnippetSpec> {
+    let meta = CodeMeta::parse(meta);
+    let file = meta.attribute("file");
+    let lines = meta.attribute("lines").map(str::to_string);
+    let target = file.filter(|f| !f.is_empty())?;
+    let (file, region) = match target.split_once('#') {
+        Some((file, region)) => (file, Some(region.to_string()).filter(|r| !r.is_empty())),
+        None => (target, None),
+    };
+    Some(SnippetSpec {
+        file: file.to_string(),
+        region,
+        lines,
+    })
+}
+
+/// Resolves a snippet file relative to the document at `document_path`.
+///
+/// This only joins the paths; the renderer additionally refuses files
+/// outside [`Options::project_root`](super::Options::project_root).
+pub fn resolve_snippet_path(document_path: &str, file: &str) -> Option<PathBuf> {
+    let dir = Path::new(document_path).parent()?;
+    Some(dir.join(file))
+}
+
+/// Selects the region and line range of `spec` from a file's contents.
+///
+/// Region marker lines are dropped and the common indentation is trimmed.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::renderer::mdast::snippets::{extract_snippet, parse_snippet_meta};
+///
+/// let source = "class A {\n  // #region body\n  run() {\n    go();\n  }\n  // #endregion body\n}\n";
+/// let spec = parse_snippet_meta("file=a.ts#body").unwrap();
+/// assert_eq!(extract_snippet(source, &spec).unwrap(), "run() {\n  go();\n}");
+/// ```
+pub fn extract_snippet(source: &str, spec: &SnippetSpec) -> Result<String, SnippetError> {
+    let all: Vec<&str> = source.lines().collect();
+    let lines = match &spec.region {
+        Some(name) => region_lines(&all, name).ok_or(SnippetError::MissingRegion)?,
+        None => all,
+    };
+    let lines = match &spec.lines {
+        Some(range) => {
+            let (start, end) = parse_line_range(range).ok_or(SnippetError::InvalidLines)?;
+            if start > lines.len() {
+                return Err(SnippetError::InvalidLines);
+            }
+            lines[start - 1..end.min(lines.len())].to_vec()
+        }
+        None => lines,
+    };
+    let lines: Vec<&str> = lines
+        .into_iter()
+        .filter(|line| region_marker(line).is_none())
+        .collect();
+    Ok(trim_indent(&lines))
+}
+
+/// Returns the lines between `#region name` and its matching `#endregion`.
+fn region_lines<'s>(lines: &[&'s str], name: &str) -> Option<Vec<&'s str>> {
+    let start = lines
+        .iter()
+        .position(|line| matches!(region_marker(line), Some(Marker::Start(n)) if n == name))?;
+    let mut depth = 0usize;
+    for (offset, line) in lines[start + 1..].iter().enumerate() {
+        match region_marker(line) {
+            Some(Marker::Start(_)) => depth += 1,
+            Some(Marker::End) if depth == 0 => {
+                return Some(lines[start + 1..start + 1 + offset].to_vec());
+            }
+            Some(Marker::End) => depth -= 1,
+            None => {}
+        }
+    }
+    Some(lines[start + 1..].to_vec())
+}
+
+enum Marker<'s> {
+    Start(&'s str),
+    End,
+}
+
+/// Recognizes `#region name` / `#endregion` in any line comment style
+/// (`// #region`, `# region`, `<!-- #region -->`, `/* #region */`).
+fn region_marker(line: &str) -> Option<Marker<'_>> {
+    let trimmed = line.trim_start();
+    let body = ["//", "<!--", "/*", "#", "--", ";", "'"]
+        .iter()
+        .find_map(|prefix| trimmed.strip_prefix(prefix))?
+        .trim_start();
+    let body = body.strip_prefix('#').unwrap_or(body).trim_start();
+    let body = body
+        .trim_end()
+        .trim_end_matches("-->")
+        .trim_end_matches("*/")
+        .trim_end();
+    if let Some(rest) = body.strip_prefix("endregion") {
+        return (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some(Marker::End);
+    }
+    let rest = body.strip_prefix("region")?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let name = rest.split_whitespace().next()?;
+    Some(Marker::Start(name))
+}
+
+/// Parses `N` or `N-M` (1-indexed, inclusive).
+fn parse_line_range(range: &str) -> Option<(usize, usize)> {
+    let (start, end) = match range.split_once('-') {
+        Some((start, end)) => (start.trim().parse().ok()?, end.trim().parse().ok()?),
+        None => {
+            let line = range.trim().parse().ok()?;
+            (line, line)
+        }
+    };
+    (start >= 1 && start <= end).then_some((start, end))
+}
+
+/// Joins lines after removing the indentation they all share.
+fn trim_indent(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(meta: &str) -> SnippetSpec {
+        parse_snippet_meta(meta).unwrap()
+    }
+
+    #[test]
+    fn selects_nested_regions_and_line_ranges() {
+        let source = "import x from 'x';\n\n# region outer\ndef outer():\n    # region inner\n    return 1\n    # endregion\n# endregion\n";
+        assert_eq!(
+            extract_snippet(source, &spec("file=a.py#outer")).unwrap(),
+            "def outer():\n    return 1"
+        );
+        assert_eq!(
+            extract_snippet(source, &spec("file=a.py#inner")).unwrap(),
+            "return 1"
+        );
+        assert_eq!(
+            extract_snippet(source, &spec("file=a.py lines=1")).unwrap(),
+            "import x from 'x';"
+        );
+        assert_eq!(
+            extract_snippet(source, &spec("file='a.py#outer' lines=2-9")).unwrap(),
+            "return 1"
+        );
+    }
+
+    #[test]
+    fn reports_missing_regions_and_bad_ranges() {
+        let source = "<!-- #region page -->\n<p>Hi</p>\n<!-- #endregion -->\n";
+        assert_eq!(
+            extract_snippet(source, &spec("file=a.html#page")).unwrap(),
+            "<p>Hi</p>"
+        );
+        assert_eq!(
+            extract_snippet(source, &spec("file=a.html#other")),
+            Err(SnippetError::MissingRegion)
+        );
+        for lines in ["0-2", "3-1", "x", "9"] {
+            assert_eq!(
+                extract_snippet(source, &spec(&format!("file=a.html lines={lines}"))),
+                Err(SnippetError::InvalidLines),
+                "{lines}"
+            );
+        }
+    }
+}
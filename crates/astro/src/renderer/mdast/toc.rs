@@ -0,0 +1,195 @@
+//! Table of contents built from the document's headings.
+//!
+//! [`build_toc`] nests the flat heading list so layouts do not each
+//! re-implement it. A `::toc` line (with [`TocOptions::directive`]) marks
+//! where the tree is rendered in the page; the marker is filled in once
+//! every heading, including later ones, is known.
+
+use super::context::{escape_code_text, escape_html_attr};
+use super::types::{HeadingEntry, PropValue, RenderBlock, TocEntry};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use xmdx_core::slug::HeadingAttributes;
+
+/// Stand-in block content for a `::toc` directive until the tree is known.
+pub(super) const TOC_PLACEHOLDER: &str = "<!--xmdx:toc-->";
+
+/// Table of contents configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TocOptions {
+    /// Shallowest heading level included. Defaults to 2.
+    #[serde(default = "default_min_depth")]
+    pub min_depth: u8,
+    /// Deepest heading level included. Defaults to 3.
+    #[serde(default = "default_max_depth")]
+    pub max_depth: u8,
+    /// Headings with this class (`## Changelog {.no-toc}`) are left out.
+    /// Defaults to `no-toc`.
+    #[serde(default = "default_exclude_class")]
+    pub exclude_class: Option<String>,
+    /// Headings with this attribute (`## Changelog {data-toc=skip}`) are
+    /// left out, whatever its value.
+    #[serde(default)]
+    pub exclude_attribute: Option<String>,
+    /// Whether a `::toc` line renders the table of contents in place.
+    #[serde(default)]
+    pub directive: bool,
+    /// Registry component rendered for `::toc`, receiving the tree as its
+    /// `toc` prop. When `None`, `::toc` renders a nested `<ul>`.
+    #[serde(default)]
+    pub component: Option<String>,
+}
+
+fn default_min_depth() -> u8 {
+    2
+}
+
+fn default_max_depth() -> u8 {
+    3
+}
+
+fn default_exclude_class() -> Option<String> {
+    Some("no-toc".to_string())
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        Self {
+            min_depth: default_min_depth(),
+            max_depth: default_max_depth(),
+            exclude_class: default_exclude_class(),
+            exclude_attribute: None,
+            directive: false,
+            component: None,
+        }
+    }
+}
+
+impl TocOptions {
+    /// Returns whether a heading with these attributes is left out.
+    pub fn excludes(&self, attrs: Option<&HeadingAttributes<'_>>) -> bool {
+        let Some(attrs) = attrs else {
+            return false;
+        };
+        let by_class = self
+            .exclude_class
+            .as_deref()
+            .is_some_and(|class| attrs.classes.contains(&class));
+        let by_attribute = self
+            .exclude_attribute
+            .as_deref()
+            .is_some_and(|name| attrs.attributes.iter().any(|(key, _)| *key == name));
+        by_class || by_attribute
+    }
+}
+
+/// Nests headings within `[min_depth, max_depth]` into a tree.
+///
+/// Each heading becomes a child of the closest preceding heading with a
+/// smaller depth; skipped levels do not create empty entries.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::renderer::mdast::HeadingEntry;
+/// use xmdx_astro::renderer::mdast::toc::{TocOptions, build_toc};
+///
+/// let heading = |depth, slug: &str| HeadingEntry {
+///     depth,
+///     slug: slug.to_string(),
+///     text: slug.to_string(),
+/// };
+/// let headings = [heading(1, "title"), heading(2, "a"), heading(4, "a-1"), heading(2, "b")];
+/// let toc = build_toc(&headings, &TocOptions { max_depth: 4, ..Default::default() });
+/// assert_eq!(toc.len(), 2);
+/// assert_eq!(toc[0].children[0].slug, "a-1");
+/// assert!(toc[1].children.is_empty());
+/// ```
+pub fn build_toc(headings: &[HeadingEntry], options: &TocOptions) -> Vec<TocEntry> {
+    let mut roots = Vec::new();
+    for heading in headings
+        .iter()
+        .filter(|h| (options.min_depth..=options.max_depth).contains(&h.depth))
+    {
+        insert_entry(
+            &mut roots,
+            TocEntry {
+                depth: heading.depth,
+                slug: heading.slug.clone(),
+                text: heading.text.clone(),
+                children: Vec::new(),
+            },
+        );
+    }
+    roots
+}
+
+fn insert_entry(siblings: &mut Vec<TocEntry>, entry: TocEntry) {
+    match siblings.last_mut() {
+        Some(last) if last.depth < entry.depth => insert_entry(&mut last.children, entry),
+        _ => siblings.push(entry),
+    }
+}
+
+/// Renders a tree as nested `<ul>` lists of heading links.
+pub fn toc_html(entries: &[TocEntry]) -> String {
+    let mut html = String::new();
+    write_list(entries, &mut html);
+    html
+}
+
+fn write_list(entries: &[TocEntry], html: &mut String) {
+    if entries.is_empty() {
+        return;
+    }
+    html.push_str("<ul>");
+    for entry in entries {
+        html.push_str("<li><a href=\"#");
+        html.push_str(&escape_html_attr(&entry.slug));
+        html.push_str("\">");
+        html.push_str(&escape_code_text(&entry.text));
+        html.push_str("</a>");
+        write_list(&entry.children, html);
+        html.push_str("</li>");
+    }
+    html.push_str("</ul>");
+}
+
+/// Replaces `::toc` placeholders with the rendered tree: a `component`
+/// block when one is configured and the placeholder is a block of its own,
+/// otherwise the nested list. Returns whether the component was emitted.
+pub(super) fn fill_toc_placeholders(
+    blocks: &mut [RenderBlock],
+    toc: &[TocEntry],
+    component: Option<&str>,
+) -> bool {
+    let mut used_component = false;
+    for block in blocks {
+        match block {
+            RenderBlock::Html { content } if content == TOC_PLACEHOLDER => {
+                if let Some(name) = component {
+                    let mut props = BTreeMap::new();
+                    let json = serde_json::to_string(toc).unwrap_or_else(|_| "[]".to_string());
+                    props.insert("toc".to_string(), PropValue::expression(json));
+                    *block = RenderBlock::Component {
+                        name: name.to_string(),
+                        props,
+                        slot_children: Vec::new(),
+                    };
+                    used_component = true;
+                } else {
+                    *content = toc_html(toc);
+                }
+            }
+            RenderBlock::Html { content } if content.contains(TOC_PLACEHOLDER) => {
+                *content = content.replace(TOC_PLACEHOLDER, &toc_html(toc));
+            }
+            RenderBlock::Component { slot_children, .. } => {
+                used_component |= fill_toc_placeholders(slot_children, toc, component);
+            }
+            _ => {}
+        }
+    }
+    used_component
+}
@@ -0,0 +1,329 @@
+//! Rendering other Markdown documents in place.
+//!
+//! Vault content embeds notes with `![[Note]]` and sections of notes with
+//! `![[Note#Section]]`; docs share boilerplate with
+//! `::include{file="../_shared/prereqs.md" section="Node"}`. The transcluded
+//! document is parsed with the including document's options and rendered
+//! into its blocks, sharing heading slugs, footnotes and hoisted assets.
+//! Only `.md`/`.mdx` files inside
+//! [`Options::project_root`](super::Options::project_root) are read.
+//! Every transclusion is reported in
+//! [`BlocksResult::transclusions`](super::BlocksResult::transclusions) so
+//! dev servers can watch the files a page depends on.
+
+use super::render::{extract_text_from_nodes, find_heading_attributes_in_last_text_node};
+use super::types::{RenderBlock, Transclusion, TransclusionIssue, TransclusionKind};
+use super::{
+    Context, LocalFileError, Options, parse_document, resolve_local_file, unmask_raw_html_blocks,
+};
+use crate::transform::code_fence;
+use markdown::mdast::{Node, Root};
+use std::collections::HashMap;
+use std::sync::Arc;
+use xmdx_core::slug::{extract_heading_attributes, slugify};
+
+/// A document that can be embedded without reading it from disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbedDocument {
+    /// Path of the document (e.g., "src/content/docs/guide/setup.md").
+    pub path: String,
+    /// Full source, including frontmatter.
+    pub source: String,
+}
+
+/// Documents available to embeds, shared across the documents of a batch.
+///
+/// Clones share the same documents.
+#[derive(Debug, Clone, Default)]
+pub struct EmbedDocuments {
+    documents: Arc<Vec<EmbedDocument>>,
+    /// Page keys of the documents, grouped by their last segment. Indices
+    /// are in document order.
+    index: Arc<HashMap<String, Vec<(usize, String)>>>,
+}
+
+impl EmbedDocuments {
+    /// Creates a table of embeddable documents.
+    pub fn new(documents: Vec<EmbedDocument>) -> Self {
+        let mut index: HashMap<String, Vec<(usize, String)>> = HashMap::new();
+        for (idx, document) in documents.iter().enumerate() {
+            let key = page_key(&document.path);
+            let last = key.rsplit('/').next().unwrap_or_default().to_string();
+            index.entry(last).or_default().push((idx, key));
+        }
+        Self {
+            documents: Arc::new(documents),
+            index: Arc::new(index),
+        }
+    }
+
+    /// Finds the document a note name refers to.
+    ///
+    /// Names match the end of a document's path by slug, without the
+    /// extension: `Getting Started` and `guide/getting-started` both match
+    /// `docs/guide/getting-started.md`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xmdx_astro::renderer::mdast::transclusion::{EmbedDocument, EmbedDocuments};
+    ///
+    /// let documents = EmbedDocuments::new(vec![EmbedDocument {
+    ///     path: "docs/guide/getting-started.md".into(),
+    ///     source: "# Getting started".into(),
+    /// }]);
+    /// assert!(documents.find("Getting Started").is_some());
+    /// assert!(documents.find("guide/getting-started.md").is_some());
+    /// assert!(documents.find("reference/getting-started").is_none());
+    /// ```
+    pub fn find(&self, name: &str) -> Option<&EmbedDocument> {
+        let key = page_key(name);
+        if key.is_empty() {
+            return None;
+        }
+        let suffix = format!("/{key}");
+        let last = key.rsplit('/').next().unwrap_or_default();
+        self.index
+            .get(last)?
+            .iter()
+            .find(|(_, path)| *path == key || path.ends_with(&suffix))
+            .map(|(idx, _)| &self.documents[*idx])
+    }
+}
+
+/// Returns the embed targets of a paragraph made only of `![[...]]` lines.
+///
+/// Targets are returned as written between the brackets (e.g.,
+/// `Setup#Install`). `None` when any part of the paragraph is something else.
+pub(super) fn paragraph_embeds(children: &[Node]) -> Option<Vec<String>> {
+    let mut text = String::new();
+    for child in children {
+        match child {
+            Node::Text(t) => text.push_str(&t.value),
+            _ => return None,
+        }
+    }
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let inner = line.strip_prefix("![[")?.strip_suffix("]]")?;
+            (!inner.trim().is_empty() && !inner.contains(['[', ']'])).then(|| inner.to_string())
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|targets| !targets.is_empty())
+}
+
+/// Renders an embed target, or an unresolved placeholder when it cannot be.
+pub(super) fn render_embed(target: &str, ctx: &mut Context) {
+    let reference = target.split('|').next().unwrap_or(target);
+    let (page, section) = match reference.split_once('#') {
+        Some((page, section)) => (page.trim(), Some(section.trim())),
+        None => (reference.trim(), None),
+    };
+    let document = find_embed_document(ctx.options(), page);
+    match transclude(TransclusionKind::Embed, target, document, section, ctx) {
+        Some(blocks) => {
+            ctx.push_raw(r#"<div class="markdown-embed" data-embed=""#);
+            ctx.push_attr_value(page);
+            ctx.push_raw(r#"">"#);
+            ctx.push_blocks(blocks);
+            ctx.push_raw("</div>");
+        }
+        None => {
+            ctx.push_raw(r#"<p><span data-embed=""#);
+            ctx.push_attr_value(page);
+            ctx.push_raw(r#"" data-unresolved>"#);
+            ctx.push_text(reference.trim());
+            ctx.push_raw("</span></p>");
+        }
+    }
+}
+
+/// Renders an `::include` directive's file (or a section of it) in place.
+///
+/// The section comes from the `section` attribute or a `#fragment` on the
+/// file. Nothing is rendered when the file cannot be included.
+pub(super) fn render_include(attrs: &[(String, String)], ctx: &mut Context) {
+    let attr = |name: &str| {
+        attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let target = attr("file").unwrap_or_default();
+    let (file, fragment) = match target.split_once('#') {
+        Some((file, fragment)) => (file, Some(fragment)),
+        None => (target, None),
+    };
+    let section = attr("section").or(fragment);
+    let document = read_relative(ctx.options(), file);
+    if let Some(blocks) = transclude(TransclusionKind::Include, target, document, section, ctx) {
+        ctx.push_blocks(blocks);
+    }
+}
+
+/// Reads the Markdown `file` relative to the directory of the current
+/// document, within [`Options::project_root`].
+fn read_relative(options: &Options, file: &str) -> Result<EmbedDocument, TransclusionIssue> {
+    if file.is_empty() {
+        return Err(TransclusionIssue::NotFound);
+    }
+    let lower = file.to_ascii_lowercase();
+    if !lower.ends_with(".md") && !lower.ends_with(".mdx") {
+        return Err(TransclusionIssue::NotMarkdown);
+    }
+    let path = resolve_local_file(options, file).map_err(|err| match err {
+        LocalFileError::Missing => TransclusionIssue::NotFound,
+        LocalFileError::OutsideRoot => TransclusionIssue::OutsideRoot,
+    })?;
+    let source = std::fs::read_to_string(&path).map_err(|_| TransclusionIssue::NotFound)?;
+    Ok(EmbedDocument {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+/// Looks a note up in [`Options::embed_documents`], then on disk next to
+/// the current document (`Note.md`, `Note.mdx`).
+fn find_embed_document(options: &Options, page: &str) -> Result<EmbedDocument, TransclusionIssue> {
+    if page.is_empty() {
+        return Err(TransclusionIssue::NotFound);
+    }
+    if let Some(document) = options.embed_documents.find(page) {
+        return Ok(document.clone());
+    }
+    let lower = page.to_ascii_lowercase();
+    let candidates = if lower.ends_with(".md") || lower.ends_with(".mdx") {
+        vec![page.to_string()]
+    } else {
+        vec![format!("{page}.md"), format!("{page}.mdx")]
+    };
+    let mut issue = TransclusionIssue::NotFound;
+    for candidate in &candidates {
+        match read_relative(options, candidate) {
+            Ok(document) => return Ok(document),
+            Err(TransclusionIssue::NotFound) => {}
+            Err(other) => issue = other,
+        }
+    }
+    Err(issue)
+}
+
+/// Renders `document` (or one of its sections) into blocks, recording the
+/// transclusion. Returns `None` when nothing could be rendered.
+fn transclude(
+    kind: TransclusionKind,
+    target: &str,
+    document: Result<EmbedDocument, TransclusionIssue>,
+    section: Option<&str>,
+    ctx: &mut Context,
+) -> Option<Vec<RenderBlock>> {
+    let mut record = Transclusion {
+        kind,
+        target: target.to_string(),
+        path: document.as_ref().ok().map(|d| d.path.clone()),
+        issue: None,
+        via: ctx.transcluded_from().map(str::to_string),
+        line: 0,
+        column: 0,
+    };
+    let document = match document {
+        Ok(document) => document,
+        Err(issue) => {
+            record.issue = Some(issue);
+            ctx.record_transclusion(record);
+            return None;
+        }
+    };
+    if ctx.is_transcluding(&document.path) {
+        record.issue = Some(TransclusionIssue::Cycle);
+        ctx.record_transclusion(record);
+        return None;
+    }
+
+    let body_start = xmdx_core::extract_frontmatter(&document.source)
+        .map(|fm| fm.body_start)
+        .unwrap_or(0);
+    let options = Options {
+        filepath: Some(document.path.clone()),
+        ..ctx.options().clone()
+    };
+    // Root `import`/`export` statements would otherwise render as text.
+    // They are not carried over into the including module.
+    let (statements, body_lines) =
+        code_fence::collect_root_statements(&document.source[body_start..]);
+    if !statements.imports.is_empty() || !statements.exports.is_empty() {
+        record.issue = Some(TransclusionIssue::DroppedEsm);
+    }
+    let body = body_lines.join("\n");
+    let Ok((tree, masks)) = parse_document(&body, &options) else {
+        record.issue = Some(TransclusionIssue::ParseError);
+        ctx.record_transclusion(record);
+        return None;
+    };
+    let children = tree.children().map(Vec::as_slice).unwrap_or_default();
+    let nodes = match section.filter(|s| !s.is_empty()) {
+        Some(section) => match select_section(children, section) {
+            Some(nodes) => nodes,
+            None => {
+                record.issue = Some(TransclusionIssue::MissingSection);
+                ctx.record_transclusion(record);
+                return None;
+            }
+        },
+        None => children,
+    };
+
+    ctx.record_transclusion(record);
+    let mut blocks = ctx.render_transcluded(&options, nodes);
+    unmask_raw_html_blocks(&mut blocks, &masks);
+    // Only the rendered section is kept, so later passes see what is on the page.
+    let rendered = Node::Root(Root {
+        children: nodes.to_vec(),
+        position: None,
+    });
+    ctx.add_transcluded_tree(document.path, rendered);
+    Some(blocks)
+}
+
+/// Returns the heading matching `section` (by slug or `{#id}`) and the
+/// nodes up to the next heading of the same or a higher level.
+fn select_section<'n>(nodes: &'n [Node], section: &str) -> Option<&'n [Node]> {
+    let wanted = slugify(section, &mut HashMap::new());
+    let start = nodes.iter().position(|node| {
+        let Node::Heading(heading) = node else {
+            return false;
+        };
+        match find_heading_attributes_in_last_text_node(&heading.children).and_then(|a| a.id) {
+            Some(id) => id == section || id == wanted,
+            None => {
+                let text = extract_text_from_nodes(&heading.children);
+                let (text, _) = extract_heading_attributes(&text);
+                slugify(text, &mut HashMap::new()) == wanted
+            }
+        }
+    })?;
+    let Node::Heading(heading) = &nodes[start] else {
+        return None;
+    };
+    let end = nodes[start + 1..]
+        .iter()
+        .position(|node| matches!(node, Node::Heading(h) if h.depth <= heading.depth))
+        .map_or(nodes.len(), |offset| start + 1 + offset);
+    Some(&nodes[start..end])
+}
+
+/// Slugs each path segment of a page name or path, dropping `.md`/`.mdx`.
+fn page_key(name: &str) -> String {
+    let lower = name.to_ascii_lowercase();
+    let name = [".mdx", ".md"]
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map_or(name, |ext| &name[..name.len() - ext.len()]);
+    name.split(['/', '\\'])
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .map(|segment| slugify(segment.trim(), &mut HashMap::new()))
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -1,5 +1,7 @@
 //! Type definitions for the mdast renderer.
 
+use crate::registry::RegistryConfig;
+use crate::transform::code_meta::CodeMeta;
 use serde::Serialize;
 use std::collections::BTreeMap;
 
@@ -72,6 +74,8 @@ pub enum RenderBlock {
         lang: Option<String>,
         /// Optional meta string (e.g., for line highlighting).
         meta: Option<String>,
+        /// The meta string parsed into title, line ranges, flags and pairs.
+        parsed_meta: Option<CodeMeta>,
     },
 }
 
@@ -86,6 +90,224 @@ pub struct HeadingEntry {
     pub text: String,
 }
 
+/// A PascalCase JSX component referenced by the document.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ComponentUsage {
+    /// Component binding name (e.g., "Tabs" for both `<Tabs>` and `<Tabs.Item>`).
+    pub name: String,
+    /// 1-indexed line of the first occurrence (0 when unknown).
+    pub line: usize,
+    /// 1-indexed column of the first occurrence (0 when unknown).
+    pub column: usize,
+}
+
+/// A use of a registry component alias that was rewritten to its canonical name.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AliasUsage {
+    /// Alias as referenced (e.g., "Callout").
+    pub alias: String,
+    /// Canonical component the alias resolves to (e.g., "Aside").
+    pub canonical: String,
+    /// Directive whose mapping targets the alias; `None` for JSX tags.
+    pub directive: Option<String>,
+    /// 1-indexed line of the use (0 when unknown).
+    pub line: usize,
+    /// 1-indexed column of the use (0 when unknown).
+    pub column: usize,
+}
+
+/// A local image whose file could not be found while probing dimensions.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MissingImage {
+    /// Image source as written (e.g., "./hero.png").
+    pub src: String,
+    /// Resolved path that was looked up.
+    pub path: String,
+    /// 1-indexed line of the image (0 when unknown).
+    pub line: usize,
+    /// 1-indexed column of the image (0 when unknown).
+    pub column: usize,
+}
+
+/// A structural problem in a math formula's TeX (e.g., an unclosed `{`).
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MathIssue {
+    /// What is wrong (e.g., "unknown environment `tikzcd`").
+    pub message: String,
+    /// The formula's TeX as written, without `$` delimiters.
+    pub formula: String,
+    /// Byte offset of the problem within `formula`.
+    pub offset: usize,
+    /// 1-indexed line of the problem (0 when unknown).
+    pub line: usize,
+    /// 1-indexed column of the problem (0 when unknown).
+    pub column: usize,
+}
+
+/// An outbound link found in the document.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct LinkReference {
+    /// Link target as written (e.g., "../guide/setup.md#install").
+    pub href: String,
+    /// Route the link was rewritten to, when link rewriting applies.
+    pub route: Option<String>,
+    /// Visible link text.
+    pub text: String,
+    /// Whether the link leaves the site: another host, or a non-web scheme
+    /// such as `mailto:`.
+    pub external: bool,
+    /// Fragment identifier without the `#`, if any.
+    pub fragment: Option<String>,
+    /// Path of the transcluded document containing the link; `None` when
+    /// it was written in this document. Relative links resolve against it.
+    pub via: Option<String>,
+    /// 1-indexed line of the link (0 when unknown or nested).
+    pub line: usize,
+    /// 1-indexed column of the link (0 when unknown or nested).
+    pub column: usize,
+}
+
+/// Kind of file referenced by an [`AssetReference`].
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetKind {
+    /// An image (`![alt](src)`, `<img>`, `<Image>`, `<Picture>`).
+    Image,
+    /// Audio or video (`<video>`, `<audio>`, `<source>`).
+    Media,
+    /// A linked file that is not a page (e.g., `[PDF](./guide.pdf)`).
+    Download,
+}
+
+/// A file referenced by the document.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct AssetReference {
+    /// Source as written (e.g., "./hero.png").
+    pub src: String,
+    /// What kind of file the source refers to.
+    pub kind: AssetKind,
+    /// Path of the transcluded document containing the reference; `None`
+    /// when it was written in this document.
+    pub via: Option<String>,
+    /// 1-indexed line of the reference (0 when unknown or nested).
+    pub line: usize,
+    /// 1-indexed column of the reference (0 when unknown or nested).
+    pub column: usize,
+}
+
+/// A wiki link whose page could not be resolved.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct UnresolvedWikiLink {
+    /// Page name as written (e.g., "Getting Started").
+    pub page: String,
+    /// 1-indexed line of the link (0 when unknown).
+    pub line: usize,
+    /// 1-indexed column of the link (0 when unknown).
+    pub column: usize,
+}
+
+/// How another document was transcluded.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TransclusionKind {
+    /// An Obsidian-style `![[Note]]` or `![[Note#Section]]` embed.
+    Embed,
+    /// An `::include{file="..."}` directive.
+    Include,
+}
+
+/// Why a transclusion could not be rendered, or rendered incompletely.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransclusionIssue {
+    /// No document matches the target.
+    NotFound,
+    /// The document has no heading matching the section selector.
+    MissingSection,
+    /// The document is already being transcluded (directly or indirectly).
+    Cycle,
+    /// The document could not be parsed.
+    ParseError,
+    /// The path is absolute or resolves outside the project root.
+    OutsideRoot,
+    /// The file is not a Markdown (`.md`/`.mdx`) document.
+    NotMarkdown,
+    /// The document has root `import`/`export` statements. It was rendered
+    /// without them, so components it imports may be unresolved.
+    DroppedEsm,
+}
+
+/// A document rendered into this one, or an attempt to.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Transclusion {
+    /// How the document was referenced.
+    pub kind: TransclusionKind,
+    /// Target as written (e.g., "Setup#Install" or "../_shared/prereqs.md").
+    pub target: String,
+    /// Path of the resolved document; `None` when it was not found.
+    pub path: Option<String>,
+    /// Why the document was not rendered (or, for
+    /// [`TransclusionIssue::DroppedEsm`], what was left out of it).
+    pub issue: Option<TransclusionIssue>,
+    /// Path of the transcluded document containing the reference; `None`
+    /// when it was written in this document.
+    pub via: Option<String>,
+    /// 1-indexed line of the reference (0 when unknown or nested).
+    pub line: usize,
+    /// 1-indexed column of the reference (0 when unknown or nested).
+    pub column: usize,
+}
+
+/// Why a code snippet could not be imported.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnippetIssue {
+    /// The file does not exist or could not be read.
+    MissingFile,
+    /// The file has no `#region` with the requested name.
+    MissingRegion,
+    /// The line range is malformed or outside the file (or region).
+    InvalidLines,
+    /// The path is absolute or resolves outside the project root.
+    OutsideRoot,
+}
+
+/// A fenced code block filled from a local file, or an attempt to.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CodeSnippet {
+    /// File as written in the fence meta (e.g., "./examples/client.ts").
+    pub file: String,
+    /// Resolved path that was read.
+    pub path: String,
+    /// Region name, if any.
+    pub region: Option<String>,
+    /// Line range as written (e.g., "10-25"), if any.
+    pub lines: Option<String>,
+    /// Why the snippet was not imported, if it was not. The fence keeps
+    /// its written contents in that case.
+    pub issue: Option<SnippetIssue>,
+    /// Path of the transcluded document containing the fence; `None` when
+    /// it was written in this document.
+    pub via: Option<String>,
+    /// 1-indexed line of the code fence (0 when unknown or nested).
+    pub line: usize,
+    /// 1-indexed column of the code fence (0 when unknown or nested).
+    pub column: usize,
+}
+
+/// A table of contents entry with the headings nested under it.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct TocEntry {
+    /// Heading depth (1-6).
+    pub depth: u8,
+    /// Slugified identifier.
+    pub slug: String,
+    /// Visible heading text.
+    pub text: String,
+    /// Deeper headings up to the next one of the same or a higher level.
+    pub children: Vec<TocEntry>,
+}
+
 /// Result of parsing markdown to blocks with extracted metadata.
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct BlocksResult {
@@ -93,6 +315,56 @@ pub struct BlocksResult {
     pub blocks: Vec<RenderBlock>,
     /// Extracted heading metadata.
     pub headings: Vec<HeadingEntry>,
+    /// Headings nested into a table of contents, per [`TocOptions`](super::toc::TocOptions).
+    pub toc: Vec<TocEntry>,
+    /// PascalCase JSX components referenced in the source, in first-use order.
+    pub components: Vec<ComponentUsage>,
+    /// Registry components rendered for directives (canonical names, first-use order).
+    pub directive_components: Vec<String>,
+    /// Registry components rendered for Markdown elements and mapped code
+    /// fences (canonical names, first-use order).
+    pub element_components: Vec<String>,
+    /// Every use of a registry component alias, in document order.
+    pub alias_usages: Vec<AliasUsage>,
+    /// Relative image sources hoisted to imports, in binding order: entry `i`
+    /// is bound to [`asset_binding`](crate::transform::imports::asset_binding)`(i)`.
+    pub assets: Vec<String>,
+    /// Local images that could not be found while probing dimensions.
+    pub missing_images: Vec<MissingImage>,
+    /// Outbound links, in document order.
+    pub links: Vec<LinkReference>,
+    /// Images, media and downloads referenced by the document, in document order.
+    pub asset_references: Vec<AssetReference>,
+    /// Wiki links whose page could not be resolved, in document order.
+    pub unresolved_wikilinks: Vec<UnresolvedWikiLink>,
+    /// Documents embedded into this one, including nested ones, in render order.
+    pub transclusions: Vec<Transclusion>,
+    /// Code fences filled from local files, including nested ones, in render order.
+    pub code_snippets: Vec<CodeSnippet>,
+    /// Structural TeX problems in math formulas, in document order.
+    pub math_issues: Vec<MathIssue>,
+}
+
+impl BlocksResult {
+    /// Component bindings the generated JSX opens besides directive and
+    /// element components: JSX components from the source, then child
+    /// components that `registry`'s slot normalizations add (e.g., `TabItem`
+    /// inside Tabs). Derived from the blocks, so the JSX need not be re-parsed.
+    pub fn jsx_component_names(&self, registry: &RegistryConfig) -> Vec<String> {
+        let mut names: Vec<String> = self.components.iter().map(|c| c.name.clone()).collect();
+        let rendered = self
+            .components
+            .iter()
+            .map(|c| c.name.as_str())
+            .chain(self.directive_components.iter().map(String::as_str))
+            .chain(self.element_components.iter().map(String::as_str));
+        for child in registry.slot_child_components(rendered) {
+            if !names.iter().any(|name| name == child) {
+                names.push(child.to_string());
+            }
+        }
+        names
+    }
 }
 
 /// Represents the type of scope currently being rendered.
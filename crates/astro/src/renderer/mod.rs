@@ -2,5 +2,8 @@
 pub mod mdast;
 
 pub use mdast::{
-    BlocksResult, HeadingEntry, Options as MdastOptions, PropValue, RenderBlock, to_blocks,
+    AliasUsage, AssetKind, AssetReference, BlocksResult, CodeSnippet, ComponentUsage, HeadingEntry,
+    LinkReference, MathIssue, MissingImage, Options as MdastOptions, PropValue, RenderBlock,
+    SnippetIssue, TocEntry, Transclusion, TransclusionIssue, TransclusionKind, UnresolvedWikiLink,
+    to_blocks,
 };
@@ -0,0 +1,302 @@
+//! Code fence meta string parsing.
+//!
+//! The text after a fence's language (```` ```ts title="a.ts" {1,3-5} ````)
+//! carries presentation hints that ExpressiveCode, Shiki transformers and
+//! rehype plugins each read differently. [`CodeMeta::parse`] splits it into
+//! the common fields once so consumers can share a single interpretation.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// An inclusive, 1-indexed range of lines within a code block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LineRange {
+    /// First line of the range.
+    pub start: u32,
+    /// Last line of the range (equal to `start` for a single line).
+    pub end: u32,
+}
+
+impl LineRange {
+    /// Returns true if `line` falls within the range.
+    pub fn contains(&self, line: u32) -> bool {
+        (self.start..=self.end).contains(&line)
+    }
+}
+
+/// Structured form of a code fence meta string.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CodeMeta {
+    /// The meta string as written.
+    pub raw: String,
+    /// Value of `title="..."`.
+    pub title: Option<String>,
+    /// Lines from bare `{1,3-5}` groups and `mark={...}`.
+    pub highlight: Vec<LineRange>,
+    /// Lines from `ins={...}`.
+    pub ins: Vec<LineRange>,
+    /// Lines from `del={...}`.
+    pub del: Vec<LineRange>,
+    /// Whether the `showLineNumbers` flag is present.
+    pub show_line_numbers: bool,
+    /// Remaining bare tokens (e.g., `wrap`, `frame`), in order.
+    pub flags: Vec<String>,
+    /// Remaining `key=value` pairs with quotes and braces removed.
+    pub attributes: BTreeMap<String, String>,
+    /// Line range entries that were not applied, as written: reversed or
+    /// malformed ranges (`3-1`, `1-`), numbers that overflow, and groups
+    /// missing their closing `}`.
+    pub invalid_ranges: Vec<String>,
+}
+
+impl CodeMeta {
+    /// Parses a fence meta string.
+    ///
+    /// Quoted values may contain spaces; `ins`, `del` and `mark` values that
+    /// are not `{...}` line groups (such as text or regex markers) are kept
+    /// in [`attributes`](Self::attributes). Line ranges that cannot be
+    /// applied are reported in [`invalid_ranges`](Self::invalid_ranges).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xmdx_astro::transform::code_meta::{CodeMeta, LineRange};
+    ///
+    /// let meta = CodeMeta::parse(r#"title="src/app.ts" {1,3-5} ins={7} showLineNumbers wrap frame=none"#);
+    /// assert_eq!(meta.title.as_deref(), Some("src/app.ts"));
+    /// assert_eq!(
+    ///     meta.highlight,
+    ///     vec![LineRange { start: 1, end: 1 }, LineRange { start: 3, end: 5 }]
+    /// );
+    /// assert_eq!(meta.ins, vec![LineRange { start: 7, end: 7 }]);
+    /// assert!(meta.show_line_numbers);
+    /// assert_eq!(meta.flags, vec!["wrap"]);
+    /// assert_eq!(meta.attributes["frame"], "none");
+    ///
+    /// let meta = CodeMeta::parse("{2,3-1} ins={4");
+    /// assert_eq!(meta.highlight, vec![LineRange { start: 2, end: 2 }]);
+    /// assert_eq!(meta.invalid_ranges, vec!["3-1", "{4"]);
+    /// ```
+    pub fn parse(raw: &str) -> Self {
+        let mut meta = CodeMeta {
+            raw: raw.to_string(),
+            ..Default::default()
+        };
+        for token in meta_tokens(raw) {
+            match token {
+                MetaToken::Lines(group) => {
+                    let ranges = parse_line_ranges(group, &mut meta.invalid_ranges);
+                    meta.highlight.extend(ranges);
+                }
+                MetaToken::UnclosedLines(group) => meta.invalid_ranges.push(format!("{{{group}")),
+                MetaToken::Flag("showLineNumbers") => meta.show_line_numbers = true,
+                MetaToken::Flag(flag) => meta.flags.push(flag.to_string()),
+                MetaToken::Pair(key, value) => meta.apply_pair(key, value),
+            }
+        }
+        meta
+    }
+
+    fn apply_pair(&mut self, key: &str, value: MetaValue<'_>) {
+        match (key, value) {
+            ("title", value) => self.title = Some(value.text().to_string()),
+            ("mark" | "highlight", MetaValue::Braced(group)) => {
+                let ranges = parse_line_ranges(group, &mut self.invalid_ranges);
+                self.highlight.extend(ranges);
+            }
+            ("ins", MetaValue::Braced(group)) => {
+                let ranges = parse_line_ranges(group, &mut self.invalid_ranges);
+                self.ins.extend(ranges);
+            }
+            ("del", MetaValue::Braced(group)) => {
+                let ranges = parse_line_ranges(group, &mut self.invalid_ranges);
+                self.del.extend(ranges);
+            }
+            ("mark" | "highlight" | "ins" | "del", MetaValue::UnclosedBraced(group)) => {
+                self.invalid_ranges.push(format!("{{{group}"));
+            }
+            (_, value) => {
+                self.attributes
+                    .insert(key.to_string(), value.text().to_string());
+            }
+        }
+    }
+
+    /// Whether the meta sets a title, line marks or line numbers, which only
+    /// a code block highlighter (ExpressiveCode, Shiki) can apply.
+    pub fn has_annotations(&self) -> bool {
+        self.title.is_some()
+            || !self.highlight.is_empty()
+            || !self.ins.is_empty()
+            || !self.del.is_empty()
+            || self.show_line_numbers
+    }
+
+    /// Returns the value of a `key=value` pair, including `title`.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        match key {
+            "title" => self.title.as_deref(),
+            _ => self.attributes.get(key).map(String::as_str),
+        }
+    }
+}
+
+/// A value on the right of `key=`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MetaValue<'a> {
+    /// `key="text"`, `key='text'` or `key=text`.
+    Text(&'a str),
+    /// `key={...}`, without the braces.
+    Braced(&'a str),
+    /// `key={...` without a closing `}`, without the opening brace.
+    UnclosedBraced(&'a str),
+}
+
+impl<'a> MetaValue<'a> {
+    fn text(self) -> &'a str {
+        match self {
+            MetaValue::Text(text) | MetaValue::Braced(text) | MetaValue::UnclosedBraced(text) => {
+                text
+            }
+        }
+    }
+}
+
+/// A single token of a meta string.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MetaToken<'a> {
+    /// A bare `{...}` line group, without the braces.
+    Lines(&'a str),
+    /// A bare `{...` line group without a closing `}`, without the brace.
+    UnclosedLines(&'a str),
+    /// A bare word or quoted string.
+    Flag(&'a str),
+    /// A `key=value` pair.
+    Pair(&'a str, MetaValue<'a>),
+}
+
+/// Splits a meta string into tokens, honoring quotes and braces.
+fn meta_tokens(meta: &str) -> Vec<MetaToken<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = meta.trim_start();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('{') {
+            let (token, next) = match until_close(after, '}') {
+                Some((group, next)) => (MetaToken::Lines(group), next),
+                None => (MetaToken::UnclosedLines(after), ""),
+            };
+            tokens.push(token);
+            rest = next.trim_start();
+            continue;
+        }
+        if let Some(quote @ ('"' | '\'')) = rest.chars().next() {
+            let (text, next) = until_close(&rest[1..], quote).unwrap_or((&rest[1..], ""));
+            tokens.push(MetaToken::Flag(text));
+            rest = next.trim_start();
+            continue;
+        }
+        let word_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or(rest.len());
+        let word = &rest[..word_end];
+        let Some(after) = rest[word_end..].strip_prefix('=') else {
+            tokens.push(MetaToken::Flag(word));
+            rest = rest[word_end..].trim_start();
+            continue;
+        };
+        let (value, next) = match after.chars().next() {
+            Some('{') => match until_close(&after[1..], '}') {
+                Some((group, next)) => (MetaValue::Braced(group), next),
+                None => (MetaValue::UnclosedBraced(&after[1..]), ""),
+            },
+            Some(quote @ ('"' | '\'')) => {
+                let (text, next) = until_close(&after[1..], quote).unwrap_or((&after[1..], ""));
+                (MetaValue::Text(text), next)
+            }
+            _ => {
+                let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                (MetaValue::Text(&after[..end]), &after[end..])
+            }
+        };
+        tokens.push(MetaToken::Pair(word, value));
+        rest = next.trim_start();
+    }
+    tokens
+}
+
+/// Returns the text before `close` and the remainder after it, or `None`
+/// when `close` never appears.
+fn until_close(input: &str, close: char) -> Option<(&str, &str)> {
+    input
+        .find(close)
+        .map(|end| (&input[..end], &input[end + close.len_utf8()..]))
+}
+
+/// Parses `1,3-5` into line ranges; entries that are not ranges of
+/// positive line numbers are pushed to `invalid` as written.
+fn parse_line_ranges(group: &str, invalid: &mut Vec<String>) -> Vec<LineRange> {
+    let mut ranges = Vec::new();
+    for part in group
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let (start, end) = part.split_once('-').unwrap_or((part, part));
+        let start = start.trim().parse::<u32>().ok().filter(|n| *n > 0);
+        let end = end.trim().parse::<u32>().ok();
+        match (start, end) {
+            (Some(start), Some(end)) if end >= start => ranges.push(LineRange { start, end }),
+            _ => invalid.push(part.to_string()),
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_and_braced_values_keep_spaces() {
+        let meta =
+            CodeMeta::parse(r#"title='My file.ts' del={ 2, 4 - 5 } ins="added" "marked text""#);
+        assert_eq!(meta.title.as_deref(), Some("My file.ts"));
+        assert_eq!(
+            meta.del,
+            vec![
+                LineRange { start: 2, end: 2 },
+                LineRange { start: 4, end: 5 }
+            ]
+        );
+        assert!(meta.ins.is_empty());
+        assert_eq!(meta.attribute("ins"), Some("added"));
+        assert_eq!(meta.flags, vec!["marked text"]);
+    }
+
+    #[test]
+    fn invalid_ranges_and_unterminated_values_are_tolerated() {
+        let meta = CodeMeta::parse("{0,x,5-3,2} mark={7-8 title=\"open");
+        assert_eq!(meta.highlight, vec![LineRange { start: 2, end: 2 }]);
+        assert_eq!(meta.title, None);
+        assert_eq!(
+            meta.invalid_ranges,
+            vec!["0", "x", "5-3", "{7-8 title=\"open"]
+        );
+        assert!(CodeMeta::parse("").flags.is_empty());
+    }
+
+    #[test]
+    fn unclosed_and_out_of_range_groups_are_reported() {
+        let meta = CodeMeta::parse("{1");
+        assert!(!meta.has_annotations());
+        assert_eq!(meta.invalid_ranges, vec!["{1"]);
+
+        let meta = CodeMeta::parse("{3-1,1-,99999999999999999999} del={2}");
+        assert!(meta.highlight.is_empty());
+        assert_eq!(meta.del, vec![LineRange { start: 2, end: 2 }]);
+        assert_eq!(
+            meta.invalid_ranges,
+            vec!["3-1", "1-", "99999999999999999999"]
+        );
+    }
+}
@@ -1,9 +1,11 @@
 //! Directive rewriting utilities.
 
+use std::collections::BTreeMap;
 use std::fmt::Write as _;
 
 use crate::registry::RegistryConfig;
 use crate::transform::code_fence::{FenceState, advance_fence_state};
+use crate::transform::imports::plan_generated_imports;
 
 /// Ensures Aside import is present when directives were rewritten.
 /// If `count > 0` and no existing import from `@astrojs/starlight/components` is present,
@@ -37,7 +39,13 @@ pub fn ensure_aside_import(hoisted: &mut Vec<String>, directive_count: usize) {
 /// Ensures necessary imports are present for components used in directive mappings.
 ///
 /// This function uses the registry to determine which components need to be imported
-/// based on the directives used in the document.
+/// based on the directives used in the document. Existing imports of the same
+/// export from the same module are reused. When a component's name is already
+/// bound by something else (e.g., `import Aside from './MyAside.astro'`), the
+/// generated import is aliased (`import { Aside as __xmdx_Aside } ...`).
+///
+/// Returns the component-to-local-name aliases that emitted JSX must use;
+/// pass them as `Options::component_aliases` when rendering.
 ///
 /// # Arguments
 ///
@@ -48,31 +56,16 @@ pub fn ensure_directive_imports(
     hoisted: &mut Vec<String>,
     used_directives: &[&str],
     registry: &RegistryConfig,
-) {
-    use std::collections::HashSet;
-
-    // Collect unique component names needed for the used directives
-    let mut components_needed: HashSet<&str> = HashSet::new();
-    for directive in used_directives {
-        if let Some(component) = registry.get_directive_component(directive) {
-            components_needed.insert(component);
-        }
-    }
-
-    // For each component, check if import exists and add if needed
-    for component in components_needed {
-        if let Some(module_path) = registry.get_component_module(component) {
-            let import_fragment = format!("from '{}'", module_path);
-            let already_imported = hoisted.iter().any(|line| line.contains(&import_fragment));
+) -> BTreeMap<String, String> {
+    let components = used_directives
+        .iter()
+        .filter_map(|directive| registry.get_directive_component(directive));
+    let planned = plan_generated_imports(components, hoisted, registry);
 
-            if !already_imported {
-                hoisted.insert(
-                    0,
-                    format!("import {{ {} }} from '{}';", component, module_path),
-                );
-            }
-        }
+    for (idx, import) in planned.imports.into_iter().enumerate() {
+        hoisted.insert(idx, import);
     }
+    planned.aliases
 }
 
 /// Parsed representation of a directive opening line (e.g. `:::note[Title] foo="bar"`).
@@ -146,6 +139,40 @@ pub(crate) fn parse_opening_directive_with(
     line: &str,
     custom_names: Option<&[&str]>,
 ) -> Option<DirectiveOpening> {
+    let (name, bracket_title, remaining) = split_opening_directive(line)?;
+    if !is_supported_name_with(&name, custom_names) {
+        return None;
+    }
+    let raw_attrs = normalize_attrs(&remaining, bracket_title.is_some());
+    Some(DirectiveOpening {
+        name,
+        bracket_title,
+        raw_attrs,
+    })
+}
+
+/// Parse a child directive routed into a named slot of its parent
+/// (`:::footer{slot=footer}`).
+///
+/// Any name is accepted as long as a `slot` attribute is present. Only call
+/// this inside a directive whose registry mapping routes child directives.
+pub(crate) fn parse_slot_child_directive(line: &str) -> Option<DirectiveOpening> {
+    let (name, bracket_title, remaining) = split_opening_directive(line)?;
+    let raw_attrs = normalize_attrs(&remaining, bracket_title.is_some());
+    parse_directive_attrs(&raw_attrs)
+        .iter()
+        .any(|(key, _)| key == "slot")
+        .then_some(DirectiveOpening {
+            name,
+            bracket_title,
+            raw_attrs,
+        })
+}
+
+/// Splits a `:::name[Title] attrs` line into its lowercased name, bracket
+/// title and trimmed attribute text, with generic-directive `{key=value}`
+/// blocks converted to quoted attributes.
+fn split_opening_directive(line: &str) -> Option<(String, Option<String>, String)> {
     let trimmed = line.trim();
     if !trimmed.starts_with(":::") {
         return None;
@@ -169,7 +196,7 @@ pub(crate) fn parse_opening_directive_with(
     // Strip leading/trailing hyphens from directive name
     let name = name.trim_matches('-').to_string();
 
-    if name.is_empty() || !is_supported_name_with(&name, custom_names) {
+    if name.is_empty() {
         return None;
     }
 
@@ -189,15 +216,50 @@ pub(crate) fn parse_opening_directive_with(
         }
     }
 
-    // Remaining slice treated as attributes (trim leading whitespace)
+    // Remaining slice treated as attributes (trim leading whitespace).
     let remaining: String = chars.collect();
-    let raw_attrs = normalize_attrs(remaining.trim(), bracket_title.is_some());
+    let remaining = remaining.trim();
+    let remaining = match remaining
+        .strip_prefix('{')
+        .and_then(|r| r.strip_suffix('}'))
+    {
+        Some(inner) => quote_brace_attrs(inner),
+        None => remaining.to_string(),
+    };
+    Some((name, bracket_title, remaining))
+}
 
-    Some(DirectiveOpening {
-        name,
-        bracket_title,
-        raw_attrs,
-    })
+/// Converts `{key=value other="x"}` block contents to quoted `key="value"` attributes.
+fn quote_brace_attrs(inner: &str) -> String {
+    tokenize_attrs(inner)
+        .into_iter()
+        .map(|tok| match tok.split_once('=') {
+            Some((key, value)) if !value.starts_with(['"', '\'']) => {
+                format!("{}=\"{}\"", key, value)
+            }
+            _ => tok.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parses a normalized directive attribute string into key/value pairs.
+///
+/// Values have their surrounding quotes removed; bare keys get an empty value.
+pub(crate) fn parse_directive_attrs(raw_attrs: &str) -> Vec<(String, String)> {
+    tokenize_attrs(raw_attrs)
+        .into_iter()
+        .map(|tok| match tok.split_once('=') {
+            Some((key, value)) => {
+                let value = value
+                    .strip_prefix(['"', '\''])
+                    .and_then(|v| v.strip_suffix(['"', '\'']))
+                    .unwrap_or(value);
+                (key.to_string(), value.to_string())
+            }
+            None => (tok.to_string(), String::new()),
+        })
+        .collect()
 }
 
 /// Tokenize attributes respecting quoted values.
@@ -457,4 +519,64 @@ mod tests {
             "title=\"Be careful here\" class=\"my class\""
         );
     }
+
+    #[test]
+    fn ensure_directive_imports_aliases_colliding_binding() {
+        let registry = crate::registry::defaults::default_starlight_registry();
+        let mut hoisted = vec!["import Aside from './MyAside.astro';".to_string()];
+        let aliases = ensure_directive_imports(&mut hoisted, &["note", "tip"], &registry);
+        assert_eq!(
+            hoisted,
+            vec![
+                "import { Aside as __xmdx_Aside } from '@astrojs/starlight/components';",
+                "import Aside from './MyAside.astro';",
+            ]
+        );
+        assert_eq!(
+            aliases.get("Aside").map(String::as_str),
+            Some("__xmdx_Aside")
+        );
+    }
+
+    #[test]
+    fn brace_attrs_are_quoted_and_enable_slot_children() {
+        let opening = parse_slot_child_directive(":::footer{slot=footer class=\"x y\"}").unwrap();
+        assert_eq!(opening.name, "footer");
+        assert_eq!(opening.raw_attrs, "slot=\"footer\" class=\"x y\"");
+        assert_eq!(
+            parse_directive_attrs(&opening.raw_attrs),
+            vec![
+                ("slot".to_string(), "footer".to_string()),
+                ("class".to_string(), "x y".to_string()),
+            ]
+        );
+        // Child directives need a slot attribute.
+        assert!(parse_slot_child_directive(":::footer{class=x}").is_none());
+        // Outside a slot-routing parent, unknown names are rejected.
+        assert!(parse_opening_directive(":::footer{slot=footer}").is_none());
+    }
+
+    #[test]
+    fn brace_attrs_on_known_directives_match_plain_attrs() {
+        for (braced, plain) in [
+            (":::note{title=\"Hi there\"}", ":::note title=\"Hi there\""),
+            (
+                ":::tip{icon=rocket data-x='y'}",
+                ":::tip icon=\"rocket\" data-x='y'",
+            ),
+            (
+                ":::caution[Title]{title=Other}",
+                ":::caution[Title] title=\"Other\"",
+            ),
+            (":::danger{}", ":::danger"),
+        ] {
+            let braced = parse_opening_directive(braced).unwrap();
+            let plain = parse_opening_directive(plain).unwrap();
+            assert_eq!(braced.raw_attrs, plain.raw_attrs);
+            assert_eq!(braced.bracket_title, plain.bracket_title);
+        }
+        // Attributes that are not a single brace block are kept as written.
+        let opening = parse_opening_directive(":::note {a} b").unwrap();
+        assert_eq!(opening.raw_attrs, "{a} b");
+    }
 }
@@ -0,0 +1,304 @@
+//! Rust-side syntax highlighting for code fences.
+//!
+//! With the `highlight` Cargo feature, fences in a language known to
+//! syntect's bundled grammars render as highlighted `<pre><code>` HTML, so
+//! pages need no JS highlighter (Shiki, ExpressiveCode). Without the feature,
+//! or for unknown languages, [`highlight_code`] returns `None` and callers
+//! keep their plain output.
+
+use serde::{Deserialize, Serialize};
+
+/// How highlighted tokens are styled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HighlightStyle {
+    /// `hl-`-prefixed scope classes, styled by [`highlight_stylesheet`].
+    #[default]
+    Classes,
+    /// Inline `style` attributes with the theme's colors.
+    Inline,
+}
+
+impl HighlightStyle {
+    /// Parses a style name (`"classes"`, `"inline"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "classes" => Some(Self::Classes),
+            "inline" => Some(Self::Inline),
+            _ => None,
+        }
+    }
+}
+
+/// Themes bundled with the highlighter, accepted as [`HighlightOptions::theme`].
+pub const HIGHLIGHT_THEMES: &[&str] = &[
+    "InspiredGitHub",
+    "Solarized (dark)",
+    "Solarized (light)",
+    "base16-eighties.dark",
+    "base16-mocha.dark",
+    "base16-ocean.dark",
+    "base16-ocean.light",
+];
+
+/// Configuration for Rust-side syntax highlighting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightOptions {
+    /// Bundled theme name (e.g., `InspiredGitHub`, `base16-ocean.dark`).
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    /// Whether tokens get classes or inline styles.
+    #[serde(default)]
+    pub style: HighlightStyle,
+}
+
+fn default_theme() -> String {
+    "InspiredGitHub".to_string()
+}
+
+impl HighlightOptions {
+    /// Checks that the theme is one of [`HIGHLIGHT_THEMES`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xmdx_astro::transform::highlight::HighlightOptions;
+    ///
+    /// assert!(HighlightOptions::default().validate().is_ok());
+    /// let options = HighlightOptions { theme: "nope".into(), ..Default::default() };
+    /// assert!(options.validate().unwrap_err().contains("\"nope\""));
+    /// ```
+    pub fn validate(&self) -> Result<(), String> {
+        if HIGHLIGHT_THEMES.contains(&self.theme.as_str()) {
+            return Ok(());
+        }
+        Err(format!(
+            "Unknown highlight theme \"{}\": expected one of {}",
+            self.theme,
+            HIGHLIGHT_THEMES.join(", ")
+        ))
+    }
+}
+
+impl Default for HighlightOptions {
+    fn default() -> Self {
+        Self {
+            theme: default_theme(),
+            style: HighlightStyle::default(),
+        }
+    }
+}
+
+/// Class prefix for highlighted tokens, keeping them clear of site styles.
+#[cfg(feature = "highlight")]
+const CLASS_PREFIX: &str = "hl-";
+
+#[cfg(feature = "highlight")]
+mod bundled {
+    use std::sync::LazyLock;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    pub(super) static SYNTAXES: LazyLock<SyntaxSet> =
+        LazyLock::new(SyntaxSet::load_defaults_newlines);
+    pub(super) static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+}
+
+/// Renders `code` as highlighted `<pre class="astro-code"><code>` HTML.
+///
+/// Returns `None` when the `highlight` feature is disabled, the language is
+/// missing or unknown, or (for inline styles) the theme does not exist.
+/// Braces, backticks and newlines are written as entities, matching the
+/// plain code output so the HTML is safe inside JSX.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::transform::highlight::{HighlightOptions, highlight_code};
+///
+/// // `None` unless built with the `highlight` feature.
+/// if let Some(html) = highlight_code("fn main() {}", Some("rust"), &HighlightOptions::default()) {
+///     assert!(html.starts_with(r#"<pre class="astro-code hl-code" tabindex="0">"#));
+///     assert!(html.contains("&#123;") && !html.contains('{'));
+/// }
+/// ```
+#[cfg(feature = "highlight")]
+pub fn highlight_code(
+    code: &str,
+    lang: Option<&str>,
+    options: &HighlightOptions,
+) -> Option<String> {
+    use syntect::easy::HighlightLines;
+    use syntect::html::{
+        ClassStyle, ClassedHTMLGenerator, IncludeBackground, styled_line_to_highlighted_html,
+    };
+    use syntect::util::LinesWithEndings;
+
+    let syntaxes = &*bundled::SYNTAXES;
+    let syntax = syntaxes.find_syntax_by_token(lang?.trim())?;
+    if syntax.name == "Plain Text" {
+        return None;
+    }
+    let lang_attr = escape_attr(lang?.trim());
+
+    let (pre_attrs, body) = match options.style {
+        HighlightStyle::Classes => {
+            let mut generator = ClassedHTMLGenerator::new_with_class_style(
+                syntax,
+                syntaxes,
+                ClassStyle::SpacedPrefixed {
+                    prefix: CLASS_PREFIX,
+                },
+            );
+            for line in LinesWithEndings::from(code) {
+                generator
+                    .parse_html_for_line_which_includes_newline(line)
+                    .ok()?;
+            }
+            (
+                format!(r#" class="astro-code {CLASS_PREFIX}code""#),
+                generator.finalize(),
+            )
+        }
+        HighlightStyle::Inline => {
+            let theme = bundled::THEMES.themes.get(&options.theme)?;
+            let mut highlighter = HighlightLines::new(syntax, theme);
+            let mut body = String::new();
+            for line in LinesWithEndings::from(code) {
+                let regions = highlighter.highlight_line(line, syntaxes).ok()?;
+                body.push_str(
+                    &styled_line_to_highlighted_html(&regions, IncludeBackground::No).ok()?,
+                );
+            }
+            let mut style = String::new();
+            if let Some(bg) = theme.settings.background {
+                style.push_str(&format!(
+                    "background-color:#{:02x}{:02x}{:02x};",
+                    bg.r, bg.g, bg.b
+                ));
+            }
+            if let Some(fg) = theme.settings.foreground {
+                style.push_str(&format!("color:#{:02x}{:02x}{:02x};", fg.r, fg.g, fg.b));
+            }
+            (format!(r#" class="astro-code" style="{style}""#), body)
+        }
+    };
+
+    Some(format!(
+        r#"<pre{pre_attrs} tabindex="0"><code class="language-{lang_attr}">{}</code></pre>"#,
+        escape_jsx_text(body.trim_end_matches('\n'))
+    ))
+}
+
+/// Renders `code` as highlighted HTML (unavailable without the `highlight` feature).
+#[cfg(not(feature = "highlight"))]
+pub fn highlight_code(
+    _code: &str,
+    _lang: Option<&str>,
+    _options: &HighlightOptions,
+) -> Option<String> {
+    None
+}
+
+/// Returns the CSS for class-styled output in the named theme.
+///
+/// Returns `None` when the `highlight` feature is disabled or the theme does
+/// not exist.
+#[cfg(feature = "highlight")]
+pub fn highlight_stylesheet(theme: &str) -> Option<String> {
+    use syntect::html::{ClassStyle, css_for_theme_with_class_style};
+
+    let theme = bundled::THEMES.themes.get(theme)?;
+    css_for_theme_with_class_style(
+        theme,
+        ClassStyle::SpacedPrefixed {
+            prefix: CLASS_PREFIX,
+        },
+    )
+    .ok()
+}
+
+/// Returns the CSS for class-styled output (unavailable without the `highlight` feature).
+#[cfg(not(feature = "highlight"))]
+pub fn highlight_stylesheet(_theme: &str) -> Option<String> {
+    None
+}
+
+/// Escapes an attribute value.
+#[cfg(feature = "highlight")]
+fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Writes JSX-significant characters in syntect's (already HTML-escaped)
+/// output as entities. Tag attributes hold only class names and colors, so
+/// every such character is code text.
+#[cfg(feature = "highlight")]
+fn escape_jsx_text(html: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    for c in html.chars() {
+        match c {
+            '`' => result.push_str("&#96;"),
+            '{' => result.push_str("&#123;"),
+            '}' => result.push_str("&#125;"),
+            '\n' => result.push_str("&#10;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(all(test, feature = "highlight"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_style_uses_theme_colors() {
+        let options = HighlightOptions {
+            theme: "base16-ocean.dark".to_string(),
+            style: HighlightStyle::Inline,
+        };
+        let html = highlight_code("let x = `a`;\nx", Some("js"), &options).unwrap();
+        assert!(
+            html.starts_with(r#"<pre class="astro-code" style="background-color:#2b303b;"#),
+            "{html}"
+        );
+        assert!(html.contains(r#"<span style="color:"#), "{html}");
+        assert!(html.contains("&#96;") && html.contains("&#10;"), "{html}");
+        assert!(!html.contains('\n'), "{html}");
+    }
+
+    #[test]
+    fn highlight_themes_match_bundled_themes() {
+        let mut bundled: Vec<_> = bundled::THEMES.themes.keys().map(String::as_str).collect();
+        bundled.sort_unstable();
+        assert_eq!(bundled, HIGHLIGHT_THEMES);
+    }
+
+    #[test]
+    fn unknown_languages_and_themes_are_not_highlighted() {
+        let inline = HighlightOptions {
+            theme: "nope".to_string(),
+            style: HighlightStyle::Inline,
+        };
+        assert_eq!(highlight_code("x", Some("js"), &inline), None);
+        assert_eq!(
+            highlight_code("x", Some("klingon"), &HighlightOptions::default()),
+            None
+        );
+        assert_eq!(
+            highlight_code("x", None, &HighlightOptions::default()),
+            None
+        );
+        assert!(
+            highlight_stylesheet("InspiredGitHub")
+                .unwrap()
+                .contains(".hl-code")
+        );
+    }
+}
@@ -0,0 +1,836 @@
+//! Import statement analysis and registry-driven component imports.
+//!
+//! MDX bodies may reference components like `<Tabs>` without importing them.
+//! This module inspects the hoisted statements to learn which bindings are
+//! already declared, and generates imports for the rest from the registry.
+
+use crate::registry::{ComponentDefinition, RegistryConfig, SlotNormalization};
+use crate::transform::code_fence::{FenceState, advance_fence_state, collect_root_statements};
+use std::collections::BTreeMap;
+
+/// Prefix used when a generated import must be renamed to avoid a collision.
+pub const GENERATED_ALIAS_PREFIX: &str = "__xmdx_";
+
+/// Prefix of the local bindings for hoisted image imports (see [`asset_binding`]).
+pub const ASSET_BINDING_PREFIX: &str = "__xmdx_img";
+
+/// Imports generated for components referenced by a document.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComponentImports {
+    /// Import statements to add, grouped per module in first-use order.
+    pub imports: Vec<String>,
+    /// Component names that are neither declared nor registered.
+    pub unresolved: Vec<String>,
+}
+
+/// Imports generated for components the compiler emits on its own (e.g., for
+/// directives), together with the local names the emitted JSX must use.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeneratedImports {
+    /// Import statements to add.
+    pub imports: Vec<String>,
+    /// Component name to local binding, for components that are not bound
+    /// under their own name (aliased to avoid a collision, or already
+    /// imported under a different local name).
+    pub aliases: BTreeMap<String, String>,
+}
+
+/// A parsed `import` statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportStatement {
+    /// Module specifier (e.g., `@astrojs/starlight/components`).
+    pub source: String,
+    /// Bindings introduced by the statement; empty for side-effect imports.
+    pub bindings: Vec<ImportBinding>,
+}
+
+/// A single binding introduced by an import statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportBinding {
+    /// Exported name: `"default"`, `"*"` for namespace imports, or a named export.
+    pub imported: String,
+    /// Local binding name.
+    pub local: String,
+}
+
+impl ImportStatement {
+    /// Formats the statement back to source with single-quoted specifier.
+    pub fn to_source(&self) -> String {
+        let mut clauses = Vec::new();
+        let mut named = Vec::new();
+        for binding in &self.bindings {
+            match binding.imported.as_str() {
+                "default" => clauses.push(binding.local.clone()),
+                "*" => clauses.push(format!("* as {}", binding.local)),
+                imported if imported == binding.local => named.push(binding.local.clone()),
+                imported => named.push(format!("{} as {}", imported, binding.local)),
+            }
+        }
+        if !named.is_empty() {
+            clauses.push(format!("{{ {} }}", named.join(", ")));
+        }
+        if clauses.is_empty() {
+            format!("import '{}';", self.source)
+        } else {
+            format!("import {} from '{}';", clauses.join(", "), self.source)
+        }
+    }
+}
+
+/// Parses a single `import` statement, returning `None` for anything else
+/// (including statements with import attributes or several statements).
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::transform::imports::parse_import;
+///
+/// let stmt = parse_import("import Card, { Tabs as T } from \"./ui\";").unwrap();
+/// assert_eq!(stmt.source, "./ui");
+/// assert_eq!(stmt.bindings[0].imported, "default");
+/// assert_eq!(stmt.bindings[1].imported, "Tabs");
+/// assert_eq!(stmt.bindings[1].local, "T");
+/// ```
+pub fn parse_import(statement: &str) -> Option<ImportStatement> {
+    let spaced = statement.replace('{', " { ").replace('}', " } ");
+    let normalized = spaced.split_whitespace().collect::<Vec<_>>().join(" ");
+    let rest = normalized.strip_prefix("import ")?;
+
+    // Side-effect imports (`import './x.css'`) have no `from` clause.
+    let (clause, specifier) = match rest.rfind(" from ") {
+        Some(idx) => (&rest[..idx], &rest[idx + " from ".len()..]),
+        None => ("", rest),
+    };
+    if clause.contains([';', '\'', '"']) {
+        return None;
+    }
+    let source = quoted_specifier(specifier)?;
+
+    let mut bindings = Vec::new();
+    let mut clause = clause.trim();
+    if let Some(stripped) = clause.strip_prefix("type ") {
+        clause = stripped.trim();
+    }
+
+    let (head, named) = match (clause.find('{'), clause.rfind('}')) {
+        (Some(open), Some(close)) if open < close => (&clause[..open], &clause[open + 1..close]),
+        _ => (clause, ""),
+    };
+
+    for piece in head.split(',') {
+        let piece = piece.trim();
+        match piece.strip_prefix("* as ") {
+            Some(namespace) => push_binding("*", namespace.trim(), &mut bindings),
+            None => push_binding("default", piece, &mut bindings),
+        }
+    }
+    for specifier in named.split(',') {
+        let specifier = specifier.trim().trim_start_matches("type ").trim();
+        match specifier.split_once(" as ") {
+            Some((imported, local)) => push_binding(imported.trim(), local.trim(), &mut bindings),
+            None => push_binding(specifier, specifier, &mut bindings),
+        }
+    }
+
+    Some(ImportStatement { source, bindings })
+}
+
+fn quoted_specifier(text: &str) -> Option<String> {
+    let text = text.trim().trim_end_matches(';').trim();
+    let quote = text.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let inner = &text[1..];
+    let end = inner.find(quote)?;
+    // Anything after the specifier (import attributes, a second statement)
+    // is not understood; callers keep such statements verbatim.
+    if !inner[end + 1..].trim().is_empty() {
+        return None;
+    }
+    Some(inner[..end].to_string())
+}
+
+fn push_binding(imported: &str, local: &str, bindings: &mut Vec<ImportBinding>) {
+    if is_identifier(local) && (imported == "default" || imported == "*" || is_identifier(imported))
+    {
+        bindings.push(ImportBinding {
+            imported: imported.to_string(),
+            local: local.to_string(),
+        });
+    }
+}
+
+fn is_identifier(candidate: &str) -> bool {
+    candidate
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && candidate
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Returns every local binding declared by the given top-level statements.
+///
+/// Understands default, named (`{ A, B as C }`), namespace (`* as NS`) and
+/// mixed (`X, { Y }`) imports, plus `export const|let|var|function|class`
+/// declarations. Unrecognized statements contribute no bindings.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::transform::imports::declared_bindings;
+///
+/// let statements = vec![
+///     "import Card, { Tabs as T } from './ui';".to_string(),
+///     "export const Note = () => null;".to_string(),
+/// ];
+/// assert_eq!(declared_bindings(&statements), vec!["Card", "T", "Note"]);
+/// ```
+pub fn declared_bindings(statements: &[String]) -> Vec<String> {
+    let mut bindings: Vec<String> = Vec::new();
+    let mut push = |name: &str| {
+        if is_identifier(name) && !bindings.iter().any(|b| b == name) {
+            bindings.push(name.to_string());
+        }
+    };
+    for statement in statements {
+        for part in split_statements(statement) {
+            if let Some(import) = parse_import(part) {
+                import.bindings.iter().for_each(|b| push(&b.local));
+            } else if let Some(rest) = part.trim_start().strip_prefix("export ") {
+                push(&export_declaration_name(rest));
+            }
+        }
+    }
+    bindings
+}
+
+/// Returns the 1-based line of the root `import`/`export` statement that
+/// binds or exports `name`, used to place export collision warnings.
+/// Statements inside code fences are not declarations.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::transform::imports::declaration_line;
+///
+/// let source = "import Stock from './Stock.astro';\n\n```js\nexport const toc = 1;\n```\n\nexport const toc = [];\n";
+/// assert_eq!(declaration_line(source, "toc"), Some(7));
+/// assert_eq!(declaration_line(source, "Stock"), Some(1));
+/// assert_eq!(declaration_line(source, "backlinks"), None);
+/// ```
+pub fn declaration_line(source: &str, name: &str) -> Option<u32> {
+    let (hoisted, _) = collect_root_statements(source);
+    let statement = hoisted
+        .imports
+        .iter()
+        .chain(&hoisted.exports)
+        .find(|statement| {
+            let statement = std::slice::from_ref(*statement);
+            declared_bindings(statement)
+                .into_iter()
+                .chain(exported_names(statement))
+                .any(|declared| declared == name)
+        })?;
+    let first_line = statement.lines().next()?;
+    let mut fence_state = FenceState::default();
+    source
+        .lines()
+        .position(|line| {
+            let outcome = advance_fence_state(line, fence_state);
+            fence_state = outcome.next_state;
+            !outcome.skip_imports && line == first_line
+        })
+        .map(|idx| idx as u32 + 1)
+}
+
+/// Returns the names exported by hoisted `export` statements: declarations
+/// (`export const toc = ...`) and export lists (`export { a as toc }`,
+/// including re-exports). `export default` is not included.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::transform::imports::exported_names;
+///
+/// let statements = vec![
+///     "export const toc = [];".to_string(),
+///     "export { data as backlinks, meta } from './data';".to_string(),
+///     "export default Layout;".to_string(),
+/// ];
+/// assert_eq!(exported_names(&statements), vec!["toc", "backlinks", "meta"]);
+/// ```
+pub fn exported_names(statements: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut push = |name: &str| {
+        if is_identifier(name) && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    };
+    for statement in statements {
+        for part in split_statements(statement) {
+            let Some(rest) = part.trim_start().strip_prefix("export") else {
+                continue;
+            };
+            let rest = rest.trim_start();
+            match rest.strip_prefix('{').and_then(|list| list.split_once('}')) {
+                Some((list, _)) => list
+                    .split(',')
+                    .filter_map(|spec| spec.split(" as ").last())
+                    .for_each(|name| push(name.trim())),
+                None => push(&export_declaration_name(rest)),
+            }
+        }
+    }
+    names
+}
+
+/// Splits a hoisted chunk into its `;`-terminated statements.
+fn split_statements(chunk: &str) -> impl Iterator<Item = &str> {
+    chunk.split(';').map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn export_declaration_name(rest: &str) -> String {
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix("async ").unwrap_or(rest);
+    for keyword in [
+        "const ",
+        "let ",
+        "var ",
+        "function* ",
+        "function ",
+        "class ",
+    ] {
+        if let Some(decl) = rest.strip_prefix(keyword) {
+            return decl
+                .trim_start()
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '$')
+                .collect();
+        }
+    }
+    String::new()
+}
+
+/// Removes imports whose bindings are already provided by an earlier import.
+///
+/// Two imports are identical when they bind the same export of the same
+/// module to the same local name, regardless of formatting. Statements that
+/// partially overlap are rewritten to keep only their new bindings, and
+/// statements that cannot be parsed are kept verbatim (exact duplicates aside).
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::transform::imports::dedupe_import_statements;
+///
+/// let imports = vec![
+///     "import { Aside } from '@astrojs/starlight/components';".to_string(),
+///     "import {Aside, Tabs} from \"@astrojs/starlight/components\"".to_string(),
+/// ];
+/// assert_eq!(
+///     dedupe_import_statements(imports),
+///     vec![
+///         "import { Aside } from '@astrojs/starlight/components';",
+///         "import { Tabs } from '@astrojs/starlight/components';",
+///     ]
+/// );
+/// ```
+pub fn dedupe_import_statements(imports: Vec<String>) -> Vec<String> {
+    let mut seen_bindings: Vec<(String, ImportBinding)> = Vec::new();
+    let mut seen_side_effects: Vec<String> = Vec::new();
+    let mut seen_raw: Vec<String> = Vec::new();
+    let mut deduped = Vec::with_capacity(imports.len());
+
+    for import in imports {
+        let Some(mut parsed) = parse_import(&import) else {
+            if !seen_raw.contains(&import) {
+                seen_raw.push(import.clone());
+                deduped.push(import);
+            }
+            continue;
+        };
+
+        if parsed.bindings.is_empty() {
+            if !seen_side_effects.contains(&parsed.source) {
+                seen_side_effects.push(parsed.source);
+                deduped.push(import);
+            }
+            continue;
+        }
+
+        let original_len = parsed.bindings.len();
+        parsed.bindings.retain(|binding| {
+            !seen_bindings
+                .iter()
+                .any(|(source, seen)| *source == parsed.source && seen == binding)
+        });
+        if parsed.bindings.is_empty() {
+            continue;
+        }
+        seen_bindings.extend(
+            parsed
+                .bindings
+                .iter()
+                .map(|binding| (parsed.source.clone(), binding.clone())),
+        );
+        if parsed.bindings.len() == original_len {
+            deduped.push(import);
+        } else {
+            deduped.push(parsed.to_source());
+        }
+    }
+    deduped
+}
+
+/// Plans imports for components the compiler emits itself (e.g., `Aside` for
+/// `:::note`), avoiding collisions with the document's own bindings.
+///
+/// For each registered component in `components`:
+/// - if `existing` already imports that export from the same module, it is
+///   reused (under its local name) and no import is generated;
+/// - if the component's name is taken by another binding, the generated
+///   import is aliased (`import { Aside as __xmdx_Aside } from '…'`);
+/// - otherwise a plain import is generated.
+///
+/// Unregistered components are ignored.
+pub fn plan_generated_imports<'a, I>(
+    components: I,
+    existing: &[String],
+    registry: &RegistryConfig,
+) -> GeneratedImports
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let existing_imports: Vec<ImportStatement> = existing
+        .iter()
+        .flat_map(|chunk| split_statements(chunk))
+        .filter_map(parse_import)
+        .collect();
+    let declared = declared_bindings(existing);
+    let mut result = GeneratedImports::default();
+    let mut handled: Vec<&str> = Vec::new();
+
+    for name in components {
+        if handled.contains(&name) {
+            continue;
+        }
+        handled.push(name);
+        let Some(def) = registry.get_component(name) else {
+            continue;
+        };
+        let imported = if def.export_type == "default" {
+            "default"
+        } else {
+            def.name.as_str()
+        };
+
+        let reusable = existing_imports
+            .iter()
+            .filter(|stmt| stmt.source == def.module_path)
+            .flat_map(|stmt| &stmt.bindings)
+            .find(|binding| binding.imported == imported);
+        if let Some(binding) = reusable {
+            if binding.local != def.name {
+                result
+                    .aliases
+                    .insert(def.name.clone(), binding.local.clone());
+            }
+            continue;
+        }
+
+        let local = if declared.contains(&def.name) {
+            let alias = format!("{}{}", GENERATED_ALIAS_PREFIX, def.name);
+            result.aliases.insert(def.name.clone(), alias.clone());
+            alias
+        } else {
+            def.name.clone()
+        };
+        let statement = ImportStatement {
+            source: def.module_path.clone(),
+            bindings: vec![ImportBinding {
+                imported: imported.to_string(),
+                local,
+            }],
+        };
+        result.imports.push(statement.to_source());
+    }
+    result
+}
+
+/// Maps registry component aliases to their canonical component names.
+///
+/// Aliases the document binds itself (e.g., via an explicit
+/// `import Callout from '…'`) are left alone, so only tags that would
+/// otherwise resolve through the registry are rewritten.
+pub fn alias_renames(registry: &RegistryConfig, existing: &[String]) -> BTreeMap<String, String> {
+    let declared = declared_bindings(existing);
+    registry
+        .components
+        .iter()
+        .flat_map(|def| def.aliases.iter().map(move |alias| (alias, &def.name)))
+        .filter(|(alias, _)| !declared.contains(alias))
+        .map(|(alias, name)| (alias.clone(), name.clone()))
+        .collect()
+}
+
+/// Adds slot normalizations for registry components the document imports
+/// under another local name, so `<S>` from
+/// `import { Steps as S } from '@astrojs/starlight/components'` is
+/// normalized like `<Steps>`.
+///
+/// A binding counts when its statement imports the component's export
+/// (named, or `default` for default exports) from its `module_path`.
+pub fn add_import_alias_normalizations(registry: &mut RegistryConfig, statements: &[String]) {
+    let mut added = Vec::new();
+    for statement in statements {
+        for import in split_statements(statement).filter_map(parse_import) {
+            for binding in &import.bindings {
+                let Some(def) = registry.components.iter().find(|def| {
+                    def.module_path == import.source
+                        && def.name != binding.local
+                        && if def.export_type == "default" {
+                            binding.imported == "default"
+                        } else {
+                            binding.imported == def.name
+                        }
+                }) else {
+                    continue;
+                };
+                if registry.get_slot_normalization(&binding.local).is_some() {
+                    continue;
+                }
+                if let Some(normalization) = registry.get_slot_normalization(&def.name) {
+                    added.push(SlotNormalization {
+                        component: binding.local.clone(),
+                        ..normalization.clone()
+                    });
+                }
+            }
+        }
+    }
+    registry.slot_normalizations.extend(added);
+}
+
+/// Formats the import statement for a single registry component.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::registry::ComponentDefinition;
+/// use xmdx_astro::transform::imports::component_import_statement;
+///
+/// let def = ComponentDefinition {
+///     name: "Hero".into(),
+///     module_path: "~/components/Hero.astro".into(),
+///     export_type: "default".into(),
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     component_import_statement(&def),
+///     "import Hero from '~/components/Hero.astro';"
+/// );
+/// ```
+pub fn component_import_statement(def: &ComponentDefinition) -> String {
+    if def.export_type == "default" {
+        format!("import {} from '{}';", def.name, def.module_path)
+    } else {
+        format!("import {{ {} }} from '{}';", def.name, def.module_path)
+    }
+}
+
+/// Resolves imports for components used by a document.
+///
+/// Names already bound by `existing` statements are skipped. Registered names
+/// get an import built from their `module_path`/`export_type`, with named
+/// exports from the same module merged into one statement. Anything else is
+/// reported in [`ComponentImports::unresolved`].
+pub fn resolve_component_imports<'a, I>(
+    used: I,
+    existing: &[String],
+    registry: &RegistryConfig,
+) -> ComponentImports
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let declared = declared_bindings(existing);
+    let mut named_groups: Vec<(&str, Vec<&str>)> = Vec::new();
+    let mut defaults: Vec<&ComponentDefinition> = Vec::new();
+    let mut result = ComponentImports::default();
+
+    for name in used {
+        if declared.iter().any(|d| d == name) {
+            continue;
+        }
+        match registry.get_component(name) {
+            Some(def) if def.export_type == "default" => {
+                if !defaults.iter().any(|d| d.name == def.name) {
+                    defaults.push(def);
+                }
+            }
+            Some(def) => match named_groups
+                .iter_mut()
+                .find(|(module, _)| *module == def.module_path)
+            {
+                Some((_, names)) => {
+                    if !names.contains(&def.name.as_str()) {
+                        names.push(&def.name);
+                    }
+                }
+                None => named_groups.push((&def.module_path, vec![&def.name])),
+            },
+            None => {
+                if !result.unresolved.iter().any(|u| u == name) {
+                    result.unresolved.push(name.to_string());
+                }
+            }
+        }
+    }
+
+    for (module, names) in named_groups {
+        result.imports.push(format!(
+            "import {{ {} }} from '{}';",
+            names.join(", "),
+            module
+        ));
+    }
+    result
+        .imports
+        .extend(defaults.into_iter().map(component_import_statement));
+    result
+}
+
+/// Returns true if an image source is a path relative to the document, as
+/// opposed to a URL, an absolute path, a data URI or an anchor.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::transform::imports::is_local_asset_path;
+///
+/// assert!(is_local_asset_path("./hero.png"));
+/// assert!(is_local_asset_path("../assets/hero.png"));
+/// assert!(is_local_asset_path("hero.png"));
+/// assert!(!is_local_asset_path("/hero.png"));
+/// assert!(!is_local_asset_path("https://example.com/hero.png"));
+/// assert!(!is_local_asset_path("//cdn.example.com/hero.png"));
+/// assert!(!is_local_asset_path("data:image/png;base64,AAAA"));
+/// ```
+pub fn is_local_asset_path(src: &str) -> bool {
+    let Some(first) = src.chars().next() else {
+        return false;
+    };
+    if matches!(first, '/' | '\\' | '#' | '?' | '{') {
+        return false;
+    }
+    // Anything with a URI scheme (`https:`, `data:`, `mailto:`, ...).
+    let scheme_len = src
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')))
+        .unwrap_or(src.len());
+    !(first.is_ascii_alphabetic() && src[scheme_len..].starts_with(':'))
+}
+
+/// Local binding for the `index`-th hoisted image import (`__xmdx_img0`, ...).
+pub fn asset_binding(index: usize) -> String {
+    format!("{ASSET_BINDING_PREFIX}{index}")
+}
+
+/// Builds the default imports for hoisted image sources, binding the
+/// `index`-th entry of `assets` to [`asset_binding`]`(index)`.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::transform::imports::asset_import_statements;
+///
+/// let assets = vec!["./hero.png".to_string()];
+/// assert_eq!(
+///     asset_import_statements(&assets),
+///     vec!["import __xmdx_img0 from './hero.png';"]
+/// );
+/// ```
+pub fn asset_import_statements(assets: &[String]) -> Vec<String> {
+    assets
+        .iter()
+        .enumerate()
+        .map(|(index, specifier)| {
+            format!(
+                "import {} from '{}';",
+                asset_binding(index),
+                specifier.replace('\\', "\\\\").replace('\'', "\\'")
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::defaults::default_starlight_registry;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_declared_bindings_import_forms() {
+        let statements = strings(&[
+            "import Default from 'a';",
+            "import { One, Two as Alias } from 'b';",
+            "import * as Ns from 'c';",
+            "import Mixed, { Inner } from 'd';",
+            "import {\n  Multi,\n  Line\n} from 'e';",
+            "import './side-effect.css';",
+            "import type { Props } from './types';",
+        ]);
+        assert_eq!(
+            declared_bindings(&statements),
+            vec![
+                "Default", "One", "Alias", "Ns", "Mixed", "Inner", "Multi", "Line", "Props"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_declared_bindings_exports() {
+        let statements = strings(&[
+            "export const Local = () => null;",
+            "export function Helper() {}",
+            "export default Layout;",
+        ]);
+        assert_eq!(declared_bindings(&statements), vec!["Local", "Helper"]);
+    }
+
+    #[test]
+    fn test_exported_names_lists_and_declarations() {
+        let statements = strings(&[
+            "export let toc = [], other = 1;",
+            "export { a, b as c };",
+            "export * from './all';",
+            "export default function Page() {}",
+        ]);
+        assert_eq!(exported_names(&statements), vec!["toc", "a", "c"]);
+    }
+
+    #[test]
+    fn test_resolve_groups_named_imports_by_module() {
+        let registry = default_starlight_registry();
+        let result = resolve_component_imports(["Tabs", "TabItem", "Card"], &[], &registry);
+        assert_eq!(
+            result.imports,
+            vec!["import { Tabs, TabItem, Card } from '@astrojs/starlight/components';"]
+        );
+        assert!(result.unresolved.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_skips_existing_and_reports_unknown() {
+        let registry = default_starlight_registry();
+        let existing = strings(&["import Card from './MyCard.astro';"]);
+        let result = resolve_component_imports(["Card", "Tabs", "Mystery"], &existing, &registry);
+        assert_eq!(
+            result.imports,
+            vec!["import { Tabs } from '@astrojs/starlight/components';"]
+        );
+        assert_eq!(result.unresolved, vec!["Mystery"]);
+    }
+
+    #[test]
+    fn test_parse_import_side_effect_and_namespace() {
+        let side_effect = parse_import("import './styles.css';").unwrap();
+        assert_eq!(side_effect.source, "./styles.css");
+        assert!(side_effect.bindings.is_empty());
+
+        let namespace = parse_import("import * as Icons from \"./icons\"").unwrap();
+        assert_eq!(namespace.bindings[0].imported, "*");
+        assert_eq!(namespace.to_source(), "import * as Icons from './icons';");
+
+        assert!(parse_import("export const x = 1;").is_none());
+    }
+
+    #[test]
+    fn test_dedupe_keeps_distinct_locals_from_same_module() {
+        let imports = strings(&[
+            "import { Aside } from 'a';",
+            "import { Aside as Other } from 'a';",
+            "import { Aside } from 'a'",
+            "import './x.css';",
+            "import \"./x.css\";",
+        ]);
+        assert_eq!(
+            dedupe_import_statements(imports),
+            vec![
+                "import { Aside } from 'a';",
+                "import { Aside as Other } from 'a';",
+                "import './x.css';",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_aliases_colliding_generated_import() {
+        let registry = default_starlight_registry();
+        let existing = strings(&["import Aside from './MyAside.astro';"]);
+        let planned = plan_generated_imports(["Aside"], &existing, &registry);
+        assert_eq!(
+            planned.imports,
+            vec!["import { Aside as __xmdx_Aside } from '@astrojs/starlight/components';"]
+        );
+        assert_eq!(planned.aliases.get("Aside").unwrap(), "__xmdx_Aside");
+    }
+
+    #[test]
+    fn test_plan_reuses_existing_import_from_same_module() {
+        let registry = default_starlight_registry();
+        let existing =
+            strings(&["import { Aside as Callout, Tabs } from '@astrojs/starlight/components';"]);
+        let planned = plan_generated_imports(["Aside", "Tabs", "Steps"], &existing, &registry);
+        assert_eq!(
+            planned.imports,
+            vec!["import { Steps } from '@astrojs/starlight/components';"]
+        );
+        assert_eq!(planned.aliases.len(), 1);
+        assert_eq!(planned.aliases.get("Aside").unwrap(), "Callout");
+    }
+
+    #[test]
+    fn test_alias_renames_skip_declared_aliases() {
+        let mut registry = default_starlight_registry();
+        for def in &mut registry.components {
+            match def.name.as_str() {
+                "Aside" => def.aliases = strings(&["Callout"]),
+                "LinkCard" => def.aliases = strings(&["LegacyCard"]),
+                _ => {}
+            }
+        }
+        let existing = strings(&["import LegacyCard from './LegacyCard.astro';"]);
+        let renames = alias_renames(&registry, &existing);
+        assert_eq!(renames.len(), 1);
+        assert_eq!(renames.get("Callout").unwrap(), "Aside");
+    }
+
+    #[test]
+    fn test_import_aliases_get_component_normalizations() {
+        let mut registry = default_starlight_registry();
+        let statements = strings(&[
+            "import { Steps as S, FileTree } from '@astrojs/starlight/components';",
+            "import { Steps as Local } from './Steps.astro';",
+        ]);
+        add_import_alias_normalizations(&mut registry, &statements);
+
+        assert_eq!(
+            registry
+                .get_slot_normalization("S")
+                .map(|n| n.strategy.as_str()),
+            Some("wrap_in_ol")
+        );
+        assert!(registry.get_slot_normalization("Local").is_none());
+        assert_eq!(
+            registry
+                .slot_normalizations
+                .iter()
+                .filter(|n| n.component == "FileTree")
+                .count(),
+            1
+        );
+    }
+}
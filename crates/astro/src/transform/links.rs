@@ -0,0 +1,350 @@
+//! Link policies: Markdown file links to routes, and external link attributes.
+//!
+//! Authors link to sibling pages by file (`[Setup](../guide/setup.md#install)`)
+//! so links work when browsing the repository. The rendered site needs the
+//! page route instead (`/docs/guide/setup/#install`). Links to other hosts
+//! can additionally get `rel`/`target` attributes and a new-tab indicator.
+
+use serde::{Deserialize, Serialize};
+
+/// Trailing slash policy for rewritten routes, mirroring Astro's `trailingSlash`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrailingSlash {
+    /// Always end routes with `/`.
+    Always,
+    /// Never end routes with `/` (except the site root).
+    Never,
+    /// Keep `/` only where the route is a directory index.
+    #[default]
+    Ignore,
+}
+
+impl TrailingSlash {
+    /// Parses an Astro `trailingSlash` value (`"always"`, `"never"`, `"ignore"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "always" => Some(Self::Always),
+            "never" => Some(Self::Never),
+            "ignore" => Some(Self::Ignore),
+            _ => None,
+        }
+    }
+}
+
+/// Configuration for rewriting `.md`/`.mdx` links to routes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkRewriteOptions {
+    /// Directory whose files map to routes (e.g., `src/content/docs`).
+    /// Relative roots match anywhere in the resolved link path.
+    #[serde(default = "default_content_root")]
+    pub content_root: String,
+    /// Base path prepended to every route (e.g., `/docs`).
+    #[serde(default = "default_base")]
+    pub base: String,
+    /// Trailing slash policy for rewritten routes.
+    #[serde(default)]
+    pub trailing_slash: TrailingSlash,
+    /// Whether to drop the file extension (`setup.md` → `setup`). When
+    /// disabled, routes use `.html` files (`setup.md` → `setup.html`).
+    #[serde(default = "default_strip_extension")]
+    pub strip_extension: bool,
+}
+
+fn default_content_root() -> String {
+    "src/content/docs".to_string()
+}
+
+fn default_base() -> String {
+    "/".to_string()
+}
+
+fn default_strip_extension() -> bool {
+    true
+}
+
+impl Default for LinkRewriteOptions {
+    fn default() -> Self {
+        Self {
+            content_root: default_content_root(),
+            base: default_base(),
+            trailing_slash: TrailingSlash::default(),
+            strip_extension: default_strip_extension(),
+        }
+    }
+}
+
+/// Rewrites a link to a `.md`/`.mdx` file into the route of that page.
+///
+/// Relative links resolve against `document_path`; root-relative links
+/// (`/guide/setup.md`) resolve against the content root. Returns `None` for
+/// links that should be left untouched: external URLs, anchor-only links,
+/// non-Markdown targets and files outside the content root.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::transform::links::{LinkRewriteOptions, TrailingSlash, rewrite_markdown_link};
+///
+/// let options = LinkRewriteOptions {
+///     base: "/docs".into(),
+///     trailing_slash: TrailingSlash::Always,
+///     ..Default::default()
+/// };
+/// let page = "/site/src/content/docs/start/intro.md";
+/// assert_eq!(
+///     rewrite_markdown_link("../guide/setup.md#install", page, &options).as_deref(),
+///     Some("/docs/guide/setup/#install")
+/// );
+/// assert_eq!(rewrite_markdown_link("#install", page, &options), None);
+/// assert_eq!(rewrite_markdown_link("https://example.com/a.md", page, &options), None);
+/// ```
+pub fn rewrite_markdown_link(
+    url: &str,
+    document_path: &str,
+    options: &LinkRewriteOptions,
+) -> Option<String> {
+    if url.starts_with('#') || url.starts_with("//") || has_scheme(url) {
+        return None;
+    }
+    let suffix_start = url.find(['?', '#']).unwrap_or(url.len());
+    let (path, suffix) = url.split_at(suffix_start);
+    let stem = strip_markdown_extension(path)?;
+
+    let root = segments(&options.content_root);
+    let page = if let Some(rooted) = stem.strip_prefix('/') {
+        normalize(segments(rooted))?
+    } else {
+        let document = segments(document_path);
+        let mut target: Vec<&str> = document[..document.len().saturating_sub(1)].to_vec();
+        target.extend(segments(stem));
+        let target = normalize(target)?;
+        let absolute_root = options.content_root.starts_with('/');
+        let start = if root.is_empty() {
+            0
+        } else if absolute_root {
+            target.starts_with(&root).then_some(root.len())?
+        } else {
+            target
+                .windows(root.len())
+                .rposition(|window| window == root.as_slice())?
+                + root.len()
+        };
+        target[start..].to_vec()
+    };
+
+    let mut route: Vec<&str> = page;
+    let is_index = route.last() == Some(&"index");
+    if is_index {
+        route.pop();
+    }
+
+    let mut href = options.base.trim_end_matches('/').to_string();
+    for segment in &route {
+        href.push('/');
+        href.push_str(segment);
+    }
+    if !options.strip_extension && !route.is_empty() && !is_index {
+        href.push_str(".html");
+    } else {
+        let directory = is_index || route.is_empty();
+        let slash = match options.trailing_slash {
+            TrailingSlash::Always => true,
+            TrailingSlash::Never => route.is_empty(),
+            TrailingSlash::Ignore => directory,
+        };
+        if slash {
+            href.push('/');
+        }
+    }
+    if href.is_empty() {
+        href.push('/');
+    }
+    href.push_str(suffix);
+    Some(href)
+}
+
+/// Attributes and indicators added to links that leave the site.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalLinkOptions {
+    /// Hosts that belong to the site; links to any other host are external.
+    #[serde(default)]
+    pub site_hosts: Vec<String>,
+    /// `rel` attribute for external links.
+    #[serde(default = "default_rel")]
+    pub rel: String,
+    /// Whether external links open in a new tab (`target="_blank"`).
+    #[serde(default)]
+    pub target_blank: bool,
+    /// Visually hidden text appended to external links, e.g. "(opens in new tab)".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_tab_label: Option<String>,
+    /// Icon component appended to external links (rendered `aria-hidden`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_component: Option<String>,
+}
+
+fn default_rel() -> String {
+    "noopener noreferrer".to_string()
+}
+
+impl Default for ExternalLinkOptions {
+    fn default() -> Self {
+        Self {
+            site_hosts: Vec::new(),
+            rel: default_rel(),
+            target_blank: false,
+            new_tab_label: None,
+            icon_component: None,
+        }
+    }
+}
+
+impl ExternalLinkOptions {
+    /// Returns true if `url` is an `http(s)` or protocol-relative URL whose
+    /// host is not one of [`site_hosts`](Self::site_hosts).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xmdx_astro::transform::links::ExternalLinkOptions;
+    ///
+    /// let options = ExternalLinkOptions {
+    ///     site_hosts: vec!["docs.example.com".into()],
+    ///     ..Default::default()
+    /// };
+    /// assert!(options.is_external("https://github.com/x"));
+    /// assert!(!options.is_external("https://DOCS.example.com:443/guide/"));
+    /// assert!(!options.is_external("/guide/"));
+    /// assert!(!options.is_external("mailto:me@example.com"));
+    /// ```
+    pub fn is_external(&self, url: &str) -> bool {
+        let lower = url.to_ascii_lowercase();
+        let Some(rest) = ["https://", "http://", "//"]
+            .iter()
+            .find_map(|prefix| lower.strip_prefix(prefix))
+        else {
+            return false;
+        };
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+        let host_port = authority.rsplit('@').next().unwrap_or(authority);
+        let host = host_port.split(':').next().unwrap_or(host_port);
+        !host.is_empty()
+            && !self
+                .site_hosts
+                .iter()
+                .any(|site| site.eq_ignore_ascii_case(host))
+    }
+}
+
+fn has_scheme(url: &str) -> bool {
+    let scheme_len = url
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')))
+        .unwrap_or(url.len());
+    url.starts_with(|c: char| c.is_ascii_alphabetic()) && url[scheme_len..].starts_with(':')
+}
+
+fn strip_markdown_extension(path: &str) -> Option<&str> {
+    let lower = path.to_ascii_lowercase();
+    [".mdx", ".md"]
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map(|ext| &path[..path.len() - ext.len()])
+        .filter(|stem| !stem.is_empty() && !stem.ends_with('/'))
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    path.split(['/', '\\'])
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+/// Resolves `.` and `..` segments; `None` if the path climbs above its root.
+fn normalize(segments: Vec<&str>) -> Option<Vec<&str>> {
+    let mut out = Vec::with_capacity(segments.len());
+    for segment in segments {
+        match segment {
+            "." => {}
+            ".." => {
+                out.pop()?;
+            }
+            _ => out.push(segment),
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "/site/src/content/docs/guide/intro.mdx";
+
+    fn rewrite(url: &str, options: &LinkRewriteOptions) -> Option<String> {
+        rewrite_markdown_link(url, PAGE, options)
+    }
+
+    #[test]
+    fn rewrites_relative_and_rooted_links() {
+        let options = LinkRewriteOptions::default();
+        assert_eq!(
+            rewrite("setup.md", &options).as_deref(),
+            Some("/guide/setup")
+        );
+        assert_eq!(
+            rewrite("./setup.MD?x=1#top", &options).as_deref(),
+            Some("/guide/setup?x=1#top")
+        );
+        assert_eq!(rewrite("../index.md", &options).as_deref(), Some("/"));
+        assert_eq!(
+            rewrite("../reference/index.mdx", &options).as_deref(),
+            Some("/reference/")
+        );
+        assert_eq!(
+            rewrite("/reference/api.md", &options).as_deref(),
+            Some("/reference/api")
+        );
+    }
+
+    #[test]
+    fn applies_base_trailing_slash_and_extension_policy() {
+        let never = LinkRewriteOptions {
+            base: "/docs/".into(),
+            trailing_slash: TrailingSlash::Never,
+            ..Default::default()
+        };
+        assert_eq!(
+            rewrite("setup.md", &never).as_deref(),
+            Some("/docs/guide/setup")
+        );
+        assert_eq!(rewrite("index.md", &never).as_deref(), Some("/docs/guide"));
+        assert_eq!(rewrite("../index.md", &never).as_deref(), Some("/docs/"));
+
+        let html = LinkRewriteOptions {
+            strip_extension: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            rewrite("setup.md#a", &html).as_deref(),
+            Some("/guide/setup.html#a")
+        );
+    }
+
+    #[test]
+    fn leaves_other_links_untouched() {
+        let options = LinkRewriteOptions::default();
+        for url in [
+            "#install",
+            "https://example.com/x.md",
+            "//cdn.example.com/x.md",
+            "mailto:me@example.com",
+            "./diagram.png",
+            "../../../../outside.md",
+            "../../../other.md",
+        ] {
+            assert_eq!(rewrite(url, &options), None, "{url}");
+        }
+    }
+}
@@ -0,0 +1,1141 @@
+//! TeX to MathML conversion for server-rendered math.
+//!
+//! Covers the LaTeX subset most docs use: fractions, roots, scripts and
+//! limits, Greek letters, operators and relations, `\left`/`\right`
+//! delimiters, accents, font commands, matrices, `cases` and aligned
+//! environments. Anything else is reported as [`MathmlError::Unsupported`]
+//! so callers can fall back to a client-side renderer.
+
+use serde::{Deserialize, Serialize};
+
+/// How `$...$` and `$$...$$` math is emitted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MathOutput {
+    /// `<MathBlock>`/`<MathInline>` components rendered on the JS side.
+    #[default]
+    Component,
+    /// MathML converted in Rust, falling back to the components for
+    /// formulas outside the supported subset.
+    Mathml,
+}
+
+impl MathOutput {
+    /// Parses an output name (`"component"`, `"mathml"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "component" => Some(Self::Component),
+            "mathml" => Some(Self::Mathml),
+            _ => None,
+        }
+    }
+}
+
+/// Why a formula could not be converted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MathmlError {
+    /// A command or environment outside the supported subset (e.g., `\color`).
+    Unsupported(String),
+    /// Malformed TeX (e.g., an unclosed group).
+    Syntax(String),
+}
+
+impl std::fmt::Display for MathmlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MathmlError::Unsupported(name) => write!(f, "unsupported TeX: {name}"),
+            MathmlError::Syntax(msg) => write!(f, "invalid TeX: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for MathmlError {}
+
+/// Converts a TeX formula to a `<math>` element.
+///
+/// The source is kept as an `application/x-tex` annotation. Braces,
+/// backticks and newlines are written as entities so the markup is safe
+/// inside JSX.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::transform::mathml::{MathmlError, tex_to_mathml};
+///
+/// let mathml = tex_to_mathml(r"\frac{1}{2}", false).unwrap();
+/// assert!(mathml.starts_with(r#"<math xmlns="http://www.w3.org/1998/Math/MathML">"#));
+/// assert!(mathml.contains("<mfrac><mn>1</mn><mn>2</mn></mfrac>"));
+///
+/// assert_eq!(
+///     tex_to_mathml(r"\color{red}{x}", false),
+///     Err(MathmlError::Unsupported(r"\color".to_string()))
+/// );
+/// ```
+pub fn tex_to_mathml(tex: &str, display: bool) -> Result<String, MathmlError> {
+    let mut parser = Parser::new(tex);
+    let row = parser.parse_row(RowEnd::Eof)?;
+    let display_attr = if display { r#" display="block""# } else { "" };
+    Ok(format!(
+        r#"<math xmlns="http://www.w3.org/1998/Math/MathML"{display_attr}><semantics>{}<annotation encoding="application/x-tex">{}</annotation></semantics></math>"#,
+        mrow(row),
+        escape_text(tex.trim())
+    ))
+}
+
+/// Where a row of atoms stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowEnd {
+    /// End of input.
+    Eof,
+    /// A closing `}`.
+    Brace,
+    /// A `\right` delimiter.
+    Right,
+    /// A table cell boundary: `&`, `\\` or `\end`.
+    Cell,
+}
+
+/// A parsed atom and whether its scripts render as limits.
+struct Atom {
+    markup: String,
+    limits: bool,
+}
+
+impl Atom {
+    fn new(markup: String) -> Self {
+        Self {
+            markup,
+            limits: false,
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(tex: &str) -> Self {
+        Self {
+            chars: tex.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// Skips whitespace and `%` comments.
+    fn skip_ws(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.pos += 1;
+            } else if c == '%' {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Returns the command at the cursor (without the backslash), if any.
+    fn peek_command(&self) -> Option<String> {
+        if self.peek() != Some('\\') {
+            return None;
+        }
+        let first = *self.chars.get(self.pos + 1)?;
+        if !first.is_ascii_alphabetic() {
+            return Some(first.to_string());
+        }
+        Some(
+            self.chars[self.pos + 1..]
+                .iter()
+                .take_while(|c| c.is_ascii_alphabetic())
+                .collect(),
+        )
+    }
+
+    /// Consumes the command at the cursor, which must exist.
+    fn take_command(&mut self) -> String {
+        let name = self.peek_command().unwrap_or_default();
+        self.pos += 1 + name.chars().count();
+        name
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), MathmlError> {
+        self.skip_ws();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(MathmlError::Syntax(format!("expected `{expected}`")))
+        }
+    }
+
+    fn at_row_end(&self, end: RowEnd) -> Result<bool, MathmlError> {
+        let Some(c) = self.peek() else {
+            return match end {
+                RowEnd::Eof => Ok(true),
+                RowEnd::Brace => Err(MathmlError::Syntax("unclosed `{`".to_string())),
+                RowEnd::Right => Err(MathmlError::Syntax(
+                    "`\\left` without `\\right`".to_string(),
+                )),
+                RowEnd::Cell => Err(MathmlError::Syntax("unclosed environment".to_string())),
+            };
+        };
+        let command = self.peek_command();
+        Ok(match end {
+            RowEnd::Eof => false,
+            RowEnd::Brace => c == '}',
+            RowEnd::Right => command.as_deref() == Some("right"),
+            RowEnd::Cell => c == '&' || matches!(command.as_deref(), Some("\\" | "end")),
+        })
+    }
+
+    /// Parses atoms (with their scripts) until `end`, leaving the
+    /// terminator unconsumed.
+    fn parse_row(&mut self, end: RowEnd) -> Result<Vec<String>, MathmlError> {
+        let mut row = Vec::new();
+        loop {
+            self.skip_ws();
+            if self.at_row_end(end)? {
+                return Ok(row);
+            }
+            match self.peek_command().as_deref() {
+                Some(style @ ("displaystyle" | "textstyle")) => {
+                    let display = style == "displaystyle";
+                    self.take_command();
+                    let rest = self.parse_row(end)?;
+                    row.push(format!(
+                        r#"<mstyle displaystyle="{display}">{}</mstyle>"#,
+                        mrow(rest)
+                    ));
+                    return Ok(row);
+                }
+                Some("middle") => {
+                    self.take_command();
+                    let delim = self.parse_delimiter()?;
+                    row.push(format!(
+                        r#"<mo fence="true" stretchy="true">{}</mo>"#,
+                        delim.unwrap_or_default()
+                    ));
+                    continue;
+                }
+                _ => {}
+            }
+            let atom = self.parse_atom(false)?;
+            row.push(self.parse_scripts(atom)?);
+        }
+    }
+
+    /// Parses a command argument or script: a `{group}` or a single token.
+    fn parse_arg(&mut self) -> Result<String, MathmlError> {
+        self.skip_ws();
+        match self.peek() {
+            None => Err(MathmlError::Syntax("missing argument".to_string())),
+            Some('{') => self.parse_group(),
+            Some(_) => Ok(self.parse_atom(true)?.markup),
+        }
+    }
+
+    fn parse_group(&mut self) -> Result<String, MathmlError> {
+        self.expect('{')?;
+        let row = self.parse_row(RowEnd::Brace)?;
+        self.expect('}')?;
+        Ok(mrow(row))
+    }
+
+    /// Reads the raw text of a `{...}` argument.
+    fn parse_raw_arg(&mut self) -> Result<String, MathmlError> {
+        self.expect('{')?;
+        let mut depth = 0usize;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Ok(text),
+                '}' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+        }
+        Err(MathmlError::Syntax("unclosed `{`".to_string()))
+    }
+
+    /// Applies `^`, `_` and primes following `base`.
+    fn parse_scripts(&mut self, base: Atom) -> Result<String, MathmlError> {
+        let mut limits = base.limits;
+        let mut sub = None;
+        let mut sup: Option<String> = None;
+        let mut primes = String::new();
+        loop {
+            self.skip_ws();
+            match self.peek() {
+                Some('^') if sup.is_none() => {
+                    self.pos += 1;
+                    sup = Some(self.parse_arg()?);
+                }
+                Some('_') if sub.is_none() => {
+                    self.pos += 1;
+                    sub = Some(self.parse_arg()?);
+                }
+                Some('^' | '_') => {
+                    return Err(MathmlError::Syntax("double script".to_string()));
+                }
+                Some('\'') => {
+                    self.pos += 1;
+                    primes.push('′');
+                }
+                Some('\\') => match self.peek_command().as_deref() {
+                    Some("limits") => {
+                        self.take_command();
+                        limits = true;
+                    }
+                    Some("nolimits") => {
+                        self.take_command();
+                        limits = false;
+                    }
+                    _ => break,
+                },
+                _ => break,
+            }
+        }
+        if !primes.is_empty() {
+            let prime = format!("<mo>{primes}</mo>");
+            sup = Some(match sup {
+                Some(sup) => format!("<mrow>{prime}{sup}</mrow>"),
+                None => prime,
+            });
+        }
+        let base = base.markup;
+        let (under, over, both) = if limits {
+            ("munder", "mover", "munderover")
+        } else {
+            ("msub", "msup", "msubsup")
+        };
+        Ok(match (sub, sup) {
+            (None, None) => base,
+            (Some(sub), None) => format!("<{under}>{base}{sub}</{under}>"),
+            (None, Some(sup)) => format!("<{over}>{base}{sup}</{over}>"),
+            (Some(sub), Some(sup)) => format!("<{both}>{base}{sub}{sup}</{both}>"),
+        })
+    }
+
+    /// Parses one atom. As a script or argument (`single`), a number is a
+    /// single digit, matching TeX (`x^10` is `x¹0`).
+    fn parse_atom(&mut self, single: bool) -> Result<Atom, MathmlError> {
+        self.skip_ws();
+        let Some(c) = self.peek() else {
+            return Err(MathmlError::Syntax("missing argument".to_string()));
+        };
+        match c {
+            '{' => Ok(Atom::new(self.parse_group()?)),
+            '}' => Err(MathmlError::Syntax("unbalanced `}`".to_string())),
+            '\\' => self.parse_command(),
+            '&' => Err(MathmlError::Syntax(
+                "`&` outside an environment".to_string(),
+            )),
+            '^' | '_' => Err(MathmlError::Syntax("script without a base".to_string())),
+            '#' | '$' => Err(MathmlError::Syntax(format!("unexpected `{c}`"))),
+            '~' => {
+                self.pos += 1;
+                Ok(Atom::new(r#"<mspace width="0.25em"></mspace>"#.to_string()))
+            }
+            '0'..='9' => {
+                let mut number = String::new();
+                while let Some(d) = self.peek() {
+                    let decimal = d == '.'
+                        && !single
+                        && self
+                            .chars
+                            .get(self.pos + 1)
+                            .is_some_and(|n| n.is_ascii_digit());
+                    if !(d.is_ascii_digit() || decimal) {
+                        break;
+                    }
+                    number.push(d);
+                    self.pos += 1;
+                    if single {
+                        break;
+                    }
+                }
+                Ok(Atom::new(format!("<mn>{number}</mn>")))
+            }
+            c if c.is_alphabetic() => {
+                self.pos += 1;
+                Ok(Atom::new(format!(
+                    "<mi>{}</mi>",
+                    escape_text(&c.to_string())
+                )))
+            }
+            c => {
+                self.pos += 1;
+                let op = match c {
+                    '-' => "−".to_string(),
+                    '*' => "∗".to_string(),
+                    '\'' => "′".to_string(),
+                    c => escape_text(&c.to_string()),
+                };
+                Ok(Atom::new(format!("<mo>{op}</mo>")))
+            }
+        }
+    }
+
+    fn parse_command(&mut self) -> Result<Atom, MathmlError> {
+        let name = self.take_command();
+        let name = name.as_str();
+
+        if let Some(letter) = greek(name) {
+            let variant = if letter.chars().next().is_some_and(char::is_uppercase) {
+                r#" mathvariant="normal""#
+            } else {
+                ""
+            };
+            return Ok(Atom::new(format!("<mi{variant}>{letter}</mi>")));
+        }
+        if let Some(symbol) = ordinary_symbol(name) {
+            return Ok(Atom::new(format!("<mi>{symbol}</mi>")));
+        }
+        if let Some(op) = operator(name) {
+            return Ok(Atom::new(format!("<mo>{op}</mo>")));
+        }
+        if let Some((op, limits)) = large_operator(name) {
+            return Ok(Atom {
+                markup: format!("<mo>{op}</mo>"),
+                limits,
+            });
+        }
+        if let Some((function, limits)) = function_name(name) {
+            return Ok(Atom {
+                markup: format!("<mi>{function}</mi>"),
+                limits,
+            });
+        }
+        if let Some(width) = space(name) {
+            return Ok(Atom::new(format!(r#"<mspace width="{width}"></mspace>"#)));
+        }
+        if let Some((accent, under)) = accent(name) {
+            let base = self.parse_arg()?;
+            return Ok(Atom::new(if under {
+                format!(r#"<munder accentunder="true">{base}<mo>{accent}</mo></munder>"#)
+            } else {
+                format!(r#"<mover accent="true">{base}<mo>{accent}</mo></mover>"#)
+            }));
+        }
+        if let Some(size) = delimiter_size(name) {
+            let delim = self.parse_delimiter()?.unwrap_or_default();
+            return Ok(Atom::new(format!(
+                r#"<mo fence="true" stretchy="true" minsize="{size}" maxsize="{size}">{delim}</mo>"#
+            )));
+        }
+
+        match name {
+            "frac" | "dfrac" | "tfrac" => {
+                let num = self.parse_arg()?;
+                let den = self.parse_arg()?;
+                let frac = format!("<mfrac>{num}{den}</mfrac>");
+                Ok(Atom::new(match name {
+                    "dfrac" => format!(r#"<mstyle displaystyle="true">{frac}</mstyle>"#),
+                    "tfrac" => format!(r#"<mstyle displaystyle="false">{frac}</mstyle>"#),
+                    _ => frac,
+                }))
+            }
+            "binom" => {
+                let n = self.parse_arg()?;
+                let k = self.parse_arg()?;
+                Ok(Atom::new(format!(
+                    r#"<mrow><mo>(</mo><mfrac linethickness="0">{n}{k}</mfrac><mo>)</mo></mrow>"#
+                )))
+            }
+            "sqrt" => {
+                self.skip_ws();
+                let index = if self.peek() == Some('[') {
+                    self.pos += 1;
+                    let mut row = Vec::new();
+                    loop {
+                        self.skip_ws();
+                        match self.peek() {
+                            Some(']') => break,
+                            None => return Err(MathmlError::Syntax("unclosed `[`".to_string())),
+                            _ => {
+                                let atom = self.parse_atom(false)?;
+                                row.push(self.parse_scripts(atom)?);
+                            }
+                        }
+                    }
+                    self.pos += 1;
+                    Some(mrow(row))
+                } else {
+                    None
+                };
+                let radicand = self.parse_arg()?;
+                Ok(Atom::new(match index {
+                    Some(index) => format!("<mroot>{radicand}{index}</mroot>"),
+                    None => format!("<msqrt>{radicand}</msqrt>"),
+                }))
+            }
+            "left" => {
+                let open = self.parse_delimiter()?;
+                let inner = self.parse_row(RowEnd::Right)?;
+                self.take_command();
+                let close = self.parse_delimiter()?;
+                let fence = |delim: Option<String>, form: &str| {
+                    delim
+                        .map(|d| format!(r#"<mo fence="true" form="{form}">{d}</mo>"#))
+                        .unwrap_or_default()
+                };
+                Ok(Atom::new(format!(
+                    "<mrow>{}{}{}</mrow>",
+                    fence(open, "prefix"),
+                    inner.concat(),
+                    fence(close, "postfix")
+                )))
+            }
+            "text" | "textrm" | "textup" | "textnormal" | "mbox" => {
+                let text = self.parse_raw_arg()?;
+                Ok(Atom::new(format!("<mtext>{}</mtext>", escape_text(&text))))
+            }
+            "operatorname" => {
+                let text = self.parse_raw_arg()?;
+                Ok(Atom::new(format!("<mi>{}</mi>", escape_text(text.trim()))))
+            }
+            "mathrm" | "mathit" | "mathbf" | "mathbb" | "mathcal" | "mathscr" | "mathfrak"
+            | "mathsf" | "mathtt" | "boldsymbol" | "bm" => {
+                let text = self.parse_raw_arg()?;
+                Ok(Atom::new(styled_identifiers(name, &text)?))
+            }
+            "begin" => self.parse_environment(),
+            "\\" => Err(MathmlError::Unsupported(
+                r"\\ outside an environment".to_string(),
+            )),
+            "right" => Err(MathmlError::Syntax(
+                "`\\right` without `\\left`".to_string(),
+            )),
+            "end" => Err(MathmlError::Syntax("`\\end` without `\\begin`".to_string())),
+            "" => Err(MathmlError::Syntax("trailing `\\`".to_string())),
+            _ => Err(MathmlError::Unsupported(format!("\\{name}"))),
+        }
+    }
+
+    /// Reads the delimiter after `\left`, `\right`, `\middle` or `\big`.
+    /// `.` is the empty delimiter.
+    fn parse_delimiter(&mut self) -> Result<Option<String>, MathmlError> {
+        self.skip_ws();
+        match self.peek() {
+            Some('.') => {
+                self.pos += 1;
+                Ok(None)
+            }
+            Some(c @ ('(' | ')' | '[' | ']' | '|' | '/')) => {
+                self.pos += 1;
+                Ok(Some(c.to_string()))
+            }
+            Some('<') => {
+                self.pos += 1;
+                Ok(Some("⟨".to_string()))
+            }
+            Some('>') => {
+                self.pos += 1;
+                Ok(Some("⟩".to_string()))
+            }
+            Some('\\') => {
+                let name = self.take_command();
+                let delim = match name.as_str() {
+                    "{" | "lbrace" => "&#123;",
+                    "}" | "rbrace" => "&#125;",
+                    "|" | "Vert" | "lVert" | "rVert" => "‖",
+                    "vert" | "lvert" | "rvert" => "|",
+                    "langle" => "⟨",
+                    "rangle" => "⟩",
+                    "lfloor" => "⌊",
+                    "rfloor" => "⌋",
+                    "lceil" => "⌈",
+                    "rceil" => "⌉",
+                    "backslash" => "∖",
+                    _ => return Err(MathmlError::Unsupported(format!("\\{name} as a delimiter"))),
+                };
+                Ok(Some(delim.to_string()))
+            }
+            _ => Err(MathmlError::Syntax("missing delimiter".to_string())),
+        }
+    }
+
+    /// Parses `\begin{name}...\end{name}` (the `\begin` is consumed).
+    fn parse_environment(&mut self) -> Result<Atom, MathmlError> {
+        let name = self.parse_raw_arg()?;
+        let name = name.trim();
+        let mut columns: Option<Vec<&str>> = None;
+        let spec;
+        let (open, close, attrs) = match name {
+            "matrix" | "smallmatrix" => ("", "", String::new()),
+            "pmatrix" => ("(", ")", String::new()),
+            "bmatrix" => ("[", "]", String::new()),
+            "Bmatrix" => ("&#123;", "&#125;", String::new()),
+            "vmatrix" => ("|", "|", String::new()),
+            "Vmatrix" => ("‖", "‖", String::new()),
+            "cases" => ("&#123;", "", r#" columnalign="left left""#.to_string()),
+            "aligned" | "align" | "align*" | "split" => (
+                "",
+                "",
+                r#" displaystyle="true" columnalign="right left" columnspacing="0em""#.to_string(),
+            ),
+            "gathered" | "gather" | "gather*" | "equation" | "equation*" => {
+                ("", "", r#" displaystyle="true""#.to_string())
+            }
+            "array" => {
+                spec = self.parse_raw_arg()?;
+                let align: Vec<&str> = spec
+                    .chars()
+                    .filter_map(|c| match c {
+                        'l' => Some("left"),
+                        'c' => Some("center"),
+                        'r' => Some("right"),
+                        _ => None,
+                    })
+                    .collect();
+                let attrs = format!(r#" columnalign="{}""#, align.join(" "));
+                columns = Some(align);
+                ("", "", attrs)
+            }
+            _ => return Err(MathmlError::Unsupported(format!("environment {name}"))),
+        };
+        let aligned = matches!(name, "aligned" | "align" | "align*" | "split");
+
+        let mut rows: Vec<Vec<String>> = vec![Vec::new()];
+        loop {
+            self.skip_ws();
+            while self.peek_command().as_deref() == Some("hline") {
+                self.take_command();
+                self.skip_ws();
+            }
+            let cell = self.parse_row(RowEnd::Cell)?;
+            let row = rows.last_mut().expect("rows start non-empty");
+            let cell = if aligned && row.len() % 2 == 1 {
+                // Leading `<mi></mi>` gives `&= x` infix spacing.
+                format!("<mrow><mi></mi>{}</mrow>", cell.concat())
+            } else {
+                mrow(cell)
+            };
+            row.push(format!("<mtd>{cell}</mtd>"));
+            if self.peek() == Some('&') {
+                self.pos += 1;
+                continue;
+            }
+            if self.take_command() == "\\" {
+                rows.push(Vec::new());
+                continue;
+            }
+            let end = self.parse_raw_arg()?;
+            if end.trim() != name {
+                return Err(MathmlError::Syntax(format!(
+                    "`\\begin{{{name}}}` closed by `\\end{{{}}}`",
+                    end.trim()
+                )));
+            }
+            break;
+        }
+        // A trailing `\\` leaves an empty last row.
+        if rows.len() > 1
+            && rows
+                .last()
+                .is_some_and(|row| row.len() == 1 && row[0] == "<mtd><mrow></mrow></mtd>")
+        {
+            rows.pop();
+        }
+        if let Some(columns) = columns
+            && rows.iter().any(|row| row.len() > columns.len().max(1))
+        {
+            return Err(MathmlError::Syntax(
+                "more cells than array columns".to_string(),
+            ));
+        }
+
+        let table = format!(
+            "<mtable{attrs}>{}</mtable>",
+            rows.iter()
+                .map(|row| format!("<mtr>{}</mtr>", row.concat()))
+                .collect::<String>()
+        );
+        let fence = |delim: &str, form: &str| {
+            if delim.is_empty() {
+                String::new()
+            } else {
+                format!(r#"<mo fence="true" form="{form}">{delim}</mo>"#)
+            }
+        };
+        Ok(Atom::new(if open.is_empty() && close.is_empty() {
+            table
+        } else {
+            format!(
+                "<mrow>{}{table}{}</mrow>",
+                fence(open, "prefix"),
+                fence(close, "postfix")
+            )
+        }))
+    }
+}
+
+/// Wraps a row in `<mrow>` unless it is a single element.
+fn mrow(mut row: Vec<String>) -> String {
+    if row.len() == 1 {
+        row.pop().unwrap_or_default()
+    } else {
+        format!("<mrow>{}</mrow>", row.concat())
+    }
+}
+
+/// Escapes text for MathML inside JSX-bound HTML.
+fn escape_text(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '{' => result.push_str("&#123;"),
+            '}' => result.push_str("&#125;"),
+            '`' => result.push_str("&#96;"),
+            '\n' => result.push_str("&#10;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+fn greek(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        _ => return None,
+    })
+}
+
+/// Symbols that behave like identifiers.
+fn ordinary_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "emptyset" | "varnothing" => "∅",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        "aleph" => "ℵ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "wp" => "℘",
+        "imath" => "ı",
+        "jmath" => "ȷ",
+        "angle" => "∠",
+        "triangle" => "△",
+        "top" => "⊤",
+        "bot" => "⊥",
+        _ => return None,
+    })
+}
+
+/// Binary operators, relations, arrows, punctuation and delimiters.
+fn operator(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "pm" => "±",
+        "mp" => "∓",
+        "times" => "×",
+        "div" => "÷",
+        "cdot" => "⋅",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "bullet" => "∙",
+        "oplus" => "⊕",
+        "ominus" => "⊖",
+        "otimes" => "⊗",
+        "odot" => "⊙",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "wedge" | "land" => "∧",
+        "vee" | "lor" => "∨",
+        "neg" | "lnot" => "¬",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "ll" => "≪",
+        "gg" => "≫",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "mid" => "∣",
+        "forall" => "∀",
+        "exists" => "∃",
+        "nexists" => "∄",
+        "to" | "rightarrow" => "→",
+        "gets" | "leftarrow" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" => "⇔",
+        "implies" => "⟹",
+        "impliedby" => "⟸",
+        "iff" => "⟺",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        "longrightarrow" => "⟶",
+        "longleftarrow" => "⟵",
+        "therefore" => "∴",
+        "because" => "∵",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "prime" => "′",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "{" | "lbrace" => "&#123;",
+        "}" | "rbrace" => "&#125;",
+        "|" | "Vert" => "‖",
+        "vert" => "|",
+        "backslash" => "∖",
+        "#" => "#",
+        "%" => "%",
+        "$" => "$",
+        "&" => "&amp;",
+        "_" => "_",
+        _ => return None,
+    })
+}
+
+/// Large operators and whether their scripts render as limits.
+fn large_operator(name: &str) -> Option<(&'static str, bool)> {
+    Some(match name {
+        "sum" => ("∑", true),
+        "prod" => ("∏", true),
+        "coprod" => ("∐", true),
+        "bigcup" => ("⋃", true),
+        "bigcap" => ("⋂", true),
+        "bigoplus" => ("⨁", true),
+        "bigotimes" => ("⨂", true),
+        "bigvee" => ("⋁", true),
+        "bigwedge" => ("⋀", true),
+        "int" => ("∫", false),
+        "iint" => ("∬", false),
+        "iiint" => ("∭", false),
+        "oint" => ("∮", false),
+        _ => return None,
+    })
+}
+
+/// Upright function names and whether their scripts render as limits.
+fn function_name(name: &str) -> Option<(&'static str, bool)> {
+    Some(match name {
+        "lim" => ("lim", true),
+        "liminf" => ("lim inf", true),
+        "limsup" => ("lim sup", true),
+        "max" => ("max", true),
+        "min" => ("min", true),
+        "sup" => ("sup", true),
+        "inf" => ("inf", true),
+        "det" => ("det", true),
+        "gcd" => ("gcd", true),
+        "Pr" => ("Pr", true),
+        "argmax" => ("arg max", true),
+        "argmin" => ("arg min", true),
+        "sin" => ("sin", false),
+        "cos" => ("cos", false),
+        "tan" => ("tan", false),
+        "cot" => ("cot", false),
+        "sec" => ("sec", false),
+        "csc" => ("csc", false),
+        "arcsin" => ("arcsin", false),
+        "arccos" => ("arccos", false),
+        "arctan" => ("arctan", false),
+        "sinh" => ("sinh", false),
+        "cosh" => ("cosh", false),
+        "tanh" => ("tanh", false),
+        "log" => ("log", false),
+        "ln" => ("ln", false),
+        "lg" => ("lg", false),
+        "exp" => ("exp", false),
+        "deg" => ("deg", false),
+        "dim" => ("dim", false),
+        "ker" => ("ker", false),
+        "hom" => ("hom", false),
+        "arg" => ("arg", false),
+        "mod" | "bmod" => ("mod", false),
+        _ => return None,
+    })
+}
+
+fn space(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "," | "thinspace" => "0.1667em",
+        ":" | ">" | "medspace" => "0.2222em",
+        ";" | "thickspace" => "0.2778em",
+        "!" | "negthinspace" => "-0.1667em",
+        " " => "0.25em",
+        "quad" => "1em",
+        "qquad" => "2em",
+        _ => return None,
+    })
+}
+
+/// Accent marks and whether they sit under the base.
+fn accent(name: &str) -> Option<(&'static str, bool)> {
+    Some(match name {
+        "hat" | "widehat" => ("^", false),
+        "bar" => ("¯", false),
+        "overline" => ("‾", false),
+        "vec" | "overrightarrow" => ("→", false),
+        "overleftarrow" => ("←", false),
+        "dot" => ("˙", false),
+        "ddot" => ("¨", false),
+        "tilde" | "widetilde" => ("~", false),
+        "acute" => ("´", false),
+        "grave" => ("`", false),
+        "breve" => ("˘", false),
+        "check" => ("ˇ", false),
+        "overbrace" => ("⏞", false),
+        "underline" => ("_", true),
+        "underbrace" => ("⏟", true),
+        _ => return None,
+    })
+}
+
+fn delimiter_size(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "big" | "bigl" | "bigr" | "bigm" => "1.2em",
+        "Big" | "Bigl" | "Bigr" | "Bigm" => "1.623em",
+        "bigg" | "biggl" | "biggr" | "biggm" => "2.047em",
+        "Bigg" | "Biggl" | "Biggr" | "Biggm" => "2.470em",
+        _ => return None,
+    })
+}
+
+/// Renders the letters and digits of a font command (`\mathbb{R}`) as
+/// identifiers in the matching Unicode math alphabet.
+fn styled_identifiers(command: &str, text: &str) -> Result<String, MathmlError> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if text.is_empty() || !text.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(MathmlError::Unsupported(format!(
+            "\\{command} with non-letter content"
+        )));
+    }
+    let ident = |s: String, upright: bool| {
+        if upright && s.chars().count() == 1 {
+            format!(r#"<mi mathvariant="normal">{s}</mi>"#)
+        } else {
+            format!("<mi>{s}</mi>")
+        }
+    };
+    Ok(match command {
+        "mathrm" => ident(text, true),
+        "mathit" => mrow(text.chars().map(|c| format!("<mi>{c}</mi>")).collect()),
+        _ => {
+            let mapped: Option<String> = text.chars().map(|c| math_alphabet(command, c)).collect();
+            let mapped =
+                mapped.ok_or_else(|| MathmlError::Unsupported(format!("\\{command}{{{text}}}")))?;
+            // Styled characters carry their own style; keep them upright.
+            ident(mapped, true)
+        }
+    })
+}
+
+/// Maps an ASCII letter or digit into a Unicode math alphabet.
+fn math_alphabet(command: &str, c: char) -> Option<char> {
+    let exception = match (command, c) {
+        ("mathbb", 'C') => Some('ℂ'),
+        ("mathbb", 'H') => Some('ℍ'),
+        ("mathbb", 'N') => Some('ℕ'),
+        ("mathbb", 'P') => Some('ℙ'),
+        ("mathbb", 'Q') => Some('ℚ'),
+        ("mathbb", 'R') => Some('ℝ'),
+        ("mathbb", 'Z') => Some('ℤ'),
+        ("mathcal" | "mathscr", 'B') => Some('ℬ'),
+        ("mathcal" | "mathscr", 'E') => Some('ℰ'),
+        ("mathcal" | "mathscr", 'F') => Some('ℱ'),
+        ("mathcal" | "mathscr", 'H') => Some('ℋ'),
+        ("mathcal" | "mathscr", 'I') => Some('ℐ'),
+        ("mathcal" | "mathscr", 'L') => Some('ℒ'),
+        ("mathcal" | "mathscr", 'M') => Some('ℳ'),
+        ("mathcal" | "mathscr", 'R') => Some('ℛ'),
+        ("mathcal" | "mathscr", 'e') => Some('ℯ'),
+        ("mathcal" | "mathscr", 'g') => Some('ℊ'),
+        ("mathcal" | "mathscr", 'o') => Some('ℴ'),
+        ("mathfrak", 'C') => Some('ℭ'),
+        ("mathfrak", 'H') => Some('ℌ'),
+        ("mathfrak", 'I') => Some('ℑ'),
+        ("mathfrak", 'R') => Some('ℜ'),
+        ("mathfrak", 'Z') => Some('ℨ'),
+        _ => None,
+    };
+    if exception.is_some() {
+        return exception;
+    }
+    // (uppercase A, lowercase a, digit 0) code points per alphabet.
+    let (upper, lower, digit) = match command {
+        "mathbf" | "boldsymbol" | "bm" => (0x1D400, Some(0x1D41A), Some(0x1D7CE)),
+        "mathbb" => (0x1D538, Some(0x1D552), Some(0x1D7D8)),
+        "mathcal" | "mathscr" => (0x1D49C, Some(0x1D4B6), None),
+        "mathfrak" => (0x1D504, Some(0x1D51E), None),
+        "mathsf" => (0x1D5A0, Some(0x1D5BA), Some(0x1D7E2)),
+        "mathtt" => (0x1D670, Some(0x1D68A), Some(0x1D7F6)),
+        _ => return None,
+    };
+    let code = match c {
+        'A'..='Z' => upper + (c as u32 - 'A' as u32),
+        'a'..='z' => lower? + (c as u32 - 'a' as u32),
+        '0'..='9' => digit? + (c as u32 - '0' as u32),
+        _ => return None,
+    };
+    char::from_u32(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(tex: &str) -> String {
+        let mathml = tex_to_mathml(tex, false).unwrap();
+        let start = mathml.find("<semantics>").unwrap() + "<semantics>".len();
+        let end = mathml.find("<annotation").unwrap();
+        mathml[start..end].to_string()
+    }
+
+    #[test]
+    fn scripts_limits_and_roots() {
+        assert_eq!(
+            body("x_i^2"),
+            "<msubsup><mi>x</mi><mi>i</mi><mn>2</mn></msubsup>"
+        );
+        assert_eq!(
+            body("x^10"),
+            "<mrow><msup><mi>x</mi><mn>1</mn></msup><mn>0</mn></mrow>"
+        );
+        assert_eq!(
+            body(r"\sum_{k=1}^n k"),
+            "<mrow><munderover><mo>∑</mo><mrow><mi>k</mi><mo>=</mo><mn>1</mn></mrow><mi>n</mi></munderover><mi>k</mi></mrow>"
+        );
+        assert_eq!(
+            body(r"\int\limits_0^1"),
+            "<munderover><mo>∫</mo><mn>0</mn><mn>1</mn></munderover>"
+        );
+        assert_eq!(
+            body(r"\sqrt[3]{x} f'"),
+            "<mrow><mroot><mi>x</mi><mn>3</mn></mroot><msup><mi>f</mi><mo>′</mo></msup></mrow>"
+        );
+        assert_eq!(
+            body(r"\mathbb{R} \Gamma"),
+            r#"<mrow><mi mathvariant="normal">ℝ</mi><mi mathvariant="normal">Γ</mi></mrow>"#
+        );
+    }
+
+    #[test]
+    fn delimiters_and_environments() {
+        assert_eq!(
+            body(r"\left\{ a \right."),
+            r#"<mrow><mo fence="true" form="prefix">&#123;</mo><mi>a</mi></mrow>"#
+        );
+        assert_eq!(
+            body(r"\begin{pmatrix} 1 & 0 \\ 0 & 1 \\ \end{pmatrix}"),
+            r#"<mrow><mo fence="true" form="prefix">(</mo><mtable><mtr><mtd><mn>1</mn></mtd><mtd><mn>0</mn></mtd></mtr><mtr><mtd><mn>0</mn></mtd><mtd><mn>1</mn></mtd></mtr></mtable><mo fence="true" form="postfix">)</mo></mrow>"#
+        );
+        let aligned = body(r"\begin{aligned} a &= b \\ &= c \end{aligned}");
+        assert!(
+            aligned.starts_with(r#"<mtable displaystyle="true" columnalign="right left""#),
+            "{aligned}"
+        );
+        assert!(
+            aligned.contains("<mtd><mrow><mi></mi><mo>=</mo><mi>b</mi></mrow></mtd>"),
+            "{aligned}"
+        );
+    }
+
+    #[test]
+    fn unsupported_and_malformed_input_is_rejected() {
+        assert_eq!(
+            tex_to_mathml(r"\begin{tikzcd}a\end{tikzcd}", true),
+            Err(MathmlError::Unsupported("environment tikzcd".to_string()))
+        );
+        assert!(matches!(
+            tex_to_mathml(r"\frac{1}{2", false),
+            Err(MathmlError::Syntax(_))
+        ));
+        assert!(matches!(
+            tex_to_mathml(r"\begin{matrix}a\end{pmatrix}", false),
+            Err(MathmlError::Syntax(_))
+        ));
+        assert!(matches!(
+            tex_to_mathml("a}", false),
+            Err(MathmlError::Syntax(_))
+        ));
+    }
+
+    #[test]
+    fn output_is_safe_inside_jsx() {
+        let mathml = tex_to_mathml("\\{x\\} < y\n", true).unwrap();
+        assert!(mathml.contains(r#"display="block""#));
+        assert!(!mathml.contains(['{', '}', '\n']), "{mathml}");
+        assert!(mathml.contains(
+            r#"<annotation encoding="application/x-tex">\&#123;x\&#125; &lt; y</annotation>"#
+        ));
+    }
+}
@@ -1,17 +1,38 @@
 //! Transform utilities for markdown processing.
 //!
 //! - `code_fence`: tracks fenced blocks to avoid hoisting/rewrites inside them.
+//! - `code_meta`: parses code fence meta strings (title, line ranges, flags).
 //! - `directives`: directive mapping traits and default implementations.
+//! - `highlight`: Rust-side code fence highlighting (`highlight` feature).
+//! - `imports`: import binding analysis and registry-driven component imports.
 //! - `jsx_normalize`: JSX indentation normalization for MDAST rendering.
+//! - `links`: rewrites `.md`/`.mdx` file links to site routes and marks external links.
+//! - `mathml`: TeX to MathML conversion for server-rendered math.
 //! - `smartypants`: smart punctuation transformations (quotes, dashes, ellipsis).
+//! - `tex`: math macro expansion and TeX validation.
+//! - `wikilinks`: parses and resolves `[[Page#Heading|label]]` wiki links.
 
 /// Code fence state tracking utilities.
 pub mod code_fence;
+/// Code fence meta string parsing.
+pub mod code_meta;
 /// Astro docs component rewrite helpers.
 pub mod components;
 /// Directive mapping traits and default implementations.
 pub mod directives;
+/// Rust-side syntax highlighting for code fences.
+pub mod highlight;
+/// Import binding analysis and registry-driven component imports.
+pub mod imports;
 /// JSX indentation normalization for MDAST rendering.
 pub mod jsx_normalize;
+/// Markdown file link to route rewriting.
+pub mod links;
+/// TeX to MathML conversion for server-rendered math.
+pub mod mathml;
 /// Smart punctuation transformations (quotes, dashes, ellipsis).
 pub mod smartypants;
+/// Math macro expansion and TeX validation.
+pub mod tex;
+/// Wiki-link parsing and page name resolution.
+pub mod wikilinks;
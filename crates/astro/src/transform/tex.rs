@@ -0,0 +1,435 @@
+//! TeX source helpers for math: macro expansion and validation.
+//!
+//! [`expand_macros`] applies a user macro table (`\R` → `\mathbb{R}`) before
+//! math is emitted, whichever output mode renders it. [`validate_tex`] finds
+//! structural mistakes (unbalanced braces, unknown or mismatched
+//! environments) at build time instead of when KaTeX throws in the browser.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+/// Macro uses expanded per formula before giving up on (likely recursive)
+/// macros, matching KaTeX's `maxExpand`.
+const MAX_EXPANSIONS: usize = 1000;
+
+/// Size of an expanded formula before giving up, for macros whose bodies
+/// double their arguments.
+const MAX_EXPANDED_BYTES: usize = 1 << 20;
+
+/// Environments KaTeX renders; others are reported by [`validate_tex`].
+const KNOWN_ENVIRONMENTS: &[&str] = &[
+    "align",
+    "align*",
+    "aligned",
+    "alignat",
+    "alignat*",
+    "alignedat",
+    "array",
+    "Bmatrix",
+    "Bmatrix*",
+    "bmatrix",
+    "bmatrix*",
+    "CD",
+    "cases",
+    "darray",
+    "dcases",
+    "drcases",
+    "equation",
+    "equation*",
+    "gather",
+    "gather*",
+    "gathered",
+    "matrix",
+    "matrix*",
+    "pmatrix",
+    "pmatrix*",
+    "rcases",
+    "smallmatrix",
+    "split",
+    "subarray",
+    "Vmatrix",
+    "Vmatrix*",
+    "vmatrix",
+    "vmatrix*",
+];
+
+/// Expands user macros in `tex`.
+///
+/// Keys name the command with or without its backslash (`"\\R"` or `"R"`).
+/// Bodies may take arguments as `#1`…`#9`, read like TeX: a `{group}` or a
+/// single token. Expansion repeats so macros can use other macros.
+///
+/// # Errors
+///
+/// Recursive definitions are cut off after 1000 macro uses or once the
+/// formula grows past 1 MiB; the overrun is returned at offset 0 instead
+/// of a half-expanded formula.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use xmdx_astro::transform::tex::expand_macros;
+///
+/// let macros = BTreeMap::from([
+///     ("\\R".to_string(), "\\mathbb{R}".to_string()),
+///     ("norm".to_string(), "\\left\\| #1 \\right\\|".to_string()),
+/// ]);
+/// assert_eq!(
+///     expand_macros(r"\norm{x} \in \R^n", &macros).unwrap(),
+///     r"\left\| x \right\| \in \mathbb{R}^n"
+/// );
+/// assert_eq!(expand_macros(r"\Rightarrow", &macros).unwrap(), r"\Rightarrow");
+///
+/// let recursive = BTreeMap::from([("x".to_string(), "\\x\\x".to_string())]);
+/// assert!(expand_macros(r"\x", &recursive).is_err());
+/// ```
+pub fn expand_macros<'a>(
+    tex: &'a str,
+    macros: &BTreeMap<String, String>,
+) -> Result<Cow<'a, str>, TexIssue> {
+    if macros.is_empty() {
+        return Ok(Cow::Borrowed(tex));
+    }
+    let table: HashMap<&str, &str> = macros
+        .iter()
+        .map(|(name, body)| (name.strip_prefix('\\').unwrap_or(name), body.as_str()))
+        .collect();
+    let mut current = Cow::Borrowed(tex);
+    let mut budget = MAX_EXPANSIONS;
+    while let Some(expanded) = expand_once(&current, &table, &mut budget)? {
+        current = Cow::Owned(expanded);
+    }
+    Ok(current)
+}
+
+/// Expands every macro use in `tex` once, spending one unit of `budget` per
+/// use; `None` when there were none.
+fn expand_once(
+    tex: &str,
+    table: &HashMap<&str, &str>,
+    budget: &mut usize,
+) -> Result<Option<String>, TexIssue> {
+    let mut result = String::with_capacity(tex.len());
+    let mut expanded = false;
+    let mut pos = 0;
+    while let Some(offset) = tex[pos..].find('\\') {
+        let start = pos + offset;
+        let name = command_name(&tex[start + 1..]);
+        let after_name = start + 1 + name.len();
+        result.push_str(&tex[pos..start]);
+        match table
+            .get(name)
+            .and_then(|body| expand_use(tex, after_name, body))
+        {
+            Some((text, end)) => {
+                if *budget == 0 {
+                    return Err(overrun(format!(
+                        "macro expansion exceeded {MAX_EXPANSIONS} expansions (recursive macro?)"
+                    )));
+                }
+                *budget -= 1;
+                result.push_str(&text);
+                if result.len() > MAX_EXPANDED_BYTES {
+                    return Err(overrun(format!(
+                        "macro expansion exceeded {MAX_EXPANDED_BYTES} bytes (recursive macro?)"
+                    )));
+                }
+                expanded = true;
+                pos = end;
+            }
+            None => {
+                result.push_str(&tex[start..after_name]);
+                pos = after_name;
+            }
+        }
+    }
+    result.push_str(&tex[pos..]);
+    Ok(expanded.then_some(result))
+}
+
+fn overrun(message: String) -> TexIssue {
+    TexIssue { offset: 0, message }
+}
+
+/// Reads a macro's arguments starting at `pos` and returns the substituted
+/// body with the offset after the last argument. `None` when an argument is
+/// missing, leaving the use unexpanded.
+fn expand_use(tex: &str, mut pos: usize, body: &str) -> Option<(String, usize)> {
+    let mut args = Vec::new();
+    for _ in 0..arity(body) {
+        let (arg, next) = read_argument(tex, pos)?;
+        args.push(arg);
+        pos = next;
+    }
+    Some((substitute(body, &args), pos))
+}
+
+/// Returns the command name at the start of `rest` (the text after a
+/// backslash): a run of letters, or a single other character.
+fn command_name(rest: &str) -> &str {
+    let letters = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    if letters > 0 {
+        return &rest[..letters];
+    }
+    rest.chars().next().map_or("", |c| &rest[..c.len_utf8()])
+}
+
+/// Reads one argument at `pos`: the contents of a `{group}`, a command, or
+/// a single character. Returns the argument and the offset after it.
+fn read_argument(tex: &str, pos: usize) -> Option<(&str, usize)> {
+    let start = pos + (tex[pos..].len() - tex[pos..].trim_start().len());
+    let rest = &tex[start..];
+    match rest.chars().next()? {
+        '{' => {
+            let close = matching_brace(tex, start)?;
+            Some((&tex[start + 1..close], close + 1))
+        }
+        '}' => None,
+        '\\' => {
+            let end = start + 1 + command_name(&rest[1..]).len();
+            Some((&tex[start..end], end))
+        }
+        c => Some((&tex[start..start + c.len_utf8()], start + c.len_utf8())),
+    }
+}
+
+/// Returns the offset of the `}` closing the `{` at `open`.
+fn matching_brace(tex: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut chars = tex[open..].char_indices();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + offset);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Number of parameters a macro body uses (the highest `#n`).
+fn arity(body: &str) -> usize {
+    body.split('#')
+        .skip(1)
+        .filter_map(|after| after.chars().next()?.to_digit(10))
+        .max()
+        .unwrap_or(0) as usize
+}
+
+/// Replaces `#1`…`#9` in `body` with `args`, and `##` with `#`.
+fn substitute(body: &str, args: &[&str]) -> String {
+    let mut result = String::with_capacity(body.len());
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '#' {
+            result.push(c);
+            continue;
+        }
+        match chars.peek().copied() {
+            Some('#') => {
+                chars.next();
+                result.push('#');
+            }
+            Some(d) if d.is_ascii_digit() && d != '0' => {
+                chars.next();
+                let index = d.to_digit(10).unwrap_or(1) as usize - 1;
+                result.push_str(args.get(index).copied().unwrap_or_default());
+            }
+            _ => result.push('#'),
+        }
+    }
+    result
+}
+
+/// A structural problem in a TeX formula.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TexIssue {
+    /// Byte offset of the offending token within the formula.
+    pub offset: usize,
+    /// What is wrong (e.g., "unclosed `{`").
+    pub message: String,
+}
+
+/// Finds unbalanced braces, unknown environments and mismatched
+/// `\begin`/`\end` pairs in `tex`, in source order.
+///
+/// Escaped braces (`\{`) and `%` comments are ignored. Commands are not
+/// checked, since macros and KaTeX extensions make any list incomplete.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::transform::tex::validate_tex;
+///
+/// assert!(validate_tex(r"\frac{a}{b} \{x\}").is_empty());
+///
+/// let issues = validate_tex(r"\begin{matrix} a \end{pmatrix} {");
+/// let found: Vec<_> = issues.iter().map(|i| (i.offset, i.message.as_str())).collect();
+/// assert_eq!(
+///     found,
+///     vec![
+///         (17, r"`\begin{matrix}` closed by `\end{pmatrix}`"),
+///         (31, "unclosed `{`"),
+///     ]
+/// );
+/// ```
+pub fn validate_tex(tex: &str) -> Vec<TexIssue> {
+    let mut issues = Vec::new();
+    let mut braces = Vec::new();
+    let mut environments: Vec<(&str, usize)> = Vec::new();
+    let mut pos = 0;
+    while let Some(c) = tex[pos..].chars().next() {
+        let start = pos;
+        pos += c.len_utf8();
+        match c {
+            '%' => pos = tex[pos..].find('\n').map_or(tex.len(), |n| pos + n),
+            '{' => braces.push(start),
+            '}' if braces.pop().is_none() => issues.push(TexIssue {
+                offset: start,
+                message: "unbalanced `}`".to_string(),
+            }),
+            '\\' => {
+                let name = command_name(&tex[pos..]);
+                pos += name.len();
+                if !matches!(name, "begin" | "end") {
+                    continue;
+                }
+                let Some((env, next)) = environment_name(tex, pos) else {
+                    issues.push(TexIssue {
+                        offset: start,
+                        message: format!("`\\{name}` without an environment name"),
+                    });
+                    continue;
+                };
+                pos = next;
+                if name == "begin" {
+                    if !KNOWN_ENVIRONMENTS.contains(&env) {
+                        issues.push(TexIssue {
+                            offset: start,
+                            message: format!("unknown environment `{env}`"),
+                        });
+                    }
+                    environments.push((env, start));
+                    continue;
+                }
+                match environments.pop() {
+                    Some((open, _)) if open == env => {}
+                    Some((open, _)) => issues.push(TexIssue {
+                        offset: start,
+                        message: format!("`\\begin{{{open}}}` closed by `\\end{{{env}}}`"),
+                    }),
+                    None => issues.push(TexIssue {
+                        offset: start,
+                        message: format!("`\\end{{{env}}}` without `\\begin`"),
+                    }),
+                }
+            }
+            _ => {}
+        }
+    }
+    issues.extend(braces.into_iter().map(|offset| TexIssue {
+        offset,
+        message: "unclosed `{`".to_string(),
+    }));
+    issues.extend(environments.into_iter().map(|(env, offset)| TexIssue {
+        offset,
+        message: format!("unclosed `\\begin{{{env}}}`"),
+    }));
+    issues.sort_by_key(|issue| issue.offset);
+    issues
+}
+
+/// Reads the `{name}` after `\begin`/`\end` at `pos`, returning the trimmed
+/// name and the offset after the group.
+fn environment_name(tex: &str, pos: usize) -> Option<(&str, usize)> {
+    let start = pos + (tex[pos..].len() - tex[pos..].trim_start().len());
+    let rest = tex[start..].strip_prefix('{')?;
+    let close = rest.find('}')?;
+    let name = rest[..close].trim();
+    (!name.is_empty()).then_some((name, start + 1 + close + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn macros(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn macros_nest_and_take_token_arguments() {
+        let table = macros(&[
+            ("RR", r"\mathbb{R}"),
+            ("vecspace", r"\RR^#1"),
+            ("pair", r"(#1, #2)"),
+        ]);
+        let expand = |tex| expand_macros(tex, &table).unwrap().into_owned();
+        assert_eq!(expand(r"\vecspace n"), r"\mathbb{R}^n");
+        assert_eq!(expand(r"\pair{\alpha}\beta"), r"(\alpha, \beta)");
+        // A missing argument leaves the use as written.
+        assert_eq!(expand(r"\pair{a}"), r"\pair{a}");
+        // Escaped backslashes are not macro uses.
+        assert_eq!(expand(r"a \\RR"), r"a \\RR");
+    }
+
+    #[test]
+    fn recursive_macros_report_an_overrun_instead_of_expanding_partly() {
+        let table = macros(&[
+            ("loop", r"\loop"),
+            ("grow", r"\grow a"),
+            ("double", r"\double\double"),
+            ("wide", r"\wide{#1#1}"),
+        ]);
+        for tex in [r"\loop", r"\grow", r"\double", r"\wide{x}"] {
+            let issue = expand_macros(tex, &table).unwrap_err();
+            assert_eq!(issue.offset, 0);
+            assert!(
+                issue.message.starts_with("macro expansion exceeded"),
+                "{tex}"
+            );
+        }
+        let issue = expand_macros(r"\loop", &table).unwrap_err();
+        assert_eq!(
+            issue.message,
+            "macro expansion exceeded 1000 expansions (recursive macro?)"
+        );
+    }
+
+    #[test]
+    fn validation_reports_each_problem_at_its_token() {
+        assert!(validate_tex(r"\left\{ x \right\} % }").is_empty());
+        let issues = validate_tex("}\\begin{tikzcd}\\end{tikzcd}\\end{cases}\\begin{aligned}");
+        let found: Vec<_> = issues
+            .iter()
+            .map(|i| (i.offset, i.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (0, "unbalanced `}`"),
+                (1, "unknown environment `tikzcd`"),
+                (27, r"`\end{cases}` without `\begin`"),
+                (38, r"unclosed `\begin{aligned}`"),
+            ]
+        );
+        assert_eq!(
+            validate_tex(r"\begin x")[0].message,
+            r"`\begin` without an environment name"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Wiki-link parsing and resolution.
+//!
+//! Content migrated from Obsidian and wikis links pages by name:
+//! `[[Getting Started]]`, `[[Setup#Install]]`, `[[Setup#Install|install it]]`.
+//! Page names resolve through a lookup table, falling back to a route built
+//! from the slug of the name.
+
+use super::links::TrailingSlash;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use xmdx_core::slug::slugify;
+
+/// Configuration for resolving wiki-link page names to hrefs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WikiLinkOptions {
+    /// Page names mapped to hrefs. Names match by slug, so `Getting Started`
+    /// and `getting-started` refer to the same page.
+    #[serde(default)]
+    pub pages: BTreeMap<String, String>,
+    /// Whether names missing from `pages` resolve to `base` + the slug of
+    /// the name. When disabled, such links are unresolved.
+    #[serde(default = "default_slug_fallback")]
+    pub slug_fallback: bool,
+    /// Base path for slug-resolved routes (e.g., `/docs`).
+    #[serde(default = "default_base")]
+    pub base: String,
+    /// Trailing slash policy for slug-resolved routes.
+    #[serde(default)]
+    pub trailing_slash: TrailingSlash,
+}
+
+fn default_slug_fallback() -> bool {
+    true
+}
+
+fn default_base() -> String {
+    "/".to_string()
+}
+
+impl Default for WikiLinkOptions {
+    fn default() -> Self {
+        Self {
+            pages: BTreeMap::new(),
+            slug_fallback: default_slug_fallback(),
+            base: default_base(),
+            trailing_slash: TrailingSlash::default(),
+        }
+    }
+}
+
+impl WikiLinkOptions {
+    /// Builds a resolver with the page table indexed by slug.
+    ///
+    /// Build one per document and reuse it; [`resolve`](Self::resolve)
+    /// rebuilds the index on every call.
+    pub fn resolver(&self) -> WikiLinkResolver<'_> {
+        let mut by_slug = HashMap::with_capacity(self.pages.len());
+        for (name, href) in &self.pages {
+            // Pages are ordered by name; the first page with a slug wins.
+            by_slug.entry(name_slug(name)).or_insert(href.as_str());
+        }
+        WikiLinkResolver {
+            options: self,
+            by_slug,
+        }
+    }
+
+    /// Resolves a page name and optional heading to an href.
+    ///
+    /// An empty page links to a heading of the current page. Returns `None`
+    /// when the page is not in [`pages`](Self::pages) and slug fallback is off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xmdx_astro::transform::wikilinks::WikiLinkOptions;
+    ///
+    /// let mut options = WikiLinkOptions { base: "/docs".into(), ..Default::default() };
+    /// options.pages.insert("Setup".into(), "/docs/guide/setup/".into());
+    /// assert_eq!(options.resolve("setup", Some("Install")).as_deref(), Some("/docs/guide/setup/#install"));
+    /// assert_eq!(options.resolve("Getting Started", None).as_deref(), Some("/docs/getting-started"));
+    /// assert_eq!(options.resolve("", Some("Usage")).as_deref(), Some("#usage"));
+    /// ```
+    pub fn resolve(&self, page: &str, heading: Option<&str>) -> Option<String> {
+        self.resolver().resolve(page, heading)
+    }
+}
+
+/// [`WikiLinkOptions`] with the page table indexed by slug, for resolving
+/// every wiki link of a document without rescanning the table.
+#[derive(Debug, Clone)]
+pub struct WikiLinkResolver<'a> {
+    options: &'a WikiLinkOptions,
+    by_slug: HashMap<String, &'a str>,
+}
+
+impl WikiLinkResolver<'_> {
+    /// Resolves a page name and optional heading to an href; see
+    /// [`WikiLinkOptions::resolve`].
+    pub fn resolve(&self, page: &str, heading: Option<&str>) -> Option<String> {
+        let options = self.options;
+        let mut href = if page.is_empty() {
+            String::new()
+        } else {
+            let slug = name_slug(page);
+            let listed = options
+                .pages
+                .get(page)
+                .map(String::as_str)
+                .or_else(|| self.by_slug.get(&slug).copied());
+            match listed {
+                Some(href) => href.to_string(),
+                None if options.slug_fallback => {
+                    let mut route = format!("{}/{}", options.base.trim_end_matches('/'), slug);
+                    if options.trailing_slash == TrailingSlash::Always {
+                        route.push('/');
+                    }
+                    route
+                }
+                None => return None,
+            }
+        };
+        if let Some(heading) = heading.filter(|h| !h.is_empty()) {
+            href.push('#');
+            href.push_str(&name_slug(heading));
+        }
+        Some(href)
+    }
+}
+
+/// A `[[...]]` wiki link found in text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WikiLink<'a> {
+    /// Byte offset of the opening `[[`.
+    pub start: usize,
+    /// Byte offset just past the closing `]]`.
+    pub end: usize,
+    /// Page name (empty for links to a heading of the current page).
+    pub page: &'a str,
+    /// Heading after `#`, if any.
+    pub heading: Option<&'a str>,
+    /// Label after `|`, if any.
+    pub label: Option<&'a str>,
+}
+
+impl WikiLink<'_> {
+    /// Text to display: the label, else `Page > Heading`, the heading or the page.
+    pub fn display_text(&self) -> String {
+        match (self.label, self.heading) {
+            (Some(label), _) => label.to_string(),
+            (None, Some(heading)) if self.page.is_empty() => heading.to_string(),
+            (None, Some(heading)) => format!("{} > {}", self.page, heading),
+            (None, None) => self.page.to_string(),
+        }
+    }
+}
+
+/// Finds the wiki links in a run of text.
+///
+/// Embeds (`![[...]]`), empty links and footnote-like targets (`[[^1]]`)
+/// are skipped, as are links spanning lines.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_astro::transform::wikilinks::find_wikilinks;
+///
+/// let links = find_wikilinks("See [[Setup#Install|install]] and [[FAQ]].");
+/// assert_eq!(links.len(), 2);
+/// assert_eq!(links[0].page, "Setup");
+/// assert_eq!(links[0].heading, Some("Install"));
+/// assert_eq!(links[0].label, Some("install"));
+/// assert_eq!(links[1].display_text(), "FAQ");
+/// ```
+pub fn find_wikilinks(text: &str) -> Vec<WikiLink<'_>> {
+    let mut links = Vec::new();
+    let mut cursor = 0;
+    while let Some(found) = text[cursor..].find("[[") {
+        let start = cursor + found;
+        let inner_start = start + 2;
+        let Some(close) = text[inner_start..].find("]]") else {
+            break;
+        };
+        let inner = &text[inner_start..inner_start + close];
+        let end = inner_start + close + 2;
+        let embed = text[..start].ends_with('!');
+        if embed
+            || inner.trim().is_empty()
+            || inner.starts_with('^')
+            || inner.contains(['\n', '[', ']'])
+        {
+            cursor = inner_start;
+            continue;
+        }
+        let (target, label) = match inner.split_once('|') {
+            Some((target, label)) => (target, Some(label.trim())),
+            None => (inner, None),
+        };
+        let (page, heading) = match target.split_once('#') {
+            Some((page, heading)) => (page.trim(), Some(heading.trim())),
+            None => (target.trim(), None),
+        };
+        links.push(WikiLink {
+            start,
+            end,
+            page,
+            heading,
+            label: label.filter(|l| !l.is_empty()),
+        });
+        cursor = end;
+    }
+    links
+}
+
+fn name_slug(name: &str) -> String {
+    slugify(name.trim(), &mut HashMap::new())
+}
@@ -29,7 +29,9 @@ pub use parse::{
     AstTransform, ParseOptions, ParserPipeline, TextTransform, parse_mdast,
     parse_mdast_with_options,
 };
-pub use slug::{Slugger, extract_custom_id, slugify};
+pub use slug::{
+    HeadingAttributes, Slugger, extract_custom_id, extract_heading_attributes, slugify,
+};
 
 pub use code_fence::{FencePhase, FenceState, LineParseOutcome, advance_fence_state};
 pub use directives::{
@@ -1564,12 +1564,9 @@ fn extract_text_from_jsx_array(arr: &str) -> Option<String> {
 fn extract_text_from_single_jsx_call(call: &str) -> Option<String> {
     let trimmed = call.trim();
     // Must start with _jsx( or _jsxs(
-    let after_prefix = if let Some(rest) = trimmed.strip_prefix("_jsxs(") {
-        rest
-    } else if let Some(rest) = trimmed.strip_prefix("_jsx(") {
-        rest
-    } else {
-        return None;
+    let after_prefix = match trimmed.strip_prefix("_jsxs(") {
+        Some(rest) => rest,
+        None => trimmed.strip_prefix("_jsx(")?,
     };
 
     // Skip past the first argument (tag name) by finding the comma at depth 0
@@ -43,6 +43,120 @@ pub fn extract_custom_id(text: &str) -> (&str, Option<&str>) {
     (text, None)
 }
 
+/// Attributes from a trailing `{#id .class key=value}` block on a heading.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeadingAttributes<'a> {
+    /// The `#id`, if given.
+    pub id: Option<&'a str>,
+    /// `.class` names, in order.
+    pub classes: Vec<&'a str>,
+    /// `key=value` pairs, in order, with quotes removed.
+    pub attributes: Vec<(&'a str, &'a str)>,
+}
+
+/// Extracts a trailing attribute block from heading text.
+///
+/// Generalizes [`extract_custom_id`]: the block may hold an `#id`,
+/// `.class` names and `key=value` pairs (values may be quoted). Any other
+/// token means the braces are heading text, so `Using {props}` is kept,
+/// but a heading ending in `{key=value}` loses that text to attributes.
+///
+/// The reserved keys are merged rather than kept as attributes: `id=x`
+/// acts as `#x` and `class="a b"` as `.a .b`, so the heading never gets
+/// two `id` or `class` attributes.
+///
+/// # Examples
+///
+/// ```
+/// use xmdx_core::slug::extract_heading_attributes;
+///
+/// let (text, attrs) = extract_heading_attributes(r#"Setup {#setup .no-toc data-level="2"}"#);
+/// let attrs = attrs.unwrap();
+/// assert_eq!(text, "Setup");
+/// assert_eq!(attrs.id, Some("setup"));
+/// assert_eq!(attrs.classes, vec!["no-toc"]);
+/// assert_eq!(attrs.attributes, vec![("data-level", "2")]);
+///
+/// let (_, attrs) = extract_heading_attributes(r#"Setup {id=install class="a b" .c}"#);
+/// let attrs = attrs.unwrap();
+/// assert_eq!(attrs.id, Some("install"));
+/// assert_eq!(attrs.classes, vec!["a", "b", "c"]);
+/// assert!(attrs.attributes.is_empty());
+///
+/// assert_eq!(extract_heading_attributes("Using {props}"), ("Using {props}", None));
+/// ```
+pub fn extract_heading_attributes(text: &str) -> (&str, Option<HeadingAttributes<'_>>) {
+    let trimmed = text.trim_end();
+    let Some(inner) = trimmed.strip_suffix('}') else {
+        return (text, None);
+    };
+    let Some(open) = inner.rfind('{') else {
+        return (text, None);
+    };
+    match parse_attribute_block(&inner[open + 1..]) {
+        Some(attrs) => (trimmed[..open].trim_end(), Some(attrs)),
+        None => (text, None),
+    }
+}
+
+/// Parses the inside of an attribute block; `None` if any token is invalid.
+fn parse_attribute_block(block: &str) -> Option<HeadingAttributes<'_>> {
+    let is_name = |s: &str| {
+        !s.is_empty()
+            && s.bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+    };
+    let mut attrs = HeadingAttributes::default();
+    let mut rest = block.trim_start();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let token_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        if let Some(id) = rest[..token_end].strip_prefix('#') {
+            if !is_name(id) {
+                return None;
+            }
+            attrs.id = Some(id);
+            rest = rest[token_end..].trim_start();
+            continue;
+        }
+        if let Some(class) = rest[..token_end].strip_prefix('.') {
+            if !is_name(class) {
+                return None;
+            }
+            attrs.classes.push(class);
+            rest = rest[token_end..].trim_start();
+            continue;
+        }
+        let (key, after) = rest.split_once('=')?;
+        if !is_name(key) || key.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let (value, next) = match after.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let close = after[1..].find(quote)?;
+                (&after[1..1 + close], &after[close + 2..])
+            }
+            _ => {
+                let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        if !next.is_empty() && !next.starts_with(char::is_whitespace) {
+            return None;
+        }
+        match key {
+            "id" if is_name(value) => attrs.id = Some(value),
+            "id" => return None,
+            "class" => attrs.classes.extend(value.split_whitespace()),
+            _ => attrs.attributes.push((key, value)),
+        }
+        rest = next.trim_start();
+    }
+    Some(attrs)
+}
+
 /// Github-slugger compatible slug generator.
 #[derive(Default)]
 pub struct Slugger {
@@ -268,6 +382,33 @@ mod tests {
         assert_eq!(id, Some("common-data-type-validators"));
     }
 
+    #[test]
+    fn extract_heading_attributes_rejects_plain_braces() {
+        assert_eq!(
+            extract_heading_attributes("Title {#bad id}"),
+            ("Title {#bad id}", None)
+        );
+        assert_eq!(extract_heading_attributes("Title {}"), ("Title {}", None));
+        assert_eq!(
+            extract_heading_attributes("Title {a=\"open}"),
+            ("Title {a=\"open}", None)
+        );
+        let (text, attrs) = extract_heading_attributes("Title {.a .b toc='no way'}");
+        let attrs = attrs.unwrap();
+        assert_eq!(text, "Title");
+        assert_eq!(attrs.id, None);
+        assert_eq!(attrs.classes, vec!["a", "b"]);
+        assert_eq!(attrs.attributes, vec![("toc", "no way")]);
+
+        // A reserved `id` must be a valid id, like `#id`.
+        assert_eq!(
+            extract_heading_attributes("Title {id=\"a b\"}"),
+            ("Title {id=\"a b\"}", None)
+        );
+        let (_, attrs) = extract_heading_attributes("Title {#first id=second}");
+        assert_eq!(attrs.unwrap().id, Some("second"));
+    }
+
     #[test]
     fn reserve_prevents_collision() {
         let mut slugger = Slugger::new();
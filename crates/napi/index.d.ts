@@ -63,6 +63,33 @@ export declare class XmdxCompiler {
   compileMdxBatch(inputs: Array<BatchInput>, options?: BatchOptions | undefined | null): MdxBatchProcessingResult
 }
 
+/** Kind of file referenced by a document. */
+export declare const enum AssetKind {
+  /** An image (`![alt](src)`, `<img>`, `<Image>`, `<Picture>`). */
+  Image = 'Image',
+  /** Audio or video (`<video>`, `<audio>`, `<source>`). */
+  Media = 'Media',
+  /** A linked file that is not a page (e.g., a PDF). */
+  Download = 'Download'
+}
+
+/** File referenced by a document. */
+export interface AssetReferenceEntry {
+  /** Source as written. */
+  src: string
+  /** What kind of file the source refers to. */
+  kind: AssetKind
+  /**
+   * Path of the embedded or included document containing the reference,
+   * if it was not written in this document.
+   */
+  via?: string
+  /** 1-indexed source line (0 when unknown or nested). */
+  line: number
+  /** 1-indexed source column (0 when unknown or nested). */
+  column: number
+}
+
 /** Structured batch error with a machine-readable code and human-readable message. */
 export interface BatchError {
   /** Error category for programmatic handling (e.g., "PARSE_ERROR", "RENDER_ERROR"). */
@@ -87,6 +114,21 @@ export interface BatchOptions {
   maxThreads?: number
   /** Whether to continue processing after an error. Defaults to true. */
   continueOnError?: boolean
+  /**
+   * Whether to check internal links and `#fragment`s against the other
+   * files of the batch. Broken links and missing anchors are reported as
+   * `broken_link` / `missing_anchor` warnings. Defaults to false.
+   */
+  validateLinks?: boolean
+  /** Whether to return the batch's link graph (`linkGraph`). Defaults to false. */
+  linkGraph?: boolean
+  /**
+   * Whether `compileBatchToModule` adds an `export const backlinks` array
+   * listing the links from other files of the batch. Defaults to false.
+   * Batch-only: single-file compiles never add it. Documents that declare
+   * `backlinks` themselves keep theirs and get an `export_collision` warning.
+   */
+  backlinksExport?: boolean
   /**
    * Compiler configuration for standalone batch functions.
    * Ignored by class methods which use `self.config` instead.
@@ -100,6 +142,8 @@ export interface BatchProcessingResult {
   results: Array<BatchResult>
   /** Processing statistics. */
   stats: BatchStats
+  /** Link graph keyed by `BatchInput.id` (when `linkGraph` is set). */
+  linkGraph?: Record<string, LinkGraphNode>
 }
 
 /** Result for a single file in a batch. */
@@ -140,6 +184,28 @@ export interface BlockOptions {
   enableMath?: boolean
 }
 
+/** Code fence meta string parsed into common fields. */
+export interface CodeMeta {
+  /** The meta string as written. */
+  raw: string
+  /** Value of `title="..."`. */
+  title?: string
+  /** Lines from `{1,3-5}` groups and `mark={...}`. */
+  highlight: Array<LineRange>
+  /** Lines from `ins={...}`. */
+  ins: Array<LineRange>
+  /** Lines from `del={...}`. */
+  del: Array<LineRange>
+  /** Whether the `showLineNumbers` flag is present. */
+  showLineNumbers: boolean
+  /** Remaining bare tokens, in order. */
+  flags: Array<string>
+  /** Remaining `key=value` pairs. */
+  attributes: Record<string, string>
+  /** Line range entries that were not applied, as written (e.g., `3-1`, `{1`). */
+  invalidRanges: Array<string>
+}
+
 /**
  * Compiles multiple files in parallel and returns IR results.
  *
@@ -171,6 +237,8 @@ export interface CompileIrResult {
   frontmatterJson: string
   /** Heading metadata collected during parsing. */
   headings: Array<HeadingEntry>
+  /** Headings nested into a table of contents. */
+  toc: Array<TocEntry>
   /** Absolute or workspace-relative file path of the source. */
   filePath: string
   /** Route URL (if provided) associated with the file. */
@@ -183,6 +251,17 @@ export interface CompileIrResult {
   diagnostics: Diagnostics
   /** Whether user provided their own `export default` statement. */
   hasUserDefaultExport: boolean
+  /** Relative image paths hoisted to imports (`__xmdx_img0`, ...), in binding order. */
+  assets: Array<string>
+  /** Outbound links, in document order. */
+  links: Array<LinkEntry>
+  /** Images, media and downloads referenced by the document, in document order. */
+  assetReferences: Array<AssetReferenceEntry>
+  /**
+   * Files rendered or imported into this document (`kind: "embed"`,
+   * `"include"` or `"snippet"`), for watching.
+   */
+  dependencies: Array<ImportedModule>
 }
 
 /**
@@ -199,11 +278,28 @@ export interface CompilerConfig {
   gfm?: boolean
   /** Enables smart punctuation substitutions (placeholder flag). */
   smartypants?: boolean
-  /** Enables syntax highlighting (placeholder flag). */
+  /**
+   * Highlights code fences in Rust for languages with a bundled grammar,
+   * instead of leaving them to Shiki or ExpressiveCode. Requires a build
+   * with the `highlight` feature; otherwise a no-op.
+   */
   syntaxHighlighting?: boolean
+  /**
+   * Bundled theme for Rust-side highlighting (e.g., `base16-ocean.dark`);
+   * an unknown name is an error. Defaults to `InspiredGitHub`.
+   */
+  highlightTheme?: string
+  /**
+   * `classes` (styled by `highlightStylesheet()`) or `inline` styles.
+   * Defaults to `classes`.
+   */
+  highlightStyle?: string
   /** Overrides the module used for JSX runtime helpers. */
   jsxImportSource?: string
-  /** Component registry configuration (JSON). */
+  /**
+   * Component registry configuration (JSON).
+   * Set `extends: "starlight"` to send only a delta on top of the built-in registry.
+   */
   registry?: JsonValue
   /**
    * Whether to rewrite JSX code blocks to HTML format for ExpressiveCode.
@@ -214,6 +310,17 @@ export interface CompilerConfig {
   enableHeadingAutolinks?: boolean
   /** Whether to enable math syntax ($inline$ and $$block$$). */
   math?: boolean
+  /**
+   * `component` (`<MathBlock>`/`<MathInline>`) or `mathml`, which converts
+   * common TeX to MathML in Rust and falls back to the components for
+   * anything else. Defaults to `component`.
+   */
+  mathOutput?: string
+  /**
+   * Macros expanded in math before it is emitted, keyed by command with
+   * or without the backslash (e.g., `{ "\\R": "\\mathbb{R}" }`).
+   */
+  mathMacros?: Record<string, string>
   /**
    * Custom directive names to recognize beyond the built-in set.
    * Each entry is a directive name (e.g., "custom-box").
@@ -224,6 +331,45 @@ export interface CompilerConfig {
    * Maps directive name to component name (e.g., {"note": "Callout", "custom-box": "Box"}).
    */
   directiveComponentMap?: any
+  /**
+   * Whether to hoist relative image sources to ESM imports so Astro can
+   * optimize them. The imported paths are reported in `assets`.
+   */
+  importLocalImages?: boolean
+  /**
+   * Whether to read local image headers and add `width`/`height` to
+   * Markdown images. Missing files are reported as diagnostics.
+   */
+  probeImageDimensions?: boolean
+  /** Rewrites links to `.md`/`.mdx` files into site routes. */
+  linkRewrite?: LinkRewriteConfig
+  /** Attributes and indicators for links to other hosts. */
+  externalLinks?: ExternalLinkConfig
+  /** Renders `[[Page#Heading|label]]` wiki links. */
+  wikiLinks?: WikiLinkConfig
+  /**
+   * Directory that includes, embeds and code snippets may read files
+   * from; paths resolving outside it are refused (default: the working
+   * directory).
+   */
+  projectRoot?: string
+  /**
+   * Renders `![[Note]]` / `![[Note#Section]]` embeds in place. Notes
+   * resolve against the current batch, then files next to the document.
+   */
+  embeds?: boolean
+  /**
+   * Renders `::include{file="..." section="..."}` lines with the Markdown
+   * file read relative to the document.
+   */
+  includes?: boolean
+  /**
+   * Fills code fences whose meta names a local file
+   * (`file=./examples/client.ts#region lines=10-25`) from that file.
+   */
+  importCodeSnippets?: boolean
+  /** Table of contents depth range and exclusions, and the `::toc` directive. */
+  toc?: TocConfig
 }
 
 /** Result returned by the streaming compiler. */
@@ -236,12 +382,20 @@ export interface CompileResult {
   frontmatterJson: string
   /** Heading metadata collected during compilation. */
   headings: Array<HeadingEntry>
-  /** Dependencies referenced while compiling (layouts/imports). */
+  /** Headings nested into a table of contents, as exported by the module. */
+  toc: Array<TocEntry>
+  /** Dependencies referenced while compiling (layouts, embedded files). */
   imports: Array<ImportedModule>
   /** Parse diagnostics (warnings, not errors) */
   diagnostics: Diagnostics
   /** Whether user provided their own `export default` statement. */
   hasUserDefaultExport: boolean
+  /** Relative image paths hoisted to imports (`__xmdx_img0`, ...), in binding order. */
+  assets: Array<string>
+  /** Outbound links, in document order. */
+  links: Array<LinkEntry>
+  /** Images, media and downloads referenced by the document, in document order. */
+  assetReferences: Array<AssetReferenceEntry>
 }
 
 /** Helper factory exposed to JavaScript for ergonomic reuse. */
@@ -269,6 +423,20 @@ export interface ExportSpec {
   isDefault: boolean
 }
 
+/** Policy for links whose host is not one of the site's hosts. */
+export interface ExternalLinkConfig {
+  /** Hosts that belong to the site; links to any other host are external. */
+  siteHosts?: Array<string>
+  /** `rel` attribute for external links (default: `noopener noreferrer`). */
+  rel?: string
+  /** Whether external links open in a new tab (`target="_blank"`). */
+  targetBlank?: boolean
+  /** Visually hidden text appended to external links, e.g. "(opens in new tab)". */
+  newTabLabel?: string
+  /** Icon component appended to external links. */
+  iconComponent?: string
+}
+
 /**
  * Extracts headings from MDX/Markdown source.
  *
@@ -322,6 +490,13 @@ export interface HeadingEntry {
   text: string
 }
 
+/**
+ * Returns the CSS for `classes`-style Rust-side highlighting in a bundled
+ * theme, or `null` when the theme is unknown or the addon was built without
+ * the `highlight` feature.
+ */
+export declare function highlightStylesheet(theme: string): string | null
+
 /**
  * Converts HTML entities to JSX-safe expressions.
  *
@@ -357,6 +532,71 @@ export interface ImportSpec {
   kind: ImportKind
 }
 
+/** Inclusive, 1-indexed range of lines in a code block. */
+export interface LineRange {
+  /** First line of the range. */
+  start: number
+  /** Last line of the range. */
+  end: number
+}
+
+/** Outbound link found in a document. */
+export interface LinkEntry {
+  /** Link target as written. */
+  href: string
+  /** Route the link was rewritten to, when link rewriting applies. */
+  route?: string
+  /** Visible link text. */
+  text: string
+  /** Whether the link leaves the site (another host or a non-web scheme). */
+  external: boolean
+  /** Fragment identifier without the `#`, if any. */
+  fragment?: string
+  /**
+   * Path of the embedded or included document containing the link, if
+   * it was not written in this document.
+   */
+  via?: string
+  /** 1-indexed source line (0 when unknown or nested). */
+  line: number
+  /** 1-indexed source column (0 when unknown or nested). */
+  column: number
+}
+
+/** A link between two files of a batch. */
+export interface LinkGraphEdge {
+  /** `BatchInput.id` of the file at the other end of the link. */
+  id: string
+  /** Route of that file, when it maps to one. */
+  route?: string
+  /** Visible link text. */
+  text: string
+  /** Fragment the link points to, if any. */
+  fragment?: string
+  /** Line of the link in the linking file. */
+  line: number
+}
+
+/** Links into and out of one file of a batch. */
+export interface LinkGraphNode {
+  /** Links from this file to other files, in document order. */
+  outgoing: Array<LinkGraphEdge>
+  /** Links from other files to this one, in batch order. */
+  incoming: Array<LinkGraphEdge>
+}
+
+/** Settings for rewriting `.md`/`.mdx` links to routes. */
+export interface LinkRewriteConfig {
+  /** Directory whose files map to routes (default: `src/content/docs`). */
+  contentRoot?: string
+  /** Base path prepended to every route (default: `/`). */
+  base?: string
+  /** Astro `trailingSlash` policy: `"always"`, `"never"` or `"ignore"` (default). */
+  trailingSlash?: string
+  /** Whether to drop the file extension; otherwise routes end in `.html` (default: true). */
+  stripExtension?: boolean
+}
+
 /** Result of MDX batch processing containing all results and statistics. */
 export interface MdxBatchProcessingResult {
   /** Individual results for each input file. */
@@ -395,6 +635,8 @@ export interface ModuleBatchProcessingResult {
   results: Array<ModuleBatchResult>
   /** Processing statistics. */
   stats: BatchStats
+  /** Link graph keyed by `BatchInput.id` (when `linkGraph` is set). */
+  linkGraph?: Record<string, LinkGraphNode>
 }
 
 /**
@@ -481,7 +723,7 @@ export interface ParseWarningEntry {
  * type RenderBlock =
  *   | { type: "html", content: string }
  *   | { type: "component", name: string, props: Record<string, string>, slotChildren: RenderBlock[] }
- *   | { type: "code", code: string, lang?: string, meta?: string }
+ *   | { type: "code", code: string, lang?: string, meta?: string, parsedMeta?: CodeMeta }
  * ```
  */
 export interface RenderBlock {
@@ -501,6 +743,8 @@ export interface RenderBlock {
   lang?: string
   /** Code meta string (for type="code") */
   meta?: string
+  /** Parsed code meta (for type="code" with a meta string) */
+  parsedMeta?: CodeMeta
 }
 
 /**
@@ -535,3 +779,54 @@ export declare function rewriteTaskListItems(jsxCode: string): string
  * The IDs should be extracted first via `extractHeadings`.
  */
 export declare function stripCustomIds(source: string): string
+
+/** Settings for the table of contents built from the headings. */
+export interface TocConfig {
+  /** Shallowest heading level included (default: 2). */
+  minDepth?: number
+  /** Deepest heading level included (default: 3). */
+  maxDepth?: number
+  /**
+   * Headings with this class (`## Changelog {.no-toc}`) are left out
+   * (default: `no-toc`).
+   */
+  excludeClass?: string
+  /** Headings with this attribute (`## Changelog {data-toc=skip}`) are left out. */
+  excludeAttribute?: string
+  /** Whether a `::toc` line renders the table of contents in place (default: false). */
+  directive?: boolean
+  /**
+   * Registry component rendered for `::toc` with the tree as its `toc`
+   * prop; otherwise `::toc` renders a nested `<ul>`.
+   */
+  component?: string
+}
+
+/** Table of contents entry with the headings nested under it. */
+export interface TocEntry {
+  /** Heading depth (1-6). */
+  depth: number
+  /** Slugified identifier. */
+  slug: string
+  /** Visible heading text. */
+  text: string
+  /** Deeper headings up to the next one of the same or a higher level. */
+  children: Array<TocEntry>
+}
+
+/** Settings for resolving `[[Page]]` wiki links. */
+export interface WikiLinkConfig {
+  /** Page names mapped to hrefs. Names match by slug. */
+  pages?: Record<string, string>
+  /**
+   * Whether batch compiles add every file of the batch to `pages`, by
+   * file name and frontmatter `title` (default: false).
+   */
+  fromBatch?: boolean
+  /** Whether unknown names resolve to `base` + the slug of the name (default: true). */
+  slugFallback?: boolean
+  /** Base path for slug-resolved routes (default: `/`). */
+  base?: string
+  /** Astro `trailingSlash` policy for slug-resolved routes (default: `"ignore"`). */
+  trailingSlash?: string
+}
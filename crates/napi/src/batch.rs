@@ -2,6 +2,8 @@
 
 use crate::types::{CompileIrResult, MdxCompileResult};
 use napi_derive::napi;
+use serde::Serialize;
+use std::collections::HashMap;
 
 /// Structured batch error with a machine-readable code and human-readable message.
 #[napi(object)]
@@ -59,11 +61,48 @@ pub struct BatchOptions {
     pub max_threads: Option<u32>,
     /// Whether to continue processing after an error. Defaults to true.
     pub continue_on_error: Option<bool>,
+    /// Whether to check internal links and `#fragment`s against the other
+    /// files of the batch. Broken links and missing anchors are reported as
+    /// `broken_link` / `missing_anchor` warnings. Defaults to false.
+    pub validate_links: Option<bool>,
+    /// Whether to return the batch's link graph (`linkGraph`). Defaults to false.
+    pub link_graph: Option<bool>,
+    /// Whether `compileBatchToModule` adds an `export const backlinks` array
+    /// listing the links from other files of the batch. Defaults to false.
+    /// Batch-only: single-file compiles never add it. Documents that declare
+    /// `backlinks` themselves keep theirs and get an `export_collision` warning.
+    pub backlinks_export: Option<bool>,
     /// Compiler configuration for standalone batch functions.
     /// Ignored by class methods which use `self.config` instead.
     pub config: Option<crate::types::CompilerConfig>,
 }
 
+/// A link between two files of a batch.
+#[napi(object)]
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkGraphEdge {
+    /// `BatchInput.id` of the file at the other end of the link.
+    pub id: String,
+    /// Route of that file, when it maps to one.
+    pub route: Option<String>,
+    /// Visible link text.
+    pub text: String,
+    /// Fragment the link points to, if any.
+    pub fragment: Option<String>,
+    /// Line of the link in the linking file.
+    pub line: u32,
+}
+
+/// Links into and out of one file of a batch.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct LinkGraphNode {
+    /// Links from this file to other files, in document order.
+    pub outgoing: Vec<LinkGraphEdge>,
+    /// Links from other files to this one, in batch order.
+    pub incoming: Vec<LinkGraphEdge>,
+}
+
 /// Result of batch processing containing all results and statistics.
 #[napi(object)]
 #[derive(Debug, Clone)]
@@ -72,6 +111,8 @@ pub struct BatchProcessingResult {
     pub results: Vec<BatchResult>,
     /// Processing statistics.
     pub stats: BatchStats,
+    /// Link graph keyed by `BatchInput.id` (when `linkGraph` is set).
+    pub link_graph: Option<HashMap<String, LinkGraphNode>>,
 }
 
 /// Result for a single MDX file in a batch.
@@ -117,4 +158,6 @@ pub struct ModuleBatchProcessingResult {
     pub results: Vec<ModuleBatchResult>,
     /// Processing statistics.
     pub stats: BatchStats,
+    /// Link graph keyed by `BatchInput.id` (when `linkGraph` is set).
+    pub link_graph: Option<HashMap<String, LinkGraphNode>>,
 }
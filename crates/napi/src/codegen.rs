@@ -10,6 +10,7 @@ pub(crate) fn generate_module_code_from_ir(
     hoisted_imports: &[String],
     hoisted_exports: &[String],
     headings_json: &str,
+    toc_json: &str,
     jsx_import_source: Option<&str>,
 ) -> Result<String> {
     let options = AstroModuleOptions {
@@ -18,11 +19,15 @@ pub(crate) fn generate_module_code_from_ir(
         hoisted_exports,
         frontmatter_json: &ir.frontmatter_json,
         headings_json,
+        toc_json: Some(toc_json),
         filepath: &ir.file_path,
         url: ir.url.as_deref(),
         layout_import: ir.layout_import.as_deref(),
         has_user_default_export: ir.has_user_default_export,
         jsx_import_source,
+        // Component imports are resolved in compile_ir, where source positions are known.
+        registry: None,
+        components: &[],
     };
 
     Ok(generate_astro_module(&options))
@@ -2,17 +2,37 @@
 
 use crate::batch::{
     BatchError, BatchInput, BatchOptions, BatchProcessingResult, BatchResult, BatchStats,
-    MdxBatchProcessingResult, MdxBatchResult, ModuleBatchProcessingResult, ModuleBatchResult,
+    LinkGraphEdge, MdxBatchProcessingResult, MdxBatchResult, ModuleBatchProcessingResult,
+    ModuleBatchResult,
 };
+use crate::links::{LinkIndex, LinkedDocument};
 use crate::types::*;
 use napi_derive::napi;
 use rayon::prelude::*;
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::time::Instant;
-use xmdx_astro::codegen::{DirectiveMappingResult, blocks_to_jsx_string};
-use xmdx_astro::{MdastOptions, code_fence, to_blocks};
-use xmdx_core::{MarkflowError, MdxCompileOptions, compile_mdx};
+use xmdx_astro::codegen::{DirectiveMappingResult, blocks_to_jsx_string_with_registry};
+use xmdx_astro::diagnostics::{WarningContext, body_line_map, compile_warnings};
+use xmdx_astro::registry::defaults::default_starlight_registry;
+use xmdx_astro::renderer::mdast::images::ImageSizeCache;
+use xmdx_astro::renderer::mdast::toc::TocOptions;
+use xmdx_astro::renderer::mdast::transclusion::{EmbedDocument, EmbedDocuments};
+use xmdx_astro::transform::highlight::{HighlightOptions, HighlightStyle};
+use xmdx_astro::transform::imports::{
+    add_import_alias_normalizations, alias_renames, asset_import_statements, declaration_line,
+    declared_bindings, exported_names, plan_generated_imports, resolve_component_imports,
+};
+use xmdx_astro::transform::links::{
+    ExternalLinkOptions, LinkRewriteOptions, TrailingSlash, rewrite_markdown_link,
+};
+use xmdx_astro::transform::mathml::MathOutput;
+use xmdx_astro::transform::wikilinks::WikiLinkOptions;
+use xmdx_astro::{
+    MdastOptions, RegistryConfig, SnippetIssue, TransclusionKind, code_fence, to_blocks,
+};
+use xmdx_core::{MarkflowError, MdxCompileOptions, compile_mdx, extract_frontmatter};
 
 /// Default JSX import source for MDX compilation.
 /// mdxjs-rs appends `/jsx-runtime` to this value (per the JSX automatic
@@ -25,8 +45,25 @@ pub(crate) struct InternalCompilerConfig {
     pub(crate) jsx_import_source: String,
     pub(crate) enable_heading_autolinks: bool,
     pub(crate) enable_math: bool,
+    pub(crate) math_output: Option<String>,
+    pub(crate) math_macros: Option<HashMap<String, String>>,
     pub(crate) rewrite_code_blocks: bool,
+    pub(crate) import_local_images: bool,
+    pub(crate) probe_image_dimensions: bool,
+    pub(crate) link_rewrite: Option<LinkRewriteConfig>,
+    pub(crate) external_links: Option<ExternalLinkConfig>,
+    pub(crate) wiki_links: Option<WikiLinkConfig>,
+    pub(crate) project_root: Option<String>,
+    pub(crate) enable_embeds: bool,
+    pub(crate) enable_includes: bool,
+    pub(crate) import_code_snippets: bool,
+    pub(crate) toc: Option<TocConfig>,
+    pub(crate) syntax_highlighting: bool,
+    pub(crate) highlight_theme: Option<String>,
+    pub(crate) highlight_style: Option<String>,
     pub(crate) directive_config: xmdx_core::DirectiveConfig,
+    /// Raw registry JSON; resolved per compile so errors surface to the caller.
+    pub(crate) registry: Option<serde_json::Value>,
 }
 
 impl InternalCompilerConfig {
@@ -38,6 +75,12 @@ impl InternalCompilerConfig {
         let enable_heading_autolinks = cfg.enable_heading_autolinks.unwrap_or(false);
         let enable_math = cfg.math.unwrap_or(false);
         let rewrite_code_blocks = cfg.rewrite_code_blocks.unwrap_or(false);
+        let import_local_images = cfg.import_local_images.unwrap_or(false);
+        let probe_image_dimensions = cfg.probe_image_dimensions.unwrap_or(false);
+        let enable_embeds = cfg.embeds.unwrap_or(false);
+        let enable_includes = cfg.includes.unwrap_or(false);
+        let import_code_snippets = cfg.import_code_snippets.unwrap_or(false);
+        let syntax_highlighting = cfg.syntax_highlighting.unwrap_or(false);
 
         // Build directive config from custom names and component map
         let mut directive_config = xmdx_core::DirectiveConfig::default();
@@ -71,11 +114,148 @@ impl InternalCompilerConfig {
             jsx_import_source,
             enable_heading_autolinks,
             enable_math,
+            math_output: cfg.math_output,
+            math_macros: cfg.math_macros,
             rewrite_code_blocks,
+            import_local_images,
+            probe_image_dimensions,
+            link_rewrite: cfg.link_rewrite,
+            external_links: cfg.external_links,
+            wiki_links: cfg.wiki_links,
+            project_root: cfg.project_root,
+            enable_embeds,
+            enable_includes,
+            import_code_snippets,
+            toc: cfg.toc,
+            syntax_highlighting,
+            highlight_theme: cfg.highlight_theme,
+            highlight_style: cfg.highlight_style,
             directive_config,
+            registry: cfg.registry,
+        }
+    }
+
+    /// Builds the renderer's link rewrite options, rejecting an unknown
+    /// trailing slash policy.
+    pub(crate) fn resolve_link_rewrite(&self) -> napi::Result<Option<LinkRewriteOptions>> {
+        let Some(cfg) = &self.link_rewrite else {
+            return Ok(None);
+        };
+        let defaults = LinkRewriteOptions::default();
+        Ok(Some(LinkRewriteOptions {
+            content_root: cfg.content_root.clone().unwrap_or(defaults.content_root),
+            base: cfg.base.clone().unwrap_or(defaults.base),
+            trailing_slash: parse_trailing_slash(cfg.trailing_slash.as_deref())?,
+            strip_extension: cfg.strip_extension.unwrap_or(defaults.strip_extension),
+        }))
+    }
+
+    /// Builds the renderer's wiki link options, rejecting an unknown
+    /// trailing slash policy.
+    pub(crate) fn resolve_wiki_links(&self) -> napi::Result<Option<WikiLinkOptions>> {
+        let Some(cfg) = &self.wiki_links else {
+            return Ok(None);
+        };
+        let defaults = WikiLinkOptions::default();
+        Ok(Some(WikiLinkOptions {
+            pages: cfg.pages.clone().unwrap_or_default().into_iter().collect(),
+            slug_fallback: cfg.slug_fallback.unwrap_or(defaults.slug_fallback),
+            base: cfg.base.clone().unwrap_or(defaults.base),
+            trailing_slash: parse_trailing_slash(cfg.trailing_slash.as_deref())?,
+        }))
+    }
+
+    /// Builds the renderer's table of contents options, rejecting a depth
+    /// range outside 1-6 or with `minDepth` above `maxDepth`.
+    pub(crate) fn resolve_toc(&self) -> napi::Result<TocOptions> {
+        let defaults = TocOptions::default();
+        let Some(cfg) = &self.toc else {
+            return Ok(defaults);
+        };
+        let min_depth = cfg.min_depth.unwrap_or(defaults.min_depth);
+        let max_depth = cfg.max_depth.unwrap_or(defaults.max_depth);
+        if !(1..=6).contains(&min_depth) || !(min_depth..=6).contains(&max_depth) {
+            return Err(napi::Error::new(
+                napi::Status::InvalidArg,
+                format!(
+                    "Invalid toc depth range {min_depth}-{max_depth}: expected 1 <= minDepth <= maxDepth <= 6"
+                ),
+            ));
+        }
+        Ok(TocOptions {
+            min_depth,
+            max_depth,
+            exclude_class: cfg.exclude_class.clone().or(defaults.exclude_class),
+            exclude_attribute: cfg.exclude_attribute.clone(),
+            directive: cfg.directive.unwrap_or(defaults.directive),
+            component: cfg.component.clone(),
+        })
+    }
+
+    /// Builds the renderer's highlighting options when Rust-side highlighting
+    /// is enabled, rejecting an unknown style or theme.
+    pub(crate) fn resolve_syntax_highlighting(&self) -> napi::Result<Option<HighlightOptions>> {
+        if !self.syntax_highlighting {
+            return Ok(None);
+        }
+        let defaults = HighlightOptions::default();
+        let style = match self.highlight_style.as_deref() {
+            None => defaults.style,
+            Some(name) => HighlightStyle::from_name(name).ok_or_else(|| {
+                napi::Error::new(
+                    napi::Status::InvalidArg,
+                    format!("Invalid highlightStyle \"{name}\": expected classes or inline"),
+                )
+            })?,
+        };
+        let options = HighlightOptions {
+            theme: self.highlight_theme.clone().unwrap_or(defaults.theme),
+            style,
+        };
+        options.validate().map_err(|message| {
+            napi::Error::new(
+                napi::Status::InvalidArg,
+                format!("Invalid highlightTheme: {message}"),
+            )
+        })?;
+        Ok(Some(options))
+    }
+
+    /// Parses the configured math output, rejecting an unknown name.
+    pub(crate) fn resolve_math_output(&self) -> napi::Result<MathOutput> {
+        match self.math_output.as_deref() {
+            None => Ok(MathOutput::default()),
+            Some(name) => MathOutput::from_name(name).ok_or_else(|| {
+                napi::Error::new(
+                    napi::Status::InvalidArg,
+                    format!("Invalid mathOutput \"{name}\": expected component or mathml"),
+                )
+            }),
         }
     }
 
+    /// Builds the renderer's external link policy, if configured.
+    pub(crate) fn external_link_options(&self) -> Option<ExternalLinkOptions> {
+        let cfg = self.external_links.as_ref()?;
+        let defaults = ExternalLinkOptions::default();
+        Some(ExternalLinkOptions {
+            site_hosts: cfg.site_hosts.clone().unwrap_or(defaults.site_hosts),
+            rel: cfg.rel.clone().unwrap_or(defaults.rel),
+            target_blank: cfg.target_blank.unwrap_or(defaults.target_blank),
+            new_tab_label: cfg.new_tab_label.clone(),
+            icon_component: cfg.icon_component.clone(),
+        })
+    }
+
+    /// Resolves the configured registry JSON (including `extends`), if any.
+    pub(crate) fn resolve_registry(&self) -> napi::Result<Option<RegistryConfig>> {
+        self.registry
+            .clone()
+            .map(RegistryConfig::from_json)
+            .transpose()
+            .map_err(|e| napi::Error::new(napi::Status::InvalidArg, e.to_string()))
+    }
+
     /// Reconstruct a `CompilerConfig` that preserves all settings including
     /// directive configuration, for passing to `compile_ir`.
     pub(crate) fn to_compiler_config(&self) -> CompilerConfig {
@@ -100,9 +280,25 @@ impl InternalCompilerConfig {
             jsx_import_source: Some(self.jsx_import_source.clone()),
             enable_heading_autolinks: Some(self.enable_heading_autolinks),
             math: Some(self.enable_math),
+            math_output: self.math_output.clone(),
+            math_macros: self.math_macros.clone(),
             rewrite_code_blocks: Some(self.rewrite_code_blocks),
+            import_local_images: Some(self.import_local_images),
+            probe_image_dimensions: Some(self.probe_image_dimensions),
+            link_rewrite: self.link_rewrite.clone(),
+            external_links: self.external_links.clone(),
+            wiki_links: self.wiki_links.clone(),
+            project_root: self.project_root.clone(),
+            embeds: Some(self.enable_embeds),
+            includes: Some(self.enable_includes),
+            import_code_snippets: Some(self.import_code_snippets),
+            toc: self.toc.clone(),
+            syntax_highlighting: Some(self.syntax_highlighting),
+            highlight_theme: self.highlight_theme.clone(),
+            highlight_style: self.highlight_style.clone(),
             custom_directive_names,
             directive_component_map,
+            registry: self.registry.clone(),
             ..CompilerConfig::default()
         }
     }
@@ -176,8 +372,7 @@ impl XmdxCompiler {
         let opts = options.unwrap_or_default();
         let continue_on_error = opts.continue_on_error.unwrap_or(true);
 
-        // Use compiler's config, ignoring any config in batch options
-        let config = Some(self.config.to_compiler_config());
+        let image_sizes = ImageSizeCache::default();
 
         // Configure thread pool if max_threads is specified
         let pool = if let Some(max_threads) = opts.max_threads {
@@ -193,9 +388,29 @@ impl XmdxCompiler {
         let succeeded = AtomicU32::new(0);
         let failed = AtomicU32::new(0);
 
+        let filepaths: Vec<String> = inputs
+            .iter()
+            .map(|input| input.filepath.clone().unwrap_or_else(|| input.id.clone()))
+            .collect();
+        let validate_links = opts.validate_links.unwrap_or(false);
+        let want_link_graph = opts.link_graph.unwrap_or(false);
+
+        // Use compiler's config, ignoring any config in batch options
+        let mut config = self.config.to_compiler_config();
+        add_batch_wiki_pages(&self.config, &mut config, &inputs, &filepaths)?;
+        let config = Some(config);
+        let embed_documents = batch_embed_documents(&self.config, &inputs, &filepaths);
+
         let process_input = |input: BatchInput| -> BatchResult {
             let filepath = input.filepath.clone().unwrap_or_else(|| input.id.clone());
-            match compile_ir(input.source, filepath, None, config.clone()) {
+            match compile_ir_cached(
+                input.source,
+                filepath,
+                None,
+                config.clone(),
+                &image_sizes,
+                &embed_documents,
+            ) {
                 Ok(result) => {
                     succeeded.fetch_add(1, Ordering::Relaxed);
                     BatchResult {
@@ -218,7 +433,7 @@ impl XmdxCompiler {
             }
         };
 
-        let results: Vec<BatchResult> = if continue_on_error {
+        let mut results: Vec<BatchResult> = if continue_on_error {
             // Process all files regardless of errors
             if let Some(pool) = pool {
                 pool.install(|| inputs.into_par_iter().map(process_input).collect())
@@ -243,6 +458,31 @@ impl XmdxCompiler {
             results
         };
 
+        let mut link_graph = None;
+        if validate_links || want_link_graph {
+            let documents: Vec<LinkedDocument> = results
+                .iter()
+                .zip(&filepaths)
+                .filter_map(|(batch, file_path)| {
+                    let ir = batch.result.as_ref()?;
+                    Some(LinkedDocument {
+                        id: &batch.id,
+                        file_path,
+                        links: &ir.links,
+                        headings: &ir.headings,
+                        markup: &ir.html,
+                    })
+                })
+                .collect();
+            let index = batch_link_index(&self.config, &documents)?;
+            let diagnostics = validate_links.then(|| index.diagnostics());
+            link_graph = want_link_graph.then(|| index.graph());
+            let compiled = results.iter_mut().filter_map(|batch| batch.result.as_mut());
+            for (ir, warnings) in compiled.zip(diagnostics.unwrap_or_default()) {
+                append_warnings(&mut ir.diagnostics, warnings);
+            }
+        }
+
         let elapsed = start.elapsed();
 
         Ok(BatchProcessingResult {
@@ -253,6 +493,7 @@ impl XmdxCompiler {
                 failed: failed.load(Ordering::Relaxed),
                 processing_time_ms: elapsed.as_secs_f64() * 1000.0,
             },
+            link_graph,
         })
     }
 
@@ -280,8 +521,7 @@ impl XmdxCompiler {
         let opts = options.unwrap_or_default();
         let continue_on_error = opts.continue_on_error.unwrap_or(true);
 
-        // Use compiler's config, ignoring any config in batch options
-        let config = Some(self.config.to_compiler_config());
+        let image_sizes = ImageSizeCache::default();
 
         // Configure thread pool if max_threads is specified
         let pool = if let Some(max_threads) = opts.max_threads {
@@ -304,10 +544,39 @@ impl XmdxCompiler {
             Some(jsx_src.as_str())
         };
 
-        let process_input = |input: BatchInput| -> ModuleBatchResult {
+        let filepaths: Vec<String> = inputs
+            .iter()
+            .map(|input| input.filepath.clone().unwrap_or_else(|| input.id.clone()))
+            .collect();
+        let validate_links = opts.validate_links.unwrap_or(false);
+        let want_link_graph = opts.link_graph.unwrap_or(false);
+        let backlinks_export = opts.backlinks_export.unwrap_or(false);
+
+        // Use compiler's config, ignoring any config in batch options
+        let mut config = self.config.to_compiler_config();
+        add_batch_wiki_pages(&self.config, &mut config, &inputs, &filepaths)?;
+        let config = Some(config);
+        let embed_documents = batch_embed_documents(&self.config, &inputs, &filepaths);
+
+        // Alongside each result: the line declaring a user `backlinks`, which
+        // the generated backlinks export would otherwise redeclare.
+        let process_input = |input: BatchInput| -> (ModuleBatchResult, Option<u32>) {
             let filepath = input.filepath.clone().unwrap_or_else(|| input.id.clone());
-            match compile_ir(input.source, filepath, None, config.clone()) {
+            let backlinks_line =
+                backlinks_export.then(|| declaration_line(&input.source, "backlinks").unwrap_or(0));
+            let mut backlinks_collision = None;
+            let result = match compile_ir_cached(
+                input.source,
+                filepath,
+                None,
+                config.clone(),
+                &image_sizes,
+                &embed_documents,
+            ) {
                 Ok(ir) => {
+                    if ir_declares(&ir, "backlinks") {
+                        backlinks_collision = backlinks_line;
+                    }
                     // Convert IR to complete module
                     match compile_document_from_ir(ir, jsx_import_source) {
                         Ok(result) => {
@@ -342,33 +611,86 @@ impl XmdxCompiler {
                         }),
                     }
                 }
-            }
+            };
+            (result, backlinks_collision)
         };
 
-        let results: Vec<ModuleBatchResult> = if continue_on_error {
-            // Process all files regardless of errors
-            if let Some(pool) = pool {
-                pool.install(|| inputs.into_par_iter().map(process_input).collect())
+        let (mut results, backlinks_lines): (Vec<ModuleBatchResult>, Vec<Option<u32>>) =
+            if continue_on_error {
+                // Process all files regardless of errors
+                if let Some(pool) = pool {
+                    pool.install(|| inputs.into_par_iter().map(process_input).unzip())
+                } else {
+                    inputs.into_par_iter().map(process_input).unzip()
+                }
             } else {
-                inputs.into_par_iter().map(process_input).collect()
-            }
-        } else {
-            // Stop on first error - sequential processing required
-            let mut results = Vec::with_capacity(inputs.len());
-            let mut had_error = false;
+                // Stop on first error - sequential processing required
+                let mut results = Vec::with_capacity(inputs.len());
+                let mut backlinks_lines = Vec::with_capacity(inputs.len());
+                let mut had_error = false;
 
-            for input in inputs {
-                if had_error {
-                    break;
+                for input in inputs {
+                    if had_error {
+                        break;
+                    }
+                    let (result, backlinks_line) = process_input(input);
+                    if result.error.is_some() {
+                        had_error = true;
+                    }
+                    results.push(result);
+                    backlinks_lines.push(backlinks_line);
                 }
-                let result = process_input(input);
-                if result.error.is_some() {
-                    had_error = true;
+                (results, backlinks_lines)
+            };
+
+        let mut link_graph = None;
+        if validate_links || want_link_graph || backlinks_export {
+            let documents: Vec<LinkedDocument> = results
+                .iter()
+                .zip(&filepaths)
+                .filter_map(|(batch, file_path)| {
+                    let compiled = batch.result.as_ref()?;
+                    Some(LinkedDocument {
+                        id: &batch.id,
+                        file_path,
+                        links: &compiled.links,
+                        headings: &compiled.headings,
+                        markup: &compiled.code,
+                    })
+                })
+                .collect();
+            let index = batch_link_index(&self.config, &documents)?;
+            let diagnostics = validate_links.then(|| index.diagnostics());
+            let graph = (want_link_graph || backlinks_export).then(|| index.graph());
+            let compiled = results
+                .iter_mut()
+                .zip(&backlinks_lines)
+                .filter_map(|(batch, line)| {
+                    Some((batch.id.as_str(), batch.result.as_mut()?, *line))
+                });
+            for (idx, (id, result, backlinks_line)) in compiled.enumerate() {
+                if let Some(warnings) = diagnostics.as_ref().and_then(|d| d.get(idx)) {
+                    append_warnings(&mut result.diagnostics, warnings.clone());
+                }
+                if let Some(line) = backlinks_line {
+                    append_warnings(
+                        &mut result.diagnostics,
+                        vec![ParseWarningEntry {
+                            warning_type: "export_collision".to_string(),
+                            line,
+                            message: "The document declares `backlinks`; the generated backlinks export was skipped".to_string(),
+                        }],
+                    );
+                } else if backlinks_export {
+                    let incoming = graph
+                        .as_ref()
+                        .and_then(|graph| graph.get(id))
+                        .map_or(&[][..], |node| node.incoming.as_slice());
+                    append_backlinks_export(&mut result.code, incoming);
                 }
-                results.push(result);
             }
-            results
-        };
+            link_graph = graph.filter(|_| want_link_graph);
+        }
 
         let elapsed = start.elapsed();
 
@@ -380,6 +702,7 @@ impl XmdxCompiler {
                 failed: failed.load(Ordering::Relaxed),
                 processing_time_ms: elapsed.as_secs_f64() * 1000.0,
             },
+            link_graph,
         })
     }
 
@@ -543,6 +866,26 @@ pub fn compile_ir(
     filepath: String,
     options: Option<FileOptions>,
     config: Option<CompilerConfig>,
+) -> napi::Result<CompileIrResult> {
+    compile_ir_cached(
+        source,
+        filepath,
+        options,
+        config,
+        &ImageSizeCache::default(),
+        &EmbedDocuments::default(),
+    )
+}
+
+/// [`compile_ir`] with a caller-provided image size cache, so a batch probes
+/// each image at most once, and the batch's documents available to embeds.
+pub(crate) fn compile_ir_cached(
+    source: String,
+    filepath: String,
+    options: Option<FileOptions>,
+    config: Option<CompilerConfig>,
+    image_sizes: &ImageSizeCache,
+    embed_documents: &EmbedDocuments,
 ) -> napi::Result<CompileIrResult> {
     let internal = InternalCompilerConfig::new(config);
     let options = options.unwrap_or_default();
@@ -552,22 +895,89 @@ pub fn compile_ir(
         .map_err(|err| super::convert_error(MarkflowError::parse_error(err.to_string(), 1, 1)))?;
     let frontmatter = frontmatter_extraction.value;
     let raw_body = source[frontmatter_extraction.body_start..].to_string();
+    let frontmatter_lines = source[..frontmatter_extraction.body_start]
+        .matches('\n')
+        .count() as u32;
 
     // Extract all imports/exports from the document (not just leading ones)
     // Uses code fence tracking to avoid extracting imports inside code blocks
     let (hoisted_statements, body_lines) = code_fence::collect_root_statements(&raw_body);
     let body_without_imports = body_lines.join("\n");
+    let line_map = body_line_map(&raw_body, &body_lines, frontmatter_lines);
     let has_user_default_export = hoisted_statements
         .exports
         .iter()
         .any(|s| s.trim_start().starts_with("export default"));
 
+    let registry = internal.resolve_registry()?;
+    let component_registry = registry.clone().unwrap_or_else(default_starlight_registry);
+
+    // Directive and element-mapping components whose names collide with the
+    // document's own bindings are imported under an alias; the renderer
+    // emits the alias.
+    // Tags using a registry alias (e.g., a renamed component) are rewritten
+    // to the canonical component, which is planned the same way.
+    let declared: Vec<String> = hoisted_statements
+        .imports
+        .iter()
+        .chain(&hoisted_statements.exports)
+        .cloned()
+        .collect();
+    let component_renames = alias_renames(&component_registry, &declared);
+    let toc = internal.resolve_toc()?;
+    let external_links = internal.external_link_options();
+    let icon_component = external_links
+        .as_ref()
+        .and_then(|links| links.icon_component.clone());
+    let directive_aliases = plan_generated_imports(
+        component_registry
+            .directive_mappings
+            .iter()
+            .map(|m| component_registry.canonical_name(&m.component))
+            .chain(component_registry.mapped_element_components())
+            .chain(toc.component.as_deref())
+            .chain(
+                external_links
+                    .as_ref()
+                    .and_then(|l| l.icon_component.as_deref()),
+            )
+            .chain(component_renames.values().map(String::as_str)),
+        &declared,
+        &component_registry,
+    )
+    .aliases;
+
     // Use mdast pipeline to generate blocks
     let mdast_options = MdastOptions {
         enable_directives: true,
         allow_raw_html: false,
         enable_heading_autolinks: internal.enable_heading_autolinks,
         enable_math: internal.enable_math,
+        math_output: internal.resolve_math_output()?,
+        math_macros: internal
+            .math_macros
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .collect(),
+        registry: registry.clone(),
+        component_aliases: directive_aliases,
+        component_renames,
+        frontmatter: frontmatter.clone(),
+        import_local_images: internal.import_local_images,
+        probe_image_dimensions: internal.probe_image_dimensions,
+        filepath: Some(effective_path.clone()),
+        project_root: internal.project_root.clone(),
+        image_sizes: image_sizes.clone(),
+        link_rewrite: internal.resolve_link_rewrite()?,
+        external_links,
+        wiki_links: internal.resolve_wiki_links()?,
+        enable_embeds: internal.enable_embeds,
+        embed_documents: embed_documents.clone(),
+        enable_includes: internal.enable_includes,
+        import_code_snippets: internal.import_code_snippets,
+        syntax_highlighting: internal.resolve_syntax_highlighting()?,
+        toc,
         ..Default::default()
     };
     let blocks_result = to_blocks(&body_without_imports, &mdast_options)
@@ -593,10 +1003,126 @@ pub fn compile_ir(
             None
         }
     };
-    let jsx_body = blocks_to_jsx_string(&blocks_result.blocks, Some(directive_mapper));
+    // Registry components imported under another name normalize their slots
+    // like the component itself.
+    let mut jsx_registry = component_registry.clone();
+    add_import_alias_normalizations(&mut jsx_registry, &declared);
+    let jsx_body = blocks_to_jsx_string_with_registry(
+        &blocks_result.blocks,
+        Some(directive_mapper),
+        Some(&jsx_registry),
+    );
+
+    // Import registered components used without an explicit import; report the rest.
+    let component_imports = resolve_component_imports(
+        blocks_result
+            .components
+            .iter()
+            .map(|c| c.name.as_str())
+            .filter(|name| !mdast_options.component_renames.contains_key(*name)),
+        &declared,
+        &component_registry,
+    );
+    let mut declared_with_components = declared.clone();
+    declared_with_components.extend(component_imports.imports.iter().cloned());
+    let directive_imports = plan_generated_imports(
+        blocks_result
+            .directive_components
+            .iter()
+            .chain(&blocks_result.element_components)
+            .map(String::as_str)
+            .chain(
+                blocks_result
+                    .alias_usages
+                    .iter()
+                    .filter(|usage| usage.directive.is_none())
+                    .map(|usage| usage.canonical.as_str()),
+            ),
+        &declared_with_components,
+        &component_registry,
+    );
+    // Components introduced by slot normalization (e.g., TabItem for Tabs)
+    // only appear in the generated JSX.
+    declared_with_components.extend(directive_imports.imports.iter().cloned());
+    let generated_names = blocks_result.jsx_component_names(&component_registry);
+    let normalization_imports = plan_generated_imports(
+        generated_names
+            .iter()
+            .map(String::as_str)
+            .filter(|name| !blocks_result.components.iter().any(|c| c.name == *name)),
+        &declared_with_components,
+        &component_registry,
+    );
+    let warning_context = WarningContext {
+        source: &source,
+        line_map: &line_map,
+        declared: &declared,
+        registry: &component_registry,
+        component_renames: &mdast_options.component_renames,
+        icon_component: icon_component.as_deref(),
+    };
+    let source_line = |line: usize| warning_context.source_line(line);
+    let warnings = compile_warnings(&blocks_result, &warning_context)
+        .into_iter()
+        .map(ParseWarningEntry::from)
+        .collect();
+    let diagnostics = Diagnostics { warnings };
 
-    // mdast doesn't produce diagnostics yet - return empty warnings
-    let diagnostics = Diagnostics { warnings: vec![] };
+    let links: Vec<LinkEntry> = blocks_result
+        .links
+        .iter()
+        .map(|link| LinkEntry {
+            href: link.href.clone(),
+            route: link.route.clone(),
+            text: link.text.clone(),
+            external: link.external,
+            fragment: link.fragment.clone(),
+            via: link.via.clone(),
+            line: source_line(link.line),
+            column: link.column as u32,
+        })
+        .collect();
+    let mut dependencies: Vec<ImportedModule> = Vec::new();
+    for transclusion in &blocks_result.transclusions {
+        if let Some(path) = &transclusion.path
+            && !dependencies.iter().any(|d| d.path == *path)
+        {
+            dependencies.push(ImportedModule {
+                path: path.clone(),
+                kind: match transclusion.kind {
+                    TransclusionKind::Embed => "embed".to_string(),
+                    TransclusionKind::Include => "include".to_string(),
+                },
+            });
+        }
+    }
+    for snippet in &blocks_result.code_snippets {
+        if !matches!(
+            snippet.issue,
+            Some(SnippetIssue::MissingFile | SnippetIssue::OutsideRoot)
+        ) && !dependencies.iter().any(|d| d.path == snippet.path)
+        {
+            dependencies.push(ImportedModule {
+                path: snippet.path.clone(),
+                kind: "snippet".to_string(),
+            });
+        }
+    }
+    let asset_references: Vec<AssetReferenceEntry> = blocks_result
+        .asset_references
+        .iter()
+        .map(|asset| AssetReferenceEntry {
+            src: asset.src.clone(),
+            kind: match asset.kind {
+                xmdx_astro::AssetKind::Image => AssetKind::Image,
+                xmdx_astro::AssetKind::Media => AssetKind::Media,
+                xmdx_astro::AssetKind::Download => AssetKind::Download,
+            },
+            via: asset.via.clone(),
+            line: source_line(asset.line),
+            column: asset.column as u32,
+        })
+        .collect();
 
     // Use headings from mdast blocks_result
     let headings: Vec<_> = blocks_result
@@ -608,6 +1134,7 @@ pub fn compile_ir(
             text: h.text,
         })
         .collect();
+    let toc = blocks_result.toc.into_iter().map(toc_entry).collect();
     let layout_import: Option<String> = frontmatter
         .get("layout")
         .and_then(|value| value.as_str())
@@ -623,6 +1150,18 @@ pub fn compile_ir(
             source,
             kind: ImportKind::Hoisted,
         })
+        .chain(
+            component_imports
+                .imports
+                .into_iter()
+                .chain(directive_imports.imports)
+                .chain(normalization_imports.imports)
+                .chain(asset_import_statements(&blocks_result.assets))
+                .map(|source| ImportSpec {
+                    source,
+                    kind: ImportKind::Transform,
+                }),
+        )
         .collect();
 
     let hoisted_exports: Vec<ExportSpec> = hoisted_statements
@@ -640,15 +1179,151 @@ pub fn compile_ir(
         hoisted_exports,
         frontmatter_json,
         headings,
+        toc,
         file_path: effective_path,
         url: options.url.clone(),
         layout_import,
         runtime_import: internal.jsx_import_source.clone(),
         diagnostics,
         has_user_default_export,
+        assets: blocks_result.assets,
+        links,
+        asset_references,
+        dependencies,
     })
 }
 
+/// Indexes the compiled documents of a batch for cross-document link passes.
+///
+/// Routes are derived with the configured link rewriting (or its defaults).
+fn batch_link_index<'a>(
+    config: &InternalCompilerConfig,
+    documents: &'a [LinkedDocument<'a>],
+) -> napi::Result<LinkIndex<'a>> {
+    let rewrite = config.resolve_link_rewrite()?.unwrap_or_default();
+    Ok(LinkIndex::new(documents, rewrite))
+}
+
+/// Returns whether the document's hoisted imports or exports bind or export `name`.
+fn ir_declares(ir: &CompileIrResult, name: &str) -> bool {
+    let statements: Vec<String> = ir
+        .hoisted_imports
+        .iter()
+        .map(|import| import.source.clone())
+        .chain(
+            ir.hoisted_exports
+                .iter()
+                .map(|export| export.source.clone()),
+        )
+        .collect();
+    declared_bindings(&statements)
+        .into_iter()
+        .chain(exported_names(&statements))
+        .any(|declared| declared == name)
+}
+
+/// Appends `export const backlinks = [...]` listing the links into a module.
+///
+/// Batch-only: backlinks need the other files of the batch. Skipped (with an
+/// `export_collision` warning) when the document declares `backlinks` itself.
+fn append_backlinks_export(code: &mut String, incoming: &[LinkGraphEdge]) {
+    let json = serde_json::to_string(incoming).unwrap_or_else(|_| "[]".to_string());
+    if !code.ends_with('\n') {
+        code.push('\n');
+    }
+    code.push_str(&format!("export const backlinks = {json};\n"));
+}
+
+fn append_warnings(diagnostics: &mut Diagnostics, warnings: Vec<ParseWarningEntry>) {
+    if !warnings.is_empty() {
+        diagnostics.warnings.extend(warnings);
+        diagnostics.warnings.sort_by_key(|warning| warning.line);
+    }
+}
+
+/// Parses an Astro `trailingSlash` name; `None` selects the default.
+fn parse_trailing_slash(name: Option<&str>) -> napi::Result<TrailingSlash> {
+    match name {
+        None => Ok(TrailingSlash::default()),
+        Some(name) => TrailingSlash::from_name(name).ok_or_else(|| {
+            napi::Error::new(
+                napi::Status::InvalidArg,
+                format!("Invalid trailingSlash \"{name}\": expected always, never or ignore"),
+            )
+        }),
+    }
+}
+
+/// Adds every file of a batch to the wiki link page table when the config
+/// asks for it. Each file is listed under its file name and frontmatter
+/// `title`; pages already in the table keep their href.
+fn add_batch_wiki_pages(
+    internal: &InternalCompilerConfig,
+    config: &mut CompilerConfig,
+    inputs: &[BatchInput],
+    filepaths: &[String],
+) -> napi::Result<()> {
+    let Some(wiki) = config.wiki_links.as_mut() else {
+        return Ok(());
+    };
+    if !wiki.from_batch.unwrap_or(false) {
+        return Ok(());
+    }
+    let rewrite = internal.resolve_link_rewrite()?.unwrap_or_default();
+    let pages = wiki.pages.get_or_insert_with(Default::default);
+    for (input, filepath) in inputs.iter().zip(filepaths) {
+        let file_name = filepath.rsplit(['/', '\\']).next().unwrap_or_default();
+        let Some(route) = rewrite_markdown_link(&format!("./{file_name}"), filepath, &rewrite)
+        else {
+            continue;
+        };
+        let stem = file_name
+            .rsplit_once('.')
+            .map_or(file_name, |(stem, _)| stem);
+        let title = extract_frontmatter(&input.source).ok().and_then(|fm| {
+            fm.value
+                .get("title")
+                .and_then(|title| title.as_str())
+                .map(str::to_string)
+        });
+        for name in std::iter::once(stem.to_string()).chain(title) {
+            pages.entry(name).or_insert_with(|| route.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Makes every file of a batch available to `![[...]]` embeds, when enabled.
+fn batch_embed_documents(
+    internal: &InternalCompilerConfig,
+    inputs: &[BatchInput],
+    filepaths: &[String],
+) -> EmbedDocuments {
+    if !internal.enable_embeds {
+        return EmbedDocuments::default();
+    }
+    EmbedDocuments::new(
+        inputs
+            .iter()
+            .zip(filepaths)
+            .map(|(input, path)| EmbedDocument {
+                path: path.clone(),
+                source: input.source.clone(),
+            })
+            .collect(),
+    )
+}
+
+/// Converts a renderer TOC entry, with its children, to the NAPI type.
+fn toc_entry(entry: xmdx_astro::TocEntry) -> TocEntry {
+    TocEntry {
+        depth: entry.depth,
+        slug: entry.slug,
+        text: entry.text,
+        children: entry.children.into_iter().map(toc_entry).collect(),
+    }
+}
+
 fn with_path(err: MarkflowError, path: &str) -> MarkflowError {
     match err {
         MarkflowError::MarkdownAdapter { message, location } => MarkflowError::MarkdownAdapter {
@@ -685,23 +1360,31 @@ pub(crate) fn compile_document_from_ir(
         .map(|spec| spec.source.clone())
         .collect();
     let headings_json = serde_json::to_string(&ir.headings).unwrap_or_else(|_| "[]".to_string());
+    let toc_json = serde_json::to_string(&ir.toc).unwrap_or_else(|_| "[]".to_string());
     let code = super::codegen::generate_module_code_from_ir(
         &ir,
         &hoisted_imports,
         &hoisted_exports,
         &headings_json,
+        &toc_json,
         jsx_import_source,
     )?;
-    let imports = super::build_import_list(ir.layout_import.as_deref(), Path::new(&ir.file_path));
+    let mut imports =
+        super::build_import_list(ir.layout_import.as_deref(), Path::new(&ir.file_path));
+    imports.extend(ir.dependencies);
 
     Ok(CompileResult {
         code,
         map: None,
         frontmatter_json: ir.frontmatter_json,
         headings: ir.headings,
+        toc: ir.toc,
         imports,
         diagnostics: ir.diagnostics,
         has_user_default_export: ir.has_user_default_export,
+        assets: ir.assets,
+        links: ir.links,
+        asset_references: ir.asset_references,
     })
 }
 
@@ -12,6 +12,8 @@ pub mod batch;
 mod codegen;
 /// The stateful compiler and its configuration.
 pub mod compiler;
+/// Cross-document link resolution for batches.
+mod links;
 /// NAPI-exposed data structures.
 pub mod types;
 /// Utility helpers.
@@ -34,6 +36,14 @@ pub fn html_entities_to_jsx_napi(s: String) -> String {
     xmdx_astro::codegen::html_entities_to_jsx(&s)
 }
 
+/// Returns the CSS for `classes`-style Rust-side highlighting in a bundled
+/// theme, or `null` when the theme is unknown or the addon was built without
+/// the `highlight` feature.
+#[napi(js_name = "highlightStylesheet")]
+pub fn highlight_stylesheet_napi(theme: String) -> Option<String> {
+    xmdx_astro::transform::highlight::highlight_stylesheet(&theme)
+}
+
 /// Checks if string contains PascalCase JSX tags (e.g., `<Card`, `<Aside`).
 ///
 /// This is used to detect nested JSX components in slot content. When components
@@ -187,6 +197,7 @@ fn convert_render_block(block: xmdx_astro::renderer::mdast::RenderBlock) -> Rend
             code: None,
             lang: None,
             meta: None,
+            parsed_meta: None,
         },
         mdast::RenderBlock::Component {
             name,
@@ -209,9 +220,15 @@ fn convert_render_block(block: xmdx_astro::renderer::mdast::RenderBlock) -> Rend
                 code: None,
                 lang: None,
                 meta: None,
+                parsed_meta: None,
             }
         }
-        mdast::RenderBlock::Code { code, lang, meta } => RenderBlock {
+        mdast::RenderBlock::Code {
+            code,
+            lang,
+            meta,
+            parsed_meta,
+        } => RenderBlock {
             r#type: "code".to_string(),
             content: None,
             name: None,
@@ -220,10 +237,35 @@ fn convert_render_block(block: xmdx_astro::renderer::mdast::RenderBlock) -> Rend
             code: Some(code),
             lang,
             meta,
+            parsed_meta: parsed_meta.map(convert_code_meta),
         },
     }
 }
 
+/// Converts parsed code fence meta to its NAPI form.
+fn convert_code_meta(meta: xmdx_astro::transform::code_meta::CodeMeta) -> types::CodeMeta {
+    let ranges = |ranges: Vec<xmdx_astro::transform::code_meta::LineRange>| {
+        ranges
+            .into_iter()
+            .map(|range| types::LineRange {
+                start: range.start,
+                end: range.end,
+            })
+            .collect()
+    };
+    types::CodeMeta {
+        raw: meta.raw,
+        title: meta.title,
+        highlight: ranges(meta.highlight),
+        ins: ranges(meta.ins),
+        del: ranges(meta.del),
+        show_line_numbers: meta.show_line_numbers,
+        flags: meta.flags,
+        attributes: meta.attributes.into_iter().collect(),
+        invalid_ranges: meta.invalid_ranges,
+    }
+}
+
 /// Parses markdown into structured RenderBlock objects using the mdast v2 renderer.
 ///
 /// This function uses the Block Architecture to return a structured representation
@@ -275,6 +317,11 @@ pub fn parse_blocks(input: String, opts: Option<BlockOptions>) -> napi::Result<P
             allow_raw_html: o.allow_raw_html.unwrap_or(false),
             enable_heading_autolinks: o.enable_heading_autolinks.unwrap_or(false),
             enable_math: o.enable_math.unwrap_or(false),
+            registry: None,
+            component_aliases: Default::default(),
+            component_renames: Default::default(),
+            frontmatter: Default::default(),
+            ..Default::default()
         }
     } else {
         mdast::Options {
@@ -664,4 +711,1047 @@ mod tests {
             result.code
         );
     }
+
+    #[test]
+    fn compile_document_uses_registry_delta() {
+        let config = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            registry: Some(serde_json::json!({
+                "extends": "starlight",
+                "directiveMappings": [{ "directive": "note", "component": "Callout" }]
+            })),
+            ..Default::default()
+        }));
+        let source = ":::note\nBody\n:::\n\n:::tip\nHint\n:::".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.mdx".into(), None, Vec::new())
+                .expect("compile success");
+        assert!(result.code.contains("<Callout"), "code: {}", result.code);
+        assert!(result.code.contains("<Aside"), "code: {}", result.code);
+    }
+
+    #[test]
+    fn compile_document_rejects_unknown_registry_base() {
+        let config = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            registry: Some(serde_json::json!({ "extends": "unknown" })),
+            ..Default::default()
+        }));
+        let err = crate::compiler::compile_document(
+            &config,
+            "# Hi".to_string(),
+            "test.mdx".into(),
+            None,
+            Vec::new(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown"), "err: {}", err);
+    }
+
+    #[test]
+    fn compile_document_auto_imports_registered_components() {
+        let config = InternalCompilerConfig::new(None);
+        let source = "---\ntitle: T\n---\nimport Card from './MyCard.astro';\n\n<Tabs>\n<TabItem label=\"a\">\n<Card title=\"x\" />\n</TabItem>\n</Tabs>\n\n<Mystery />\n".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.mdx".into(), None, Vec::new())
+                .expect("compile success");
+        assert!(
+            result
+                .code
+                .contains("import { Tabs, TabItem } from '@astrojs/starlight/components';"),
+            "code: {}",
+            result.code
+        );
+        assert!(
+            !result.code.contains("import { Card }"),
+            "code: {}",
+            result.code
+        );
+
+        let warnings = &result.diagnostics.warnings;
+        assert_eq!(warnings.len(), 1, "warnings: {:?}", warnings);
+        assert_eq!(warnings[0].warning_type, "unknown_component");
+        assert_eq!(warnings[0].line, 12);
+        assert!(warnings[0].message.contains("Mystery"));
+    }
+
+    #[test]
+    fn compile_document_aliases_directive_import_on_collision() {
+        let config = InternalCompilerConfig::new(None);
+        let source =
+            "import Aside from './MyAside.astro';\n\n:::note\nHello\n:::\n\n<Aside>Mine</Aside>\n"
+                .to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.mdx".into(), None, Vec::new())
+                .expect("compile success");
+        assert!(
+            result
+                .code
+                .contains("import { Aside as __xmdx_Aside } from '@astrojs/starlight/components';"),
+            "code: {}",
+            result.code
+        );
+        assert!(
+            result
+                .code
+                .contains("<__xmdx_Aside {...{\"type\": \"note\"}}>"),
+            "code: {}",
+            result.code
+        );
+        assert!(
+            result.code.contains("<Aside>Mine</Aside>"),
+            "code: {}",
+            result.code
+        );
+        assert!(result.diagnostics.warnings.is_empty());
+    }
+
+    #[test]
+    fn compile_document_dedupes_equivalent_imports() {
+        let config = InternalCompilerConfig::new(None);
+        let source = "import { Tabs } from '@astrojs/starlight/components';\nimport {Tabs, TabItem} from \"@astrojs/starlight/components\";\n\n<Tabs><TabItem label=\"a\">x</TabItem></Tabs>\n".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.mdx".into(), None, Vec::new())
+                .expect("compile success");
+        assert_eq!(
+            result.code.matches("Tabs }").count() + result.code.matches("Tabs,").count(),
+            1,
+            "code: {}",
+            result.code
+        );
+        assert!(
+            result
+                .code
+                .contains("import { TabItem } from '@astrojs/starlight/components';"),
+            "code: {}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn compile_document_normalizes_aliased_component_slots() {
+        let config = InternalCompilerConfig::new(None);
+        let source = "import { Steps as S } from '@astrojs/starlight/components';\n\n<S>\n\nFirst step.\n\n</S>\n".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.mdx".into(), None, Vec::new())
+                .expect("compile success");
+        assert!(
+            result.code.contains(
+                "<S><_Fragment set:html={\"<ol><li><p>First step.</p></li></ol>\"} /></S>"
+            ),
+            "code: {}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn compile_document_imports_slot_normalization_children() {
+        let config = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            registry: Some(serde_json::json!({
+                "extends": "starlight",
+                "slotNormalizations": [{
+                    "component": "Tabs",
+                    "strategy": "split_by_heading",
+                    "childComponent": "TabItem",
+                    "childProp": "label"
+                }]
+            })),
+            ..Default::default()
+        }));
+        let source = "<Tabs>\n\n## npm\n\nnpm i\n\n## pnpm\n\npnpm add\n\n</Tabs>\n".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.mdx".into(), None, Vec::new())
+                .expect("compile success");
+        assert!(
+            result.code.contains("<TabItem label=\"npm\">"),
+            "code: {}",
+            result.code
+        );
+        assert!(
+            result
+                .code
+                .contains("import { TabItem } from '@astrojs/starlight/components';"),
+            "code: {}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn compile_document_rewrites_deprecated_component_aliases() {
+        let config = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            registry: Some(serde_json::json!({
+                "extends": "starlight",
+                "components": [{
+                    "name": "Card",
+                    "modulePath": "@astrojs/starlight/components",
+                    "exportType": "named",
+                    "aliases": ["LegacyCard"],
+                    "deprecated": "use Card instead"
+                }]
+            })),
+            ..Default::default()
+        }));
+        let source = "---\ntitle: T\n---\n<LegacyCard title=\"a\">A</LegacyCard>\n\n<LegacyCard title=\"b\">B</LegacyCard>\n".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.mdx".into(), None, Vec::new())
+                .expect("compile success");
+        assert!(
+            result
+                .code
+                .contains("import { Card } from '@astrojs/starlight/components';"),
+            "code: {}",
+            result.code
+        );
+        assert!(!result.code.contains("LegacyCard"), "code: {}", result.code);
+
+        let warnings = &result.diagnostics.warnings;
+        let lines: Vec<_> = warnings.iter().map(|w| w.line).collect();
+        assert_eq!(lines, vec![4, 6], "warnings: {:?}", warnings);
+        assert!(
+            warnings
+                .iter()
+                .all(|w| w.warning_type == "deprecated_component")
+        );
+        assert_eq!(
+            warnings[0].message,
+            "<LegacyCard> is a deprecated alias of <Card>: use Card instead"
+        );
+    }
+
+    #[test]
+    fn compile_document_imports_element_mapping_components() {
+        let config = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            registry: Some(serde_json::json!({
+                "extends": "starlight",
+                "components": [
+                    { "name": "Image", "modulePath": "astro:assets", "exportType": "named" }
+                ],
+                "elementMappings": [{ "element": "img", "component": "Image" }]
+            })),
+            ..Default::default()
+        }));
+        let source = "![Hero](./hero.png)\n".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.md".into(), None, Vec::new())
+                .expect("compile success");
+        assert!(
+            result
+                .code
+                .contains("import { Image } from 'astro:assets';"),
+            "code: {}",
+            result.code
+        );
+        assert!(result.code.contains("<Image"), "code: {}", result.code);
+    }
+
+    #[test]
+    fn compile_document_imports_fence_mapping_components() {
+        let config = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            registry: Some(serde_json::json!({
+                "extends": "starlight",
+                "components": [
+                    { "name": "Mermaid", "modulePath": "~/Mermaid.astro", "exportType": "default" }
+                ],
+                "fenceMappings": [{ "lang": "mermaid", "component": "Mermaid" }]
+            })),
+            ..Default::default()
+        }));
+        let source = "```mermaid\ngraph TD;\n  A-->B\n```\n\n```ts\nlet a;\n```\n".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.md".into(), None, Vec::new())
+                .expect("compile success");
+        assert!(
+            result
+                .code
+                .contains("import Mermaid from '~/Mermaid.astro';"),
+            "code: {}",
+            result.code
+        );
+        assert!(
+            result.code.contains(r#""code": "graph TD;\n  A-->B""#),
+            "code: {}",
+            result.code
+        );
+        assert!(result.code.contains("language-ts"), "code: {}", result.code);
+    }
+
+    #[test]
+    fn compile_document_hoists_local_image_imports() {
+        let config = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            import_local_images: Some(true),
+            ..Default::default()
+        }));
+        let source = "![Hero](./hero.png)\n\n![Remote](https://example.com/x.png)\n".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.md".into(), None, Vec::new())
+                .expect("compile success");
+        assert_eq!(result.assets, vec!["./hero.png"]);
+        assert!(
+            result
+                .code
+                .contains("import __xmdx_img0 from './hero.png';"),
+            "code: {}",
+            result.code
+        );
+        assert!(
+            result.code.contains("src={__xmdx_img0.src}"),
+            "code: {}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn compile_document_reports_missing_probed_images() {
+        let config = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            probe_image_dimensions: Some(true),
+            ..Default::default()
+        }));
+        let source = "---\ntitle: Hi\n---\n\n![Gone](./does-not-exist.png)\n".to_string();
+        let result = crate::compiler::compile_document(
+            &config,
+            source,
+            "/nonexistent/docs/page.md".into(),
+            None,
+            Vec::new(),
+        )
+        .expect("compile success");
+        let warnings = &result.diagnostics.warnings;
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert_eq!(warnings[0].warning_type, "missing_image");
+        assert_eq!(warnings[0].line, 5);
+        assert!(
+            warnings[0].message.contains("./does-not-exist.png"),
+            "{}",
+            warnings[0].message
+        );
+    }
+
+    #[test]
+    fn compile_document_rewrites_markdown_links() {
+        let config = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            link_rewrite: Some(crate::LinkRewriteConfig {
+                base: Some("/docs/".into()),
+                trailing_slash: Some("never".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }));
+        let source = "See [setup](./setup.md#install).\n".to_string();
+        let result = crate::compiler::compile_document(
+            &config,
+            source.clone(),
+            "/site/src/content/docs/guide/intro.md".into(),
+            None,
+            Vec::new(),
+        )
+        .expect("compile success");
+        assert!(
+            result.code.contains("/docs/guide/setup#install"),
+            "code: {}",
+            result.code
+        );
+
+        let invalid = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            link_rewrite: Some(crate::LinkRewriteConfig {
+                trailing_slash: Some("sometimes".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }));
+        assert!(
+            crate::compiler::compile_document(&invalid, source, "a.md".into(), None, Vec::new())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn compile_document_marks_external_links() {
+        let config = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            external_links: Some(crate::ExternalLinkConfig {
+                site_hosts: Some(vec!["docs.example.com".into()]),
+                target_blank: Some(true),
+                new_tab_label: Some("(opens in new tab)".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }));
+        let source = "[Astro](https://astro.build) and [guide](https://docs.example.com/guide/).\n"
+            .to_string();
+        let result = crate::compiler::compile_document(
+            &config,
+            source,
+            "/site/src/content/docs/intro.md".into(),
+            None,
+            Vec::new(),
+        )
+        .expect("compile success");
+        assert!(
+            result
+                .code
+                .contains("rel=\\\"noopener noreferrer\\\" target=\\\"_blank\\\""),
+            "code: {}",
+            result.code
+        );
+        assert!(
+            result.code.contains("(opens in new tab)"),
+            "code: {}",
+            result.code
+        );
+        assert!(
+            result
+                .code
+                .contains("href=\\\"https://docs.example.com/guide/\\\">guide</a>"),
+            "code: {}",
+            result.code
+        );
+    }
+
+    #[test]
+    fn compile_document_imports_external_link_icon() {
+        let config = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            registry: Some(serde_json::json!({
+                "extends": "starlight",
+                "components": [{
+                    "name": "ExternalIcon",
+                    "modulePath": "~/icons/External.astro",
+                    "exportType": "default"
+                }]
+            })),
+            external_links: Some(crate::ExternalLinkConfig {
+                icon_component: Some("ExternalIcon".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }));
+        let source = "[Astro](https://astro.build)\n".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.md".into(), None, Vec::new())
+                .expect("compile success");
+        assert!(
+            result
+                .code
+                .contains("import ExternalIcon from '~/icons/External.astro';"),
+            "code: {}",
+            result.code
+        );
+        assert!(result.diagnostics.warnings.is_empty());
+
+        let config = InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            external_links: Some(crate::ExternalLinkConfig {
+                icon_component: Some("ExternalIcon".into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }));
+        let source = "[Astro](https://astro.build)\n".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.md".into(), None, Vec::new())
+                .expect("compile success");
+        let warnings: Vec<_> = result
+            .diagnostics
+            .warnings
+            .iter()
+            .map(|w| (w.warning_type.as_str(), w.message.as_str()))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![(
+                "unknown_component",
+                "External link icon <ExternalIcon> is used but neither imported nor registered"
+            )]
+        );
+    }
+
+    #[test]
+    fn compile_document_reports_links_and_assets() {
+        let config = InternalCompilerConfig::new(None);
+        let source = "---\ntitle: Intro\n---\nimport Card from './Card.astro';\n\nSee [setup](./setup.md#install) and [Astro](https://astro.build).\n\n![Hero](./hero.png)\n".to_string();
+        let result = crate::compiler::compile_document(
+            &config,
+            source,
+            "/site/src/content/docs/intro.mdx".into(),
+            None,
+            Vec::new(),
+        )
+        .expect("compile success");
+        let links: Vec<_> = result
+            .links
+            .iter()
+            .map(|l| (l.href.as_str(), l.external, l.fragment.as_deref(), l.line))
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                ("./setup.md#install", false, Some("install"), 6),
+                ("https://astro.build", true, None, 6),
+            ]
+        );
+        assert_eq!(result.asset_references.len(), 1);
+        assert_eq!(result.asset_references[0].kind, crate::AssetKind::Image);
+        assert_eq!(result.asset_references[0].line, 8);
+    }
+
+    #[test]
+    fn compile_batch_validates_links_across_files() {
+        let input = |path: &str, source: &str| crate::BatchInput {
+            id: path.to_string(),
+            source: source.to_string(),
+            filepath: None,
+        };
+        let inputs = vec![
+            input(
+                "src/content/docs/guide/intro.md",
+                "# Intro\n\n[Setup](./setup.md#install) [Gone](./gone.md)\n\n[Bad anchor](/guide/setup/#nope) [Self](#intro) [Ext](https://example.com/x.md)\n",
+            ),
+            input(
+                "src/content/docs/guide/setup.md",
+                "## Install\n\n[Back](./intro.md#missing) [Asset](./file.pdf)\n",
+            ),
+        ];
+        let result = crate::compile_batch(
+            inputs,
+            Some(crate::BatchOptions {
+                validate_links: Some(true),
+                ..Default::default()
+            }),
+        )
+        .expect("batch success");
+        let warnings = |idx: usize| -> Vec<(String, u32)> {
+            result.results[idx]
+                .result
+                .as_ref()
+                .expect("compiled")
+                .diagnostics
+                .warnings
+                .iter()
+                .map(|w| (w.warning_type.clone(), w.line))
+                .collect()
+        };
+        assert_eq!(
+            warnings(0),
+            vec![
+                ("broken_link".to_string(), 3),
+                ("missing_anchor".to_string(), 5),
+            ]
+        );
+        assert_eq!(warnings(1), vec![("missing_anchor".to_string(), 3)]);
+    }
+
+    #[test]
+    fn compile_batch_decodes_percent_encoded_anchors() {
+        let inputs = vec![crate::BatchInput {
+            id: "src/content/docs/kanji.md".to_string(),
+            source: "## 日\n\n[Day](#%E6%97%A5) [Night](#%E5%A4%9C)\n".to_string(),
+            filepath: None,
+        }];
+        let result = crate::compile_batch(
+            inputs,
+            Some(crate::BatchOptions {
+                validate_links: Some(true),
+                ..Default::default()
+            }),
+        )
+        .expect("batch success");
+        let warnings = &result.results[0]
+            .result
+            .as_ref()
+            .expect("compiled")
+            .diagnostics
+            .warnings;
+        assert_eq!(warnings.len(), 1, "warnings: {warnings:?}");
+        assert_eq!(warnings[0].warning_type, "missing_anchor");
+        assert!(warnings[0].message.contains("#%E5%A4%9C"));
+    }
+
+    #[test]
+    fn compile_batch_to_module_exports_backlinks() {
+        let input = |id: &str, source: &str| crate::BatchInput {
+            id: id.to_string(),
+            source: source.to_string(),
+            filepath: Some(format!("src/content/docs/{id}.md")),
+        };
+        let inputs = vec![
+            input(
+                "intro",
+                "# Intro\n\nNext: [Setup guide](./setup.md#install).\n",
+            ),
+            input(
+                "setup",
+                "## Install\n\nBack to [the intro](./intro.md) or [top](#install).\n",
+            ),
+            input("orphan", "Nothing links here.\n"),
+        ];
+        let result = crate::compile_batch_to_module(
+            inputs,
+            Some(crate::BatchOptions {
+                link_graph: Some(true),
+                backlinks_export: Some(true),
+                ..Default::default()
+            }),
+        )
+        .expect("batch success");
+
+        let graph = result.link_graph.expect("link graph");
+        let setup = &graph["setup"];
+        assert_eq!(setup.incoming.len(), 1);
+        assert_eq!(setup.incoming[0].id, "intro");
+        assert_eq!(setup.incoming[0].text, "Setup guide");
+        assert_eq!(setup.incoming[0].route.as_deref(), Some("/intro"));
+        assert_eq!(setup.outgoing.len(), 1, "self links are left out");
+        assert_eq!(
+            graph["intro"].outgoing[0].fragment.as_deref(),
+            Some("install")
+        );
+        assert!(graph["orphan"].incoming.is_empty());
+
+        let code = |idx: usize| result.results[idx].result.as_ref().unwrap().code.clone();
+        assert!(
+            code(1).contains(
+                r#"export const backlinks = [{"id":"intro","route":"/intro","text":"Setup guide","fragment":"install","line":3}];"#
+            ),
+            "{}",
+            code(1)
+        );
+        assert!(code(2).contains("export const backlinks = [];"));
+    }
+
+    #[test]
+    fn compile_batch_to_module_skips_backlinks_declared_by_document() {
+        let inputs = vec![
+            crate::BatchInput {
+                id: "intro".to_string(),
+                source: "See [setup](./setup.md).\n".to_string(),
+                filepath: Some("src/content/docs/intro.md".to_string()),
+            },
+            crate::BatchInput {
+                id: "setup".to_string(),
+                source:
+                    "---\ntitle: Setup\n---\nimport Feedbacklinks from './Feedbacklinks.astro';\n\n```js\nexport { backlinks };\n```\n\nexport const backlinks = ['manual'];\n\n# Setup\n"
+                        .to_string(),
+                filepath: Some("src/content/docs/setup.md".to_string()),
+            },
+        ];
+        let result = crate::compile_batch_to_module(
+            inputs,
+            Some(crate::BatchOptions {
+                backlinks_export: Some(true),
+                ..Default::default()
+            }),
+        )
+        .expect("batch success");
+
+        let setup = result.results[1].result.as_ref().unwrap();
+        assert_eq!(setup.code.matches("export const backlinks").count(), 1);
+        assert!(setup.code.contains("export const backlinks = ['manual'];"));
+        let warning = setup
+            .diagnostics
+            .warnings
+            .iter()
+            .find(|warning| warning.warning_type == "export_collision")
+            .expect("collision warning");
+        assert_eq!(warning.line, 10);
+    }
+
+    #[test]
+    fn compile_batch_resolves_wiki_links_from_batch() {
+        let input = |path: &str, source: &str| crate::BatchInput {
+            id: path.to_string(),
+            source: source.to_string(),
+            filepath: None,
+        };
+        let inputs = vec![
+            input(
+                "src/content/docs/guide/intro.md",
+                "# Intro\n\nRead [[Setup Guide#Install]] or [[Nowhere]].\n",
+            ),
+            input(
+                "src/content/docs/guide/setup.md",
+                "---\ntitle: Setup Guide\n---\n## Install\n",
+            ),
+        ];
+        let result = crate::compile_batch(
+            inputs,
+            Some(crate::BatchOptions {
+                config: Some(crate::CompilerConfig {
+                    wiki_links: Some(crate::WikiLinkConfig {
+                        from_batch: Some(true),
+                        slug_fallback: Some(false),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        )
+        .expect("batch success");
+        let intro = result.results[0].result.as_ref().expect("compiled");
+        assert!(
+            intro
+                .html
+                .contains("href=\\\"/guide/setup#install\\\" data-wikilink=\\\"Setup Guide\\\""),
+            "{}",
+            intro.html
+        );
+        let warnings: Vec<_> = intro
+            .diagnostics
+            .warnings
+            .iter()
+            .map(|w| (w.warning_type.as_str(), w.line))
+            .collect();
+        assert_eq!(warnings, vec![("unresolved_wikilink", 3)]);
+    }
+
+    #[test]
+    fn compile_batch_renders_embeds_from_batch() {
+        let input = |path: &str, source: &str| crate::BatchInput {
+            id: path.to_string(),
+            source: source.to_string(),
+            filepath: None,
+        };
+        let inputs = vec![
+            input(
+                "src/content/docs/guide/intro.md",
+                "# Intro\n\n![[setup#Install]]\n\n![[Nowhere]]\n",
+            ),
+            input(
+                "src/content/docs/guide/setup.md",
+                "---\ntitle: Setup\n---\n## Install\n\n:::note\nRun it.\n:::\n\n## Usage\n",
+            ),
+        ];
+        let result = crate::compile_batch(
+            inputs,
+            Some(crate::BatchOptions {
+                config: Some(crate::CompilerConfig {
+                    embeds: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        )
+        .expect("batch success");
+        let intro = result.results[0].result.as_ref().expect("compiled");
+        assert!(
+            intro.html.contains("data-embed=\\\"setup\\\""),
+            "{}",
+            intro.html
+        );
+        assert!(intro.html.contains("Run it."), "{}", intro.html);
+        assert!(!intro.html.contains("Usage"), "{}", intro.html);
+        assert!(
+            intro
+                .hoisted_imports
+                .iter()
+                .any(|spec| spec.source.contains("Aside")),
+            "{:?}",
+            intro.hoisted_imports
+        );
+        assert_eq!(
+            intro
+                .dependencies
+                .iter()
+                .map(|d| (d.path.as_str(), d.kind.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("src/content/docs/guide/setup.md", "embed")]
+        );
+        let warnings: Vec<_> = intro
+            .diagnostics
+            .warnings
+            .iter()
+            .map(|w| (w.warning_type.as_str(), w.line))
+            .collect();
+        assert_eq!(warnings, vec![("unresolved_embed", 5)]);
+    }
+
+    #[test]
+    fn compile_lists_included_files_as_imports() {
+        let dir = std::env::temp_dir().join(format!("xmdx-napi-includes-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("_shared")).unwrap();
+        std::fs::write(
+            dir.join("_shared/prereqs.md"),
+            "---\ntitle: Prerequisites\n---\n## Node\n\nInstall Node.\n",
+        )
+        .unwrap();
+        let compiler = crate::compiler::XmdxCompiler::new(Some(crate::CompilerConfig {
+            includes: Some(true),
+            project_root: Some(dir.display().to_string()),
+            ..Default::default()
+        }));
+        let source = "# Guide\n\n::include{file=\"./_shared/prereqs.md\"}\n\n::include{file=\"./_shared/gone.md\"}\n";
+        let result = compiler
+            .compile_mdx(
+                source.to_string(),
+                dir.join("guide.md").display().to_string(),
+                None,
+            )
+            .expect("compiles");
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(result.code.contains("Install Node."), "{}", result.code);
+        assert!(!result.code.contains("Prerequisites"), "{}", result.code);
+        assert_eq!(
+            result
+                .headings
+                .iter()
+                .map(|h| h.slug.as_str())
+                .collect::<Vec<_>>(),
+            vec!["guide", "node"]
+        );
+        assert_eq!(
+            result
+                .imports
+                .iter()
+                .map(|i| (i.path.as_str(), i.kind.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(
+                dir.join("./_shared/prereqs.md")
+                    .display()
+                    .to_string()
+                    .as_str(),
+                "include"
+            )]
+        );
+        let warnings: Vec<_> = result
+            .diagnostics
+            .warnings
+            .iter()
+            .map(|w| (w.warning_type.as_str(), w.line))
+            .collect();
+        assert_eq!(warnings, vec![("unresolved_include", 5)]);
+    }
+
+    #[test]
+    fn compile_imports_code_snippets_and_reports_missing_regions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::write(
+            dir.join("client.ts"),
+            "// #region setup\nconst client = connect();\n// #endregion\n",
+        )
+        .unwrap();
+        let compiler = crate::compiler::XmdxCompiler::new(Some(crate::CompilerConfig {
+            import_code_snippets: Some(true),
+            project_root: Some(dir.display().to_string()),
+            ..Default::default()
+        }));
+        let source = "---\ntitle: Guide\n---\n```ts file=./client.ts#setup\n```\n\n```ts file=./client.ts#teardown\n```\n";
+        let result = compiler
+            .compile_mdx(
+                source.to_string(),
+                dir.join("guide.md").display().to_string(),
+                None,
+            )
+            .expect("compiles");
+
+        assert!(
+            result.code.contains("const client = connect();"),
+            "{}",
+            result.code
+        );
+        assert_eq!(
+            result
+                .imports
+                .iter()
+                .map(|i| i.kind.as_str())
+                .collect::<Vec<_>>(),
+            vec!["snippet"]
+        );
+        let warnings: Vec<_> = result
+            .diagnostics
+            .warnings
+            .iter()
+            .map(|w| (w.warning_type.as_str(), w.line))
+            .collect();
+        assert_eq!(warnings, vec![("missing_snippet", 7)]);
+    }
+
+    #[test]
+    fn parse_blocks_exposes_parsed_code_meta() {
+        let result = crate::parse_blocks(
+            "```js title=\"demo.js\" ins={1,3} collapse=2-4\nrun();\n```\n".to_string(),
+            None,
+        )
+        .expect("parses");
+        let meta = result.blocks[0]
+            .parsed_meta
+            .as_ref()
+            .expect("code block has parsed meta");
+        assert_eq!(meta.title.as_deref(), Some("demo.js"));
+        assert_eq!(
+            meta.ins
+                .iter()
+                .map(|range| (range.start, range.end))
+                .collect::<Vec<_>>(),
+            vec![(1, 1), (3, 3)]
+        );
+        assert!(!meta.show_line_numbers);
+        assert_eq!(
+            meta.attributes.get("collapse").map(String::as_str),
+            Some("2-4")
+        );
+        assert_eq!(
+            result.blocks[0].meta.as_deref(),
+            Some("title=\"demo.js\" ins={1,3} collapse=2-4")
+        );
+    }
+
+    #[test]
+    fn syntax_highlighting_config_rejects_unknown_style_and_theme() {
+        let config = crate::compiler::InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            syntax_highlighting: Some(true),
+            highlight_theme: Some("base16-ocean.dark".to_string()),
+            highlight_style: Some("inline".to_string()),
+            ..Default::default()
+        }));
+        let options = config.resolve_syntax_highlighting().unwrap().unwrap();
+        assert_eq!(options.theme, "base16-ocean.dark");
+        assert_eq!(
+            options.style,
+            xmdx_astro::transform::highlight::HighlightStyle::Inline
+        );
+
+        let config = crate::compiler::InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            syntax_highlighting: Some(true),
+            highlight_style: Some("fancy".to_string()),
+            ..Default::default()
+        }));
+        let err = config.resolve_syntax_highlighting().unwrap_err();
+        assert!(err.reason.contains("highlightStyle"), "{}", err.reason);
+
+        let config = crate::compiler::InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            syntax_highlighting: Some(true),
+            highlight_theme: Some("base16-ocean".to_string()),
+            ..Default::default()
+        }));
+        let err = config.resolve_syntax_highlighting().unwrap_err();
+        assert!(err.reason.contains("highlightTheme"), "{}", err.reason);
+
+        let config = crate::compiler::InternalCompilerConfig::new(None);
+        assert!(config.resolve_syntax_highlighting().unwrap().is_none());
+    }
+
+    #[test]
+    fn math_output_mathml_renders_supported_formulas() {
+        let config = crate::compiler::InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            math: Some(true),
+            math_output: Some("mathml".to_string()),
+            ..Default::default()
+        }));
+        let source = "$$\\frac{a}{b}$$\n\nand $\\color{red}{x}$".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.md".into(), None, Vec::new())
+                .expect("compile success");
+        assert!(
+            result.code.contains("<mfrac><mi>a</mi><mi>b</mi></mfrac>"),
+            "code: {}",
+            result.code
+        );
+        assert!(result.code.contains("<MathInline"), "code: {}", result.code);
+
+        let config = crate::compiler::InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            math_output: Some("katex".to_string()),
+            ..Default::default()
+        }));
+        let err = config.resolve_math_output().unwrap_err();
+        assert!(err.reason.contains("mathOutput"), "{}", err.reason);
+    }
+
+    #[test]
+    fn math_macros_expand_and_invalid_tex_is_reported() {
+        let config = crate::compiler::InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            math: Some(true),
+            math_macros: Some(std::collections::HashMap::from([(
+                "R".to_string(),
+                "\\mathbb{R}".to_string(),
+            )])),
+            ..Default::default()
+        }));
+        let source = "---\ntitle: T\n---\n\n$\\R^n$ and $\\frac{a}{b$".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.md".into(), None, Vec::new())
+                .expect("compile success");
+        assert!(
+            result.code.contains("\\\\mathbb{R}^n"),
+            "code: {}",
+            result.code
+        );
+        let warning = result
+            .diagnostics
+            .warnings
+            .iter()
+            .find(|w| w.warning_type == "invalid_math")
+            .expect("invalid_math warning");
+        assert_eq!(warning.line, 5);
+        assert_eq!(warning.message, "Invalid TeX at column 21: unclosed `{`");
+    }
+
+    #[test]
+    fn toc_is_returned_exported_and_rendered_for_directive() {
+        let config = crate::compiler::InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            toc: Some(crate::TocConfig {
+                directive: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }));
+        let source =
+            "# Guide\n\n::toc\n\n## Setup\n\n### Linux\n\n## Changelog {.no-toc}\n".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.md".into(), None, Vec::new())
+                .expect("compile success");
+        assert_eq!(result.toc.len(), 1);
+        assert_eq!(result.toc[0].slug, "setup");
+        assert_eq!(result.toc[0].children[0].slug, "linux");
+        assert!(
+            result.code.contains(
+                r#"export const toc = [{"depth":2,"slug":"setup","text":"Setup","children":[{"depth":3,"slug":"linux","text":"Linux","children":[]}]}];"#
+            ),
+            "code: {}",
+            result.code
+        );
+        assert!(
+            result
+                .code
+                .contains(r##"<li><a href=\"#setup\">Setup</a>"##),
+            "code: {}",
+            result.code
+        );
+        assert!(
+            result
+                .code
+                .contains(r#"<h2 id=\"changelog\" class=\"no-toc\">Changelog</h2>"#),
+            "code: {}",
+            result.code
+        );
+
+        let config = crate::compiler::InternalCompilerConfig::new(Some(crate::CompilerConfig {
+            toc: Some(crate::TocConfig {
+                min_depth: Some(4),
+                max_depth: Some(2),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }));
+        let err = config.resolve_toc().unwrap_err();
+        assert!(
+            err.reason.contains("Invalid toc depth range 4-2"),
+            "{}",
+            err.reason
+        );
+    }
+
+    #[test]
+    fn user_toc_export_replaces_generated_toc_with_warning() {
+        let config = crate::compiler::InternalCompilerConfig::new(None);
+        let source = "import Stock from './Stock.astro';\n\n```js\nexport { toc };\n```\n\nexport const toc = [];\n\n# Guide\n\n## Setup\n".to_string();
+        let result =
+            crate::compiler::compile_document(&config, source, "test.mdx".into(), None, Vec::new())
+                .expect("compile success");
+        assert_eq!(result.code.matches("export const toc").count(), 1);
+        assert!(result.code.contains("export const toc = [];"));
+        let warnings: Vec<_> = result
+            .diagnostics
+            .warnings
+            .iter()
+            .map(|w| (w.warning_type.as_str(), w.line))
+            .collect();
+        assert_eq!(warnings, vec![("export_collision", 7)]);
+    }
 }
@@ -0,0 +1,250 @@
+//! Cross-document link resolution for batch compiles.
+//!
+//! Every compiled document reports its outbound links and headings. This
+//! module indexes a batch by file path and route so that internal links can
+//! be checked against the pages (and anchors) compiled alongside them, and
+//! so the batch can report which pages link to which.
+
+use crate::batch::{LinkGraphEdge, LinkGraphNode};
+use crate::types::{HeadingEntry, LinkEntry, ParseWarningEntry};
+use std::collections::{HashMap, HashSet};
+use xmdx_astro::renderer::mdast::images::percent_decode;
+use xmdx_astro::transform::links::{LinkRewriteOptions, rewrite_markdown_link};
+
+/// The link-related parts of one compiled document.
+pub(crate) struct LinkedDocument<'a> {
+    /// `BatchInput.id` of the document.
+    pub id: &'a str,
+    /// Path the document was compiled from.
+    pub file_path: &'a str,
+    /// Outbound links reported by the compiler.
+    pub links: &'a [LinkEntry],
+    /// Headings reported by the compiler.
+    pub headings: &'a [HeadingEntry],
+    /// Rendered output, scanned for element `id`s.
+    pub markup: &'a str,
+}
+
+/// Where a link points within the batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LinkTarget {
+    /// External, asset or relative route link that cannot be checked.
+    Unchecked,
+    /// The document at this index; its anchor (if any) exists.
+    Document(usize),
+    /// A page link that matches no document of the batch.
+    MissingDocument,
+    /// The document at this index, which has no matching anchor.
+    MissingAnchor(usize),
+}
+
+/// Lookup tables for resolving links between the documents of a batch.
+pub(crate) struct LinkIndex<'a> {
+    documents: &'a [LinkedDocument<'a>],
+    rewrite: LinkRewriteOptions,
+    by_path: HashMap<String, usize>,
+    by_route: HashMap<String, usize>,
+    routes: Vec<Option<String>>,
+    anchors: Vec<HashSet<&'a str>>,
+}
+
+impl<'a> LinkIndex<'a> {
+    /// Indexes `documents`, deriving page routes with `rewrite`.
+    pub(crate) fn new(documents: &'a [LinkedDocument<'a>], rewrite: LinkRewriteOptions) -> Self {
+        let mut by_path = HashMap::new();
+        let mut by_route = HashMap::new();
+        let mut routes = Vec::with_capacity(documents.len());
+        let mut anchors = Vec::with_capacity(documents.len());
+        for (idx, doc) in documents.iter().enumerate() {
+            by_path.entry(normalize_path(doc.file_path)).or_insert(idx);
+            let file_name = doc.file_path.rsplit(['/', '\\']).next().unwrap_or_default();
+            let route = rewrite_markdown_link(&format!("./{file_name}"), doc.file_path, &rewrite);
+            if let Some(route) = &route {
+                by_route.entry(route_key(route).to_string()).or_insert(idx);
+            }
+            routes.push(route);
+            anchors.push(
+                doc.headings
+                    .iter()
+                    .map(|h| h.slug.as_str())
+                    .chain(element_ids(doc.markup))
+                    .collect(),
+            );
+        }
+        Self {
+            documents,
+            rewrite,
+            by_path,
+            by_route,
+            routes,
+            anchors,
+        }
+    }
+
+    /// Resolves a link of the document at index `from`.
+    pub(crate) fn resolve(&self, from: usize, link: &LinkEntry) -> LinkTarget {
+        if link.external {
+            return LinkTarget::Unchecked;
+        }
+        // Links of embedded and included documents resolve against their file.
+        let from_path = link
+            .via
+            .as_deref()
+            .unwrap_or(self.documents[from].file_path);
+        let href = link.href.as_str();
+        let path = href.split(['?', '#']).next().unwrap_or(href);
+        let target = if path.is_empty() {
+            Some(from)
+        } else if is_markdown_file(path) {
+            let by_path = (!path.starts_with('/'))
+                .then(|| self.by_path.get(&join_path(from_path, path)))
+                .flatten();
+            by_path
+                .or_else(|| {
+                    rewrite_markdown_link(href, from_path, &self.rewrite)
+                        .and_then(|route| self.by_route.get(route_key(&route)))
+                })
+                .copied()
+        } else if path.starts_with('/') && !has_extension(path) {
+            self.by_route.get(route_key(path)).copied()
+        } else {
+            return LinkTarget::Unchecked;
+        };
+        let Some(target) = target else {
+            return LinkTarget::MissingDocument;
+        };
+        match link.fragment.as_deref().filter(|f| !f.is_empty()) {
+            Some(fragment) if !self.anchors[target].contains(percent_decode(fragment).as_str()) => {
+                LinkTarget::MissingAnchor(target)
+            }
+            _ => LinkTarget::Document(target),
+        }
+    }
+
+    /// Returns `broken_link` and `missing_anchor` warnings for each document.
+    pub(crate) fn diagnostics(&self) -> Vec<Vec<ParseWarningEntry>> {
+        self.documents
+            .iter()
+            .enumerate()
+            .map(|(idx, doc)| {
+                doc.links
+                    .iter()
+                    .filter_map(|link| match self.resolve(idx, link) {
+                        LinkTarget::MissingDocument => Some(ParseWarningEntry {
+                            warning_type: "broken_link".to_string(),
+                            line: link.line,
+                            message: format!(
+                                "Link {} does not match any page in this batch",
+                                link.href
+                            ),
+                        }),
+                        LinkTarget::MissingAnchor(target) => Some(ParseWarningEntry {
+                            warning_type: "missing_anchor".to_string(),
+                            line: link.line,
+                            message: format!(
+                                "Link {} points to #{}, which is not a heading or id in {}",
+                                link.href,
+                                link.fragment.as_deref().unwrap_or_default(),
+                                self.documents[target].file_path
+                            ),
+                        }),
+                        LinkTarget::Unchecked | LinkTarget::Document(_) => None,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Builds the outgoing and incoming links of every document, keyed by id.
+    ///
+    /// Links a document makes to itself (e.g., `#section`) are left out.
+    pub(crate) fn graph(&self) -> HashMap<String, LinkGraphNode> {
+        let mut nodes = vec![LinkGraphNode::default(); self.documents.len()];
+        for (from, doc) in self.documents.iter().enumerate() {
+            for link in doc.links {
+                let target = match self.resolve(from, link) {
+                    LinkTarget::Document(target) | LinkTarget::MissingAnchor(target) => target,
+                    LinkTarget::Unchecked | LinkTarget::MissingDocument => continue,
+                };
+                if target != from {
+                    nodes[from].outgoing.push(self.edge(target, link));
+                    nodes[target].incoming.push(self.edge(from, link));
+                }
+            }
+        }
+        self.documents
+            .iter()
+            .map(|doc| doc.id.to_string())
+            .zip(nodes)
+            .collect()
+    }
+
+    fn edge(&self, other: usize, link: &LinkEntry) -> LinkGraphEdge {
+        LinkGraphEdge {
+            id: self.documents[other].id.to_string(),
+            route: self.routes[other].clone(),
+            text: link.text.clone(),
+            fragment: link.fragment.clone(),
+            line: link.line,
+        }
+    }
+}
+
+fn is_markdown_file(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    lower.ends_with(".md") || lower.ends_with(".mdx")
+}
+
+fn has_extension(path: &str) -> bool {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    file.rsplit_once('.')
+        .is_some_and(|(stem, _)| !stem.is_empty())
+}
+
+/// Routes compare without their query, fragment and trailing slash.
+fn route_key(route: &str) -> &str {
+    let route = route.split(['?', '#']).next().unwrap_or(route);
+    route.trim_end_matches('/')
+}
+
+/// Resolves `relative` against the directory of `document_path`.
+fn join_path(document_path: &str, relative: &str) -> String {
+    match document_path.rfind(['/', '\\']) {
+        Some(end) => normalize_path(&format!("{}/{relative}", &document_path[..end])),
+        None => normalize_path(relative),
+    }
+}
+
+/// Collapses `.`/`..` segments and separators so equal paths compare equal.
+fn normalize_path(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    let joined = segments.join("/");
+    if path.starts_with(['/', '\\']) {
+        format!("/{joined}")
+    } else {
+        joined
+    }
+}
+
+/// Scans rendered output for `id="..."`, `id=\"...\"` and `id={"..."}` values.
+fn element_ids(markup: &str) -> impl Iterator<Item = &str> {
+    markup.match_indices("id=").filter_map(move |(pos, _)| {
+        if !markup[..pos].ends_with(char::is_whitespace) {
+            return None;
+        }
+        let rest = &markup[pos + 3..];
+        let rest = rest.strip_prefix('{').unwrap_or(rest);
+        let rest = rest.strip_prefix('\\').unwrap_or(rest);
+        let rest = rest.strip_prefix('"')?;
+        rest.find(['"', '\\']).map(|end| &rest[..end])
+    })
+}
@@ -3,6 +3,7 @@
 use napi_derive::napi;
 use serde::Serialize;
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
 
 /// Parsed frontmatter document plus any parser errors.
 #[napi(object)]
@@ -22,11 +23,20 @@ pub struct CompilerConfig {
     pub gfm: Option<bool>,
     /// Enables smart punctuation substitutions (placeholder flag).
     pub smartypants: Option<bool>,
-    /// Enables syntax highlighting (placeholder flag).
+    /// Highlights code fences in Rust for languages with a bundled grammar,
+    /// instead of leaving them to Shiki or ExpressiveCode. Requires a build
+    /// with the `highlight` feature; otherwise a no-op.
     pub syntax_highlighting: Option<bool>,
+    /// Bundled theme for Rust-side highlighting (e.g., `base16-ocean.dark`);
+    /// an unknown name is an error. Defaults to `InspiredGitHub`.
+    pub highlight_theme: Option<String>,
+    /// `classes` (styled by `highlightStylesheet()`) or `inline` styles.
+    /// Defaults to `classes`.
+    pub highlight_style: Option<String>,
     /// Overrides the module used for JSX runtime helpers.
     pub jsx_import_source: Option<String>,
     /// Component registry configuration (JSON).
+    /// Set `extends: "starlight"` to send only a delta on top of the built-in registry.
     pub registry: Option<JsonValue>,
     /// Whether to rewrite JSX code blocks to HTML format for ExpressiveCode.
     /// Only set to true when ExpressiveCode is enabled.
@@ -35,12 +45,113 @@ pub struct CompilerConfig {
     pub enable_heading_autolinks: Option<bool>,
     /// Whether to enable math syntax ($inline$ and $$block$$).
     pub math: Option<bool>,
+    /// `component` (`<MathBlock>`/`<MathInline>`) or `mathml`, which converts
+    /// common TeX to MathML in Rust and falls back to the components for
+    /// anything else. Defaults to `component`.
+    pub math_output: Option<String>,
+    /// Macros expanded in math before it is emitted, keyed by command with
+    /// or without the backslash (e.g., `{ "\\R": "\\mathbb{R}" }`).
+    pub math_macros: Option<HashMap<String, String>>,
     /// Custom directive names to recognize beyond the built-in set.
     /// Each entry is a directive name (e.g., "custom-box").
     pub custom_directive_names: Option<Vec<String>>,
     /// Component name overrides for directives.
     /// Maps directive name to component name (e.g., {"note": "Callout", "custom-box": "Box"}).
     pub directive_component_map: Option<serde_json::Value>,
+    /// Whether to hoist relative image sources to ESM imports so Astro can
+    /// optimize them. The imported paths are reported in `assets`.
+    pub import_local_images: Option<bool>,
+    /// Whether to read local image headers and add `width`/`height` to
+    /// Markdown images. Missing files are reported as diagnostics.
+    pub probe_image_dimensions: Option<bool>,
+    /// Rewrites links to `.md`/`.mdx` files into site routes.
+    pub link_rewrite: Option<LinkRewriteConfig>,
+    /// Attributes and indicators for links to other hosts.
+    pub external_links: Option<ExternalLinkConfig>,
+    /// Renders `[[Page#Heading|label]]` wiki links.
+    pub wiki_links: Option<WikiLinkConfig>,
+    /// Directory that includes, embeds and code snippets may read files
+    /// from; paths resolving outside it are refused (default: the working
+    /// directory).
+    pub project_root: Option<String>,
+    /// Renders `![[Note]]` / `![[Note#Section]]` embeds in place. Notes
+    /// resolve against the current batch, then files next to the document.
+    pub embeds: Option<bool>,
+    /// Renders `::include{file="..." section="..."}` lines with the Markdown
+    /// file read relative to the document.
+    pub includes: Option<bool>,
+    /// Fills code fences whose meta names a local file
+    /// (`file=./examples/client.ts#region lines=10-25`) from that file.
+    pub import_code_snippets: Option<bool>,
+    /// Table of contents depth range and exclusions, and the `::toc` directive.
+    pub toc: Option<TocConfig>,
+}
+
+/// Settings for the table of contents built from the headings.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct TocConfig {
+    /// Shallowest heading level included (default: 2).
+    pub min_depth: Option<u8>,
+    /// Deepest heading level included (default: 3).
+    pub max_depth: Option<u8>,
+    /// Headings with this class (`## Changelog {.no-toc}`) are left out
+    /// (default: `no-toc`).
+    pub exclude_class: Option<String>,
+    /// Headings with this attribute (`## Changelog {data-toc=skip}`) are left out.
+    pub exclude_attribute: Option<String>,
+    /// Whether a `::toc` line renders the table of contents in place (default: false).
+    pub directive: Option<bool>,
+    /// Registry component rendered for `::toc` with the tree as its `toc`
+    /// prop; otherwise `::toc` renders a nested `<ul>`.
+    pub component: Option<String>,
+}
+
+/// Settings for resolving `[[Page]]` wiki links.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct WikiLinkConfig {
+    /// Page names mapped to hrefs. Names match by slug.
+    pub pages: Option<HashMap<String, String>>,
+    /// Whether batch compiles add every file of the batch to `pages`, by
+    /// file name and frontmatter `title` (default: false).
+    pub from_batch: Option<bool>,
+    /// Whether unknown names resolve to `base` + the slug of the name (default: true).
+    pub slug_fallback: Option<bool>,
+    /// Base path for slug-resolved routes (default: `/`).
+    pub base: Option<String>,
+    /// Astro `trailingSlash` policy for slug-resolved routes (default: `"ignore"`).
+    pub trailing_slash: Option<String>,
+}
+
+/// Policy for links whose host is not one of the site's hosts.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct ExternalLinkConfig {
+    /// Hosts that belong to the site; links to any other host are external.
+    pub site_hosts: Option<Vec<String>>,
+    /// `rel` attribute for external links (default: `noopener noreferrer`).
+    pub rel: Option<String>,
+    /// Whether external links open in a new tab (`target="_blank"`).
+    pub target_blank: Option<bool>,
+    /// Visually hidden text appended to external links, e.g. "(opens in new tab)".
+    pub new_tab_label: Option<String>,
+    /// Icon component appended to external links.
+    pub icon_component: Option<String>,
+}
+
+/// Settings for rewriting `.md`/`.mdx` links to routes.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct LinkRewriteConfig {
+    /// Directory whose files map to routes (default: `src/content/docs`).
+    pub content_root: Option<String>,
+    /// Base path prepended to every route (default: `/`).
+    pub base: Option<String>,
+    /// Astro `trailingSlash` policy: `"always"`, `"never"` or `"ignore"` (default).
+    pub trailing_slash: Option<String>,
+    /// Whether to drop the file extension; otherwise routes end in `.html` (default: true).
+    pub strip_extension: Option<bool>,
 }
 
 /// File-specific overrides that accompany each compilation.
@@ -77,6 +188,72 @@ pub struct HeadingEntry {
     pub text: String,
 }
 
+/// Table of contents entry with the headings nested under it.
+#[napi(object)]
+#[derive(Debug, Clone, Serialize)]
+pub struct TocEntry {
+    /// Heading depth (1-6).
+    pub depth: u8,
+    /// Slugified identifier.
+    pub slug: String,
+    /// Visible heading text.
+    pub text: String,
+    /// Deeper headings up to the next one of the same or a higher level.
+    pub children: Vec<TocEntry>,
+}
+
+/// Outbound link found in a document.
+#[napi(object)]
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkEntry {
+    /// Link target as written.
+    pub href: String,
+    /// Route the link was rewritten to, when link rewriting applies.
+    pub route: Option<String>,
+    /// Visible link text.
+    pub text: String,
+    /// Whether the link leaves the site (another host or a non-web scheme).
+    pub external: bool,
+    /// Fragment identifier without the `#`, if any.
+    pub fragment: Option<String>,
+    /// Path of the embedded or included document containing the link, if
+    /// it was not written in this document.
+    pub via: Option<String>,
+    /// 1-indexed source line (0 when unknown or nested).
+    pub line: u32,
+    /// 1-indexed source column (0 when unknown or nested).
+    pub column: u32,
+}
+
+/// Kind of file referenced by a document.
+#[napi(string_enum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AssetKind {
+    /// An image (`![alt](src)`, `<img>`, `<Image>`, `<Picture>`).
+    Image,
+    /// Audio or video (`<video>`, `<audio>`, `<source>`).
+    Media,
+    /// A linked file that is not a page (e.g., a PDF).
+    Download,
+}
+
+/// File referenced by a document.
+#[napi(object)]
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetReferenceEntry {
+    /// Source as written.
+    pub src: String,
+    /// What kind of file the source refers to.
+    pub kind: AssetKind,
+    /// Path of the embedded or included document containing the reference,
+    /// if it was not written in this document.
+    pub via: Option<String>,
+    /// 1-indexed source line (0 when unknown or nested).
+    pub line: u32,
+    /// 1-indexed source column (0 when unknown or nested).
+    pub column: u32,
+}
+
 /// Imported module referenced by the compiled output.
 #[napi(object)]
 #[derive(Debug, Clone, Serialize)]
@@ -99,6 +276,16 @@ pub struct ParseWarningEntry {
     pub message: String,
 }
 
+impl From<xmdx_astro::diagnostics::CompileWarning> for ParseWarningEntry {
+    fn from(warning: xmdx_astro::diagnostics::CompileWarning) -> Self {
+        Self {
+            warning_type: warning.warning_type,
+            line: warning.line,
+            message: warning.message,
+        }
+    }
+}
+
 /// Diagnostics returned with compilation result
 #[napi(object)]
 #[derive(Debug, Clone)]
@@ -119,12 +306,20 @@ pub struct CompileResult {
     pub frontmatter_json: String,
     /// Heading metadata collected during compilation.
     pub headings: Vec<HeadingEntry>,
-    /// Dependencies referenced while compiling (layouts/imports).
+    /// Headings nested into a table of contents, as exported by the module.
+    pub toc: Vec<TocEntry>,
+    /// Dependencies referenced while compiling (layouts, embedded files).
     pub imports: Vec<ImportedModule>,
     /// Parse diagnostics (warnings, not errors)
     pub diagnostics: Diagnostics,
     /// Whether user provided their own `export default` statement.
     pub has_user_default_export: bool,
+    /// Relative image paths hoisted to imports (`__xmdx_img0`, ...), in binding order.
+    pub assets: Vec<String>,
+    /// Outbound links, in document order.
+    pub links: Vec<LinkEntry>,
+    /// Images, media and downloads referenced by the document, in document order.
+    pub asset_references: Vec<AssetReferenceEntry>,
 }
 
 /// Neutral IR returned when Astro-compat codegen is disabled.
@@ -141,6 +336,8 @@ pub struct CompileIrResult {
     pub frontmatter_json: String,
     /// Heading metadata collected during parsing.
     pub headings: Vec<HeadingEntry>,
+    /// Headings nested into a table of contents.
+    pub toc: Vec<TocEntry>,
     /// Absolute or workspace-relative file path of the source.
     pub file_path: String,
     /// Route URL (if provided) associated with the file.
@@ -153,6 +350,15 @@ pub struct CompileIrResult {
     pub diagnostics: Diagnostics,
     /// Whether user provided their own `export default` statement.
     pub has_user_default_export: bool,
+    /// Relative image paths hoisted to imports (`__xmdx_img0`, ...), in binding order.
+    pub assets: Vec<String>,
+    /// Outbound links, in document order.
+    pub links: Vec<LinkEntry>,
+    /// Images, media and downloads referenced by the document, in document order.
+    pub asset_references: Vec<AssetReferenceEntry>,
+    /// Files rendered or imported into this document (`kind: "embed"`,
+    /// `"include"` or `"snippet"`), for watching.
+    pub dependencies: Vec<ImportedModule>,
 }
 
 /// Structured import returned by the compiler IR.
@@ -210,7 +416,7 @@ pub struct BlockOptions {
 /// type RenderBlock =
 ///   | { type: "html", content: string }
 ///   | { type: "component", name: string, props: Record<string, string>, slotChildren: RenderBlock[] }
-///   | { type: "code", code: string, lang?: string, meta?: string }
+///   | { type: "code", code: string, lang?: string, meta?: string, parsedMeta?: CodeMeta }
 /// ```
 #[napi(object)]
 #[derive(Debug, Clone)]
@@ -231,6 +437,42 @@ pub struct RenderBlock {
     pub lang: Option<String>,
     /// Code meta string (for type="code")
     pub meta: Option<String>,
+    /// Parsed code meta (for type="code" with a meta string)
+    pub parsed_meta: Option<CodeMeta>,
+}
+
+/// Inclusive, 1-indexed range of lines in a code block.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct LineRange {
+    /// First line of the range.
+    pub start: u32,
+    /// Last line of the range.
+    pub end: u32,
+}
+
+/// Code fence meta string parsed into common fields.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct CodeMeta {
+    /// The meta string as written.
+    pub raw: String,
+    /// Value of `title="..."`.
+    pub title: Option<String>,
+    /// Lines from `{1,3-5}` groups and `mark={...}`.
+    pub highlight: Vec<LineRange>,
+    /// Lines from `ins={...}`.
+    pub ins: Vec<LineRange>,
+    /// Lines from `del={...}`.
+    pub del: Vec<LineRange>,
+    /// Whether the `showLineNumbers` flag is present.
+    pub show_line_numbers: bool,
+    /// Remaining bare tokens, in order.
+    pub flags: Vec<String>,
+    /// Remaining `key=value` pairs.
+    pub attributes: HashMap<String, String>,
+    /// Line range entries that were not applied, as written (e.g., `3-1`, `{1`).
+    pub invalid_ranges: Vec<String>,
 }
 
 /// Result of parseBlocks() with blocks and extracted headings.
@@ -4,6 +4,7 @@ use crate::types::ImportedModule;
 use serde_json::Value as JsonValue;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use xmdx_astro::transform::imports::dedupe_import_statements;
 
 pub(crate) fn normalize_import_key(source: &str) -> String {
     let mut key = String::with_capacity(source.len());
@@ -55,7 +56,9 @@ pub(crate) fn dedupe_imports(mut imports: Vec<String>) -> Vec<String> {
             deduped.push(import);
         }
     }
-    deduped
+    // Also drop bindings already imported from the same module by an
+    // earlier, differently formatted statement.
+    dedupe_import_statements(deduped)
 }
 
 pub(crate) fn build_import_list(layout: Option<&str>, filepath: &Path) -> Vec<ImportedModule> {
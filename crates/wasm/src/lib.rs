@@ -1,10 +1,22 @@
 use serde::Serialize;
 use wasm_bindgen::JsValue;
 use wasm_bindgen::prelude::*;
-use xmdx_astro::MdastOptions;
 use xmdx_astro::code_fence::collect_root_statements;
-use xmdx_astro::codegen::{AstroModuleOptions, DirectiveMappingResult, blocks_to_jsx_string};
+use xmdx_astro::codegen::{
+    AstroModuleOptions, DirectiveMappingResult, blocks_to_jsx_string_with_registry,
+};
+use xmdx_astro::diagnostics::{CompileWarning, WarningContext, body_line_map, compile_warnings};
+use xmdx_astro::registry::defaults::default_starlight_registry;
 use xmdx_astro::renderer::mdast::to_blocks;
+use xmdx_astro::renderer::mdast::toc::TocOptions;
+use xmdx_astro::transform::highlight::HighlightOptions;
+use xmdx_astro::transform::imports::{
+    add_import_alias_normalizations, alias_renames, asset_import_statements, plan_generated_imports,
+};
+use xmdx_astro::transform::links::{ExternalLinkOptions, LinkRewriteOptions};
+use xmdx_astro::transform::mathml::MathOutput;
+use xmdx_astro::transform::wikilinks::WikiLinkOptions;
+use xmdx_astro::{AssetReference, LinkReference, MdastOptions, RegistryConfig, TocEntry};
 use xmdx_core::DEFAULT_DIRECTIVE_NAMES;
 
 // ============================================================================
@@ -21,6 +33,13 @@ pub struct WasmCompilerConfig {
     pub enable_heading_autolinks: Option<bool>,
     #[serde(default)]
     pub math: Option<bool>,
+    /// `"component"` (default) or `"mathml"`, which renders common TeX as
+    /// MathML and falls back to the components for anything else.
+    #[serde(default, alias = "mathOutput")]
+    pub math_output: Option<MathOutput>,
+    /// Macros expanded in math before it is emitted (`{ "\\R": "\\mathbb{R}" }`).
+    #[serde(default, alias = "mathMacros")]
+    pub math_macros: Option<std::collections::BTreeMap<String, String>>,
     #[serde(default, alias = "enableSmartypants")]
     pub enable_smartypants: Option<bool>,
     #[serde(default, alias = "enableLazyImages")]
@@ -33,16 +52,110 @@ pub struct WasmCompilerConfig {
     pub custom_directive_names: Option<Vec<String>>,
     #[serde(default, alias = "directiveComponentMap")]
     pub directive_component_map: Option<serde_json::Value>,
+    /// Component registry JSON. Supports `extends: "starlight"` deltas.
+    #[serde(default)]
+    pub registry: Option<serde_json::Value>,
+    /// Hoist relative image sources to ESM imports for Astro asset optimization.
+    #[serde(default, alias = "importLocalImages")]
+    pub import_local_images: Option<bool>,
+    /// Rewrites links to `.md`/`.mdx` files into site routes.
+    #[serde(default, alias = "linkRewrite")]
+    pub link_rewrite: Option<LinkRewriteOptions>,
+    /// Attributes and indicators for links to other hosts.
+    #[serde(default, alias = "externalLinks")]
+    pub external_links: Option<ExternalLinkOptions>,
+    /// Renders `[[Page#Heading|label]]` wiki links.
+    #[serde(default, alias = "wikiLinks")]
+    pub wiki_links: Option<WikiLinkOptions>,
+    /// Highlights code fences in Rust (`true` or `{ theme, style }`).
+    /// Requires a build with the `highlight` feature.
+    #[serde(default, alias = "syntaxHighlighting")]
+    pub syntax_highlighting: Option<HighlightSetting>,
+    /// Table of contents depth range and exclusions, and the `::toc` directive.
+    #[serde(default)]
+    pub toc: Option<TocOptions>,
+}
+
+/// `syntaxHighlighting` config: a switch for the defaults, or explicit options.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(untagged)]
+pub enum HighlightSetting {
+    /// Enables (`true`) or disables highlighting with default options.
+    Enabled(bool),
+    /// Enables highlighting with the given theme and style.
+    Options(HighlightOptions),
+}
+
+impl HighlightSetting {
+    fn options(&self) -> Option<HighlightOptions> {
+        match self {
+            HighlightSetting::Enabled(enabled) => enabled.then(HighlightOptions::default),
+            HighlightSetting::Options(options) => Some(options.clone()),
+        }
+    }
 }
 
-fn parse_config(config: JsValue) -> WasmCompilerConfig {
+/// The structured config options, checked on their own so that one bad
+/// value (e.g., an unknown `trailingSlash`) is an error instead of silently
+/// resetting the whole config to its defaults.
+#[derive(Debug, serde::Deserialize)]
+struct StructuredConfig {
+    #[serde(default, alias = "mathOutput")]
+    math_output: Option<MathOutput>,
+    #[serde(default, alias = "linkRewrite")]
+    link_rewrite: Option<LinkRewriteOptions>,
+    #[serde(default, alias = "externalLinks")]
+    external_links: Option<ExternalLinkOptions>,
+    #[serde(default, alias = "wikiLinks")]
+    wiki_links: Option<WikiLinkOptions>,
+    #[serde(default, alias = "syntaxHighlighting")]
+    syntax_highlighting: Option<HighlightSetting>,
+    #[serde(default)]
+    toc: Option<TocOptions>,
+}
+
+/// Reads the compiler config.
+///
+/// The structured options must be valid, and an unknown highlight theme is
+/// rejected. An invalid value anywhere else keeps the existing behavior of
+/// falling back to the defaults, with the structured options preserved.
+fn parse_config(config: JsValue) -> Result<WasmCompilerConfig, JsError> {
     if config.is_undefined() || config.is_null() {
-        return WasmCompilerConfig::default();
+        return Ok(WasmCompilerConfig::default());
     }
-    serde_wasm_bindgen::from_value(config).unwrap_or_default()
+    let structured: StructuredConfig = serde_wasm_bindgen::from_value(config.clone())
+        .map_err(|e| JsError::new(&format!("Invalid config: {}", e)))?;
+    if let Some(options) = structured
+        .syntax_highlighting
+        .as_ref()
+        .and_then(HighlightSetting::options)
+    {
+        options
+            .validate()
+            .map_err(|e| JsError::new(&format!("Invalid config: {}", e)))?;
+    }
+    Ok(
+        serde_wasm_bindgen::from_value(config).unwrap_or_else(|_| WasmCompilerConfig {
+            math_output: structured.math_output,
+            link_rewrite: structured.link_rewrite,
+            external_links: structured.external_links,
+            wiki_links: structured.wiki_links,
+            syntax_highlighting: structured.syntax_highlighting,
+            toc: structured.toc,
+            ..Default::default()
+        }),
+    )
 }
 
-fn build_mdast_options(cfg: &WasmCompilerConfig) -> MdastOptions {
+fn resolve_registry(cfg: &WasmCompilerConfig) -> Result<Option<RegistryConfig>, JsError> {
+    cfg.registry
+        .clone()
+        .map(RegistryConfig::from_json)
+        .transpose()
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn build_mdast_options(cfg: &WasmCompilerConfig, registry: Option<RegistryConfig>) -> MdastOptions {
     MdastOptions {
         enable_directives: cfg.enable_directives.unwrap_or(true),
         enable_smartypants: cfg.enable_smartypants.unwrap_or(false),
@@ -50,6 +163,19 @@ fn build_mdast_options(cfg: &WasmCompilerConfig) -> MdastOptions {
         allow_raw_html: cfg.allow_raw_html.unwrap_or(false),
         enable_heading_autolinks: cfg.enable_heading_autolinks.unwrap_or(false),
         enable_math: cfg.math.unwrap_or(false),
+        math_output: cfg.math_output.unwrap_or_default(),
+        math_macros: cfg.math_macros.clone().unwrap_or_default(),
+        registry,
+        import_local_images: cfg.import_local_images.unwrap_or(false),
+        link_rewrite: cfg.link_rewrite.clone(),
+        external_links: cfg.external_links.clone(),
+        wiki_links: cfg.wiki_links.clone(),
+        syntax_highlighting: cfg
+            .syntax_highlighting
+            .as_ref()
+            .and_then(HighlightSetting::options),
+        toc: cfg.toc.clone().unwrap_or_default(),
+        ..Default::default()
     }
 }
 
@@ -125,8 +251,20 @@ pub struct CompileResult {
     pub frontmatter_json: String,
     /// Extracted heading metadata.
     pub headings: Vec<HeadingEntry>,
+    /// Headings nested into a table of contents, as exported by the module.
+    pub toc: Vec<TocEntry>,
     /// Whether the user provided their own export default.
     pub has_user_default_export: bool,
+    /// Relative image paths hoisted to imports, in binding order.
+    pub assets: Vec<String>,
+    /// Outbound links, in document order (lines relative to the Markdown body).
+    pub links: Vec<LinkReference>,
+    /// Images, media and downloads referenced by the document, in document order.
+    pub asset_references: Vec<AssetReference>,
+    /// Unknown and deprecated components, missing images, invalid math and
+    /// unresolved wiki links, with the same types, messages and source file
+    /// lines as the NAPI compiler, ordered by line.
+    pub warnings: Vec<CompileWarning>,
 }
 
 // ============================================================================
@@ -150,7 +288,7 @@ pub struct CompileResult {
 /// and heading metadata.
 #[wasm_bindgen]
 pub fn compile(source: &str, filepath: &str, config: JsValue) -> Result<JsValue, JsError> {
-    let cfg = parse_config(config);
+    let cfg = parse_config(config)?;
 
     // 1. Extract frontmatter
     let extraction = xmdx_core::extract_frontmatter(source)
@@ -166,23 +304,99 @@ pub fn compile(source: &str, filepath: &str, config: JsValue) -> Result<JsValue,
         .exports
         .iter()
         .any(|s| s.trim_start().starts_with("export default"));
-    let hoisted_imports = hoisted_statements.imports;
+    let mut hoisted_imports = hoisted_statements.imports;
     let hoisted_exports = hoisted_statements.exports;
 
-    // 3. Parse to blocks and render JSX
-    let mdast_options = build_mdast_options(&cfg);
+    // 3. Parse to blocks and render JSX. Directive and element-mapping components that collide
+    //    with the document's own bindings are imported under an alias, and
+    //    registry aliases in tags are rewritten to their canonical component.
+    let registry = resolve_registry(&cfg)?;
+    let component_registry = registry.clone().unwrap_or_else(default_starlight_registry);
+    let declared: Vec<String> = hoisted_imports
+        .iter()
+        .chain(&hoisted_exports)
+        .cloned()
+        .collect();
+    let component_renames = alias_renames(&component_registry, &declared);
+    let directive_components = component_registry
+        .directive_mappings
+        .iter()
+        .map(|m| component_registry.canonical_name(&m.component))
+        .chain(component_registry.mapped_element_components())
+        .chain(cfg.toc.as_ref().and_then(|toc| toc.component.as_deref()))
+        .chain(
+            cfg.external_links
+                .as_ref()
+                .and_then(|links| links.icon_component.as_deref()),
+        )
+        .chain(component_renames.values().map(String::as_str));
+    let mdast_options = MdastOptions {
+        component_aliases: plan_generated_imports(
+            directive_components,
+            &declared,
+            &component_registry,
+        )
+        .aliases,
+        component_renames,
+        frontmatter: extraction.value.clone(),
+        filepath: Some(filepath.to_string()),
+        ..build_mdast_options(&cfg, registry.clone())
+    };
     let blocks_result = to_blocks(&body_without_imports, &mdast_options)
         .map_err(|e| JsError::new(&format!("Parse error: {}", e)))?;
+    let directive_imports = plan_generated_imports(
+        blocks_result
+            .directive_components
+            .iter()
+            .chain(&blocks_result.element_components)
+            .map(String::as_str)
+            .chain(
+                blocks_result
+                    .alias_usages
+                    .iter()
+                    .filter(|usage| usage.directive.is_none())
+                    .map(|usage| usage.canonical.as_str()),
+            ),
+        &declared,
+        &component_registry,
+    );
+    hoisted_imports.extend(directive_imports.imports);
+    hoisted_imports.extend(asset_import_statements(&blocks_result.assets));
 
+    // Registry components imported under another name normalize their slots
+    // like the component itself.
+    let mut jsx_registry = component_registry.clone();
+    add_import_alias_normalizations(&mut jsx_registry, &declared);
     let jsx_body = if let Some(mapper) = build_directive_mapper(&cfg) {
-        blocks_to_jsx_string(&blocks_result.blocks, Some(mapper))
+        blocks_to_jsx_string_with_registry(&blocks_result.blocks, Some(mapper), Some(&jsx_registry))
     } else {
-        blocks_to_jsx_string(
+        blocks_to_jsx_string_with_registry(
             &blocks_result.blocks,
             None::<fn(&str) -> Option<DirectiveMappingResult>>,
+            Some(&jsx_registry),
         )
     };
 
+    let jsx_components = blocks_result.jsx_component_names(&component_registry);
+
+    let icon_component = cfg
+        .external_links
+        .as_ref()
+        .and_then(|links| links.icon_component.as_deref());
+    let frontmatter_lines = source[..extraction.body_start].matches('\n').count() as u32;
+    let line_map = body_line_map(raw_body, &body_lines, frontmatter_lines);
+    let warnings = compile_warnings(
+        &blocks_result,
+        &WarningContext {
+            source,
+            line_map: &line_map,
+            declared: &declared,
+            registry: &component_registry,
+            component_renames: &mdast_options.component_renames,
+            icon_component,
+        },
+    );
+
     // 4. Convert headings
     let headings: Vec<HeadingEntry> = blocks_result
         .headings
@@ -195,19 +409,24 @@ pub fn compile(source: &str, filepath: &str, config: JsValue) -> Result<JsValue,
         .collect();
 
     let headings_json = serde_json::to_string(&headings).unwrap_or_else(|_| "[]".to_string());
+    let toc_json = serde_json::to_string(&blocks_result.toc).unwrap_or_else(|_| "[]".to_string());
 
-    // 5. Generate module code with jsx_import_source threaded through codegen
+    // 5. Generate module code with jsx_import_source threaded through codegen;
+    //    registered components used without an import are imported automatically.
     let code = xmdx_astro::codegen::generate_astro_module(&AstroModuleOptions {
         jsx: &jsx_body,
         hoisted_imports: &hoisted_imports,
         hoisted_exports: &hoisted_exports,
         frontmatter_json: &frontmatter_json,
         headings_json: &headings_json,
+        toc_json: Some(&toc_json),
         filepath,
         url: None,
         layout_import: None,
         has_user_default_export,
         jsx_import_source: cfg.jsx_import_source.as_deref(),
+        registry: Some(&component_registry),
+        components: &jsx_components,
     });
 
     // 7. Build result
@@ -215,7 +434,12 @@ pub fn compile(source: &str, filepath: &str, config: JsValue) -> Result<JsValue,
         code,
         frontmatter_json,
         headings,
+        toc: blocks_result.toc,
         has_user_default_export,
+        assets: blocks_result.assets,
+        links: blocks_result.links,
+        asset_references: blocks_result.asset_references,
+        warnings,
     };
 
     serde_wasm_bindgen::to_value(&result)
@@ -244,6 +468,9 @@ pub fn compile(source: &str, filepath: &str, config: JsValue) -> Result<JsValue,
 /// Returns a JavaScript array of RenderBlock objects. Each block is either:
 /// - `{type: "html", content: "<p>...</p>"}` - Plain HTML content
 /// - `{type: "component", name: "note", props: {title: "..."}, slot_html: "..."}` - Component block
+/// - `{type: "code", code: "...", lang: "ts", meta: "...", parsed_meta: {...}}` - Code block,
+///   with `parsed_meta` holding the meta string's title, `highlight`/`ins`/`del`
+///   line ranges, `show_line_numbers`, `flags` and `attributes`
 ///
 /// # Example (JavaScript)
 ///
@@ -269,7 +496,7 @@ pub fn parse_blocks(input: &str, opts: JsValue) -> Result<JsValue, JsError> {
     use xmdx_astro::renderer::mdast::{Options, to_blocks};
 
     // Parse options from JavaScript
-    let options: Options = if opts.is_undefined() || opts.is_null() {
+    let mut options: Options = if opts.is_undefined() || opts.is_null() {
         Options {
             enable_directives: true,
             ..Default::default()
@@ -278,6 +505,13 @@ pub fn parse_blocks(input: &str, opts: JsValue) -> Result<JsValue, JsError> {
         serde_wasm_bindgen::from_value(opts)
             .map_err(|e| JsError::new(&format!("Invalid options: {}", e)))?
     };
+    if let Some(registry) = options.registry.take() {
+        options.registry = Some(
+            registry
+                .resolve()
+                .map_err(|e| JsError::new(&e.to_string()))?,
+        );
+    }
 
     // Parse markdown to blocks
     let blocks = to_blocks(input, &options).map_err(|e| JsError::new(&e.to_string()))?;
@@ -286,3 +520,12 @@ pub fn parse_blocks(input: &str, opts: JsValue) -> Result<JsValue, JsError> {
     serde_wasm_bindgen::to_value(&blocks)
         .map_err(|e| JsError::new(&format!("Serialization error: {}", e)))
 }
+
+/// Returns the CSS for `classes`-style highlighting in a bundled theme.
+///
+/// Returns `undefined` when the theme is unknown or the module was built
+/// without the `highlight` feature.
+#[wasm_bindgen(js_name = highlight_stylesheet)]
+pub fn highlight_stylesheet(theme: &str) -> Option<String> {
+    xmdx_astro::transform::highlight::highlight_stylesheet(theme)
+}
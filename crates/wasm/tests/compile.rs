@@ -9,6 +9,15 @@ struct CompileResult {
     frontmatter_json: String,
     headings: Vec<HeadingEntry>,
     has_user_default_export: bool,
+    #[serde(default)]
+    warnings: Vec<WarningEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+struct WarningEntry {
+    warning_type: String,
+    line: usize,
+    message: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -114,3 +123,59 @@ fn compile_filepath_in_output() {
     assert!(result.code.contains("export const file ="));
     assert!(result.code.contains("/path/to/file.mdx"));
 }
+
+#[wasm_bindgen_test]
+fn compile_reports_unknown_components_as_warnings() {
+    let source = "# Hello\n\n<Aside>Registered</Aside>\n\n<Missing />";
+    let result = compile(source, "test.mdx", JsValue::NULL).expect("compile should succeed");
+
+    let result: CompileResult = serde_wasm_bindgen::from_value(result).expect("deserialize result");
+
+    assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+    assert_eq!(result.warnings[0].warning_type, "unknown_component");
+    assert_eq!(result.warnings[0].line, 5);
+    assert!(result.code.contains("import { Aside }"));
+    assert!(result.warnings[0].message.contains("<Missing>"));
+}
+
+#[wasm_bindgen_test]
+fn compile_reports_warning_lines_relative_to_the_source_file() {
+    let source =
+        "---\ntitle: Guide\n---\nimport Local from './Local.astro';\n\n<Local />\n\n<Missing />\n";
+    let result = compile(source, "test.mdx", JsValue::NULL).expect("compile should succeed");
+
+    let result: CompileResult = serde_wasm_bindgen::from_value(result).expect("deserialize result");
+
+    assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+    assert_eq!(result.warnings[0].line, 8);
+}
+
+#[wasm_bindgen_test]
+fn compile_rejects_invalid_structured_config_values() {
+    let config = js_sys::Object::new();
+    js_sys::Reflect::set(&config, &"mathOutput".into(), &"svg".into()).expect("set config");
+    assert!(compile("# Test", "test.mdx", config.into()).is_err());
+
+    let link_rewrite = js_sys::Object::new();
+    js_sys::Reflect::set(&link_rewrite, &"trailingSlash".into(), &"sometimes".into())
+        .expect("set trailingSlash");
+    let config = js_sys::Object::new();
+    js_sys::Reflect::set(&config, &"linkRewrite".into(), &link_rewrite).expect("set config");
+    assert!(compile("# Test", "test.mdx", config.into()).is_err());
+}
+
+#[wasm_bindgen_test]
+fn compile_falls_back_to_defaults_for_other_invalid_config_values() {
+    let config = js_sys::Object::new();
+    js_sys::Reflect::set(&config, &"enableDirectives".into(), &"yes".into()).expect("set config");
+    assert!(compile("# Test", "test.mdx", config.into()).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn compile_rejects_unknown_highlight_theme() {
+    let options = js_sys::Object::new();
+    js_sys::Reflect::set(&options, &"theme".into(), &"base16-ocean".into()).expect("set theme");
+    let config = js_sys::Object::new();
+    js_sys::Reflect::set(&config, &"syntaxHighlighting".into(), &options).expect("set config");
+    assert!(compile("# Test", "test.mdx", config.into()).is_err());
+}
@@ -734,4 +734,55 @@ describe('blocksToJsx', () => {
       expect(result).toContain('<Fragment slot="x" title="a>b" />');
     });
   });
+
+  describe('slot normalization', () => {
+    const registry = createRegistry([{
+      ...starlightLibrary,
+      slotNormalizations: [
+        { component: 'Tabs', strategy: 'split_by_heading', childComponent: 'TabItem', childProp: 'label', headingLevel: 3 },
+        { component: 'CardGrid', strategy: 'list_to_items', childComponent: 'Card', childProp: 'title' },
+        { component: 'Aside', strategy: 'wrap_in_div', wrapperClass: 'grid' },
+      ],
+    }]);
+
+    it('should split sections into child components and import the child', () => {
+      const blocks: Block[] = [{
+        type: 'component',
+        name: 'Tabs',
+        props: {},
+        slotChildren: [{ type: 'html', content: '<h3 id="a">A</h3><p>one</p><h3 id="b">B</h3><p>two</p>' }],
+      }];
+
+      const result = blocksToJsx(blocks, {}, [], registry);
+
+      expect(result).toContain('<TabItem label="A"><p>one</p></TabItem><TabItem label="B"><p>two</p></TabItem>');
+      expect(result).toMatch(/import \{[^}]*TabItem[^}]*\} from '@astrojs\/starlight\/components'/);
+    });
+
+    it('should turn list items into child components', () => {
+      const blocks: Block[] = [{
+        type: 'component',
+        name: 'CardGrid',
+        props: {},
+        slotChildren: [{ type: 'html', content: '<ul><li><p>Stars</p><p>Body</p></li></ul>' }],
+      }];
+
+      const result = blocksToJsx(blocks, {}, [], registry);
+
+      expect(result).toContain('<Card title="Stars"><p>Body</p></Card>');
+    });
+
+    it('should wrap slot content in a div', () => {
+      const blocks: Block[] = [{
+        type: 'component',
+        name: 'Aside',
+        props: {},
+        slotChildren: [{ type: 'html', content: '<p>Hi</p>' }],
+      }];
+
+      const result = blocksToJsx(blocks, {}, [], registry);
+
+      expect(result).toContain(JSON.stringify('<div class="grid"><p>Hi</p></div>'));
+    });
+  });
 });
@@ -13,11 +13,22 @@ import { asPropValue } from '../ops/type-narrowing.js';
 export interface BlocksRegistry {
   getSupportedDirectives(): string[];
   getDirectiveMapping(directive: string): { component: string; injectProps?: Record<string, { source: string; value?: string }> } | undefined;
-  getSlotNormalization(component: string): { strategy: 'wrap_in_ol' | 'wrap_in_ul' } | undefined;
+  getSlotNormalization(component: string): SlotNormalizationConfig | undefined;
   getComponent(name: string): { modulePath: string; exportType: string } | undefined;
 }
 import { htmlEntitiesToJsx, hasPascalCaseTag } from '@xmdx/napi';
 
+/**
+ * Slot normalization options read by the fallback renderer.
+ */
+export interface SlotNormalizationConfig {
+  strategy: string;
+  wrapperClass?: string;
+  childComponent?: string;
+  childProp?: string;
+  headingLevel?: number;
+}
+
 /**
  * Prop value from the Rust compiler.
  */
@@ -408,19 +419,46 @@ function stripParagraphFragmentWrappers(input: string): string {
 
 /**
  * Normalizes slot content based on a slot normalization strategy.
- * Ensures content is wrapped in the appropriate list structure.
+ * Mirrors the Rust codegen so the fallback renders the same slot structure.
  *
  * @param slot - The slot HTML content to normalize
- * @param strategy - The normalization strategy ('wrap_in_ol' or 'wrap_in_ul')
- * @returns Normalized slot content with proper list wrapping
+ * @param normalization - The registry slot normalization
+ * @returns Normalized slot content, or the slot unchanged for unknown strategies
  */
-function normalizeSlotByStrategy(slot: string, strategy: 'wrap_in_ol' | 'wrap_in_ul'): string {
-  const tag = strategy === 'wrap_in_ol' ? 'ol' : 'ul';
+function normalizeSlotByStrategy(slot: string, normalization: SlotNormalizationConfig): string {
+  const { strategy, wrapperClass, childComponent, childProp } = normalization;
+  switch (strategy) {
+    case 'wrap_in_ol':
+      return wrapInList(slot, 'ol');
+    case 'wrap_in_ul':
+      return wrapInList(slot, 'ul', wrapperClass);
+    case 'wrap_in_div':
+      return `<div${classAttr(wrapperClass)}>${slot.trim()}</div>`;
+    case 'split_by_heading':
+      return childComponent
+        ? splitByHeading(slot, `h${normalization.headingLevel ?? 2}`, childComponent, childProp)
+        : slot;
+    case 'list_to_items':
+      return childComponent ? listToItems(slot, childComponent, childProp) : slot;
+    default:
+      return slot;
+  }
+}
+
+function classAttr(wrapperClass: string | undefined): string {
+  return wrapperClass ? ` class="${wrapperClass.replace(/"/g, '&quot;')}"` : '';
+}
+
+/**
+ * Wraps slot content in a single list element, adding an `<li>` when missing.
+ */
+function wrapInList(slot: string, tag: 'ol' | 'ul', wrapperClass?: string): string {
   const trimmed = slot.trim();
+  const open = `<${tag}${classAttr(wrapperClass)}>`;
 
   // Empty content: create minimal valid structure
   if (!trimmed) {
-    return `<${tag}><li></li></${tag}>`;
+    return `${open}<li></li></${tag}>`;
   }
 
   // Check if content already has the correct wrapper
@@ -433,8 +471,157 @@ function normalizeSlotByStrategy(slot: string, strategy: 'wrap_in_ol' | 'wrap_in
 
   // Content needs wrapping
   return /<li[\s>]/i.test(trimmed)
-    ? `<${tag}>${slot}</${tag}>`
-    : `<${tag}><li>${slot}</li></${tag}>`;
+    ? `${open}${slot}</${tag}>`
+    : `${open}<li>${slot}</li></${tag}>`;
+}
+
+/**
+ * Turns each `heading` section into a child component. Content before the
+ * first heading is kept; the heading text becomes `childProp` when set.
+ */
+function splitByHeading(slot: string, heading: string, child: string, childProp?: string): string {
+  const first = findOpenTag(slot, heading);
+  if (first === -1) {
+    return slot;
+  }
+
+  let result = slot.slice(0, first);
+  let rest = slot.slice(first);
+  let element = matchElement(rest, heading);
+  while (element) {
+    const label = stripTags(rest.slice(element.innerStart, element.innerEnd));
+    const after = rest.slice(element.end);
+    const next = findOpenTag(after, heading);
+    const sectionEnd = next === -1 ? after.length : next;
+    result += childComponentHtml(child, childProp, label, after.slice(0, sectionEnd));
+    rest = after.slice(sectionEnd);
+    element = rest ? matchElement(rest, heading) : undefined;
+  }
+  // Malformed trailing heading: keep it verbatim.
+  return result + rest;
+}
+
+/**
+ * Turns each top-level `<li>` of the first list into a child component. The
+ * item's leading text becomes `childProp` when set.
+ */
+function listToItems(slot: string, child: string, childProp?: string): string {
+  const lists = (['ul', 'ol'] as const)
+    .map((tag) => ({ tag, pos: findOpenTag(slot, tag) }))
+    .filter(({ pos }) => pos !== -1)
+    .sort((a, b) => a.pos - b.pos);
+  const list = lists[0];
+  if (!list) {
+    return slot;
+  }
+  const element = matchElement(slot.slice(list.pos), list.tag);
+  if (!element) {
+    return slot;
+  }
+
+  let result = slot.slice(0, list.pos);
+  let items = slot.slice(list.pos + element.innerStart, list.pos + element.innerEnd);
+  for (let liStart = findOpenTag(items, 'li'); liStart !== -1; liStart = findOpenTag(items, 'li')) {
+    const li = matchElement(items.slice(liStart), 'li');
+    if (!li) {
+      break;
+    }
+    const item = items.slice(liStart + li.innerStart, liStart + li.innerEnd);
+    if (childProp) {
+      const [lead, body] = splitLeadingItemText(item);
+      result += childComponentHtml(child, childProp, stripTags(lead), body);
+    } else {
+      result += childComponentHtml(child, undefined, '', item);
+    }
+    items = items.slice(liStart + li.end);
+  }
+  return result + slot.slice(list.pos + element.end);
+}
+
+/**
+ * Splits a list item into its leading text and the remaining content.
+ */
+function splitLeadingItemText(item: string): [string, string] {
+  const trimmed = item.trimStart();
+  const close = trimmed.indexOf('</p>');
+  if (trimmed.startsWith('<p>') && close !== -1) {
+    return [trimmed.slice(3, close), trimmed.slice(close + 4)];
+  }
+  const split = ['\n', '<p', '<ul', '<ol', '<div', '<pre']
+    .map((marker) => trimmed.indexOf(marker))
+    .filter((pos) => pos !== -1)
+    .reduce((min, pos) => Math.min(min, pos), trimmed.length);
+  return [trimmed.slice(0, split), trimmed.slice(split)];
+}
+
+function childComponentHtml(child: string, childProp: string | undefined, label: string, body: string): string {
+  const prop = childProp ? ` ${childProp}="${label.trim().replace(/"/g, '&quot;')}"` : '';
+  return `<${child}${prop}>${body.trim()}</${child}>`;
+}
+
+/**
+ * Finds the next `<tag` opening (followed by `>`, `/` or whitespace),
+ * ignoring text inside quoted attribute values of other tags.
+ */
+function findOpenTag(input: string, tag: string): number {
+  let inTag = false;
+  let quote: string | undefined;
+  for (let pos = 0; pos < input.length; pos++) {
+    const ch = input[pos];
+    if (quote) {
+      if (ch === quote) quote = undefined;
+    } else if (inTag) {
+      if (ch === '"' || ch === "'") quote = ch;
+      else if (ch === '>') inTag = false;
+    } else if (ch === '<') {
+      if (input.startsWith(tag, pos + 1) && /^(?:[>/\s]|$)/.test(input.charAt(pos + 1 + tag.length))) {
+        return pos;
+      }
+      inTag = true;
+    }
+  }
+  return -1;
+}
+
+/**
+ * For `input` starting with `<tag ...>`, returns the offsets of the element's
+ * content and the end of its closing tag, accounting for nested same-name tags.
+ */
+function matchElement(
+  input: string,
+  tag: string
+): { innerStart: number; innerEnd: number; end: number } | undefined {
+  const openEnd = input.indexOf('>');
+  if (openEnd === -1) return undefined;
+  const innerStart = openEnd + 1;
+  const closing = `</${tag}>`;
+  let depth = 1;
+  let pos = innerStart;
+  while (pos < input.length) {
+    const rest = input.slice(pos);
+    const nextClose = rest.indexOf(closing);
+    if (nextClose === -1) return undefined;
+    const nextOpen = findOpenTag(rest, tag);
+    if (nextOpen !== -1 && nextOpen < nextClose) {
+      depth += 1;
+      pos += nextOpen + 1;
+    } else {
+      depth -= 1;
+      if (depth === 0) {
+        const innerEnd = pos + nextClose;
+        return { innerStart, innerEnd, end: innerEnd + closing.length };
+      }
+      pos += nextClose + closing.length;
+    }
+  }
+  return undefined;
+}
+
+/**
+ * Removes HTML tags, keeping text content (entities are left encoded).
+ */
+function stripTags(html: string): string {
+  return html.replace(/<[^>]*>/g, '');
 }
 
 /**
@@ -699,10 +886,18 @@ export function blocksToJsx(
         }
       }
 
-      // Apply slot normalization from registry (e.g., Steps → wrap_in_ol, FileTree → wrap_in_ul)
+      // Apply slot normalization from registry (e.g., Steps → wrap_in_ol, Tabs → split_by_heading)
       const slotNorm = registry?.getSlotNormalization(componentName);
       if (slotNorm) {
-        effectiveSlot = normalizeSlotByStrategy(effectiveSlot, slotNorm.strategy);
+        effectiveSlot = normalizeSlotByStrategy(effectiveSlot, slotNorm);
+        const child = slotNorm.childComponent;
+        if (child && child !== 'Fragment' && !userImportedNames.has(child)) {
+          const childDef = registry?.getComponent(child);
+          componentImports.set(child, {
+            modulePath: childDef?.modulePath ?? '@astrojs/starlight/components',
+            exportType: childDef?.exportType ?? 'default',
+          });
+        }
       }
 
       // Skip Fragment (built-in) and user-imported components
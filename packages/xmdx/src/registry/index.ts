@@ -7,6 +7,8 @@ import type {
   ComponentDefinition,
   DirectiveMapping,
   SlotNormalization,
+  ElementMapping,
+  FenceMapping,
   ComponentLibrary,
   Registry,
 } from './types.js';
@@ -27,6 +29,8 @@ export function createRegistry(libraries: ComponentLibrary[]): Registry {
   const components = new Map<string, ComponentDefinition>();
   const directives = new Map<string, DirectiveMapping>();
   const slotNormalizations = new Map<string, SlotNormalization>();
+  const elements = new Map<string, ElementMapping>();
+  const fences = new Map<string, FenceMapping>();
 
   for (const lib of libraries) {
     for (const comp of lib.components) {
@@ -38,6 +42,12 @@ export function createRegistry(libraries: ComponentLibrary[]): Registry {
     for (const norm of lib.slotNormalizations ?? []) {
       slotNormalizations.set(norm.component, norm);
     }
+    for (const mapping of lib.elementMappings ?? []) {
+      elements.set(mapping.element, mapping);
+    }
+    for (const mapping of lib.fenceMappings ?? []) {
+      fences.set(mapping.lang.toLowerCase(), mapping);
+    }
   }
 
   return {
@@ -57,6 +67,16 @@ export function createRegistry(libraries: ComponentLibrary[]): Registry {
     getSlotNormalization: (component: string): SlotNormalization | undefined =>
       slotNormalizations.get(component),
 
+    /**
+     * Get the component mapping for a Markdown element.
+     */
+    getElementMapping: (element: string): ElementMapping | undefined => elements.get(element),
+
+    /**
+     * Get the component mapping for a code fence language.
+     */
+    getFenceMapping: (lang: string): FenceMapping | undefined => fences.get(lang.toLowerCase()),
+
     /**
      * Get all registered components.
      */
@@ -99,6 +119,8 @@ export function createRegistry(libraries: ComponentLibrary[]): Registry {
       components: Array.from(components.values()),
       directiveMappings: Array.from(directives.values()),
       slotNormalizations: Array.from(slotNormalizations.values()),
+      elementMappings: Array.from(elements.values()),
+      fenceMappings: Array.from(fences.values()),
     }),
   };
 }
@@ -108,6 +130,8 @@ export type {
   ComponentDefinition,
   DirectiveMapping,
   SlotNormalization,
+  ElementMapping,
+  FenceMapping,
   ComponentLibrary,
   Registry,
   ValidationError,
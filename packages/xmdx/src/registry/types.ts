@@ -13,16 +13,42 @@ export interface ComponentDefinition {
   modulePath: string;
   /** Export type: "named" for named exports, "default" for default exports */
   exportType: 'named' | 'default';
+  /** Former names rewritten to this component, reported as deprecated at each use (e.g., ["Callout"]) */
+  aliases?: string[];
+  /** Migration note reported with each alias use */
+  deprecated?: string;
 }
 
 /**
  * Prop injection source specification.
  */
 export interface PropSource {
-  /** Source of the prop value */
-  source: 'directive_name' | 'bracket_title' | 'literal';
+  /**
+   * Source of the prop value:
+   * - "directive_name": the directive's name
+   * - "bracket_title": the bracket title (`:::note[Title]`)
+   * - "literal": the fixed `value`
+   * - "attribute": the directive attribute `name` (`:::note{icon=star}`)
+   * - "frontmatter": the dotted `path` into the document frontmatter
+   * - "heading_slug": the slug of the enclosing section's heading
+   * - "index": the zero-based ordinal among sibling directives
+   */
+  source:
+    | 'directive_name'
+    | 'bracket_title'
+    | 'literal'
+    | 'attribute'
+    | 'frontmatter'
+    | 'heading_slug'
+    | 'index';
   /** Literal value when source is 'literal' */
   value?: string;
+  /** Attribute name when source is 'attribute' */
+  name?: string;
+  /** Dotted frontmatter path when source is 'frontmatter' (e.g., "hero.title") */
+  path?: string;
+  /** Fallback when the attribute, frontmatter value or heading is missing */
+  default?: string;
 }
 
 /**
@@ -35,6 +61,22 @@ export interface DirectiveMapping {
   component: string;
   /** Props to inject into the component */
   injectProps?: Record<string, PropSource>;
+  /** Rules routing parts of the directive into named slots */
+  slots?: SlotRouting;
+}
+
+/**
+ * Named slot routing rules for a directive's content.
+ */
+export interface SlotRouting {
+  /** Slot receiving the bracket title instead of the `title` prop */
+  bracketTitle?: string;
+  /** Slot receiving the first heading, when it is the first child */
+  firstHeading?: string;
+  /** Slot receiving the first paragraph, when it is the first child */
+  firstParagraph?: string;
+  /** Route nested directives with a `slot` attribute into that slot */
+  childDirectives?: boolean;
 }
 
 /**
@@ -51,10 +93,43 @@ export interface SlotNormalization {
    * Normalization strategy to apply:
    * - "wrap_in_ol": Wrap content in a single `<ol>` element
    * - "wrap_in_ul": Wrap content in a single `<ul>` element
+   * - "wrap_in_div": Wrap content in a single `<div>` element
+   * - "split_by_heading": Turn each `##` section (see `headingLevel`) into a `childComponent`
+   * - "list_to_items": Turn each `<li>` into a `childComponent`
    */
-  strategy: 'wrap_in_ol' | 'wrap_in_ul';
+  strategy: 'wrap_in_ol' | 'wrap_in_ul' | 'wrap_in_div' | 'split_by_heading' | 'list_to_items';
   /** Optional CSS class to add to the wrapper element */
   wrapperClass?: string;
+  /** Child component created per section/item (e.g., "TabItem", "Card") */
+  childComponent?: string;
+  /** Child prop receiving the heading text or the item's leading text (e.g., "label") */
+  childProp?: string;
+  /** Heading level that starts a section for `split_by_heading` (1-6, default 2) */
+  headingLevel?: number;
+}
+
+/**
+ * Maps a standard Markdown element to a component, resolved at compile time.
+ *
+ * Only Markdown syntax is mapped: raw HTML and JSX elements written in the
+ * source (`<img>`, `<a>`, ...) are left untouched.
+ */
+export interface ElementMapping {
+  /** Element kind */
+  element: 'img' | 'a' | 'table' | 'h1' | 'h2' | 'h3' | 'h4' | 'h5' | 'h6' | 'blockquote' | 'pre';
+  /** Component rendered in place of the element (e.g., "Image") */
+  component: string;
+}
+
+/**
+ * Maps a code fence language to a component that receives the fence source
+ * as its `code` prop (e.g., `mermaid` → `<Mermaid code={...} />`).
+ */
+export interface FenceMapping {
+  /** Fence language, matched case-insensitively (e.g., "mermaid", "d2") */
+  lang: string;
+  /** Component rendered in place of the code block (e.g., "Mermaid") */
+  component: string;
 }
 
 /**
@@ -73,6 +148,10 @@ export interface ComponentLibrary {
   directiveMappings?: DirectiveMapping[];
   /** Slot normalization rules for this library */
   slotNormalizations?: SlotNormalization[];
+  /** Markdown elements rendered through components */
+  elementMappings?: ElementMapping[];
+  /** Code fence languages rendered through components */
+  fenceMappings?: FenceMapping[];
 }
 
 /**
@@ -85,6 +164,17 @@ export interface Registry {
   getDirectiveMapping(directive: string): DirectiveMapping | undefined;
   /** Get slot normalization for a component */
   getSlotNormalization(component: string): SlotNormalization | undefined;
+  /**
+   * Get the component mapping for a Markdown element.
+   * Optional so registries written before element mappings stay valid;
+   * call as `registry.getElementMapping?.(element)`.
+   */
+  getElementMapping?(element: string): ElementMapping | undefined;
+  /**
+   * Get the component mapping for a code fence language.
+   * Optional for the same reason as `getElementMapping`.
+   */
+  getFenceMapping?(lang: string): FenceMapping | undefined;
   /** Get all registered components */
   getAllComponents(): ComponentDefinition[];
   /** Get all supported directive names */
@@ -102,6 +192,8 @@ export interface Registry {
     components: ComponentDefinition[];
     directiveMappings: DirectiveMapping[];
     slotNormalizations: SlotNormalization[];
+    elementMappings?: ElementMapping[];
+    fenceMappings?: FenceMapping[];
   };
 }
 
@@ -109,8 +201,8 @@ export interface Registry {
  * Error found during validation.
  */
 export interface ValidationError {
-  /** Error type: 'component' or 'directive' */
-  type: 'component' | 'directive';
+  /** Error type: 'component', 'directive' or 'slotNormalization' */
+  type: 'component' | 'directive' | 'slotNormalization';
   /** Name of the invalid item */
   name: string;
   /** Human-readable error message */
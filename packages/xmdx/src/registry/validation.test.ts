@@ -152,6 +152,28 @@ describe('validateLibrary', () => {
     expect(result.errors).toEqual([]);
   });
 
+  it('should return errors for invalid slot normalizations', () => {
+    const library = {
+      components: [
+        { name: 'Tabs', modulePath: 'some/path' },
+      ],
+      slotNormalizations: [
+        { component: 'Tabs', strategy: 'split_by_heading' },
+        { component: 'Box', strategy: 'wrap_in_span' },
+        { component: 'Tabs', strategy: 'split_by_heading', childComponent: 'TabItem', headingLevel: 7 },
+      ],
+    };
+
+    const result = validateLibrary(library);
+
+    expect(result.valid).toBe(false);
+    expect(result.errors.map((e) => e.message)).toEqual([
+      'Slot normalization "split_by_heading" requires a "childComponent" string',
+      'Slot normalization has unknown strategy "wrap_in_span"',
+      'Slot normalization "headingLevel" must be an integer from 1 to 6',
+    ]);
+  });
+
   it('should collect multiple errors', () => {
     const library = {
       components: [
@@ -86,6 +86,59 @@ function validateDirectiveMapping(
   return errors;
 }
 
+const SLOT_NORMALIZATION_STRATEGIES = new Set([
+  'wrap_in_ol',
+  'wrap_in_ul',
+  'wrap_in_div',
+  'split_by_heading',
+  'list_to_items',
+]);
+
+/**
+ * Validate a slot normalization has a known strategy and the options it needs.
+ * Mirrors the checks the Rust registry loader applies.
+ */
+function validateSlotNormalization(normalization: unknown): ValidationError[] {
+  if (!isRecord(normalization)) {
+    return [{
+      type: 'slotNormalization',
+      name: 'unknown',
+      message: 'Slot normalization must be an object',
+    }];
+  }
+
+  const name = typeof normalization.component === 'string' ? normalization.component : 'unknown';
+  const strategy = normalization.strategy;
+  if (typeof strategy !== 'string' || !SLOT_NORMALIZATION_STRATEGIES.has(strategy)) {
+    return [{
+      type: 'slotNormalization',
+      name,
+      message: `Slot normalization has unknown strategy "${String(strategy)}"`,
+    }];
+  }
+
+  const errors: ValidationError[] = [];
+  if (
+    (strategy === 'split_by_heading' || strategy === 'list_to_items') &&
+    typeof normalization.childComponent !== 'string'
+  ) {
+    errors.push({
+      type: 'slotNormalization',
+      name,
+      message: `Slot normalization "${strategy}" requires a "childComponent" string`,
+    });
+  }
+  const level = normalization.headingLevel;
+  if (level !== undefined && !(typeof level === 'number' && Number.isInteger(level) && level >= 1 && level <= 6)) {
+    errors.push({
+      type: 'slotNormalization',
+      name,
+      message: 'Slot normalization "headingLevel" must be an integer from 1 to 6',
+    });
+  }
+  return errors;
+}
+
 /**
  * Validate a component library preset.
  *
@@ -133,6 +186,12 @@ export function validateLibrary(library: unknown): ValidationResult {
     errors.push(...directiveErrors);
   }
 
+  // Validate slot normalizations
+  const normalizations = Array.isArray(library.slotNormalizations) ? library.slotNormalizations : [];
+  for (const normalization of normalizations) {
+    errors.push(...validateSlotNormalization(normalization));
+  }
+
   return {
     valid: errors.length === 0,
     errors,